@@ -0,0 +1,53 @@
+// Instanced zombie quads, one instance per zombie
+
+// Only the leading field of bevy's View uniform is needed here
+struct View {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> view: View;
+
+@group(1) @binding(0) var atlas_texture: texture_2d<f32>;
+@group(1) @binding(1) var atlas_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn vertex(
+    @builtin(vertex_index) vertex_index: u32,
+    // xy center, zw world size
+    @location(0) i_pos_size: vec4<f32>,
+    // xy uv min, zw uv max
+    @location(1) i_uv_rect: vec4<f32>,
+    @location(2) i_color: vec4<f32>,
+) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+    );
+    let corner = corners[vertex_index % 6u];
+    let position = i_pos_size.xy + (corner - vec2<f32>(0.5, 0.5)) * i_pos_size.zw;
+
+    var out: VertexOutput;
+    out.clip_position = view.view_proj * vec4<f32>(position, 1.0, 1.0);
+    // Texture v grows downwards while world y grows upwards
+    out.uv = vec2<f32>(
+        mix(i_uv_rect.x, i_uv_rect.z, corner.x),
+        mix(i_uv_rect.w, i_uv_rect.y, corner.y),
+    );
+    out.color = i_color;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(atlas_texture, atlas_sampler, in.uv) * in.color;
+}
@@ -1,15 +1,58 @@
 use bevy::{
+    a11y::{accesskit, AccessibilityNode, ActionRequest, Focus},
     app::AppExit,
-    asset::AssetMetaCheck,
-    audio::{PlaybackMode, Volume, VolumeLevel},
+    asset::{io::Reader, AssetLoader, AssetMetaCheck, AsyncReadExt, LoadContext},
+    audio::{Volume, VolumeLevel},
+    core_pipeline::{clear_color::ClearColorConfig, core_2d::Transparent2d},
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::system::{
+        lifetimeless::{Read, SRes},
+        SystemParamItem,
+    },
+    input::mouse::MouseWheel,
     math::{vec2, vec3},
     prelude::*,
-    time::{common_conditions::on_timer, Stopwatch},
-    utils::{Duration, HashSet, Instant},
-    window::PrimaryWindow,
+    render::{
+        camera::{ScalingMode, Viewport},
+        render_asset::RenderAssets,
+        render_phase::{
+            AddRenderCommand, DrawFunctions, PhaseItem, RenderCommand, RenderCommandResult,
+            RenderPhase, SetItemPipeline, TrackedRenderPass,
+        },
+        render_resource::{
+            BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutDescriptor,
+            BindGroupLayoutEntry, BindingType, BlendState, BufferBindingType, BufferUsages,
+            BufferVec, ColorTargetState, ColorWrites, FragmentState, MultisampleState,
+            PipelineCache, PrimitiveState, RenderPipelineDescriptor, SamplerBindingType,
+            ShaderStages, ShaderType, SpecializedRenderPipeline, SpecializedRenderPipelines,
+            TextureFormat, TextureSampleType, TextureViewDimension, VertexAttribute,
+            VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        texture::BevyDefault,
+        view::{
+            screenshot::ScreenshotManager, ExtractedView, ViewTarget, ViewUniform,
+            ViewUniformOffset, ViewUniforms, VisibilitySystems,
+        },
+        Extract, Render, RenderApp, RenderSet,
+    },
+    sprite::Anchor,
+    time::Stopwatch,
+    utils::{BoxedFuture, Duration, FloatOrd, HashMap, HashSet},
+    window::{PrimaryWindow, WindowFocused},
+    winit::{UpdateMode, WinitSettings},
+};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng, SeedableRng,
 };
-use rand::Rng;
+// StdRng's algorithm may change between rand releases, seeds must give the same run everywhere
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+#[cfg(feature = "audio")]
+use bevy::audio::{AddAudioSource, PlaybackMode, Source};
 
 // Sprite
 const SPRITE_SHEET_PATH: &str = "jam-assets.png";
@@ -17,6 +60,14 @@ const TILE_W: usize = 16;
 const TILE_H: usize = 16;
 const SPRITE_SHEET_W: usize = 160 / TILE_W;
 const SPRITE_SHEET_H: usize = 160 / TILE_H;
+// Per kind atlases, tiles outside these use the whole sheet above
+const ATLAS_MANIFEST_PATH: &str = "sprites.atlases.ron";
+
+// Tuning
+// Speeds, damage, spawn rates and tiles, see Tuning for the defaults
+const TUNING_PATH: &str = "tuning.ron";
+// Where zombies come from around the car, see SpawnPatterns for the defaults
+const SPAWN_PATTERNS_PATH: &str = "zombies.spawns.ron";
 
 // Window
 const WW: usize = 1000;
@@ -24,29 +75,673 @@ const WH: usize = 800;
 const BG_COLOR: (u8, u8, u8) = (23, 23, 38);
 
 // Car
-const TURN_SPEED: f32 = 20.0;
-const CAR_THRUST: f32 = 20.0;
-const MAX_SPEED: f32 = 40.0;
-const FRICTION: f32 = 20.0;
-const MAX_CAR_HEALTH: f32 = 200.0;
 const MIN_SPEED_TO_STEER: f32 = 0.0;
-const TURBO_BOOST: f32 = 60.0;
-const TURBO_INTERVAL_SEC: f32 = 5.0;
+// Half the side of the box around an obstacle the car's center can't enter
+const OBSTACLE_HALF_SIZE: f32 = 25.0;
+// Fraction of the speed into the obstacle bounced back, and of the speed along it kept
+const OBSTACLE_BOUNCE: f32 = 0.3;
+const OBSTACLE_SCRAPE_KEEP: f32 = 0.8;
+// Slower impacts just nudge the car, no damage or hit-stop
+const OBSTACLE_MIN_IMPACT_SPEED: f32 = 5.0;
+// Camera leads the car by this much on Y, and trails it after reversing for a while
+const CAMERA_LEAD_Y: f32 = 200.0;
+const CAMERA_REVERSE_LEAD_Y: f32 = -150.0;
+const CAMERA_REVERSE_DELAY_SEC: f32 = 1.0;
+
+// Health regeneration, always on for Easy and a mutator for the rest
+const HEALTH_REGEN_DELAY_SEC: f32 = 8.0;
+const HEALTH_REGEN_PER_SEC: f32 = 4.0;
+// Fraction of the max car health it heals back up to
+const HEALTH_REGEN_CAP: f32 = 0.5;
+// The health bar pulses green while it heals
+const HEALTH_REGEN_SHIMMER: f32 = 0.35;
+const HEALTH_REGEN_SHIMMER_SPEED: f32 = 6.0;
+
+// Frame rate
+// Menus tick at this rate with the battery saver on, input still wakes them right away
+const BATTERY_SAVER_MENU_FPS: f64 = 20.0;
+
+// Gamepad
+const DEAD_ZONE_STEPS: [f32; 6] = [0.05, 0.1, 0.15, 0.2, 0.3, 0.4];
+const SENSITIVITY_STEPS: [f32; 6] = [0.5, 0.75, 1.0, 1.25, 1.5, 2.0];
+// Crosshair speed of the right stick at 1x aim sensitivity
+const GAMEPAD_AIM_SPEED: f32 = 900.0;
+const GAMEPAD_AIM_MAX_DISTANCE: f32 = 450.0;
+
+// Recoil, speed units to pixels per second of sideways drift and how fast it dies down
+const CAR_RECOIL_DRIFT_SCALE: f32 = 10.0;
+const CAR_RECOIL_DRIFT_DECAY: f32 = 4.0;
+
+// Mouse steering
+// Steer per radian between the car's nose and the cursor
+const MOUSE_STEER_GAIN: f32 = 2.0;
+// Cursor this close to the car holds the wheel straight
+const MOUSE_STEER_DEAD_RADIUS: f32 = 40.0;
+
+// Crosshair, drawn in place of the OS cursor during a run
+const CROSSHAIR_SIZE: f32 = 32.0;
+const AIM_LINE_WIDTH: f32 = 2.0;
+const AIM_LINE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+
+// On foot
+const ON_FOOT_ESCAPE_TIME_SEC: f32 = 15.0;
+const SPARE_VEHICLE_DISTANCE: f32 = 500.0;
+// Rows either side of that spot searched for clear road
+const SPARE_VEHICLE_SEARCH_ROWS: i32 = 4;
+const SPARE_VEHICLE_CLEARANCE: f32 = 60.0;
+
+// Score arena
+// Where setup_game puts the car
+const ARENA_CENTER: Vec2 = Vec2::new(150.0, 50.0);
+const ARENA_RADIUS: f32 = 900.0;
+const ARENA_FLOOR_COLOR: Color = Color::rgb(0.6, 0.58, 0.55);
+const ARENA_FENCE_SPACING: f32 = 60.0;
+const ARENA_WAVE_INTERVAL_SEC: f32 = 12.0;
+const ARENA_WAVE_BASE: usize = 20;
+const ARENA_WAVE_GROWTH: usize = 12;
+// Waves from this one on bring big zombies along
+const ARENA_BIG_ZOMBIE_WAVE: usize = 3;
+const ARENA_PICKUP_INTERVAL_SEC: f32 = 15.0;
+const ARENA_WEAPONS: [Weapon; 3] = [Weapon::Smg, Weapon::Shotgun, Weapon::Pistol];
+const ARENA_SCORE_PER_SEC: u32 = 2;
+
+// Start zone
+const START_COUNTDOWN_SEC: f32 = 3.0;
+// How long GO! stays up while the gates swing open
+const START_GO_SEC: f32 = 1.0;
+// No zombies spawn below the gates, the car starts at y 50
+const START_GATE_Y: f32 = 400.0;
+const START_GATE_THICKNESS: f32 = 24.0;
+const START_WALL_BOTTOM_Y: f32 = -300.0;
+const START_WALL_THICKNESS: f32 = 20.0;
+
+// Toll gates
+// Progress fractions the gates close the road at, boss rush roads are too short for them
+const TOLL_GATE_PROGRESS: [f32; 2] = [0.35, 0.7];
+const TOLL_GATE_THICKNESS: f32 = 24.0;
+// Fences carry on past the road so going around off road doesn't work either
+const TOLL_GATE_FENCE_TILES: f32 = 6.0;
+const TOLL_GATE_FENCE_THICKNESS: f32 = 12.0;
+// The car is held back this far below the gate
+const TOLL_GATE_STOP_DIST: f32 = 40.0;
+const TOLL_PANEL_SIZE: Vec2 = Vec2::new(36.0, 36.0);
+const TOLL_PANEL_COLOR: Color = Color::rgb(0.35, 0.55, 0.75);
+const TOLL_PANEL_RADIUS: f32 = 110.0;
+// Nearly stopped counts as holding position
+const TOLL_PANEL_MAX_SPEED: f32 = 5.0;
+const TOLL_GATE_HOLD_SEC: f32 = 5.0;
+const TOLL_GATE_OPEN_SEC: f32 = 1.0;
+// The panel's alarm keeps calling the horde in while it works
+const TOLL_GATE_ALARM_RADIUS: f32 = 1200.0;
+const TOLL_GATE_ALARM_INTERVAL_SEC: f32 = 0.5;
+const TOLL_GATE_PROMPT_RANGE: f32 = 500.0;
+
+// Commandeering
+const COMMANDEER_RADIUS: f32 = 70.0;
+// Parked cars can only be taken once the car is down to this fraction of its health
+const COMMANDEER_HEALTH_THRESHOLD: f32 = 0.35;
 
 // Guns and Bullets
 const BULLET_TIME: f32 = 1.0;
-const BULLET_SPEED: f32 = 20.0 * 100.0;
-const BULLET_SPAWN_INTERVAL: f32 = 0.3;
 const BULLET_HIT_BOX: f32 = 10.0;
+// Damage to nests and bosses after a bullet bounced off an obstacle
+const RICOCHET_DAMAGE: f32 = 0.5;
+const BULLET_TRAIL_POOL_SIZE: usize = 48;
+const BULLET_TRAIL_MAX_LENGTH: f32 = 120.0;
+const BULLET_TRAIL_WIDTH: f32 = 3.0;
+const BULLET_TRAIL_FADE_SEC: f32 = 0.12;
+const BULLET_TRAIL_COLOR: Color = Color::rgba(1.0, 0.94, 0.85, 0.6);
+const MUZZLE_FLASH_SEC: f32 = 0.05;
+const MUZZLE_FLASH_OFFSET: f32 = 22.0;
+const MUZZLE_FLASH_COLOR: Color = Color::rgb(1.0, 0.85, 0.4);
+
+// Drone
+// Hovers here relative to the car, behind and off to the left
+const DRONE_OFFSET: Vec2 = Vec2::new(-45.0, -35.0);
+const DRONE_FOLLOW_SPEED: f32 = 8.0;
+const DRONE_RANGE: f32 = 350.0;
+const DRONE_FIRE_INTERVAL_SEC: f32 = 0.5;
+// Damage to nests and bosses, zombies still drop in one hit
+const DRONE_SHOT_DAMAGE: f32 = 0.25;
+const DRONE_BOB_HEIGHT: f32 = 4.0;
+const DRONE_BOB_HZ: f32 = 1.5;
+const DRONE_COLOR: Color = Color::rgb(0.55, 0.85, 1.0);
+
+// Particles
+const PARTICLE_POOL_SIZE: usize = 256;
+// Pulls particles back down to the road, they fly in a fake height above it
+const PARTICLE_GRAVITY: f32 = 900.0;
+const PARTICLE_BOUNCE: f32 = 0.3;
+const PARTICLE_GROUND_FRICTION: f32 = 0.5;
+// Fraction of the lifetime spent fading out
+const PARTICLE_FADE: f32 = 0.3;
+const DIRT_INTERVAL_SEC: f32 = 0.05;
+const DIRT_MIN_SPEED: f32 = 8.0;
 
 // Roads
 const ROAD_WIDTH: usize = 5;
-const ROAD_HEIGHT: usize = 600;
 const ROAD_SCALE: f32 = 5.0;
+const SURFACE_PATCH_CHANCE: f32 = 0.06;
+const ROAD_BOTTOM_Y: i32 = -10;
+const ROAD_CHUNK_ROWS: i32 = 20;
+// Chunks within this distance of the camera are kept spawned, about two screens ahead
+const ROAD_STREAM_AHEAD: f32 = WH as f32 * 2.0;
+const ROAD_STREAM_BEHIND: f32 = WH as f32 * 1.5;
+
+// Pickups
+const PICKUP_RADIUS: f32 = 30.0;
+
+// Ramps
+const RAMP_SIZE: Vec2 = Vec2::new(60.0, 36.0);
+const RAMP_COLOR: Color = Color::rgb(0.6, 0.42, 0.25);
+const RAMP_MIN_SPEED: f32 = 20.0;
+const RAMP_AIR_SEC: f32 = 1.0;
+// Extra car scale at the top of the jump, fakes the height
+const RAMP_AIR_SCALE: f32 = 0.35;
+const RAMP_SHADOW_OFFSET: Vec2 = Vec2::new(12.0, -18.0);
+const RAMP_SHADOW_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.35);
+// Bypasses the armor like obstacles do
+const RAMP_OFF_ROAD_LANDING_DAMAGE: f32 = 5.0;
+
+// Main menu background
+const MENU_ZOMBIE_COUNT: usize = 200;
+// Area around the camera the background zombies wander in
+const MENU_HALF_SIZE: Vec2 = Vec2::new(650.0, 550.0);
+const MENU_WAYPOINT_RADIUS: f32 = 250.0;
+const MENU_ZOMBIE_SPEED_FACTOR: f32 = 0.1;
+const MENU_ZOMBIE_MAX_PAUSE_SEC: f32 = 3.0;
+// Driverless cars roll across every so often, randomized by up to half either way
+const MENU_CAR_INTERVAL_SEC: f32 = 8.0;
+const MENU_CAR_SPEED: f32 = 350.0;
+const MENU_CAR_SCATTER_RADIUS: f32 = 90.0;
+const MENU_CAR_VOLUME: f32 = 0.08;
+
+// Status effects
+// Special rounds a status ammo pickup loads into the gun
+const STATUS_AMMO_ROUNDS: u32 = 12;
+const FROZEN_SEC: f32 = 4.0;
+const FROZEN_COLOR: Color = Color::rgb(0.7, 1.0, 1.0);
+// Car speed that shatters a frozen zombie it runs into
+const FROZEN_SHATTER_SPEED: f32 = 15.0;
+const FROZEN_SHATTER_RADIUS: f32 = 25.0;
+// Burning zombies drop once it runs out
+const BURNING_SEC: f32 = 3.0;
+const BURNING_COLOR: Color = Color::rgb(1.0, 0.45, 0.2);
+const BURNING_SPREAD_INTERVAL_SEC: f32 = 0.75;
+const BURNING_SPREAD_RADIUS: f32 = 40.0;
+const BURNING_SPREAD_CHANCE: f64 = 0.35;
+const STUNNED_SEC: f32 = 2.0;
+const STUNNED_COLOR: Color = Color::rgb(1.0, 1.0, 0.45);
+
+// Slow motion
+const SLOW_MO_DURATION_SEC: f32 = 3.0;
+const SLOW_MO_TIME_SCALE: f32 = 0.3;
+const SLOW_MO_TICK_INTERVAL_SEC: f32 = 0.5;
+const SLOW_MO_OVERLAY_COLOR: Color = Color::rgba(0.45, 0.45, 0.45, 0.45);
+
+// Checkpoint shop
+// Opens at every progress milestone before the finale, run score buys the items
+const SHOP_DURATION_SEC: f32 = 10.0;
+const SHOP_REPAIR_COST: u32 = 40;
+// Of the max car health
+const SHOP_REPAIR_AMOUNT: f32 = 0.4;
+const SHOP_ROUNDS_COST: u32 = 30;
+const SHOP_COLD_BARREL_COST: u32 = 50;
+// The gun doesn't heat up for this long
+const SHOP_COLD_BARREL_SEC: f32 = 20.0;
+const SHOP_UNAFFORDABLE_COLOR: Color = Color::rgb(0.45, 0.4, 0.4);
+
+// Finale
+// Progress fractions that fire a ProgressMilestoneEvent, once per run each
+const PROGRESS_MILESTONES: [f32; 4] = [0.25, 0.5, 0.75, 0.9];
+const FINALE_PROGRESS: f32 = 0.9;
+const FINALE_BANNER_SEC: f32 = 3.0;
+const FINALE_VIGNETTE_COLOR: Color = Color::rgb(0.6, 0.05, 0.05);
+const FINALE_VIGNETTE_MAX_ALPHA: f32 = 0.55;
+const FINALE_PULSE_HZ: f32 = 1.2;
+// Two semitones up
+#[cfg(feature = "audio")]
+const FINALE_MUSIC_SPEED: f32 = 1.122;
+
+// Finish layouts
+// Rows short of the road end the barrier goes, then lined up mid straight like the toll gates
+const FINISH_ROWS_FROM_TOP: i32 = 6;
+const FINISH_WATER_HEIGHT: f32 = 160.0;
+const FINISH_WATER_COLOR: Color = Color::rgb(0.2, 0.35, 0.55);
+// The car is held back this far below the barrier
+const FINISH_STOP_DIST: f32 = 40.0;
+const FINISH_PROMPT_RANGE: f32 = 700.0;
+// Ferry horn and bridge machinery call the horde in while they work
+const FINISH_ALARM_RADIUS: f32 = 1400.0;
+const FINISH_ALARM_INTERVAL_SEC: f32 = 0.5;
+const FERRY_DOCK_HEIGHT: f32 = 240.0;
+const FERRY_DOCK_COLOR: Color = Color::rgba(1.0, 0.6, 0.2, 0.2);
+const FERRY_WAIT_SEC: f32 = 20.0;
+const FERRY_ARRIVE_SEC: f32 = 3.0;
+const FERRY_COLOR: Color = Color::rgb(0.6, 0.55, 0.5);
+const DRAWBRIDGE_LEVER_COLOR: Color = Color::rgb(0.85, 0.75, 0.3);
+// Off the road to one side, in tiles past the edge
+const DRAWBRIDGE_LEVER_OFFSET_TILES: f32 = 2.5;
+const DRAWBRIDGE_LEVER_RADIUS: f32 = 90.0;
+const DRAWBRIDGE_LEVER_HOLD_SEC: f32 = 3.0;
+const DRAWBRIDGE_LOWER_SEC: f32 = 4.0;
+const BUNKER_GATE_HEALTH: f32 = 100.0;
+const BUNKER_GATE_THICKNESS: f32 = 48.0;
+const BUNKER_GATE_COLOR: Color = Color::rgb(0.45, 0.45, 0.42);
+// Slower hits just bounce off
+const BUNKER_RAM_MIN_SPEED: f32 = 12.0;
+const BUNKER_RAM_DAMAGE_PER_SPEED: f32 = 1.0;
+const BUNKER_COLLAPSE_SEC: f32 = 0.5;
+// On foot the gate is pried open instead, slowly and with the alarm going
+const BUNKER_PRY_SEC: f32 = 8.0;
+const BUNKER_PRY_RANGE: f32 = 60.0;
+
+// Dust storm
+// Bands of the road, the first one starting at DUST_STORM_FIRST_Y and repeating
+const DUST_STORM_FIRST_Y: f32 = 8000.0;
+const DUST_STORM_PERIOD: f32 = 14000.0;
+const DUST_STORM_LENGTH: f32 = 3000.0;
+// Thins out towards both ends of a band
+const DUST_STORM_FADE: f32 = 600.0;
+const DUST_STORM_COLOR: Color = Color::rgb(0.62, 0.5, 0.32);
+const DUST_STORM_MAX_ALPHA: f32 = 0.7;
+const DUST_STORM_BULLET_FACTOR: f32 = 0.5;
+// Spawns ahead of the car come in this much closer
+const DUST_STORM_SPAWN_PULL: f32 = 0.6;
+
+// Hit-stop
+const HIT_STOP_OBSTACLE_MS: u64 = 80;
+const HIT_STOP_NEST_MS: u64 = 60;
+
+// Mutators
+const EXPLODING_ZOMBIE_RADIUS: f32 = 60.0;
+// Each hit goes through the regular zombie damage, armor included
+const EXPLODING_ZOMBIE_HITS: usize = 5;
+const NIGHT_OVERLAY_COLOR: Color = Color::rgba(0.02, 0.02, 0.1, 0.6);
+const MUTATOR_PRESET_SLOTS: usize = 3;
+
+// Tutorial
+const TUTORIAL_STEER_DISTANCE: f32 = 400.0;
+const TUTORIAL_DUMMY_DISTANCE: f32 = 350.0;
+const TUTORIAL_PROGRESS_GOAL: f32 = 0.03;
+
+// Radio
+const RADIO_SCRIPT_PATH: &str = "chatter.radio.ron";
+const RADIO_CHARS_PER_SEC: f32 = 40.0;
+// How long a fully typed message stays up
+const RADIO_HOLD_SEC: f32 = 3.5;
+
+// Adaptive quality
+const ADAPTIVE_TARGET_FPS: f64 = 50.0;
+// Extra headroom before ramping back up, keeps it from oscillating around the target
+const ADAPTIVE_RECOVER_FPS: f64 = 58.0;
+const ADAPTIVE_DROP_RATE: f32 = 0.5;
+const ADAPTIVE_RECOVER_RATE: f32 = 0.1;
+const ADAPTIVE_MIN_ZOMBIE_FRACTION: f32 = 0.25;
+const ADAPTIVE_MAX_AI_STRIDE: u32 = 4;
 
 // Zombies
-const ZOMBIE_SPEED: f32 = 2.55 * 100.0;
-const ZOMBIE_ATTACK: f32 = 2.0;
+const ZOMBIE_BITE_COOLDOWN_SEC: f32 = 0.5;
+const ZOMBIE_BITE_ANIM_SEC: f32 = 0.2;
+const ZOMBIE_BITE_SCALE: f32 = 0.3;
+// Chance a landed bite latches the zombie onto the car
+const GRAB_CHANCE: f64 = 0.25;
+const GRAB_MAX_LATCHED: usize = 4;
+// Max speed and steering lost per latched zombie
+const GRAB_SPEED_PENALTY: f32 = 0.12;
+const GRAB_STEER_PENALTY: f32 = 0.1;
+// Turning this hard at speed for long enough flings one zombie off
+const GRAB_SHAKE_SEC: f32 = 0.6;
+const GRAB_SWERVE_TURN_FRACTION: f32 = 0.4;
+const GRAB_SHAKE_MIN_SPEED: f32 = 15.0;
+// Scraping past an obstacle this close knocks all of them off
+const GRAB_SCRAPE_DISTANCE: f32 = 45.0;
+const GRAB_FLING_DISTANCE: f32 = 40.0;
+const GRAB_REGRAB_COOLDOWN_SEC: f32 = 1.5;
+const ZOMBIE_DESPAWN_BUDGET: usize = 200;
+const ZOMBIE_DESPAWN_SCAN_BUDGET: usize = 2000;
+// Zombies outside this rect around the camera get despawned
+const ZOMBIE_DESPAWN_HALF_W: f32 = 2200.0;
+const ZOMBIE_DESPAWN_BEHIND: f32 = 900.0;
+const ZOMBIE_DESPAWN_AHEAD: f32 = 3200.0;
+// Zombies this far outside every camera view are hidden and skip animation
+const ZOMBIE_CULL_MARGIN: f32 = 150.0;
+// Tide rendering, past this radius from the car zombies fold into one blob per grid cell
+const TIDE_RADIUS: f32 = 900.0;
+// Cells past this range are left empty, the pool only covers the views around the car
+const TIDE_MAX_RANGE: f32 = 2400.0;
+const TIDE_POOL_SIZE: usize = 1024;
+// A lone zombie is drawn at its own size, the blob grows with the square root of the count
+const TIDE_BLOB_SCALE: f32 = 3.0;
+const TIDE_BLOB_MAX_SCALE: f32 = 12.0;
+const TIDE_COLOR: Color = Color::rgb(0.55, 0.7, 0.5);
+const TIDE_WOBBLE_SPEED: f32 = 3.0;
+const TIDE_WOBBLE_ANGLE: f32 = 0.25;
+const TIDE_PULSE_SCALE: f32 = 0.1;
+const TIDE_FRAME_SEC: f32 = 0.4;
+// Zombie AI, the car is heard further the faster it goes. The radius grows with the
+// square of the speed, so coasting along slowly barely carries past the idle rumble
+const ZOMBIE_HEAR_ENGINE_BASE: f32 = 150.0;
+const ZOMBIE_HEAR_ENGINE_FULL: f32 = 1450.0;
+const ZOMBIE_HEAR_FULL_SPEED: f32 = 40.0;
+const ZOMBIE_GUNSHOT_NOISE_RADIUS: f32 = 1000.0;
+const ZOMBIE_CHASE_RADIUS: f32 = 500.0;
+const ZOMBIE_LOSE_RADIUS: f32 = 1600.0;
+const ZOMBIE_ALERT_TURN_SEC: f32 = 0.5;
+const ZOMBIE_ALERT_TIMEOUT_SEC: f32 = 4.0;
+const ZOMBIE_WANDER_SPEED_FACTOR: f32 = 0.25;
+const ZOMBIE_INVESTIGATE_SPEED_FACTOR: f32 = 0.8;
+const ZOMBIE_ALERT_GROAN_INTERVAL_SEC: f32 = 0.4;
+// Honking draws the horde in, handy as a lure
+const HORN_NOISE_RADIUS: f32 = 1400.0;
+// Parked cars are climbed over rather than walked through, slowly and drawn up on the roof
+const ZOMBIE_CLIMB_HALF_SIZE: f32 = 24.0;
+const ZOMBIE_CLIMB_SPEED_FACTOR: f32 = 0.3;
+// In sprite heights
+const ZOMBIE_CLIMB_LIFT: f32 = 0.25;
+const OBSTACLE_GRID_CELL: f32 = 64.0;
+// Around the visible area, so nothing pops in right at the edge
+const ZOMBIE_SPAWN_VIEW_MARGIN: f32 = 120.0;
+const ZOMBIE_SPAWN_ATTEMPTS: usize = 4;
+// Parked cars between a zombie and the car muffle it down to this fraction of its volume
+const SOUND_OCCLUSION_VOLUME: f32 = 0.35;
+const SOUND_OCCLUDER_HALF_SIZE: f32 = 24.0;
+
+// Spitters
+const SPITTER_MIN_PROGRESS: f32 = 0.2;
+const SPITTER_COLOR: Color = Color::rgb(0.55, 1.0, 0.35);
+const SPITTER_RANGE: f32 = 650.0;
+const SPITTER_COOLDOWN_SEC: f32 = 3.5;
+const ACID_GLOB_FLIGHT_SEC: f32 = 1.1;
+const ACID_GLOB_ARC_HEIGHT: f32 = 140.0;
+const ACID_GLOB_HIT_BOX: f32 = 30.0;
+// Direct hits splash harder than driving through the pool
+const ACID_GLOB_HITS: usize = 3;
+const ACID_POOL_RADIUS: f32 = 45.0;
+const ACID_POOL_LIFETIME_SEC: f32 = 6.0;
+const ACID_POOL_TICK_SEC: f32 = 0.25;
+const ACID_COLOR: Color = Color::rgba(0.45, 0.95, 0.2, 0.55);
+
+// Bloaters
+const BLOATER_MIN_PROGRESS: f32 = 0.15;
+const BLOATER_COLOR: Color = Color::rgb(0.9, 0.85, 0.3);
+const BLOATER_SCALE: f32 = 3.2;
+const BLOATER_SWELL_SEC: f32 = 0.4;
+// Bloaters caught in a blast pop quicker, so chains ripple through the horde
+const BLOATER_CHAIN_SWELL_SEC: f32 = 0.15;
+const BLOATER_SWELL_SCALE: f32 = 0.6;
+const BLOATER_BLAST_RADIUS: f32 = 110.0;
+const BLOATER_CAR_HITS: usize = 8;
+
+// Zombie dogs
+const DOG_MIN_PROGRESS: f32 = 0.6;
+// Hard runs get the packs early on
+const DOG_HARD_MIN_PROGRESS: f32 = 0.2;
+// Half open
+const DOG_PACK_SIZE: (usize, usize) = (2, 5);
+const DOG_PACK_SPREAD: f32 = 40.0;
+const DOG_SCALE: f32 = 2.2;
+const DOG_LUNGE_RANGE: f32 = 220.0;
+const DOG_LUNGE_SPEED: f32 = 900.0;
+const DOG_LUNGE_SEC: f32 = 0.3;
+const DOG_LUNGE_COOLDOWN_SEC: f32 = 1.5;
+// The crouch before a lunge flashes and flattens the dog
+const DOG_WINDUP_COLOR: Color = Color::rgb(1.0, 0.35, 0.3);
+const DOG_WINDUP_SQUASH: f32 = 0.25;
+const ZOMBIE_GRID_CELL: f32 = 128.0;
+
+// Street sweeper
+const SWEEPER_MIN_PROGRESS: f32 = 0.5;
+const SWEEPER_FIRST_DELAY_SEC: f32 = 10.0;
+const SWEEPER_INTERVAL_SEC: f32 = 45.0;
+// The car tops out around 400, keeping behind the plow costs time
+const SWEEPER_SPEED: f32 = 250.0;
+const SWEEPER_SPAWN_BEHIND: f32 = 500.0;
+const SWEEPER_DESPAWN_DISTANCE: f32 = 1500.0;
+const SWEEPER_HALF_WIDTH: f32 = 70.0;
+const SWEEPER_BLADE_REACH: f32 = 90.0;
+// How far back of the plow the car is still sheltered from the horde
+const SWEEPER_SHELTER_LENGTH: f32 = 350.0;
+const SWEEPER_SCALE: f32 = 4.0;
+const SWEEPER_COLOR: Color = Color::rgb(0.45, 0.5, 0.3);
+
+// Airdrops, cargo planes cross the road and drop crates or debris ahead of the car
+const AIRDROP_MIN_PROGRESS: f32 = 0.15;
+const AIRDROP_FIRST_DELAY_SEC: f32 = 15.0;
+// Halves toward the end of the road
+const AIRDROP_INTERVAL_SEC: f32 = 40.0;
+const AIRDROP_DEBRIS_CHANCE_START: f32 = 0.2;
+const AIRDROP_DEBRIS_CHANCE_END: f32 = 0.6;
+const AIRDROP_AHEAD_MIN: f32 = 500.0;
+const AIRDROP_AHEAD_MAX: f32 = 900.0;
+const AIRDROP_SPREAD: f32 = 120.0;
+const AIRDROP_PLANE_SPEED: f32 = 900.0;
+// The plane starts and ends this far to the side of the drop
+const AIRDROP_PLANE_LEAD: f32 = 1400.0;
+const AIRDROP_PLANE_SCALE: f32 = 8.0;
+const AIRDROP_PLANE_COLOR: Color = Color::rgba(0.15, 0.15, 0.15, 0.6);
+const AIRDROP_FALL_SEC: f32 = 2.5;
+const AIRDROP_SCALE: f32 = 3.0;
+const AIRDROP_SHADOW_ALPHA: f32 = 0.5;
+const AIRDROP_CRUSH_RADIUS: f32 = 50.0;
+const AIRDROP_CRUSH_DAMAGE: f32 = 25.0;
+const AIRDROP_DEBRIS_COLOR: Color = Color::rgb(0.55, 0.5, 0.45);
+
+// Corpses
+const CORPSE_PILE_CAP: usize = 200;
+const CORPSE_MERGE_RADIUS: f32 = 40.0;
+const CORPSE_PILE_MAX_RADIUS: f32 = 40.0;
+const CORPSE_PILE_BLOCKING_SIZE: u32 = 5;
+const CORPSE_PILE_DRAG: f32 = 1.5;
+const CORPSE_GRID_CELL: f32 = 64.0;
+const CORPSE_PILE_COLOR: Color = Color::rgb(0.35, 0.2, 0.25);
+
+// Gore
+const BLOOD_SPLAT_POOL_SIZE: usize = 300;
+const BLOOD_SPLAT_SIZE: f32 = 22.0;
+const BLOOD_SPLAT_COLOR: Color = Color::rgba(0.45, 0.04, 0.06, 0.85);
+const GORE_LOW_BLOOD_CAP: usize = 40;
+const GORE_LOW_BLOOD_SEC: f32 = 3.0;
+const GORE_FULL_BLOOD_SEC: f32 = 12.0;
+const GORE_LOW_CORPSE_PILE_CAP: usize = 40;
+// Low gore piles rot away instead of lining the road for the whole run
+const GORE_LOW_CORPSE_SEC: f32 = 20.0;
+
+// Nests
+const NEST_HEALTH: f32 = 15.0;
+const NEST_HIT_BOX: f32 = 40.0;
+const NEST_SCORE_BONUS: u32 = 50;
+
+// Boss rush
+const BOSS_RUSH_ROAD_HEIGHT: usize = 150;
+const BOSS_ARENA_TILES: i32 = 15;
+const BOSS_ARENA_FLOOR_COLOR: Color = Color::rgb(0.75, 0.7, 0.7);
+const BOSS_ARENA_FENCE_SPACING: f32 = 60.0;
+// Fraction of the arena kept after each boss
+const BOSS_ARENA_SHRINK: f32 = 0.8;
+const BOSS_ARENA_CLOSE_SPEED: f32 = 120.0;
+const BOSS_COUNT: usize = 4;
+const BOSS_BASE_HEALTH: f32 = 60.0;
+const BOSS_BASE_SPEED: f32 = 140.0;
+const BOSS_HIT_BOX: f32 = 55.0;
+const BOSS_ATTACK_INTERVAL_SEC: f32 = 0.6;
+const BOSS_ATTACK_HITS: usize = 5;
+const BOSS_RAM_MIN_SPEED: f32 = 15.0;
+const BOSS_RAM_DAMAGE_PER_SPEED: f32 = 0.5;
+const BOSS_KNOCKBACK: f32 = 90.0;
+const BOSS_INTERMISSION_SEC: f32 = 4.0;
+const BOSS_SCORE_BONUS: u32 = 200;
+const BOSS_COLORS: [Color; BOSS_COUNT] = [
+    Color::rgb(1.0, 0.85, 0.85),
+    Color::rgb(0.7, 1.0, 0.6),
+    Color::rgb(0.6, 0.75, 1.0),
+    Color::rgb(1.0, 0.45, 0.35),
+];
+
+// Score popups
+const SCORE_POPUP_POOL_SIZE: usize = 64;
+const SCORE_POPUP_LIFETIME_SEC: f32 = 0.8;
+const SCORE_POPUP_RISE_SPEED: f32 = 80.0;
+
+// Profile
+#[cfg(not(target_arch = "wasm32"))]
+const PROFILE_PATH: &str = "profile.ron";
+
+// Speedrun
+// One split for every 10% of the road
+const SPLIT_COUNT: usize = 10;
+#[cfg(not(target_arch = "wasm32"))]
+const SPLITS_PATH: &str = "splits.ron";
+const SPLIT_AHEAD_COLOR: Color = Color::rgb(0.45, 0.9, 0.4);
+const SPLIT_BEHIND_COLOR: Color = Color::rgb(0.95, 0.35, 0.3);
+
+// Run setup
+// Leads every run code, bumped when the format changes
+const RUN_CODE_PREFIX: &str = "ZE1";
+
+// Statistics
+const STATS_RECENT_RUNS: usize = 10;
+// Car movement in a frame above this is a teleport, not driving
+const STATS_MAX_STEP: f32 = 100.0;
+const STATS_PX_PER_METER: f32 = 20.0;
+const STATS_LABEL_WIDTH: f32 = 220.0;
+const STATS_CHART_WIDTH: f32 = 380.0;
+const STATS_CHART_HEIGHT: f32 = 120.0;
+
+// Run history
+const RUN_HISTORY_LEN: usize = 50;
+const RUN_HISTORY_PAGE_SIZE: usize = 10;
+// Damage strip on the game over screen, the road split into equal stretches
+const DAMAGE_STRIP_BUCKETS: usize = 32;
+const DAMAGE_STRIP_WIDTH: f32 = 480.0;
+const DAMAGE_STRIP_HEIGHT: f32 = 24.0;
+const DAMAGE_STRIP_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+const DAMAGE_STRIP_HOT_COLOR: Color = Color::rgb(0.9, 0.1, 0.1);
+
+// Benchmark
+const BENCHMARK_DURATION_SEC: f32 = 60.0;
+const BENCHMARK_SEED: u64 = 0x00be_7c4a;
+// Rows ahead of the car the autopilot aims for
+const BENCHMARK_LOOKAHEAD_ROWS: i32 = 4;
+#[cfg(not(target_arch = "wasm32"))]
+const BENCHMARK_PATH: &str = "benchmark.csv";
+const BENCHMARK_CSV_HEADER: &str =
+    "frames,avg_fps,p50_ms,p95_ms,p99_ms,max_ms,max_entities,avg_zombies,max_zombies";
+
+// Telemetry
+// Opt-in, anonymous run stats posted over https to Profile::telemetry_endpoint, desktop builds
+// with the telemetry feature only
+#[cfg(all(feature = "telemetry", not(target_arch = "wasm32")))]
+const TELEMETRY_TIMEOUT_SEC: f32 = 5.0;
+// A long run at a high refresh rate stops sampling here
+const TELEMETRY_MAX_FRAMES: usize = 100_000;
+
+// Crash reports
+// Native only, the log lines are kept around to go into the report if the game panics
+#[cfg(not(target_arch = "wasm32"))]
+const CRASH_REPORT_DIR: &str = "crash-reports";
+#[cfg(not(target_arch = "wasm32"))]
+const CRASH_LOG_LINES: usize = 200;
+const GAME_LOG_COUNTS_INTERVAL_SEC: f32 = 10.0;
+
+// Captures
+// F12 screenshots and F9 clips go here, the browser downloads them instead
+const CAPTURES_DIR: &str = "captures";
+// The clip is the last few seconds, recorded small and at a low frame rate to keep it light
+#[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+const GIF_CAPTURE_FPS: f32 = 10.0;
+#[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+const GIF_CAPTURE_FRAMES: usize = 50;
+#[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+const GIF_CAPTURE_WIDTH: u32 = 480;
+
+// Horde ambience
+#[cfg(feature = "audio")]
+const HORDE_GROAN_RADIUS: f32 = 800.0;
+// Zombies near the car for the groan to reach full volume
+#[cfg(feature = "audio")]
+const HORDE_GROAN_FULL_COUNT: f32 = 120.0;
+#[cfg(feature = "audio")]
+const HORDE_GROAN_MAX_VOLUME: f32 = 0.6;
+#[cfg(feature = "audio")]
+const HORDE_GROAN_SMOOTHING: f32 = 3.0;
+#[cfg(feature = "audio")]
+const HORDE_GROAN_SAMPLE_RATE: u32 = 44_100;
+
+// Rich presence
+#[cfg(all(feature = "rich-presence", not(target_arch = "wasm32")))]
+const RICH_PRESENCE_INTERVAL_SEC: f32 = 15.0;
+
+// HUD
+const HUD_MINIMAP_SIZE: f32 = 180.0;
+const HUD_MINIMAP_SCALE: f32 = 12.0;
+const HUD_MIN_OPACITY: f32 = 0.2;
+const HUD_OPACITY_STEP: f32 = 0.1;
+const REAR_VIEW_WIDTH: f32 = 360.0;
+const REAR_VIEW_HEIGHT: f32 = 90.0;
+const REAR_VIEW_SCALE: f32 = 4.0;
+const COMPASS_SIZE: f32 = 64.0;
+const HEAT_BAR_WIDTH: f32 = 200.0;
+const HEAT_BAR_LOCKED_COLOR: Color = Color::rgb(0.9, 0.2, 0.15);
+// Zombies this close to the car weigh on the horde pressure gauge, tougher types count extra
+const HORDE_PRESSURE_RADIUS: f32 = 450.0;
+const HORDE_PRESSURE_FULL: f32 = 60.0;
+const HORDE_PRESSURE_SPITTER_WEIGHT: f32 = 1.5;
+const HORDE_PRESSURE_BLOATER_WEIGHT: f32 = 2.5;
+const HORDE_PRESSURE_BAR_WIDTH: f32 = 240.0;
+const HORDE_PRESSURE_SMOOTHING: f32 = 4.0;
+// Past this the bar turns red
+const HORDE_PRESSURE_HIGH: f32 = 0.75;
+const HORDE_PRESSURE_HIGH_COLOR: Color = Color::rgb(0.9, 0.2, 0.15);
+// Heading away from the finish this long makes the compass pulse
+const COMPASS_WRONG_WAY_SEC: f32 = 3.0;
+const COMPASS_WRONG_WAY_MIN_SPEED: f32 = 5.0;
+const COMPASS_PULSE_SPEED: f32 = 8.0;
+const COMPASS_WRONG_WAY_COLOR: Color = Color::rgb(0.95, 0.35, 0.3);
+const DAMAGE_INDICATOR_COLOR: Color = Color::rgba(0.9, 0.1, 0.1, 0.8);
+const DAMAGE_INDICATOR_THICKNESS: f32 = 10.0;
+const DAMAGE_INDICATOR_FADE_SEC: f32 = 1.0;
+// Screen side each indicator sits on, counter-clockwise starting from the right
+const DAMAGE_INDICATOR_SECTORS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+// Skull on the screen edge a group of zombies just spawned behind, same sectors as above
+const SPAWN_WARNING_COLOR: Color = Color::rgba(0.95, 0.25, 0.2, 0.9);
+const SPAWN_WARNING_SIZE: f32 = 32.0;
+const SPAWN_WARNING_INSET: f32 = 16.0;
+const SPAWN_WARNING_FADE_SEC: f32 = 1.5;
+// A few stragglers don't count as a group, and far away spawns aren't an ambush
+const SPAWN_WARNING_MIN_GROUP: usize = 3;
+const SPAWN_WARNING_RANGE: f32 = 1800.0;
+
+// Debug
+#[cfg(feature = "debug-tools")]
+const DEBUG_HEATMAP_POOL_SIZE: usize = 512;
+#[cfg(feature = "debug-tools")]
+const DEBUG_HEATMAP_INTERVAL_SEC: f32 = 0.25;
+// Cells with this many zombies or more show at full strength
+#[cfg(feature = "debug-tools")]
+const DEBUG_HEATMAP_FULL_COUNT: f32 = 20.0;
+#[cfg(feature = "debug-tools")]
+const DEBUG_HEATMAP_MAX_ALPHA: f32 = 0.5;
+// Spawn ramp editor, the graph sits in the bottom left of the view
+#[cfg(feature = "debug-tools")]
+const DEBUG_RAMP_GRAPH_SIZE: Vec2 = Vec2::new(360.0, 200.0);
+#[cfg(feature = "debug-tools")]
+const DEBUG_RAMP_GRAPH_MARGIN: f32 = 40.0;
+#[cfg(feature = "debug-tools")]
+const DEBUG_RAMP_VALUE_STEP: f32 = 0.05;
+#[cfg(feature = "debug-tools")]
+const DEBUG_RAMP_PROGRESS_STEP: f32 = 0.02;
+#[cfg(all(feature = "debug-tools", not(target_arch = "wasm32")))]
+const DEBUG_RAMP_EXPORT_PATH: &str = "assets/tuning.ron";
 
 // UI
 const COLOR_BROWN: Color = Color::rgb(0.5, 0.25, 0.33);
@@ -55,8 +750,198 @@ const COLOR_ORANGE: Color = Color::rgb(0.85, 0.61, 0.38);
 const COLOR_LIGHT_ORANGE: Color = Color::rgb(1.0, 0.94, 0.85);
 
 // Textures
+// Whole sprite sheet, plus the atlases from the manifest once it loads
 #[derive(Resource)]
-struct GlobalTextureHandle(Option<Handle<TextureAtlas>>);
+struct GlobalTextureHandle(
+    Option<Handle<TextureAtlas>>,
+    HashMap<AtlasKind, Handle<TextureAtlas>>,
+);
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+enum AtlasKind {
+    Characters,
+    Vehicles,
+    Environment,
+    Ui,
+}
+// Read from assets/sprites.atlases.ron, each atlas can have its own image and tile size
+#[derive(Asset, TypePath, Deserialize)]
+struct AtlasManifest {
+    atlases: Vec<AtlasEntry>,
+}
+#[derive(Deserialize)]
+struct AtlasEntry {
+    kind: AtlasKind,
+    path: String,
+    tile_size: (f32, f32),
+    columns: usize,
+    rows: usize,
+}
+#[derive(Resource, Default)]
+struct AtlasManifestHandle(Handle<AtlasManifest>);
+#[derive(Default)]
+struct AtlasManifestLoader;
+// Named tiles, the atlas kind decides which manifest entry they're read from
+#[derive(Clone, Copy)]
+enum SpriteId {
+    Zombie,
+    BigZombie,
+    Dog,
+    Survivor,
+    Bullet,
+    Nest,
+    CorpsePile,
+    // Plain square, tinted for pickups, posts, acid pools and the drone
+    Block,
+    RoadEnd,
+    RoadsideDecor,
+    Hatchback,
+    WreckedCar,
+    Muscle,
+    Van,
+    RoadEdgeLeft,
+    Road,
+    RoadEdgeRight,
+    CurveExitLeft,
+    CurveExitRight,
+    CurveEntryLeft,
+    CurveEntryRight,
+    CargoPlane,
+    Crate,
+    Debris,
+    HealthIcon,
+    HealthBar,
+    ArmorBar,
+    TurboIcon,
+    ProgressFlag,
+    ProgressCar,
+    SpawnWarning,
+    CrosshairCross,
+    CrosshairDot,
+    CrosshairCircle,
+}
+
+// Tuning
+// Gameplay balance read from assets/tuning.ron, missing fields keep their defaults
+#[derive(Asset, Resource, TypePath, Deserialize, Clone)]
+#[serde(default)]
+struct Tuning {
+    // Car
+    turn_speed: f32,
+    car_thrust: f32,
+    max_speed: f32,
+    friction: f32,
+    max_car_health: f32,
+    max_car_armor: f32,
+    turbo_boost: f32,
+    turbo_interval_sec: f32,
+    // Head on at the base max speed, scales with the impact speed
+    obstacle_damage: f32,
+    // On foot
+    on_foot_speed: f32,
+    on_foot_health: f32,
+    // Fraction of the max car health the spare vehicle starts with
+    spare_vehicle_health: f32,
+    // Same for a parked car taken over on the road
+    commandeer_health: f32,
+    // Guns and Bullets
+    bullet_speed: f32,
+    bullet_spawn_interval: f32,
+    pistol_heat: WeaponHeat,
+    smg_heat: WeaponHeat,
+    shotgun_heat: WeaponHeat,
+    // Speed knocked out of the car per shot, away from the aim. Off on Easy
+    pistol_recoil: f32,
+    smg_recoil: f32,
+    shotgun_recoil: f32,
+    // Venting locks the gun for this fraction of the overheat time, scaled by the heat dumped
+    vent_lockout_factor: f32,
+    // Pickups
+    armor_pickup_value: f32,
+    // Zombies
+    zombie_speed: f32,
+    zombie_attack: f32,
+    // A bite goes through as this many regular hits, once per cooldown
+    zombie_bite_hits: usize,
+    zombie_spawn_budget: usize,
+    // Fraction of the max zombies out at a given progress, (progress, fraction) points in order
+    spawn_ramp: Vec<(f32, f32)>,
+    spitter_chance: f64,
+    bloater_chance: f64,
+    // Chance for a spawn to be a whole pack of dogs instead
+    dog_pack_chance: f64,
+    dog_speed: f32,
+    dog_windup_sec: f32,
+    nest_spawn_interval_sec: f32,
+    nest_spawn_count: usize,
+    // Per difficulty scaling on top of the values above
+    easy: DifficultyProfile,
+    moderate: DifficultyProfile,
+    hard: DifficultyProfile,
+}
+// Multipliers for the run's difficulty, picked from the tuning at GameInit
+#[derive(Resource, Deserialize, Clone, Copy)]
+#[serde(default)]
+struct DifficultyProfile {
+    zombie_speed: f32,
+    zombie_attack: f32,
+    spitter_ratio: f32,
+    bloater_ratio: f32,
+    dog_ratio: f32,
+    dog_speed: f32,
+    // Scales the telegraph before a lunge, shorter is harder to dodge
+    dog_windup: f32,
+    // Armor and slow motion pickups on the road
+    pickup_frequency: f32,
+    boss_health: f32,
+    // Zombie perception radii
+    sight_range: f32,
+    gunshot_hearing: f32,
+    engine_hearing: f32,
+}
+// Sustained fire heats the gun up, at 1.0 it locks up for overheat_sec
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+struct WeaponHeat {
+    per_shot: f32,
+    cool_per_sec: f32,
+    overheat_sec: f32,
+}
+#[derive(Resource, Default)]
+struct TuningHandle(Handle<Tuning>);
+#[derive(Default)]
+struct TuningLoader;
+// Areas around the car zombies spawn in, read from assets/zombies.spawns.ron
+#[derive(Asset, TypePath, Resource, Deserialize, Clone)]
+struct SpawnPatterns {
+    patterns: Vec<SpawnPattern>,
+    // Added on top during the finale, by how the road ends
+    #[serde(default)]
+    finales: HashMap<FinishLayout, Vec<SpawnPattern>>,
+}
+#[derive(Deserialize, Clone)]
+struct SpawnPattern {
+    // Offset from the car in pixels, +y is up the road
+    x: (f32, f32),
+    y: (f32, f32),
+    // Half the spawns go to the other side of the car
+    #[serde(default)]
+    mirror: bool,
+    // Odds against the other patterns, 0 turns it off
+    weight: PerDifficulty,
+    // Car progress the pattern starts at
+    #[serde(default)]
+    min_progress: PerDifficulty,
+}
+#[derive(Deserialize, Clone, Copy, Default)]
+struct PerDifficulty {
+    easy: f32,
+    moderate: f32,
+    hard: f32,
+}
+#[derive(Resource, Default)]
+struct SpawnPatternsHandle(Handle<SpawnPatterns>);
+#[derive(Default)]
+struct SpawnPatternsLoader;
 #[derive(Component)]
 struct GameEntity;
 
@@ -71,62 +956,721 @@ struct Speed(f32);
 struct Turbo(Stopwatch);
 #[derive(Component)]
 struct Obstacle;
+// Parked car the player took over, drives with that model's stats instead of the garage car
+#[derive(Component)]
+struct Commandeered(CarModel);
+#[derive(Component)]
+struct CommandeerPrompt;
 #[derive(Resource)]
 struct VehicleObstacleTiles(Vec<VehicleObstacle>);
 #[derive(Event)]
 struct PlayerDeadEvent;
+// Out of lives for this run, after the on-foot escape
+#[derive(Event)]
+struct PlayerOutEvent;
 #[derive(Resource)]
 struct PlayerScore(u32);
+
+// Index of the keyboard/gamepad focused menu button, in on-screen order
+#[derive(Resource)]
+struct MenuFocus(Option<usize>);
+// States that led to the current one, Esc walks back through them
+#[derive(Resource, Default)]
+struct MenuHistory(Vec<GameState>);
+#[derive(Event)]
+struct ScorePopupEvent {
+    pos: Vec3,
+    score: u32,
+}
+#[derive(Component)]
+struct CrosshairImage;
+#[derive(Component)]
+struct AimLine;
+#[derive(Component, Default)]
+struct ScorePopup {
+    active: bool,
+    age: f32,
+}
 #[derive(Resource)]
 struct PlayerPos(Vec3);
 
+// On foot
+#[derive(Component)]
+struct OnFoot;
+#[derive(Component)]
+struct SpareVehicle;
+#[derive(Resource, Default)]
+struct OnFootEscape {
+    active: bool,
+    used: bool,
+    timer: Stopwatch,
+}
+
 // Bullet
 #[derive(Component)]
-struct Bullet(Instant);
+struct Bullet(Stopwatch);
 #[derive(Component)]
 struct BulletDirection(Vec3);
+// Already counted towards accuracy, bullets pierce through the horde
+#[derive(Component)]
+struct BulletLanded;
+// Fired by the companion drone, weak against anything that takes more than one hit
+#[derive(Component)]
+struct DroneShot;
+// Companion that trails the car and picks off the closest zombie in range
+#[derive(Component)]
+struct Drone {
+    fire: Stopwatch,
+    bob: Stopwatch,
+    // Position before the bob is added on
+    hover: Vec2,
+}
+// Fired with the ricochet upgrade, bounces once off obstacles
+#[derive(Component)]
+struct Ricochet {
+    bounced: bool,
+}
+#[derive(Component, Default)]
+struct BulletTrail {
+    active: bool,
+    bullet: Option<Entity>,
+    origin: Vec2,
+    head: Vec2,
+    // Seconds since the trail was handed out, oldest get recycled first
+    age: f32,
+    // Seconds since its bullet went away
+    fade: f32,
+}
+#[derive(Component, Default)]
+struct MuzzleFlash(f32);
+
+// Particles
+#[derive(Component, Default)]
+struct Particle {
+    active: bool,
+    kind: ParticleKind,
+    pos: Vec2,
+    vel: Vec2,
+    height: f32,
+    vertical_vel: f32,
+    spin: f32,
+    age: f32,
+}
+#[derive(Default, Clone, Copy)]
+enum ParticleKind {
+    #[default]
+    Casing,
+    Dirt,
+    Spark,
+    Toxic,
+}
+#[derive(Event)]
+struct ParticleBurstEvent {
+    pos: Vec2,
+    // Main direction the burst flies off in
+    dir: Vec2,
+    kind: ParticleKind,
+}
 
 // Roads
 #[derive(Component)]
 struct Road;
 #[derive(Resource)]
-struct RoadTiles(HashSet<(i32, i32)>);
+struct RoadTiles(HashMap<(i32, i32), RoadSurface>);
+#[derive(Component)]
+struct RoadChunk;
+#[derive(Resource, Default)]
+struct RoadStream {
+    // None once the whole road has been generated
+    generator: Option<RoadGenerator>,
+    chunks: HashMap<i32, RoadChunkData>,
+    // Chunks currently in the world, as their parent entity
+    spawned: HashMap<i32, Entity>,
+}
+// Lays the road out a row at a time, always consuming the seeded rng in the same order
+// so streaming it in lazily still gives the same road for a seed
+struct RoadGenerator {
+    rng: ChaCha8Rng,
+    next_row: i32,
+    top_y: i32,
+    offset: i32,
+    n_offset: i32,
+    p_offset: i32,
+    // Surface patch as (surface, first lane, last lane, rows left)
+    patch: (RoadSurface, i32, i32, u32),
+    boss_rush: bool,
+    pickup_frequency: f32,
+    finish: Option<FinishLayout>,
+}
+#[derive(Default)]
+struct RoadChunkData {
+    sprites: Vec<RoadSprite>,
+    obstacles: Vec<RoadSprite>,
+    // Only spawned the first time the chunk streams in
+    props: Vec<RoadProp>,
+}
+struct RoadSprite {
+    id: SpriteId,
+    pos: Vec3,
+    scale: f32,
+    color: Color,
+}
+enum RoadProp {
+    SlowMoPickup(Vec3),
+    ArmorPickup(Vec3),
+    StatusAmmoPickup(Vec3, StatusKind),
+    Nest(Vec3),
+    Ramp(Vec3),
+    // Gate center, road width and the panel beside it
+    TollGate(Vec3, f32, Vec2),
+    // Barrier center, road width and the drawbridge lever
+    Finish(FinishLayout, Vec3, f32, Vec2),
+}
+#[derive(Default, Clone, Copy, PartialEq)]
+enum RoadSurface {
+    #[default]
+    Asphalt,
+    DamagedAsphalt,
+    Gravel,
+    Ice,
+}
+
+// Pickups
+#[derive(Component)]
+struct ArmorPickup;
+#[derive(Component)]
+struct SlowMoPickup;
+#[derive(Component)]
+struct StatusAmmoPickup(StatusKind);
+
+// Status effects
+#[derive(Clone, Copy, PartialEq)]
+enum StatusKind {
+    Frozen,
+    Burning,
+    Stunned,
+}
+// On a zombie, one at a time, the tint goes back to base_color when it wears off
+#[derive(Component)]
+struct StatusEffect {
+    kind: StatusKind,
+    left: f32,
+    base_color: Color,
+    // Burning only, seconds until it tries to ignite the neighbors
+    spread: f32,
+}
+// Loaded from a pickup, fired before the regular rounds
+#[derive(Resource, Default)]
+struct StatusAmmo {
+    kind: Option<StatusKind>,
+    rounds: u32,
+}
+// Bullet that puts a status on the zombie instead of killing it
+#[derive(Component)]
+struct StatusRound(StatusKind);
+
+// Ramps
+#[derive(Component)]
+struct Ramp;
+// In the air off a ramp, zombies and obstacles can't reach the car
+#[derive(Component)]
+struct Airborne {
+    elapsed: f32,
+    base_scale: Vec3,
+    shadow: Entity,
+}
+#[derive(Component)]
+struct CarShadow;
+#[derive(Component)]
+struct SlowMoOverlay;
+#[derive(Resource, Default)]
+struct SlowMotion {
+    // Real time since the pickup, None while time runs normally
+    timer: Option<Stopwatch>,
+    tick_sound: Option<Handle<Pitch>>,
+}
+#[derive(Event)]
+struct HitStopEvent(Duration);
+// Fired as the car's progress crosses each of PROGRESS_MILESTONES
+#[derive(Event)]
+struct ProgressMilestoneEvent(f32);
+// Time since the final stretch began, None before it
+#[derive(Resource, Default)]
+struct Finale(Option<Stopwatch>);
+#[derive(Component)]
+struct FinaleVignette;
+#[derive(Component)]
+struct FinaleBanner;
+#[derive(Component)]
+struct DustStormOverlay;
+#[derive(Resource, Default)]
+struct HitStop(Option<Timer>);
+// Time since the run started, the car is held at the gates for the countdown
+#[derive(Resource, Default)]
+struct StartCountdown(Stopwatch);
+// Half of the start gate, slides out to this side once the countdown is over
+#[derive(Component)]
+struct StartGate(f32);
+#[derive(Component)]
+struct StartBanner;
+// Closed across the road until the car holds position at its panel for long enough
+#[derive(Component)]
+struct TollGate {
+    width: f32,
+    panel: Vec2,
+    hold: f32,
+    // 0 closed to 1 fully open, the halves slide apart in between
+    open: f32,
+    alarm: f32,
+}
+#[derive(Component)]
+struct TollGateHalf(f32);
+#[derive(Component)]
+struct TollGateFill;
+#[derive(Component)]
+struct TollGatePrompt;
+// Closes off the end of the road until the run's finish objective is done
+#[derive(Component)]
+struct FinishBarrier {
+    layout: FinishLayout,
+    width: f32,
+    // Half the barrier's height
+    depth: f32,
+    // Drawbridge lever, off the road to one side
+    lever: Vec2,
+    // Seconds docked for the ferry or held at the lever for the drawbridge
+    hold: f32,
+    // Bunker gate health
+    health: f32,
+    // 0 closed to 1 passable, the ferry, bridge or gate moves in between
+    open: f32,
+    alarm: f32,
+}
+#[derive(Component)]
+struct FinishMover;
+#[derive(Component)]
+struct FinishFill;
+#[derive(Component)]
+struct FinishPrompt;
+// How the road ends, picked from the run seed
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum FinishLayout {
+    // Hold the dock until the ferry comes in
+    Ferry,
+    // Pull the lever beside the road to lower the bridge
+    Drawbridge,
+    // Ram the gate down
+    Bunker,
+}
 
 // Zombies
 #[derive(Component)]
 struct Zombie;
+// Outside every active camera view or folded into the tide, see cull_zombies
+#[derive(Component)]
+struct Culled;
+// Hanging on to the car as its child, the transform is local to the car
+#[derive(Component)]
+struct Latched;
+#[derive(Component, Default)]
+struct ZombieAi {
+    state: ZombieState,
+    // Where the car or a gunshot was last heard
+    last_heard: Vec2,
+    // Seconds since entering the state, or since the last noise while alert
+    timer: f32,
+    wander_dir: Vec2,
+    // On the roof of a parked car
+    climbing: bool,
+    // The last step is spread over the game time until the next turn
+    glide_from: Vec2,
+    glide_to: Vec2,
+    glide_elapsed: f32,
+    glide_sec: f32,
+}
+#[derive(Component)]
+struct ZombieAttack {
+    // Seconds until the next bite can land
+    cooldown: f32,
+    // Seconds left of the bite animation
+    bite: f32,
+    base_scale: f32,
+}
+#[derive(Default, Clone, Copy, PartialEq)]
+enum ZombieState {
+    #[default]
+    Idle,
+    Alert,
+    Chase,
+}
+#[derive(Event)]
+struct NoiseEvent {
+    pos: Vec2,
+    radius: f32,
+}
+#[derive(Event)]
+struct ZombieHitPlayer {
+    // Where the hit came from, for the damage indicators
+    from: Vec2,
+}
+#[derive(Component, Default)]
+struct Spitter(Stopwatch);
+// Fast and fragile, closes in then lunges at the car after a crouch
+#[derive(Component, Default)]
+struct ZombieDog {
+    cooldown: f32,
+    // Seconds left of the crouch, then of the lunge itself
+    windup: f32,
+    lunge: f32,
+    // Locked in when the crouch starts, so a telegraphed lunge can be dodged
+    lunge_dir: Vec2,
+}
+#[derive(Component, Default)]
+struct Bloater {
+    // Seconds of swelling left before the blast, set once the bloater is killed
+    fuse: Option<f32>,
+}
+// Zombie positions bucketed by cell, rebuilt every frame
+#[derive(Resource, Default)]
+struct ZombieGrid(HashMap<(i32, i32), Vec<(Entity, Vec2)>>);
+#[derive(Component)]
+struct AcidGlob {
+    origin: Vec2,
+    target: Vec2,
+    flight: Stopwatch,
+}
+#[derive(Component, Default)]
+struct AcidPool {
+    lifetime: Stopwatch,
+    // Time since the pool last burnt the car
+    tick: Stopwatch,
+}
+#[derive(Resource, Default)]
+struct ZombieScheduler {
+    // The horde as it was at the start of the current despawn sweep
+    despawn_queue: Vec<Entity>,
+    despawn_cursor: usize,
+    next_spawn_order: u64,
+}
+#[derive(Component, Clone, Copy)]
+struct SpawnOrder(u64);
+// The horde as it was when the pause menu opened, put back as-is on resume
+#[derive(Resource, Default)]
+struct HordeSnapshot {
+    zombies: Vec<(Entity, Transform)>,
+    despawn_cursor: usize,
+    next_spawn_order: u64,
+}
 #[derive(Event)]
-struct ZombieHitPlayer;
+struct ZombieKilledEvent {
+    pos: Vec3,
+}
+// Military plow that drives up the road clearing its lane, late in the run
+#[derive(Component)]
+struct StreetSweeper;
+// Flies across the road, lets go of its cargo once over drop_x
+#[derive(Component)]
+struct CargoPlane {
+    drop_x: f32,
+    cargo: Option<(AirdropKind, Vec2)>,
+}
+// Shadow on the landing spot, the falling cargo is its child
+#[derive(Component)]
+struct Airdrop {
+    kind: AirdropKind,
+    fall: Stopwatch,
+}
+#[derive(Component)]
+struct AirdropCargo;
+#[derive(Clone, Copy)]
+enum AirdropKind {
+    Crate,
+    Debris,
+}
+// The car is in the plow's wake, zombies can't get at it
+#[derive(Component)]
+struct Sheltered;
+#[derive(Component)]
+struct CorpsePile {
+    size: u32,
+    // Seconds since a corpse last landed on the pile
+    age: f32,
+}
+// Pooled stand-in for a grid cell of distant zombies, see update_tide_blobs
+#[derive(Component)]
+struct TideBlob;
+#[derive(Component, Default)]
+struct BloodSplat {
+    active: bool,
+    age: f32,
+}
+// 1.0 is full quality, drops while the framerate is below target
+#[derive(Resource)]
+struct AdaptiveQuality(f32);
+#[derive(Resource, Default)]
+struct CorpsePileGrid(HashMap<(i32, i32), Vec<(Vec2, f32)>>);
+// Parked car positions bucketed for the zombies climbing over them
+#[derive(Resource, Default)]
+struct ObstacleGrid(HashMap<(i32, i32), Vec<Vec2>>);
+#[derive(Component)]
+struct ZombieNest {
+    health: f32,
+    spawn_timer: Stopwatch,
+}
+
+// Boss rush
+#[derive(Resource, Default)]
+struct BossArena {
+    center: Vec2,
+    // Current and shrinking-towards half size of the fenced square
+    half_size: f32,
+    target_half_size: f32,
+    // Bosses beaten so far
+    boss: usize,
+    state: BossRushState,
+}
+#[derive(Default)]
+enum BossRushState {
+    // Still on the road, or driving through the open gate
+    #[default]
+    Entering,
+    Fighting,
+    Intermission(Stopwatch),
+    Done,
+}
+#[derive(Component)]
+struct Boss {
+    health: f32,
+    max_health: f32,
+    speed: f32,
+    attack_timer: Stopwatch,
+}
+// Post on one side of the arena, side 0 is the gate at the bottom
+#[derive(Component)]
+struct ArenaFence {
+    side: u8,
+    t: f32,
+}
+#[derive(Component)]
+struct BossRushText;
+// Waves and weapon drops for the score arena, the run's score is time survived plus kills
+#[derive(Resource, Default)]
+struct ScoreArena {
+    wave: usize,
+    wave_timer: Stopwatch,
+    pickup_timer: Stopwatch,
+    // Index into ARENA_WEAPONS of the next drop
+    next_weapon: usize,
+    survived: f32,
+}
+#[derive(Component)]
+struct WeaponPickup(Weapon);
+// Picked up in the arena, replaces the garage weapon for the rest of the run
+#[derive(Component)]
+struct HeldWeapon(Weapon);
+#[derive(Component)]
+struct ScoreArenaText;
+// Tallied during a run, folded into the profile's lifetime stats at game over
+#[derive(Resource, Default, Debug)]
+struct RunStats {
+    kills: u32,
+    shots: u32,
+    hits: u32,
+    distance: f32,
+    died: bool,
+    // Health lost in each stretch of the road
+    damage: [f32; DAMAGE_STRIP_BUCKETS],
+}
+
+// Recent log lines and the run as it was, written out if the game panics
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct CrashContext {
+    log: std::collections::VecDeque<String>,
+    seed: u64,
+    run: String,
+    settings: String,
+    run_stats: String,
+}
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Resource, Clone, Default)]
+struct CrashReport(std::sync::Arc<std::sync::Mutex<CrashContext>>);
+// Tracing layer copying every log event into the crash report
+#[cfg(not(target_arch = "wasm32"))]
+struct CrashLogLayer(CrashReport);
+// Ring of the last few seconds of frames, filled from the render threads
+#[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+#[derive(Resource, Default)]
+struct GifCapture {
+    frames: std::sync::Arc<std::sync::Mutex<std::collections::VecDeque<image::RgbImage>>>,
+    since_frame: f32,
+}
 
 // Stats
 #[derive(Resource)]
 struct CarHealth(f32);
 #[derive(Resource)]
+struct CarArmor(f32);
+#[derive(Resource, Default)]
+struct HealthRegen {
+    since_damage: f32,
+    last_health: f32,
+    is_active: bool,
+}
+#[derive(Resource)]
 struct CarProgress(f32);
+// Real time spent in the run, and when each split was reached
+#[derive(Resource, Default)]
+struct RunTimer {
+    elapsed: f32,
+    splits: Vec<f32>,
+}
+// Written out at the end of every run for speedrun verification
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Serialize)]
+struct SplitsExport<'a> {
+    seed: u64,
+    difficulty: Difficulty,
+    run_length: RunLength,
+    modifiers: &'a [Modifier],
+    daily: Option<u64>,
+    boss_rush: bool,
+    time: f32,
+    splits: &'a [f32],
+}
 #[derive(Component)]
 struct DebugText;
+#[cfg(feature = "debug-tools")]
+#[derive(Resource, Default)]
+struct DebugHeatmap(bool);
+#[cfg(feature = "debug-tools")]
+#[derive(Component)]
+struct HeatmapCell;
+// Live edits the spawn ramp in the tuning, F6 writes it back to the file
+#[cfg(feature = "debug-tools")]
+#[derive(Resource, Default)]
+struct DebugRampEditor {
+    is_open: bool,
+    selected: usize,
+}
+#[derive(Component)]
+struct MainCamera;
+#[derive(Component)]
+struct MinimapCamera;
+#[derive(Component)]
+struct RearViewCamera;
+#[derive(Component)]
+struct RearViewFrame;
+#[derive(Component)]
+struct LetterboxBar;
 
 // Music
+#[cfg(feature = "audio")]
 #[derive(Component)]
 struct BgMusic;
+#[cfg(feature = "audio")]
+#[derive(Component, Default)]
+struct HordeGroan {
+    intensity: f32,
+}
+// Procedural groan loop, there's no recorded track for it
+#[cfg(feature = "audio")]
+#[derive(Asset, TypePath)]
+struct HordeGroanAudio;
+#[cfg(feature = "audio")]
+struct HordeGroanDecoder {
+    sample_idx: u64,
+    phase: f32,
+    noise_seed: u32,
+    noise: f32,
+}
 #[derive(Component)]
-struct InGameMusic;
+struct MainMenuZombie {
+    waypoint: Vec2,
+    // Seconds left idling at the current waypoint
+    pause: f32,
+}
 #[derive(Component)]
-struct EffectGunShotMusic;
+struct MainMenuCar(Vec2);
+
+// Rich presence
+#[cfg(all(feature = "rich-presence", not(target_arch = "wasm32")))]
+#[derive(Resource)]
+struct RichPresence(Option<discord_rich_presence::DiscordIpcClient>);
+
+// UI
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+enum HudElement {
+    Health,
+    Turbo,
+    Progress,
+    Minimap,
+    Debug,
+    Timer,
+    Compass,
+    HordePressure,
+}
+// Where the player moved a HUD element to, relative to its anchor
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+struct HudLayout {
+    offset: (f32, f32),
+    opacity: f32,
+}
 #[derive(Component)]
-struct EffectZombieKillMusic;
+struct HudEditorComponent;
 #[derive(Component)]
-struct EffectZombieSpawnMusic;
+struct HudEditorHelpText;
 #[derive(Component)]
-struct MainMenuZombie(Vec2);
-
-// UI
+enum HudEditorButtonAction {
+    Reset,
+    Done,
+}
+#[derive(Clone, Copy)]
+enum HudAnchor {
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    Left,
+    Right,
+    BottomLeft,
+    BottomRight,
+}
 #[derive(Component)]
 struct GameUIHealthBar;
 #[derive(Component)]
+struct GameUIArmorBar;
+#[derive(Component)]
 struct GameUITurbo;
 #[derive(Component)]
+struct GameUIHeatBar;
+#[derive(Component)]
+struct GameUIHordePressure;
+#[derive(Component)]
 struct GameUICarProgress;
+#[derive(Component)]
+struct GameUIOnFootTimer;
+#[derive(Component)]
+struct GameUIRunTimer;
+// Needle pointing towards the end of the road, relative to where the car faces
+#[derive(Component, Default)]
+struct GameUICompass {
+    // Seconds spent heading away from the finish
+    wrong_way: f32,
+}
+// Screen edge bracket lighting up when hits come from its side
+#[derive(Component)]
+struct DamageIndicator {
+    sector: usize,
+    strength: f32,
+}
+// Screen edge skull showing where a group of zombies just came in from
+#[derive(Component)]
+struct SpawnWarning {
+    sector: usize,
+    strength: f32,
+}
 
 #[derive(Component)]
 struct MainMenuComponent;
@@ -139,67 +1683,519 @@ struct SettingsMenuComponent;
 #[derive(Component)]
 enum MainMenuButtonAction {
     Play,
+    DailyChallenge,
+    Coop,
+    Unlocks,
+    Statistics,
     Settings,
     Quit,
 }
 #[derive(Component)]
 enum PauseMenuButtonAction {
     Resume,
+    SkipTutorial,
+    Settings,
     // Restart,
     ExitToMainMenu,
 }
 #[derive(Component)]
+struct CheckpointShopComponent;
+#[derive(Component)]
+struct CheckpointShopText;
+#[derive(Component)]
+enum CheckpointShopButtonAction {
+    Buy(ShopItem),
+    Continue,
+}
+#[derive(Clone, Copy)]
+enum ShopItem {
+    Repair,
+    IncendiaryRounds,
+    ColdBarrel,
+}
+// Real seconds left before the shop closes on its own
+#[derive(Resource, Default)]
+struct CheckpointShop(f32);
+#[derive(Component)]
 enum GameOverMenuButtonAction {
     Restart,
     ExitToMainMenu,
 }
 #[derive(Component)]
 enum SettingsMenuButtonAction {
+    Page,
     Difficulty,
+    RunLength,
     EntityCount,
     Music,
     GodMode,
+    Letterbox,
+    HitStop,
+    CheckpointShop,
+    InstancedHorde,
+    AdaptiveQuality,
+    RearView,
+    TideRendering,
+    #[cfg(all(feature = "telemetry", not(target_arch = "wasm32")))]
+    Telemetry,
+    #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+    ClipRecording,
+    FpsCap,
+    BatterySaver,
     DebugInfo,
+    Gore,
+    SpawnWarnings,
+    PauseOnFocusLoss,
+    MouseSteering,
+    AutoFire,
+    Crosshair,
+    AimLine,
+    Drone,
+    DeadZone,
+    SteerSensitivity,
+    AimSensitivity,
+    Benchmark,
+    Hud(HudElement),
+    EditHudLayout,
+    Back,
+}
+#[derive(Resource, Default, Clone, Copy)]
+enum SettingsPage {
+    #[default]
+    General,
+    Hud,
+    Controls,
+    Performance,
+}
+#[derive(Component)]
+struct UnlocksMenuComponent;
+#[derive(Component)]
+enum UnlocksMenuButtonAction {
+    Page,
+    Item(UnlockItem),
+    Cosmetic(Cosmetic),
     ExitToMainMenu,
 }
-
-// wasd controls
-struct CarControls(bool, bool, bool, bool);
-
-enum VehicleObstacleType {
-    Car1,
-    Car2,
-    Car3,
-    // Truck,
+#[derive(Component)]
+struct StatsMenuComponent;
+#[derive(Component)]
+enum StatsMenuButtonAction {
+    RunHistory,
+    ExitToMainMenu,
 }
-
-struct VehicleObstacle {
-    pos: (f32, f32),
-    vehicle_type: VehicleObstacleType,
+#[derive(Component)]
+struct RunHistoryMenuComponent;
+#[derive(Component)]
+enum RunHistoryMenuButtonAction {
+    Page,
+    // Index into the profile's run history
+    Select(usize),
+    RetrySeed,
+    Back,
+}
+#[derive(Resource, Default)]
+struct RunHistoryMenu {
+    page: usize,
+    selected: Option<usize>,
+}
+#[derive(Resource, Default, Clone, Copy)]
+enum UnlocksPage {
+    #[default]
+    Weapons,
+    Cars,
+    Cosmetics,
+}
+
+// Profile, persisted across runs
+#[derive(Resource, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Profile {
+    total_score: u64,
+    unlocked: Vec<UnlockItem>,
+    weapon: Weapon,
+    // Equipped upgrades, on top of the weapon
+    upgrades: Vec<Upgrade>,
+    car: CarModel,
+    palette: CarPalette,
+    decal: Decal,
+    horn: HornSound,
+    turbo_sound: TurboSound,
+    // Best score for the most recent daily challenge, as (day, score)
+    daily_best: Option<(u64, u32)>,
+    // Mutators picked for the next regular run
+    mutators: Vec<Modifier>,
+    mutator_presets: [Vec<Modifier>; MUTATOR_PRESET_SLOTS],
+    tutorial_done: bool,
+    hud_layout: HashMap<HudElement, HudLayout>,
+    // Splits of the fastest finished run on each road length
+    best_splits: HashMap<RunLength, Vec<f32>>,
+    stats: LifetimeStats,
+    // Last RUN_HISTORY_LEN runs, oldest first
+    run_history: Vec<RunRecord>,
+    // Where run stats go with Settings > Performance > Send Run Stats on, e.g.
+    // "https://example.com/runs", nothing is sent while it's empty
+    telemetry_endpoint: String,
+}
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct LifetimeStats {
+    runs: u32,
+    deaths: u32,
+    kills: u64,
+    // Meters driven
+    distance: f32,
+    shots: u64,
+    hits: u64,
+    // Indexed by Difficulty
+    difficulty_runs: [u32; 3],
+    // Last STATS_RECENT_RUNS scores, oldest first
+    recent_scores: Vec<u32>,
+}
+#[derive(Serialize, Deserialize, Clone)]
+struct RunRecord {
+    // Days since the unix epoch
+    day: u64,
+    // Everything needed to drive the same road again, see RunConfig::run_code
+    code: String,
+    daily: bool,
+    score: u32,
+    outcome: RunOutcome,
+}
+#[derive(Serialize, Deserialize, Clone, Copy)]
+enum RunOutcome {
+    WrongWay,
+    Survived,
+    OutOfTime,
+    Mauled,
+}
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+enum Weapon {
+    #[default]
+    Pistol,
+    Smg,
+    Shotgun,
+}
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+enum Upgrade {
+    Ricochet,
+    Drone,
+}
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+enum CarModel {
+    #[default]
+    Hatchback,
+    Muscle,
+    Van,
+}
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+enum CarPalette {
+    #[default]
+    Factory,
+    Crimson,
+    Toxic,
+    Midnight,
+}
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+enum Decal {
+    #[default]
+    None,
+    RacingStripe,
+    Flag,
+}
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+enum HornSound {
+    #[default]
+    Classic,
+    Truck,
+    Clown,
+}
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+enum TurboSound {
+    #[default]
+    Classic,
+    Jet,
+    Rumble,
+}
+#[derive(Clone, Copy)]
+enum Cosmetic {
+    Decal,
+    Horn,
+    TurboSound,
+}
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+enum UnlockItem {
+    Weapon(Weapon),
+    Upgrade(Upgrade),
+    Car(CarModel),
+    Palette(CarPalette),
+}
+#[derive(Resource, Default)]
+struct GunCooldown {
+    fire: Stopwatch,
+    heat: f32,
+    // Seconds the gun stays locked after overheating or venting
+    lockout: f32,
+    // Seconds left of the shop's cold barrel buff
+    cold_barrel: f32,
+}
+// Right stick crosshair, relative to the car
+#[derive(Resource, Default)]
+struct GamepadAim(Vec2);
+// Kick from the gun, waiting to be applied and the sideways drift it left
+#[derive(Resource, Default)]
+struct CarRecoil {
+    impulse: Vec2,
+    drift: Vec2,
+}
+// Firing toggled on with the auto-fire setting, aimed with the right stick if RB toggled it
+#[derive(Resource, Default)]
+struct AutoFire {
+    is_firing: bool,
+    from_gamepad: bool,
+}
+
+// Run setup, fixed for the whole run so a seed reproduces the same road
+#[derive(Resource, Default)]
+struct RunConfig {
+    seed: u64,
+    difficulty: Difficulty,
+    run_length: RunLength,
+    modifiers: Vec<Modifier>,
+    // Days since the unix epoch for daily challenge runs
+    daily: Option<u64>,
+    boss_rush: bool,
+    // Fenced score attack away from the road
+    arena: bool,
+}
+// Separate rng per subsystem, all derived from the run seed, so an extra roll in one
+// doesn't shift what the others generate
+#[derive(Resource, Default)]
+struct RunRng {
+    seed: u64,
+    streams: HashMap<RngStream, ChaCha8Rng>,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum RngStream {
+    Road,
+    ZombieSpawns,
+    Loot,
+    Ai,
+    Finish,
+}
+// Scripted fixed seed run driven by an autopilot, frame times are reported at the end
+#[derive(Resource, Default)]
+struct Benchmark {
+    active: bool,
+    elapsed: f32,
+    frame_times: Vec<f32>,
+    zombie_counts: Vec<usize>,
+    max_entities: usize,
+    // Settings the benchmark overrides, put back once it's over
+    saved: Option<(EntityCount, bool, bool, FpsCap)>,
+}
+// Frame times and horde size over the run, only sampled with telemetry on
+#[derive(Resource, Default)]
+struct RunTelemetry {
+    frame_times: Vec<f32>,
+    max_zombies: usize,
+}
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+enum Modifier {
+    ExplodingZombies,
+    OneHitCar,
+    InfiniteTurbo,
+    NightOnly,
+    DoubleSpeedZombies,
+    NoTurbo,
+    MirroredControls,
+    // The camera is flipped so the road runs down the screen toward the finish
+    ReversedRoad,
+    UpsideDownCamera,
+    // Health heals back after a while without taking damage
+    RegeneratingArmor,
+}
+#[derive(Component)]
+struct NightOverlay;
+#[derive(Component)]
+struct MutatorsMenuComponent;
+#[derive(Component)]
+enum MutatorsMenuButtonAction {
+    StartBossRush,
+    StartArena,
+    Toggle(Modifier),
+    PresetSlot,
+    LoadPreset,
+    SavePreset,
+    StartRun,
+    ExitToMainMenu,
+}
+#[derive(Resource, Default)]
+struct MutatorPresetSlot(usize);
+#[derive(Component)]
+struct RunSetupMenuComponent;
+#[derive(Component)]
+struct RunSetupText;
+#[derive(Component)]
+struct RunSetupStatusText;
+#[derive(Component)]
+enum RunSetupButtonAction {
+    Start,
+    CopyRunCode,
+    PasteRunCode,
+    Back,
+}
+
+// Tutorial
+#[derive(Resource, Default)]
+struct Tutorial(Option<TutorialStep>);
+#[derive(Clone, Copy, PartialEq)]
+enum TutorialStep {
+    Steer,
+    Shoot,
+    Turbo,
+    Progress,
+}
+#[derive(Component)]
+struct TutorialPrompt;
+#[derive(Component)]
+struct TutorialDummy;
+
+// Radio
+// Chatter from the evac site, messages are in progress order
+#[derive(Asset, TypePath, Deserialize)]
+struct RadioScript {
+    messages: Vec<RadioMessage>,
+}
+#[derive(Deserialize, Clone)]
+struct RadioMessage {
+    // Car progress the message plays at
+    progress: f32,
+    speaker: String,
+    text: String,
+    // Voice line, relative to the assets folder
+    #[serde(default)]
+    audio: Option<String>,
+}
+#[derive(Resource, Default)]
+struct RadioScriptHandle(Handle<RadioScript>);
+#[derive(Default)]
+struct RadioScriptLoader;
+#[derive(Resource, Default)]
+struct Radio {
+    // Index of the next message to play this run
+    next: usize,
+    // Message on screen and seconds since it started typing
+    current: Option<(RadioMessage, f32)>,
+}
+#[derive(Component)]
+struct RadioBox;
+#[derive(Component)]
+struct RadioText;
+
+// Keyboard and gamepad input, mapped to -1.0..=1.0
+struct CarControls {
+    // Forward when positive, brakes when negative
+    throttle: f32,
+    // Left when positive
+    steer: f32,
+}
+
+enum VehicleObstacleType {
+    Car1,
+    Car2,
+    Car3,
+    // Dropped from a cargo plane
+    Debris,
+    // Truck,
 }
 
+struct VehicleObstacle {
+    pos: (f32, f32),
+    vehicle_type: VehicleObstacleType,
+}
+
+// Phases of an InGame frame, see main for the order
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+enum InGameSet {
+    // Player controls, the car moves and the gun fires here
+    Input,
+    // Zombies, bullets, the camera and the road streaming in
+    Simulation,
+    // Hits between all of the above, and what they lead to
+    Collision,
+    // HUD, overlays and effects showing the frame's outcome
+    Ui,
+}
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 enum GameState {
     #[default]
     LoadAssets,
     MainMenu,
     SettingsMenu,
+    UnlocksMenu,
+    StatsMenu,
+    RunHistory,
+    MutatorsMenu,
+    RunSetup,
+    Lobby,
+    HudEditor,
     GameInit,
     InGame,
     PauseMenu,
+    CheckpointShop,
     GameOver,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Debug)]
 struct GameSettings {
     difficulty: Difficulty,
     entity_count: EntityCount,
     music: bool,
     god_mode: bool,
     debug_info: bool,
+    show_health: bool,
+    show_turbo: bool,
+    show_progress: bool,
+    show_minimap: bool,
+    show_timer: bool,
+    show_compass: bool,
+    show_horde_pressure: bool,
+    rear_view: bool,
+    // Steer toward the cursor and hold right-click for throttle
+    mouse_steering: bool,
+    // A click, Q or RB starts and stops firing instead of holding it down
+    auto_fire: bool,
+    crosshair: Crosshair,
+    // Faint line from the car to the crosshair
+    aim_line: bool,
+    // The drone flies only when this is on and the upgrade is equipped
+    drone: bool,
+    stick_dead_zone: f32,
+    steer_sensitivity: f32,
+    aim_sensitivity: f32,
+    instanced_horde: bool,
+    letterbox: bool,
+    adaptive_quality: bool,
+    hit_stop: bool,
+    // Stop at the progress milestones to spend the run score
+    checkpoint_shop: bool,
+    run_length: RunLength,
+    fps_cap: FpsCap,
+    battery_saver: bool,
+    gore: Gore,
+    spawn_warnings: bool,
+    // Distant zombies drawn as crowd blobs instead of one sprite each
+    tide_rendering: bool,
+    // Anonymous run stats sent after each run, off unless the player turns it on
+    telemetry: bool,
+    // Alt-tabbing or hiding the browser tab opens the pause menu
+    pause_on_focus_loss: bool,
+    // Keeps the last few seconds around for F9, every grab is a GPU readback so it's opt-in
+    #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+    clip_recording: bool,
 }
 
-#[derive(Default)]
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug)]
 enum Difficulty {
     #[default]
     Easy,
@@ -207,7 +2203,43 @@ enum Difficulty {
     Hard,
 }
 
-#[derive(Default)]
+// Road rows, the zombie ramp and the finale follow the car's progress so they scale along
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum RunLength {
+    Short,
+    #[default]
+    Standard,
+    Marathon,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+enum FpsCap {
+    Thirty,
+    Sixty,
+    OneTwenty,
+    #[default]
+    Uncapped,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+enum Crosshair {
+    Off,
+    #[default]
+    Cross,
+    Dot,
+    Circle,
+}
+
+// Blood and corpses, for squeamish players and low-end machines
+#[derive(Default, Clone, Copy, Debug)]
+enum Gore {
+    Off,
+    Low,
+    #[default]
+    Full,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
 enum EntityCount {
     Hundred,
     FiveHundred,
@@ -220,69 +2252,181 @@ enum EntityCount {
 }
 
 fn main() {
-    App::new()
+    let default_plugins = DefaultPlugins
+        .set(ImagePlugin::default_nearest())
+        .set(WindowPlugin {
+            primary_window: Some(Window {
+                resizable: true,
+                // mode: WindowMode::Fullscreen,
+                focused: true,
+                // present_mode: PresentMode::Immediate,
+                resolution: (WW as f32, WH as f32).into(),
+                ..default()
+            }),
+            ..default()
+        });
+    // Native builds log through their own subscriber, it feeds the crash report too
+    #[cfg(not(target_arch = "wasm32"))]
+    let default_plugins = default_plugins.disable::<bevy::log::LogPlugin>();
+    #[cfg(not(target_arch = "wasm32"))]
+    let crash_report = CrashReport::install();
+
+    let mut app = App::new();
+    app
         // Before anything, meta check never, to be able to run on itch
         .insert_resource(AssetMetaCheck::Never)
         .add_state::<GameState>()
-        .add_plugins(
-            DefaultPlugins
-                .set(ImagePlugin::default_nearest())
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        resizable: true,
-                        // mode: WindowMode::Fullscreen,
-                        focused: true,
-                        // present_mode: PresentMode::Immediate,
-                        resolution: (WW as f32, WH as f32).into(),
-                        ..default()
-                    }),
-                    ..default()
-                }),
-        )
+        .add_plugins(default_plugins)
+        .add_plugins(HordeRenderPlugin)
+        // Adaptive quality goes by the fps in every build, not only with the debug overlay
         .add_plugins(FrameTimeDiagnosticsPlugin)
         // Events
         .add_event::<ZombieHitPlayer>()
         .add_event::<PlayerDeadEvent>()
+        .add_event::<PlayerOutEvent>()
+        .add_event::<ParticleBurstEvent>()
+        .add_event::<ReviveEvent>()
+        .add_event::<PingEvent>()
+        .add_event::<ChatEvent>()
+        .add_event::<ScorePopupEvent>()
+        .add_event::<ZombieKilledEvent>()
+        .init_resource::<StatusAmmo>()
+        .init_resource::<RunRng>()
+        .init_resource::<CheckpointShop>()
+        .add_event::<HitStopEvent>()
+        .add_event::<NoiseEvent>()
+        .add_event::<ProgressMilestoneEvent>()
         // Resources
         .insert_resource(ClearColor(Color::rgba_u8(
             BG_COLOR.0, BG_COLOR.1, BG_COLOR.2, 255,
         )))
         .insert_resource(Msaa::Off)
-        .insert_resource(GlobalTextureHandle(None))
-        .insert_resource(RoadTiles(HashSet::new()))
+        .insert_resource(GlobalTextureHandle(None, HashMap::new()))
+        .init_resource::<AtlasManifestHandle>()
+        .init_asset::<AtlasManifest>()
+        .init_asset_loader::<AtlasManifestLoader>()
+        .insert_resource(Tuning::default())
+        .insert_resource(DifficultyProfile::default())
+        .init_resource::<TuningHandle>()
+        .init_asset::<Tuning>()
+        .init_asset_loader::<TuningLoader>()
+        .insert_resource(SpawnPatterns::default())
+        .init_resource::<SpawnPatternsHandle>()
+        .init_asset::<SpawnPatterns>()
+        .init_asset_loader::<SpawnPatternsLoader>()
+        .insert_resource(Radio::default())
+        .init_resource::<RadioScriptHandle>()
+        .init_asset::<RadioScript>()
+        .init_asset_loader::<RadioScriptLoader>()
+        .insert_resource(RoadTiles(HashMap::new()))
+        .insert_resource(RoadStream::default())
         .insert_resource(VehicleObstacleTiles(Vec::new()))
-        .insert_resource(CarHealth(MAX_CAR_HEALTH))
+        .insert_resource(CarHealth(Tuning::default().max_car_health))
+        .insert_resource(CarArmor(Tuning::default().max_car_armor))
+        .insert_resource(HealthRegen::default())
         .insert_resource(CarProgress(0.0))
+        .insert_resource(RunTimer::default())
         .insert_resource(GameSettings::default())
+        .insert_resource(SettingsPage::default())
         .insert_resource(PlayerScore(0))
         .insert_resource(PlayerPos(Vec3::ZERO))
+        .insert_resource(OnFootEscape::default())
+        .insert_resource(ZombieScheduler::default())
+        .insert_resource(HordeSnapshot::default())
+        .insert_resource(CorpsePileGrid::default())
+        .insert_resource(ObstacleGrid::default())
+        .insert_resource(ZombieGrid::default())
+        .insert_resource(SlowMotion::default())
+        .insert_resource(HitStop::default())
+        .insert_resource(Finale::default())
+        .insert_resource(RunStats::default())
+        .insert_resource(StartCountdown::default())
+        .insert_resource(AdaptiveQuality(1.0))
+        .insert_resource(RunConfig::default())
+        .insert_resource(Benchmark::default())
+        .insert_resource(MenuFocus(None))
+        .insert_resource(Profile::load())
+        .insert_resource(UnlocksPage::default())
+        .insert_resource(MutatorPresetSlot::default())
+        .insert_resource(Tutorial::default())
+        .insert_resource(Coop::default())
+        .insert_resource(MenuHistory::default())
+        .insert_resource(BossArena::default())
+        .insert_resource(ScoreArena::default())
+        .insert_resource(Spectator::default())
+        .insert_resource(ReviveTimers::default())
+        .insert_resource(PingWheel::default())
+        .insert_resource(CoopChat::default())
+        .insert_resource(LobbyAddress("127.0.0.1".to_string()))
+        .insert_resource(GunCooldown::default())
+        .insert_resource(GamepadAim::default())
+        .insert_resource(AutoFire::default())
+        .insert_resource(CarRecoil::default())
+        .insert_resource(RunTelemetry::default())
         // Systems
         .add_systems(Startup, setup_camera)
-        .add_systems(Startup, setup_music)
-        .add_systems(Update, update_music)
+        .add_systems(Update, (update_letterbox, log_game_events, take_screenshot))
+        .add_systems(
+            Update,
+            (
+                record_menu_history,
+                navigate_back.after(record_menu_history),
+                pause_on_focus_loss.run_if(in_state(GameState::InGame)),
+            ),
+        )
         .add_systems(
             Update,
-            menu_buttons_update.run_if(not(in_state(GameState::InGame))),
+            (
+                update_menu_accessibility.before(menu_focus_navigation),
+                menu_focus_navigation
+                    .before(menu_buttons_update)
+                    .before(handle_main_menu_btn_click)
+                    .before(handle_settings_menu_btn_click)
+                    .before(handle_unlocks_menu_btn_click)
+                    .before(handle_mutators_menu_btn_click)
+                    .before(handle_lobby_btn_click)
+                    .before(handle_hud_editor_btn_click)
+                    .before(handle_pause_menu_btn_click)
+                    .before(handle_game_over_menu_btn_click),
+                menu_buttons_update,
+            )
+                .run_if(not(in_state(GameState::InGame))),
         )
         // Loading Systems
         .add_systems(OnEnter(GameState::LoadAssets), load_assets)
+        .add_systems(
+            Update,
+            (
+                apply_tuning,
+                apply_spawn_patterns,
+                apply_atlas_manifest,
+                apply_power_mode,
+                orient_main_camera,
+            ),
+        )
         // MainMenu Systems
         .add_systems(
             OnEnter(GameState::MainMenu),
             (
                 setup_main_menu,
                 cleanup_previous_game,
+                reset_run_state,
                 spawn_main_menu_zombies,
+                stop_benchmark,
             ),
         )
         .add_systems(
             Update,
-            (handle_main_menu_btn_click, update_main_menu_zombies)
+            (
+                handle_main_menu_btn_click,
+                update_main_menu_zombies,
+                update_main_menu_cars,
+            )
                 .run_if(in_state(GameState::MainMenu)),
         )
         .add_systems(
             OnExit(GameState::MainMenu),
-            (cleanup_main_menu, cleanup_main_menu_zombies),
+            (cleanup_main_menu, cleanup_main_menu_background),
         )
         // Settings Systems
         .add_systems(OnEnter(GameState::SettingsMenu), setup_settings_menu)
@@ -291,81 +2435,456 @@ fn main() {
             handle_settings_menu_btn_click.run_if(in_state(GameState::SettingsMenu)),
         )
         .add_systems(OnExit(GameState::SettingsMenu), cleanup_settings_menu)
+        // HUD Editor Systems
+        .add_systems(
+            OnEnter(GameState::HudEditor),
+            (setup_game_ui, setup_hud_editor),
+        )
+        .add_systems(
+            Update,
+            (handle_hud_editor_btn_click, update_hud_editor).run_if(in_state(GameState::HudEditor)),
+        )
+        .add_systems(OnExit(GameState::HudEditor), cleanup_hud_editor)
+        .add_systems(Update, apply_hud_layout)
+        // Unlocks Systems
+        .add_systems(OnEnter(GameState::UnlocksMenu), setup_unlocks_menu)
+        .add_systems(
+            Update,
+            handle_unlocks_menu_btn_click.run_if(in_state(GameState::UnlocksMenu)),
+        )
+        .add_systems(OnExit(GameState::UnlocksMenu), cleanup_unlocks_menu)
+        // Statistics Systems
+        .add_systems(OnEnter(GameState::StatsMenu), setup_stats_menu)
+        .add_systems(
+            Update,
+            handle_stats_menu_btn_click.run_if(in_state(GameState::StatsMenu)),
+        )
+        .add_systems(OnExit(GameState::StatsMenu), cleanup_stats_menu)
+        .insert_resource(RunHistoryMenu::default())
+        .add_systems(OnEnter(GameState::RunHistory), setup_run_history_menu)
+        .add_systems(
+            Update,
+            handle_run_history_menu_btn_click.run_if(in_state(GameState::RunHistory)),
+        )
+        .add_systems(OnExit(GameState::RunHistory), cleanup_run_history_menu)
+        // Mutators Systems
+        .add_systems(OnEnter(GameState::MutatorsMenu), setup_mutators_menu)
+        .add_systems(
+            Update,
+            handle_mutators_menu_btn_click.run_if(in_state(GameState::MutatorsMenu)),
+        )
+        .add_systems(OnExit(GameState::MutatorsMenu), cleanup_mutators_menu)
+        // Run Setup Systems
+        .add_systems(OnEnter(GameState::RunSetup), setup_run_setup_menu)
+        .add_systems(
+            Update,
+            (handle_run_setup_btn_click, update_run_setup_menu)
+                .run_if(in_state(GameState::RunSetup)),
+        )
+        .add_systems(OnExit(GameState::RunSetup), cleanup_run_setup_menu)
+        // Co-op Systems
+        .add_systems(OnEnter(GameState::Lobby), setup_lobby_menu)
+        .add_systems(
+            Update,
+            (handle_lobby_btn_click, update_lobby_menu).run_if(in_state(GameState::Lobby)),
+        )
+        .add_systems(OnExit(GameState::Lobby), cleanup_lobby_menu)
+        .add_systems(OnEnter(GameState::MainMenu), leave_coop_session)
+        .add_systems(Update, (net_receive, net_send_state).chain())
+        .add_systems(
+            Update,
+            (
+                update_remote_cars
+                    .after(net_receive)
+                    .in_set(InGameSet::Simulation),
+                (
+                    handle_player_out,
+                    update_spectator,
+                    handle_revive,
+                    update_revive_teammates,
+                )
+                    .in_set(InGameSet::Collision),
+                update_ping_wheel.run_if(in_coop).in_set(InGameSet::Input),
+                (spawn_pings, update_pings, update_chat_text)
+                    .chain()
+                    .in_set(InGameSet::Ui),
+            ),
+        )
+        // Typing swallows the keyboard before anything else in the run reads it
+        .add_systems(
+            Update,
+            update_chat_input
+                .run_if(in_state(GameState::InGame))
+                .run_if(in_coop)
+                .before(InGameSet::Input)
+                .before(navigate_back),
+        )
         // PauseMenu Systems
-        .add_systems(OnEnter(GameState::PauseMenu), setup_pause_menu)
+        .add_systems(
+            OnEnter(GameState::PauseMenu),
+            (setup_pause_menu, freeze_horde),
+        )
         .add_systems(
             Update,
             handle_pause_menu_btn_click.run_if(in_state(GameState::PauseMenu)),
         )
-        .add_systems(OnExit(GameState::PauseMenu), cleanup_pause_menu)
+        .add_systems(
+            OnExit(GameState::PauseMenu),
+            (cleanup_pause_menu, restore_horde),
+        )
+        // CheckpointShop Systems
+        .add_systems(
+            OnEnter(GameState::CheckpointShop),
+            (setup_checkpoint_shop, freeze_horde),
+        )
+        .add_systems(
+            Update,
+            (handle_checkpoint_shop_btn_click, update_checkpoint_shop)
+                .chain()
+                .run_if(in_state(GameState::CheckpointShop)),
+        )
+        .add_systems(
+            OnExit(GameState::CheckpointShop),
+            (cleanup_checkpoint_shop, restore_horde),
+        )
         // GameOver Systems
-        .add_systems(OnEnter(GameState::GameOver), setup_game_over_menu)
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            (
+                apply_score_multiplier,
+                record_run_splits,
+                record_lifetime_stats,
+                record_run_history,
+                send_run_telemetry,
+                bank_run_score,
+                setup_game_over_menu,
+            )
+                .chain(),
+        )
         .add_systems(
             Update,
             handle_game_over_menu_btn_click.run_if(in_state(GameState::GameOver)),
         )
         .add_systems(
             OnExit(GameState::GameOver),
-            (cleanup_game_over_menu, cleanup_previous_game),
+            (
+                cleanup_game_over_menu,
+                cleanup_previous_game,
+                reset_run_state,
+            ),
         )
         // GameInit Systems
         .add_systems(
             OnEnter(GameState::GameInit),
-            (setup_game, spawn_road, setup_game_ui),
+            (
+                select_difficulty_profile.before(spawn_road),
+                setup_game,
+                setup_slow_mo_overlay,
+                setup_finale,
+                setup_night_overlay,
+                setup_dust_storm_overlay,
+                setup_tutorial,
+                setup_radio,
+                (setup_commandeer_prompt, setup_toll_gate_prompt),
+                setup_start_zone,
+                spawn_road,
+                setup_game_ui,
+                (setup_damage_indicators, setup_spawn_warnings),
+                setup_minimap,
+                setup_rear_view,
+                (setup_score_popups, setup_crosshair, setup_coop_comms),
+                (setup_blood_splats, setup_tide_blobs),
+                setup_bullet_vfx,
+                setup_boss_rush_ui,
+                setup_score_arena,
+            ),
         )
         .add_systems(
             Update,
             mark_game_setup_done.run_if(in_state(GameState::GameInit)),
         )
+        .add_systems(OnExit(GameState::InGame), hide_crosshair)
         // InGame Systems
+        // Every frame runs Input, Simulation, Collision then Ui, with commands applied in
+        // between so each phase sees what the one before spawned, moved or despawned
+        .configure_sets(
+            Update,
+            (
+                InGameSet::Input,
+                InGameSet::Simulation,
+                InGameSet::Collision,
+                InGameSet::Ui,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        )
         .add_systems(
             Update,
             (
-                car_manual_input_system,
-                bullet_hit_zombie,
-                check_obstacle_collision,
-                check_zombie_collision,
-                handle_zombie_player_hit,
-                update_car_progress,
+                apply_deferred
+                    .after(InGameSet::Input)
+                    .before(InGameSet::Simulation),
+                apply_deferred
+                    .after(InGameSet::Simulation)
+                    .before(InGameSet::Collision),
+                apply_deferred
+                    .after(InGameSet::Collision)
+                    .before(InGameSet::Ui),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                update_start_zone.before(car_manual_input_system),
+                car_manual_input_system
+                    .run_if(hit_stop_inactive)
+                    .run_if(start_countdown_done),
+                commandeer_vehicle.after(car_manual_input_system),
+                on_foot_input_system.run_if(hit_stop_inactive),
                 handle_turbo_input,
+                handle_horn_input,
+                update_gamepad_aim,
+                update_auto_fire,
+                shoot_gun.after(update_gamepad_aim).after(update_auto_fire),
+                update_gun_heat.after(shoot_gun),
+                apply_car_recoil.after(shoot_gun),
+            )
+                .in_set(InGameSet::Input),
+        )
+        .add_systems(
+            Update,
+            (
+                update_ramps,
+                update_car_progress.run_if(road_run),
+                update_on_foot_escape,
+                // Parked cars first, the horde climbs over them
+                update_obstacle_grid.before(update_zombies),
                 update_zombies,
-                handle_camera_zoom,
+                update_zombie_dogs.after(update_zombies),
+                cull_zombies
+                    .after(camera_follow_player)
+                    .after(update_zombies),
                 despawn_zombies,
-                spawn_zombies,
+                spawn_zombies.run_if(road_run),
+                spawn_nest_zombies.run_if(road_run),
+                update_bullet,
                 despawn_bullets,
+                handle_camera_zoom,
                 camera_follow_player,
-                update_bullet,
-                handle_escape_key,
+                stream_road.run_if(road_run).after(camera_follow_player),
+                update_tutorial,
+            )
+                .in_set(InGameSet::Simulation),
+        )
+        .add_systems(
+            Update,
+            (
+                (update_spitters, update_acid_globs, update_acid_pools).chain(),
+                update_street_sweeper.run_if(road_run),
+                (update_cargo_planes, land_airdrops)
+                    .chain()
+                    .run_if(road_run),
+                update_toll_gates.run_if(road_run),
+                update_finish_barrier.run_if(road_run),
+                (update_score_arena, update_weapon_pickups).run_if(arena_run),
+            )
+                .in_set(InGameSet::Simulation),
+        )
+        .add_systems(
+            Update,
+            (
+                // A bullet is used up by the first nest, boss or obstacle it hits. The flushes
+                // in between drop it before the next system looks, so it can't score twice
+                (
+                    bullet_hit_nest,
+                    apply_deferred,
+                    bullet_hit_boss,
+                    apply_deferred,
+                    bullet_hit_obstacle,
+                    apply_deferred,
+                    bullet_hit_zombie,
+                )
+                    .chain(),
+                check_obstacle_collision,
+                check_zombie_collision,
+                check_armor_pickup,
+                check_corpse_pile_collision,
+                update_latched_zombies.after(check_zombie_collision),
+                handle_zombie_player_hit,
+                update_health_regen.after(handle_zombie_player_hit),
                 handle_player_dead_event,
+                handle_game_complete.run_if(road_run),
+                explode_killed_zombies,
+                handle_zombie_corpses,
+                update_blood_splats,
+                update_corpse_pile_grid,
+            )
+                .in_set(InGameSet::Collision),
+        )
+        .add_systems(
+            Update,
+            (
+                (update_zombie_grid, update_bloaters)
+                    .chain()
+                    .after(bullet_hit_zombie)
+                    .after(check_zombie_collision)
+                    .before(handle_zombie_player_hit),
+                update_drone.after(update_zombie_grid),
+                update_status_effects
+                    .after(update_zombie_grid)
+                    .before(update_bloaters),
+                (update_boss_arena, update_bosses)
+                    .chain()
+                    .before(bullet_hit_boss),
+                (handle_boss_defeated, update_boss_rush_ui)
+                    .chain()
+                    .after(bullet_hit_boss)
+                    .before(handle_zombie_player_hit),
+                check_slow_mo_pickup,
+                check_status_ammo_pickup,
+                update_slow_motion,
+                update_hit_stop
+                    .after(check_slow_mo_pickup)
+                    .after(update_slow_motion)
+                    .after(check_obstacle_collision)
+                    .after(bullet_hit_nest),
+            )
+                .in_set(InGameSet::Collision),
+        )
+        .add_systems(
+            Update,
+            (
+                update_score_popups,
+                update_hud_visibility,
+                update_minimap,
+                update_rear_view,
                 update_game_ui_health_bar,
+                update_game_ui_armor_bar,
+                update_game_ui_heat_bar,
+                update_game_ui_horde_pressure,
                 update_game_ui_turbo,
                 update_game_ui_car_progress,
-                handle_game_complete,
+                update_run_timer,
+                update_game_ui_run_timer.after(update_run_timer),
+                update_game_ui_compass.run_if(road_run),
+                update_damage_indicators,
+                update_spawn_warnings,
+                update_tide_blobs.after(update_zombie_grid),
+                update_crosshair.after(camera_follow_player),
             )
-                .run_if(in_state(GameState::InGame)),
+                .in_set(InGameSet::Ui),
         )
         .add_systems(
             Update,
-            update_debug_text
-                // .run_if(on_timer(Duration::from_secs_f32(1.0)))
-                .run_if(in_state(GameState::InGame)),
+            (
+                (
+                    emit_progress_milestones,
+                    start_finale.after(emit_progress_milestones),
+                    open_checkpoint_shop.after(emit_progress_milestones),
+                    update_finale,
+                    update_radio,
+                    update_dust_storm_overlay,
+                )
+                    .run_if(road_run),
+                update_benchmark,
+                update_adaptive_quality,
+                track_run_stats,
+                record_telemetry_frame,
+            )
+                .in_set(InGameSet::Ui),
         )
+        // VFX Systems
         .add_systems(
             Update,
-            shoot_gun
-                .run_if(on_timer(Duration::from_secs_f32(BULLET_SPAWN_INTERVAL)))
-                .run_if(in_state(GameState::InGame)),
-        )
-        // .add_systems(Update, close_on_esc)
-        .run();
+            (
+                update_muzzle_flash,
+                update_bullet_trails,
+                emit_casings,
+                emit_dirt,
+                update_particles.after(emit_casings).after(emit_dirt),
+            )
+                .in_set(InGameSet::Ui),
+        );
+    // app.add_systems(Update, close_on_esc);
+
+    #[cfg(feature = "audio")]
+    app.add_audio_source::<HordeGroanAudio>()
+        .add_systems(Startup, setup_music)
+        .add_systems(Update, update_music)
+        .add_systems(Update, update_finale_music)
+        .add_systems(OnEnter(GameState::GameInit), setup_horde_groan)
+        .add_systems(Update, update_horde_groan.in_set(InGameSet::Ui))
+        .add_systems(OnExit(GameState::InGame), pause_horde_groan);
+
+    #[cfg(feature = "debug-tools")]
+    app.insert_resource(DebugHeatmap::default())
+        .insert_resource(DebugRampEditor::default())
+        .add_systems(OnEnter(GameState::GameInit), setup_debug_heatmap)
+        .add_systems(
+            Update,
+            (
+                update_debug_text,
+                toggle_debug_heatmap,
+                update_debug_heatmap
+                    .after(toggle_debug_heatmap)
+                    .after(update_zombie_grid)
+                    .run_if(bevy::time::common_conditions::on_timer(
+                        Duration::from_secs_f32(DEBUG_HEATMAP_INTERVAL_SEC),
+                    )),
+                draw_debug_spawn_zones,
+                update_debug_ramp_editor,
+                draw_debug_ramp_editor.after(update_debug_ramp_editor),
+            )
+                .in_set(InGameSet::Ui),
+        );
+
+    // The browser paces the web build, sleeping there would block its main thread
+    #[cfg(not(target_arch = "wasm32"))]
+    app.add_systems(Last, limit_frame_rate);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    app.insert_resource(crash_report)
+        .add_systems(Update, update_crash_report);
+
+    #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+    app.insert_resource(GifCapture::default()).add_systems(
+        Update,
+        (
+            // F12 gets the frame's only screenshot slot, the clip skips a frame instead
+            record_gif_frames
+                .run_if(in_state(GameState::InGame))
+                .after(take_screenshot),
+            save_gif_capture,
+        ),
+    );
+
+    #[cfg(all(feature = "rich-presence", not(target_arch = "wasm32")))]
+    app.add_systems(Startup, setup_rich_presence).add_systems(
+        Update,
+        update_rich_presence.run_if(bevy::time::common_conditions::on_timer(
+            Duration::from_secs_f32(RICH_PRESENCE_INTERVAL_SEC),
+        )),
+    );
+
+    app.run();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn load_assets(
     mut next_state: ResMut<NextState<GameState>>,
     mut global_texture_handle: ResMut<GlobalTextureHandle>,
+    mut tuning_handle: ResMut<TuningHandle>,
+    mut spawn_patterns_handle: ResMut<SpawnPatternsHandle>,
+    mut radio_script_handle: ResMut<RadioScriptHandle>,
+    mut atlas_manifest_handle: ResMut<AtlasManifestHandle>,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 ) {
+    tuning_handle.0 = asset_server.load(TUNING_PATH);
+    spawn_patterns_handle.0 = asset_server.load(SPAWN_PATTERNS_PATH);
+    radio_script_handle.0 = asset_server.load(RADIO_SCRIPT_PATH);
+    atlas_manifest_handle.0 = asset_server.load(ATLAS_MANIFEST_PATH);
+
     let texture_handle = asset_server.load(SPRITE_SHEET_PATH);
     let texture_atlas = TextureAtlas::from_grid(
         texture_handle,
@@ -379,25 +2898,268 @@ fn load_assets(
     next_state.set(GameState::MainMenu);
 }
 
-fn setup_camera(mut commands: Commands) {
-    commands.spawn(Camera2dBundle::default());
-}
-
-fn setup_music(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn((
-        AudioBundle {
-            source: asset_server.load("menubg.mp3"),
-            settings: PlaybackSettings {
-                volume: Volume::Absolute(VolumeLevel::new(0.7)),
-                mode: PlaybackMode::Loop,
-                ..Default::default()
-            },
-            ..default()
-        },
-        BgMusic,
-    ));
+fn apply_atlas_manifest(
+    mut reader_manifest: EventReader<AssetEvent<AtlasManifest>>,
+    manifest_handle: Res<AtlasManifestHandle>,
+    manifests: Res<Assets<AtlasManifest>>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut global_texture_handle: ResMut<GlobalTextureHandle>,
+) {
+    for ev in reader_manifest.read() {
+        if !ev.is_loaded_with_dependencies(&manifest_handle.0)
+            && !ev.is_modified(&manifest_handle.0)
+        {
+            continue;
+        }
+        let Some(manifest) = manifests.get(&manifest_handle.0) else {
+            continue;
+        };
+        for entry in manifest.atlases.iter() {
+            let texture_atlas = TextureAtlas::from_grid(
+                asset_server.load(&entry.path),
+                vec2(entry.tile_size.0, entry.tile_size.1),
+                entry.columns,
+                entry.rows,
+                None,
+                None,
+            );
+            global_texture_handle
+                .1
+                .insert(entry.kind, texture_atlases.add(texture_atlas));
+        }
+    }
 }
 
+fn apply_power_mode(
+    settings: Res<GameSettings>,
+    state: Res<State<GameState>>,
+    mut winit_settings: ResMut<WinitSettings>,
+) {
+    let in_run = matches!(
+        state.get(),
+        GameState::LoadAssets | GameState::GameInit | GameState::InGame
+    );
+    let is_saving = matches!(
+        winit_settings.focused_mode,
+        UpdateMode::ReactiveLowPower { .. }
+    );
+    if settings.battery_saver && !in_run {
+        if !is_saving {
+            winit_settings.focused_mode = UpdateMode::ReactiveLowPower {
+                wait: Duration::from_secs_f64(1.0 / BATTERY_SAVER_MENU_FPS),
+            };
+        }
+    } else if is_saving {
+        winit_settings.focused_mode = UpdateMode::Continuous;
+    }
+}
+
+// Sleeps off whatever is left of the capped frame time
+#[cfg(not(target_arch = "wasm32"))]
+fn limit_frame_rate(
+    settings: Res<GameSettings>,
+    mut frame_start: Local<Option<std::time::Instant>>,
+) {
+    if let (Some(frame_time), Some(start)) = (settings.fps_cap.frame_time(), *frame_start) {
+        if let Some(left) = frame_time.checked_sub(start.elapsed()) {
+            std::thread::sleep(left);
+        }
+    }
+    *frame_start = Some(std::time::Instant::now());
+}
+
+// State changes, the run setup, settings and entity counts go into the log
+#[allow(clippy::too_many_arguments)]
+fn log_game_events(
+    time: Res<Time<Real>>,
+    mut since_counts: Local<f32>,
+    game_state: Res<State<GameState>>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+    zombie_query: Query<With<Zombie>>,
+    bullet_query: Query<With<Bullet>>,
+    entity_query: Query<Entity>,
+) {
+    if game_state.is_changed() {
+        info!(target: "gameplay", "State {:?}", game_state.get());
+    }
+    if run_config.is_changed() {
+        info!(
+            target: "gameplay",
+            "Run seed {:X}, {}",
+            run_config.seed,
+            run_config.describe()
+        );
+    }
+    if settings.is_changed() {
+        info!(target: "gameplay", "Settings {:?}", *settings);
+    }
+
+    *since_counts += time.delta_seconds();
+    if *game_state.get() == GameState::InGame && *since_counts >= GAME_LOG_COUNTS_INTERVAL_SEC {
+        *since_counts = 0.0;
+        info!(
+            target: "gameplay",
+            "Entities {}, zombies {}, bullets {}",
+            entity_query.iter().len(),
+            zombie_query.iter().len(),
+            bullet_query.iter().len()
+        );
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn update_crash_report(
+    crash_report: Res<CrashReport>,
+    run_config: Res<RunConfig>,
+    settings: Res<GameSettings>,
+    run_stats: Res<RunStats>,
+) {
+    if !run_config.is_changed() && !settings.is_changed() && !run_stats.is_changed() {
+        return;
+    }
+    let Ok(mut context) = crash_report.0.lock() else {
+        return;
+    };
+    context.seed = run_config.seed;
+    context.run = run_config.describe();
+    context.settings = format!("{:?}", *settings);
+    context.run_stats = format!("{:?}", *run_stats);
+}
+
+fn apply_tuning(
+    mut reader_tuning: EventReader<AssetEvent<Tuning>>,
+    tuning_handle: Res<TuningHandle>,
+    tuning_assets: Res<Assets<Tuning>>,
+    mut tuning: ResMut<Tuning>,
+) {
+    // Also runs when the file is edited, with the hot-reload feature on
+    for ev in reader_tuning.read() {
+        if !ev.is_loaded_with_dependencies(&tuning_handle.0) && !ev.is_modified(&tuning_handle.0) {
+            continue;
+        }
+        if let Some(loaded) = tuning_assets.get(&tuning_handle.0) {
+            *tuning = loaded.clone();
+        }
+    }
+}
+
+fn apply_spawn_patterns(
+    mut reader_patterns: EventReader<AssetEvent<SpawnPatterns>>,
+    patterns_handle: Res<SpawnPatternsHandle>,
+    patterns_assets: Res<Assets<SpawnPatterns>>,
+    mut spawn_patterns: ResMut<SpawnPatterns>,
+) {
+    for ev in reader_patterns.read() {
+        if !ev.is_loaded_with_dependencies(&patterns_handle.0)
+            && !ev.is_modified(&patterns_handle.0)
+        {
+            continue;
+        }
+        if let Some(loaded) = patterns_assets.get(&patterns_handle.0) {
+            *spawn_patterns = loaded.clone();
+        }
+    }
+}
+
+fn setup_camera(mut commands: Commands) {
+    commands.spawn((Camera2dBundle::default(), MainCamera));
+
+    // Bars covering whatever is beyond the WW x WH play area, sized in update_letterbox
+    for _ in 0..2 {
+        commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                z_index: ZIndex::Global(-1),
+                ..default()
+            },
+            LetterboxBar,
+        ));
+    }
+}
+
+fn update_letterbox(
+    settings: Res<GameSettings>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut cam_query: Query<&mut OrthographicProjection, With<MainCamera>>,
+    mut bar_query: Query<&mut Style, With<LetterboxBar>>,
+) {
+    if q_window.is_empty() || cam_query.is_empty() {
+        return;
+    }
+
+    // Everyone sees the same world area, wider windows get bars instead of more road
+    let mut projection = cam_query.single_mut();
+    let is_letterboxed = matches!(projection.scaling_mode, ScalingMode::AutoMin { .. });
+    if settings.letterbox != is_letterboxed {
+        projection.scaling_mode = if settings.letterbox {
+            ScalingMode::AutoMin {
+                min_width: WW as f32,
+                min_height: WH as f32,
+            }
+        } else {
+            ScalingMode::WindowSize(1.0)
+        };
+    }
+
+    let window = q_window.single();
+    let (width, height) = (window.width(), window.height());
+    let play_aspect = WW as f32 / WH as f32;
+    let (bar_w, bar_h) = if !settings.letterbox {
+        (0.0, 0.0)
+    } else if width / height > play_aspect {
+        ((width - height * play_aspect) / 2.0, 0.0)
+    } else {
+        (0.0, (height - width / play_aspect) / 2.0)
+    };
+
+    for (idx, mut style) in bar_query.iter_mut().enumerate() {
+        let is_first = idx == 0;
+        let (left, top) = match (bar_w > 0.0, is_first) {
+            (_, true) => (Val::Px(0.0), Val::Px(0.0)),
+            (true, false) => (Val::Px(width - bar_w), Val::Px(0.0)),
+            (false, false) => (Val::Px(0.0), Val::Px(height - bar_h)),
+        };
+        let (bar_width, bar_height) = if bar_w > 0.0 {
+            (Val::Px(bar_w), Val::Percent(100.0))
+        } else {
+            (Val::Percent(100.0), Val::Px(bar_h))
+        };
+        // Only touch the style on resize, it triggers a relayout
+        if style.left != left
+            || style.top != top
+            || style.width != bar_width
+            || style.height != bar_height
+        {
+            style.left = left;
+            style.top = top;
+            style.width = bar_width;
+            style.height = bar_height;
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+fn setup_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load("menubg.mp3"),
+            settings: PlaybackSettings {
+                volume: Volume::Absolute(VolumeLevel::new(0.7)),
+                mode: PlaybackMode::Loop,
+                ..Default::default()
+            },
+        },
+        BgMusic,
+    ));
+}
+
+#[cfg(feature = "audio")]
 fn update_music(music_query: Query<&AudioSink, With<BgMusic>>, settings: Res<GameSettings>) {
     if music_query.is_empty() {
         return;
@@ -410,19 +3172,252 @@ fn update_music(music_query: Query<&AudioSink, With<BgMusic>>, settings: Res<Gam
     }
     if !settings.music && !music.is_paused() {
         music.pause();
+    }
+}
+
+#[cfg(feature = "audio")]
+fn update_finale_music(music_query: Query<&AudioSink, With<BgMusic>>, finale: Res<Finale>) {
+    let speed = if finale.0.is_some() {
+        FINALE_MUSIC_SPEED
+    } else {
+        1.0
+    };
+    for music in music_query.iter() {
+        if music.speed() != speed {
+            music.set_speed(speed);
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+fn setup_horde_groan(mut commands: Commands, mut groan_sources: ResMut<Assets<HordeGroanAudio>>) {
+    commands.spawn((
+        AudioSourceBundle {
+            source: groan_sources.add(HordeGroanAudio),
+            settings: PlaybackSettings {
+                volume: Volume::Absolute(VolumeLevel::new(0.0)),
+                mode: PlaybackMode::Loop,
+                ..Default::default()
+            },
+        },
+        HordeGroan::default(),
+        GameEntity,
+    ));
+}
+
+#[cfg(feature = "audio")]
+fn update_horde_groan(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    obstacle_grid: Res<ObstacleGrid>,
+    mut groan_query: Query<(&AudioSink, &mut HordeGroan)>,
+    zombie_query: Query<&Transform, With<Zombie>>,
+    car_query: Query<&Transform, (With<Car>, Without<Zombie>)>,
+) {
+    let Ok((sink, mut groan)) = groan_query.get_single_mut() else {
+        return;
+    };
+    if car_query.is_empty() {
+        return;
+    }
+
+    // Off-screen zombies count too, the groan warns about what's coming
+    let car_pos = car_query.single().translation.truncate();
+    let occluders = obstacle_grid.occluders_near(car_pos, HORDE_GROAN_RADIUS);
+    let loudness = zombie_query
+        .iter()
+        .map(|z| z.translation.truncate())
+        .filter(|pos| pos.distance_squared(car_pos) <= HORDE_GROAN_RADIUS.powi(2))
+        .map(|pos| {
+            if is_sound_occluded(&occluders, pos, car_pos) {
+                SOUND_OCCLUSION_VOLUME
+            } else {
+                1.0
+            }
+        })
+        .sum::<f32>();
+    let target = (loudness / HORDE_GROAN_FULL_COUNT).min(1.0);
+    let t = (HORDE_GROAN_SMOOTHING * time.delta_seconds()).min(1.0);
+    groan.intensity += (target - groan.intensity) * t;
+
+    let volume = if settings.music {
+        groan.intensity * HORDE_GROAN_MAX_VOLUME
+    } else {
+        0.0
+    };
+    sink.set_volume(volume);
+    sink.set_speed(0.8 + 0.4 * groan.intensity);
+    if sink.is_paused() {
+        sink.play();
+    }
+}
+
+#[cfg(feature = "audio")]
+fn pause_horde_groan(groan_query: Query<&AudioSink, With<HordeGroan>>) {
+    for sink in groan_query.iter() {
+        sink.pause();
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Decodable for HordeGroanAudio {
+    type DecoderItem = f32;
+    type Decoder = HordeGroanDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        HordeGroanDecoder {
+            sample_idx: 0,
+            phase: 0.0,
+            noise_seed: 0x9e37_79b9,
+            noise: 0.0,
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Iterator for HordeGroanDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use std::f32::consts::TAU;
+
+        let t = self.sample_idx as f32 / HORDE_GROAN_SAMPLE_RATE as f32;
+        // Every modulation below completes whole cycles in 10 minutes, wrap there
+        self.sample_idx = (self.sample_idx + 1) % (HORDE_GROAN_SAMPLE_RATE as u64 * 600);
+
+        // Low sawtooth with a slow wobble in pitch, like many voices drifting apart
+        let freq = 62.0 + 6.0 * (TAU * 0.3 * t).sin() + 3.0 * (TAU * 0.11 * t).sin();
+        self.phase = (self.phase + freq / HORDE_GROAN_SAMPLE_RATE as f32) % 1.0;
+        let saw = 2.0 * self.phase - 1.0;
+        let sub = (TAU * self.phase * 0.5).sin();
+
+        // Low passed noise for the throaty rasp
+        self.noise_seed ^= self.noise_seed << 13;
+        self.noise_seed ^= self.noise_seed >> 17;
+        self.noise_seed ^= self.noise_seed << 5;
+        let white = self.noise_seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+        self.noise += (white - self.noise) * 0.05;
+
+        // Breathing swell so the loop doesn't sound like a flat drone
+        let swell = 0.6 + 0.25 * (TAU * 0.45 * t).sin() + 0.15 * (TAU * 0.17 * t).sin();
+        Some((0.35 * saw + 0.35 * sub + 0.8 * self.noise) * swell * 0.7)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Source for HordeGroanDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        HORDE_GROAN_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(all(feature = "rich-presence", not(target_arch = "wasm32")))]
+fn setup_rich_presence(mut commands: Commands) {
+    use discord_rich_presence::{DiscordIpc, DiscordIpcClient};
+
+    // The app id is baked in at build time, without it presence stays off
+    let Some(app_id) = option_env!("DISCORD_APP_ID") else {
+        commands.insert_resource(RichPresence(None));
+        return;
+    };
+
+    let mut client = DiscordIpcClient::new(app_id);
+    if let Err(e) = client.connect() {
+        warn!("Discord rich presence unavailable: {e}");
+        commands.insert_resource(RichPresence(None));
+        return;
+    }
+    commands.insert_resource(RichPresence(Some(client)));
+}
+
+#[cfg(all(feature = "rich-presence", not(target_arch = "wasm32")))]
+fn update_rich_presence(
+    mut rich_presence: ResMut<RichPresence>,
+    game_state: Res<State<GameState>>,
+    car_progress: Res<CarProgress>,
+    player_score: Res<PlayerScore>,
+    settings: Res<GameSettings>,
+) {
+    use discord_rich_presence::{activity::Activity, DiscordIpc};
+
+    let Some(client) = rich_presence.0.as_mut() else {
         return;
+    };
+
+    let details = match game_state.get() {
+        GameState::InGame | GameState::PauseMenu | GameState::CheckpointShop => format!(
+            "Escaping - {:.0}%",
+            (car_progress.0 * 100.0).clamp(0.0, 100.0)
+        ),
+        GameState::GameOver => "Game Over".to_string(),
+        _ => "In the menus".to_string(),
+    };
+    let state = format!(
+        "{} | Score: {}",
+        settings.difficulty_as_str(),
+        player_score.0
+    );
+
+    let activity = Activity::new().details(details).state(state);
+    if client.set_activity(activity).is_err() && client.reconnect().is_err() {
+        // Discord went away, stop trying until the next launch
+        rich_presence.0 = None;
     }
 }
 
-fn setup_game_ui(mut commands: Commands, handle: Res<GlobalTextureHandle>) {
+fn setup_slow_mo_overlay(mut commands: Commands) {
+    // Washes out the colors under the HUD while time is slowed
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: SLOW_MO_OVERLAY_COLOR.into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(-2),
+            ..default()
+        },
+        SlowMoOverlay,
+        GameEntity,
+    ));
+}
+
+fn setup_tutorial(
+    mut commands: Commands,
+    mut tutorial: ResMut<Tutorial>,
+    profile: Res<Profile>,
+    asset_server: Res<AssetServer>,
+) {
+    // Only brand new players, anyone with a banked score knows the ropes
+    if profile.tutorial_done || profile.total_score > 0 {
+        tutorial.0 = None;
+        return;
+    }
+
+    tutorial.0 = Some(TutorialStep::Steer);
     commands
         .spawn((
             NodeBundle {
                 style: Style {
                     width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    align_items: AlignItems::Start,
-                    justify_content: JustifyContent::End,
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(90.0),
+                    justify_content: JustifyContent::Center,
                     ..default()
                 },
                 ..default()
@@ -430,755 +3425,880 @@ fn setup_game_ui(mut commands: Commands, handle: Res<GlobalTextureHandle>) {
             GameEntity,
         ))
         .with_children(|parent| {
-            parent
-                .spawn(NodeBundle {
-                    style: Style {
-                        width: Val::Percent(40.0),
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::End,
-                        ..default()
+            parent.spawn((
+                TextBundle::from_section(
+                    TutorialStep::Steer.prompt(),
+                    TextStyle {
+                        font_size: 35.0,
+                        font: asset_server.load("font.ttf"),
+                        color: COLOR_LIGHT_ORANGE,
                     },
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent
-                        .spawn(NodeBundle {
-                            style: Style {
-                                flex_direction: FlexDirection::RowReverse,
-                                align_items: AlignItems::Center,
-                                align_content: AlignContent::Start,
-                                ..default()
-                            },
-                            ..default()
-                        })
-                        .with_children(|parent| {
-                            parent.spawn(AtlasImageBundle {
-                                style: Style {
-                                    width: Val::Px(64.0),
-                                    height: Val::Px(64.0),
-                                    margin: UiRect::px(16.0, 32.0, 8.0, 8.0),
-                                    ..default()
-                                },
-                                texture_atlas: handle.0.clone().unwrap(),
-                                texture_atlas_image: UiTextureAtlasImage {
-                                    index: 10,
-                                    ..default()
-                                },
-                                ..default()
-                            });
-                            parent.spawn((
-                                AtlasImageBundle {
-                                    style: Style {
-                                        width: Val::Px(200.0),
-                                        height: Val::Px(32.0),
-                                        margin: UiRect::px(16.0, 16.0, 8.0, 8.0),
-                                        ..default()
-                                    },
-                                    texture_atlas: handle.0.clone().unwrap(),
-                                    texture_atlas_image: UiTextureAtlasImage {
-                                        index: 13,
-                                        ..default()
-                                    },
-                                    ..default()
-                                },
-                                GameUIHealthBar,
-                            ));
-                        });
-                });
+                )
+                .with_text_alignment(TextAlignment::Center),
+                TutorialPrompt,
+            ));
         });
+}
+
+fn setup_radio(mut commands: Commands, mut radio: ResMut<Radio>, asset_server: Res<AssetServer>) {
+    *radio = Radio::default();
 
+    let font = asset_server.load("font.ttf");
     commands
         .spawn((
             NodeBundle {
                 style: Style {
                     width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    align_items: AlignItems::End,
-                    justify_content: JustifyContent::End,
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(120.0),
+                    justify_content: JustifyContent::Center,
                     ..default()
                 },
+                visibility: Visibility::Hidden,
                 ..default()
             },
+            RadioBox,
             GameEntity,
         ))
         .with_children(|parent| {
             parent
                 .spawn(NodeBundle {
                     style: Style {
-                        width: Val::Percent(40.0),
-                        flex_direction: FlexDirection::ColumnReverse,
-                        align_items: AlignItems::End,
+                        max_width: Val::Px(600.0),
+                        padding: UiRect::all(Val::Px(12.0)),
                         ..default()
                     },
+                    background_color: COLOR_BLACK.with_a(0.8).into(),
                     ..default()
                 })
                 .with_children(|parent| {
-                    parent
-                        .spawn(NodeBundle {
-                            style: Style {
-                                flex_direction: FlexDirection::ColumnReverse,
-                                ..default()
-                            },
-                            ..default()
-                        })
-                        .with_children(|parent| {
-                            parent.spawn((
-                                AtlasImageBundle {
-                                    style: Style {
-                                        width: Val::Px(50.0),
-                                        height: Val::Px(50.0),
-                                        margin: UiRect::all(Val::Px(40.0)),
-                                        ..default()
-                                    },
-                                    texture_atlas: handle.0.clone().unwrap(),
-                                    texture_atlas_image: UiTextureAtlasImage {
-                                        index: 12,
-                                        ..default()
-                                    },
-                                    ..default()
+                    parent.spawn((
+                        TextBundle::from_sections([
+                            TextSection::new(
+                                "",
+                                TextStyle {
+                                    font_size: 24.0,
+                                    font: font.clone(),
+                                    color: COLOR_ORANGE,
                                 },
-                                GameUITurbo,
-                            ));
-                        });
+                            ),
+                            TextSection::new(
+                                "",
+                                TextStyle {
+                                    font_size: 24.0,
+                                    font,
+                                    color: COLOR_LIGHT_ORANGE,
+                                },
+                            ),
+                        ]),
+                        RadioText,
+                    ));
                 });
         });
-
-    commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    position_type: PositionType::Absolute,
-                    ..default()
-                },
-                ..default()
-            },
-            GameEntity,
-        ))
-        .with_children(|parent| {
-            parent.spawn(NodeBundle {
-                style: Style {
-                    width: Val::Px(40.0),
-                    height: Val::Percent(50.0),
-                    top: Val::Percent(25.0),
-                    margin: UiRect::all(Val::Px(16.0)),
-                    border: UiRect::all(Val::Px(3.0)),
-                    ..default()
-                },
-                border_color: COLOR_LIGHT_ORANGE.into(),
-                background_color: COLOR_BROWN.into(),
-                ..default()
-            });
-        });
-
-    commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    flex_direction: FlexDirection::Column,
-                    ..default()
-                },
-                ..default()
-            },
-            GameEntity,
-        ))
-        .with_children(|parent| {
-            parent.spawn(AtlasImageBundle {
-                style: Style {
-                    width: Val::Px(40.0),
-                    height: Val::Px(40.0),
-                    margin: UiRect::all(Val::Px(16.0)),
-                    top: Val::Percent(25.0),
-                    ..default()
-                },
-                texture_atlas: handle.0.clone().unwrap(),
-                texture_atlas_image: UiTextureAtlasImage {
-                    index: 14,
-                    ..default()
-                },
-                ..default()
-            });
-        });
-
-    commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    flex_direction: FlexDirection::Column,
-                    ..default()
-                },
-                ..default()
-            },
-            GameEntity,
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn(AtlasImageBundle {
-                    style: Style {
-                        width: Val::Px(40.0),
-                        height: Val::Px(40.0),
-                        margin: UiRect::all(Val::Px(16.0)),
-                        top: Val::Percent(70.0),
-                        ..default()
-                    },
-                    texture_atlas: handle.0.clone().unwrap(),
-                    texture_atlas_image: UiTextureAtlasImage {
-                        index: 15,
-                        ..default()
-                    },
-                    ..default()
-                })
-                .insert(GameUICarProgress);
-        });
 }
 
-fn update_game_ui_health_bar(
-    mut ui_bar_query: Query<&mut Style, With<GameUIHealthBar>>,
-    car_health: Res<CarHealth>,
+#[allow(clippy::too_many_arguments)]
+fn update_radio(
+    mut commands: Commands,
+    mut radio: ResMut<Radio>,
+    time: Res<Time<Real>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    car_progress: Res<CarProgress>,
+    tutorial: Res<Tutorial>,
+    settings: Res<GameSettings>,
+    asset_server: Res<AssetServer>,
+    script_handle: Res<RadioScriptHandle>,
+    scripts: Res<Assets<RadioScript>>,
+    mut box_query: Query<&mut Visibility, With<RadioBox>>,
+    mut text_query: Query<&mut Text, With<RadioText>>,
 ) {
-    if ui_bar_query.is_empty() {
+    let (Ok(mut visibility), Ok(mut text)) =
+        (box_query.get_single_mut(), text_query.get_single_mut())
+    else {
         return;
+    };
+
+    // Next message comes in once the car reaches its milestone, none during the tutorial
+    if radio.current.is_none() && tutorial.0.is_none() {
+        let message = scripts
+            .get(&script_handle.0)
+            .and_then(|script| script.messages.get(radio.next))
+            .filter(|message| car_progress.0 >= message.progress)
+            .cloned();
+        if let Some(message) = message {
+            if let Some(audio) = message.audio.clone() {
+                if cfg!(feature = "audio") && settings.music {
+                    commands.spawn(AudioBundle {
+                        source: asset_server.load(audio),
+                        settings: PlaybackSettings::DESPAWN,
+                    });
+                }
+            }
+            radio.next += 1;
+            radio.current = Some((message, 0.0));
+        }
     }
 
-    let mut health_bar = ui_bar_query.single_mut();
-    health_bar.width = Val::Px(car_health.0);
-}
+    let Some((message, age)) = radio.current.as_mut() else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
 
-fn update_game_ui_car_progress(
-    mut car_progress_ui: Query<&mut Style, With<GameUICarProgress>>,
-    car_progress: Res<CarProgress>,
-) {
-    if car_progress_ui.is_empty() {
+    // First press finishes typing the message, the second one dismisses it
+    let typing_sec = message.text.chars().count() as f32 / RADIO_CHARS_PER_SEC;
+    let is_skip = keyboard_input.just_pressed(KeyCode::Return)
+        || gamepads.iter().any(|gamepad| {
+            gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::North))
+        });
+    *age += time.delta_seconds();
+    if is_skip {
+        *age = if *age < typing_sec {
+            typing_sec
+        } else {
+            typing_sec + RADIO_HOLD_SEC
+        };
+    }
+    if *age >= typing_sec + RADIO_HOLD_SEC {
+        radio.current = None;
+        visibility.set_if_neq(Visibility::Hidden);
         return;
     }
 
-    let mut style = car_progress_ui.single_mut();
-    let scaled_progress = ((((car_progress.0 * 100.0) / 100.0) + 1.0) * (70.0 - 27.0)) + 27.0;
-    style.top = Val::Percent(70.0 + (70.0 - scaled_progress));
+    let num_chars = (*age * RADIO_CHARS_PER_SEC) as usize;
+    text.sections[0].value = format!("{}: ", message.speaker);
+    text.sections[1].value = message.text.chars().take(num_chars).collect();
+    visibility.set_if_neq(Visibility::Inherited);
 }
 
-fn handle_game_complete(
-    mut car_progress: ResMut<CarProgress>,
-    mut game_state: ResMut<NextState<GameState>>,
+#[allow(clippy::too_many_arguments)]
+fn update_tutorial(
+    mut commands: Commands,
+    mut tutorial: ResMut<Tutorial>,
+    mut profile: ResMut<Profile>,
+    mut steered: Local<bool>,
+    keyboard_input: Res<Input<KeyCode>>,
+    car_progress: Res<CarProgress>,
+    run_config: Res<RunConfig>,
+    texture_handle: Res<GlobalTextureHandle>,
+    car_query: Query<(&Transform, &Turbo), With<Car>>,
+    dummy_query: Query<With<TutorialDummy>>,
+    mut prompt_query: Query<(&mut Text, &Parent), With<TutorialPrompt>>,
 ) {
-    // Player going the opposite way
-    if car_progress.0 < -0.1 {
-        game_state.set(GameState::GameOver);
+    let Some(step) = tutorial.0 else {
+        // Skipped from the pause menu
+        for (_, parent) in prompt_query.iter() {
+            commands.entity(parent.get()).despawn_recursive();
+        }
+        return;
+    };
+    if car_query.is_empty() {
         return;
     }
 
-    if car_progress.0 < 1.0 {
+    let (car_transform, turbo) = car_query.single();
+    let is_step_done = match step {
+        TutorialStep::Steer => {
+            *steered |=
+                keyboard_input.any_pressed([KeyCode::A, KeyCode::D, KeyCode::Left, KeyCode::Right]);
+            *steered && car_transform.translation.y >= TUTORIAL_STEER_DISTANCE
+        }
+        TutorialStep::Shoot => dummy_query.is_empty(),
+        TutorialStep::Turbo => run_config.has(Modifier::NoTurbo) || turbo.0.elapsed_secs() < 0.2,
+        TutorialStep::Progress => car_progress.0 >= TUTORIAL_PROGRESS_GOAL,
+    };
+    if !is_step_done {
         return;
     }
 
-    car_progress.0 = 1.0;
-    game_state.set(GameState::GameOver);
-}
+    let next_step = match step {
+        TutorialStep::Steer => Some(TutorialStep::Shoot),
+        TutorialStep::Shoot => Some(TutorialStep::Turbo),
+        TutorialStep::Turbo => Some(TutorialStep::Progress),
+        TutorialStep::Progress => None,
+    };
+    tutorial.0 = next_step;
+    *steered = false;
 
-fn update_game_ui_turbo(
-    mut turbo_ui: Query<&mut Visibility, With<GameUITurbo>>,
-    car_query: Query<&Turbo, With<Car>>,
-) {
-    if turbo_ui.is_empty() {
-        return;
-    }
-    if car_query.is_empty() {
+    let Some(next_step) = next_step else {
+        profile.tutorial_done = true;
+        profile.save();
         return;
+    };
+    for (mut text, _) in prompt_query.iter_mut() {
+        text.sections[0].value = next_step.prompt().to_string();
     }
 
-    let turbo = car_query.single();
-    let mut turbo_button = turbo_ui.single_mut();
-    let turbo_percentage = (turbo.0.elapsed().as_secs_f32() / TURBO_INTERVAL_SEC).min(1.0) * 100.0;
-
-    if turbo_percentage >= 100.0 {
-        *turbo_button = Visibility::Visible;
-    } else {
-        *turbo_button = Visibility::Hidden;
+    if next_step == TutorialStep::Shoot {
+        // Doesn't move, see update_zombies
+        let pos = car_transform.translation + car_transform.local_y() * TUTORIAL_DUMMY_DISTANCE;
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(SpriteId::Zombie.atlas()),
+                sprite: TextureAtlasSprite::new(SpriteId::Zombie.index()),
+                transform: Transform::from_scale(Vec3::splat(2.5))
+                    .with_translation(vec3(pos.x, pos.y, 1.0)),
+                ..default()
+            },
+            Zombie,
+            TutorialDummy,
+            GameEntity,
+        ));
     }
 }
 
-fn setup_game(
-    mut commands: Commands, 
-    mut player_pos: ResMut<PlayerPos>, 
-    global_texture_handle: Res<GlobalTextureHandle>, 
-    asset_server: Res<AssetServer>
-) {
+fn setup_night_overlay(mut commands: Commands, run_config: Res<RunConfig>) {
+    if !run_config.has(Modifier::NightOnly) {
+        return;
+    }
+
+    // Sits under the slow-mo wash so both can show at once
     commands.spawn((
-        TextBundle::from_section(
-            "",
-            TextStyle {
-                font_size: 20.0,
-                font: asset_server.load("font.ttf"),
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
                 ..default()
             },
-        ).with_style(Style {
-            margin: UiRect::all(Val::Px(16.0)),
+            background_color: NIGHT_OVERLAY_COLOR.into(),
+            z_index: ZIndex::Global(-3),
             ..default()
-        }),
-        DebugText,
+        },
+        NightOverlay,
         GameEntity,
     ));
+}
 
-    // Spawn Car
-    let (x, y, z) = (150.0, 50.0, 10.0);
+fn setup_dust_storm_overlay(mut commands: Commands) {
+    // Under the night tint, a storm at night is darker still
     commands.spawn((
-        SpriteSheetBundle {
-            texture_atlas: global_texture_handle.0.clone().unwrap(),
-            sprite: TextureAtlasSprite::new(0),
-            transform: Transform::from_scale(Vec3::splat(3.0))
-                .with_translation(vec3(x, y, z)),
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: DUST_STORM_COLOR.with_a(0.0).into(),
+            z_index: ZIndex::Global(-4),
             ..default()
         },
-        Car,
-        Speed(10.0),
-        TurnSpeed(0.0),
-        Turbo(Stopwatch::new()),
+        DustStormOverlay,
         GameEntity,
     ));
-    player_pos.0 = vec3(x, y, z);
 }
 
-fn cleanup_main_menu_zombies(mut commands: Commands, zombies: Query<Entity, With<MainMenuZombie>>) {
-    for e in zombies.iter() {
-        commands.entity(e).despawn();
-    }
-}
-
-fn spawn_main_menu_zombies(
-    mut commands: Commands,
-    camera_query: Query<&Transform, With<Camera>>,
-    texture_handle: Res<GlobalTextureHandle>,
+fn update_dust_storm_overlay(
+    run_config: Res<RunConfig>,
+    car_query: Query<&Transform, With<Car>>,
+    mut overlay_query: Query<&mut BackgroundColor, With<DustStormOverlay>>,
 ) {
-    let mut rng = rand::thread_rng();
-    let camera_transform = camera_query.single().translation;
-    for _ in 0..200 {
-        let mut tile = rng.gen_range(30..40);
-        let mut scale = 2.5;
+    let Ok(car_transform) = car_query.get_single() else {
+        return;
+    };
 
-        if rng.gen_range(0.0..1.0) > 0.8 {
-            tile = rng.gen_range(40..44);
-            scale = 3.2;
+    let alpha =
+        dust_storm_intensity(&run_config, car_transform.translation.y) * DUST_STORM_MAX_ALPHA;
+    for mut color in overlay_query.iter_mut() {
+        if color.0.a() != alpha {
+            color.0 = DUST_STORM_COLOR.with_a(alpha);
         }
-
-        let x = rng.gen_range(-500.0 + camera_transform.x..500.0 + camera_transform.x + 300.0);
-        let y = rng.gen_range(-500.0 + camera_transform.y..500.0 + camera_transform.y + 300.0);
-        commands.spawn((
-            SpriteSheetBundle {
-                texture_atlas: texture_handle.0.clone().unwrap(),
-                sprite: TextureAtlasSprite::new(tile),
-                transform: Transform::from_scale(Vec3::splat(scale))
-                    .with_translation(vec3(x, y, 0.0)),
-                ..default()
-            },
-            MainMenuZombie(vec2(
-                x + rng.gen_range(-500.0..500.0),
-                y + rng.gen_range(-500.0..500.0),
-            )),
-        ));
     }
 }
 
-fn update_main_menu_zombies(
-    mut zombies: Query<(&mut Transform, &MainMenuZombie), With<MainMenuZombie>>,
-    time: Res<Time>
-) {
-    for (mut transform, target) in zombies.iter_mut() {
-        let dir = vec3(
-            target.0.x - transform.translation.x,
-            target.0.y - transform.translation.y,
-            0.0,
-        )
-        .normalize();
-        transform.translation += dir * ZOMBIE_SPEED * 0.025 * time.delta_seconds();
+// How thick the dust is at a point along the road, 0.0 outside the storm bands
+fn dust_storm_intensity(run_config: &RunConfig, y: f32) -> f32 {
+    if run_config.arena || y < DUST_STORM_FIRST_Y {
+        return 0.0;
     }
-}
 
-fn setup_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let button_style = Style {
-        width: Val::Px(250.0),
-        height: Val::Px(65.0),
-        margin: UiRect::all(Val::Px(20.0)),
-        justify_content: JustifyContent::Center,
-        align_items: AlignItems::Center,
-        ..default()
-    };
-    let button_text_style = TextStyle {
-        font_size: 40.0,
-        color: COLOR_BLACK,
-        font: asset_server.load("font.ttf"),
-        ..Default::default()
-    };
+    let into_band = (y - DUST_STORM_FIRST_Y) % DUST_STORM_PERIOD;
+    if into_band > DUST_STORM_LENGTH {
+        return 0.0;
+    }
+    (into_band.min(DUST_STORM_LENGTH - into_band) / DUST_STORM_FADE).min(1.0)
+}
 
+fn setup_game_ui(
+    mut commands: Commands,
+    handle: Res<GlobalTextureHandle>,
+    asset_server: Res<AssetServer>,
+) {
     commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    align_items: AlignItems::Center,
-                    justify_content: JustifyContent::Center,
-                    ..default()
-                },
-                ..default()
-            },
-            MainMenuComponent,
-        ))
+        .spawn(HudElement::Health.root_bundle())
         .with_children(|parent| {
             parent
                 .spawn(NodeBundle {
                     style: Style {
-                        flex_direction: FlexDirection::Column,
+                        flex_direction: FlexDirection::RowReverse,
                         align_items: AlignItems::Center,
+                        align_content: AlignContent::Start,
                         ..default()
                     },
                     ..default()
                 })
                 .with_children(|parent| {
-                    parent.spawn(
-                        TextBundle::from_section(
-                            "Paused",
-                            TextStyle {
-                                font_size: 70.0,
-                                font: asset_server.load("font.ttf"),
-                                color: COLOR_LIGHT_ORANGE,
-                                ..Default::default()
+                    parent.spawn(AtlasImageBundle {
+                        style: Style {
+                            width: Val::Px(64.0),
+                            height: Val::Px(64.0),
+                            margin: UiRect::px(16.0, 32.0, 8.0, 8.0),
+                            ..default()
+                        },
+                        texture_atlas: handle.atlas(SpriteId::HealthIcon.atlas()),
+                        texture_atlas_image: UiTextureAtlasImage {
+                            index: SpriteId::HealthIcon.index(),
+                            ..default()
+                        },
+                        ..default()
+                    });
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::End,
+                                ..default()
                             },
-                        )
-                        .with_style(Style {
-                            margin: UiRect::all(Val::Px(30.0)),
                             ..default()
-                        }),
-                    );
-                    parent.spawn(
-                        TextBundle::from_section(
-                            "- WASD to move\n- SpaceBar for Turbo (when available)\n- Hold left click to shoot",
-                            TextStyle {
-                                font_size: 30.0,
-                                font: asset_server.load("font.ttf"),
-                                color: COLOR_LIGHT_ORANGE,
-                                ..Default::default()
-                            },
-                        )
-                        .with_style(Style {
-                            margin: UiRect::all(Val::Px(30.0)),
-                            ..default()
-                        }),
-                    );
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: COLOR_ORANGE.into(),
-                                ..default()
-                            },
-                            PauseMenuButtonAction::Resume,
-                        ))
+                        })
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Resume",
-                                button_text_style.clone(),
+                            parent.spawn((
+                                AtlasImageBundle {
+                                    style: Style {
+                                        width: Val::Px(200.0),
+                                        height: Val::Px(12.0),
+                                        margin: UiRect::px(16.0, 16.0, 8.0, 0.0),
+                                        ..default()
+                                    },
+                                    texture_atlas: handle.atlas(SpriteId::ArmorBar.atlas()),
+                                    texture_atlas_image: UiTextureAtlasImage {
+                                        index: SpriteId::ArmorBar.index(),
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                GameUIArmorBar,
+                            ));
+                            parent.spawn((
+                                AtlasImageBundle {
+                                    style: Style {
+                                        width: Val::Px(200.0),
+                                        height: Val::Px(32.0),
+                                        margin: UiRect::px(16.0, 16.0, 4.0, 8.0),
+                                        ..default()
+                                    },
+                                    texture_atlas: handle.atlas(SpriteId::HealthBar.atlas()),
+                                    texture_atlas_image: UiTextureAtlasImage {
+                                        index: SpriteId::HealthBar.index(),
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                GameUIHealthBar,
+                            ));
+                            parent.spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        width: Val::Px(0.0),
+                                        height: Val::Px(8.0),
+                                        margin: UiRect::px(16.0, 16.0, 0.0, 8.0),
+                                        ..default()
+                                    },
+                                    background_color: COLOR_ORANGE.into(),
+                                    ..default()
+                                },
+                                GameUIHeatBar,
                             ));
-                        });
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style,
-                                background_color: COLOR_ORANGE.into(),
-                                ..default()
-                            },
-                            PauseMenuButtonAction::ExitToMainMenu,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section("Main Menu", button_text_style));
                         });
                 });
         });
-}
-
-fn setup_game_over_menu(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    player_score: Res<PlayerScore>,
-    car_progress: Res<CarProgress>,
-) {
-    let button_style = Style {
-        width: Val::Px(250.0),
-        height: Val::Px(65.0),
-        margin: UiRect::all(Val::Px(20.0)),
-        justify_content: JustifyContent::Center,
-        align_items: AlignItems::Center,
-        ..default()
-    };
-    let button_text_style = TextStyle {
-        font_size: 40.0,
-        font: asset_server.load("font.ttf"),
-        color: COLOR_BLACK,
-        ..Default::default()
-    };
-    let message = if car_progress.0 < 0.0 {
-        "Zombies that way ;)\nGo north!"
-    } else if car_progress.0 >= 0.98 {
-        "You Survived!"
-    } else {
-        "You got Mauled"
-    };
 
     commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    align_items: AlignItems::Center,
-                    justify_content: JustifyContent::Center,
+        .spawn(HudElement::Turbo.root_bundle())
+        .with_children(|parent| {
+            parent.spawn((
+                AtlasImageBundle {
+                    style: Style {
+                        width: Val::Px(50.0),
+                        height: Val::Px(50.0),
+                        margin: UiRect::all(Val::Px(40.0)),
+                        ..default()
+                    },
+                    texture_atlas: handle.atlas(SpriteId::TurboIcon.atlas()),
+                    texture_atlas_image: UiTextureAtlasImage {
+                        index: SpriteId::TurboIcon.index(),
+                        ..default()
+                    },
                     ..default()
                 },
-                ..default()
-            },
-            GameOverMenuComponent,
-        ))
+                GameUITurbo,
+            ));
+        });
+
+    // The flag and the car marker sit inside the progress track,
+    // so the marker only has to move between 0% and 100% of it
+    commands
+        .spawn(HudElement::Progress.root_bundle())
         .with_children(|parent| {
             parent
                 .spawn(NodeBundle {
                     style: Style {
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
+                        width: Val::Px(40.0),
+                        height: Val::Percent(50.0),
+                        margin: UiRect::all(Val::Px(16.0)),
+                        border: UiRect::all(Val::Px(3.0)),
                         ..default()
                     },
+                    border_color: COLOR_LIGHT_ORANGE.into(),
+                    background_color: COLOR_BROWN.into(),
                     ..default()
                 })
                 .with_children(|parent| {
-                    parent.spawn(
-                        TextBundle::from_section(
-                            message,
-                            TextStyle {
-                                font: asset_server.load("font.ttf"),
-                                font_size: 70.0,
-                                color: COLOR_LIGHT_ORANGE,
-                                ..Default::default()
-                            },
-                        )
-                        .with_style(Style {
-                            margin: UiRect::bottom(Val::Px(16.0)),
+                    parent.spawn(AtlasImageBundle {
+                        style: Style {
+                            width: Val::Px(40.0),
+                            height: Val::Px(40.0),
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(-3.0),
+                            top: Val::Px(-3.0),
                             ..default()
-                        }),
-                    );
-                    parent.spawn(
-                        TextBundle::from_section(
-                            format!("Score: {:?}", player_score.0).to_string(),
-                            TextStyle {
-                                font: asset_server.load("font.ttf"),
-                                font_size: 60.0,
-                                color: COLOR_LIGHT_ORANGE,
-                                ..Default::default()
-                            },
-                        )
-                        .with_style(Style {
-                            margin: UiRect::bottom(Val::Px(32.0)),
+                        },
+                        texture_atlas: handle.atlas(SpriteId::ProgressFlag.atlas()),
+                        texture_atlas_image: UiTextureAtlasImage {
+                            index: SpriteId::ProgressFlag.index(),
                             ..default()
-                        }),
-                    );
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: COLOR_ORANGE.into(),
+                        },
+                        ..default()
+                    });
+                    parent.spawn((
+                        AtlasImageBundle {
+                            style: Style {
+                                width: Val::Px(40.0),
+                                height: Val::Px(40.0),
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(-3.0),
+                                top: Val::Percent(100.0),
+                                margin: UiRect::top(Val::Px(-40.0)),
                                 ..default()
                             },
-                            GameOverMenuButtonAction::Restart,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Restart",
-                                button_text_style.clone(),
-                            ));
-                        });
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style,
-                                background_color: COLOR_ORANGE.into(),
+                            texture_atlas: handle.atlas(SpriteId::ProgressCar.atlas()),
+                            texture_atlas_image: UiTextureAtlasImage {
+                                index: SpriteId::ProgressCar.index(),
                                 ..default()
                             },
-                            GameOverMenuButtonAction::ExitToMainMenu,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section("Main Menu", button_text_style));
-                        });
+                            ..default()
+                        },
+                        GameUICarProgress,
+                    ));
                 });
         });
-}
-
-fn setup_settings_menu(
-    mut commands: Commands,
-    settings: Res<GameSettings>,
-    asset_server: Res<AssetServer>,
-) {
-    let button_style = Style {
-        width: Val::Px(500.0),
-        height: Val::Px(85.0),
-        margin: UiRect::axes(Val::Px(40.0), Val::Px(1.0)),
-        justify_content: JustifyContent::Center,
-        align_items: AlignItems::Center,
-        ..default()
-    };
-    let button_text_style = TextStyle {
-        font_size: 40.0,
-        color: COLOR_BLACK,
-        font: asset_server.load("font.ttf"),
-        ..Default::default()
-    };
 
     commands
-        .spawn((
-            NodeBundle {
+        .spawn(HudElement::Minimap.root_bundle())
+        .with_children(|parent| {
+            parent.spawn(NodeBundle {
                 style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    align_items: AlignItems::Center,
-                    justify_content: JustifyContent::Center,
+                    width: Val::Px(HUD_MINIMAP_SIZE),
+                    height: Val::Px(HUD_MINIMAP_SIZE),
+                    margin: UiRect::all(Val::Px(16.0)),
+                    border: UiRect::all(Val::Px(3.0)),
                     ..default()
                 },
+                border_color: COLOR_LIGHT_ORANGE.into(),
                 ..default()
-            },
-            SettingsMenuComponent,
-        ))
+            });
+        });
+
+    commands
+        .spawn(HudElement::Timer.root_bundle())
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::new(
+                        RunTimer::format(0.0),
+                        TextStyle {
+                            font_size: 30.0,
+                            font: asset_server.load("font.ttf"),
+                            color: COLOR_LIGHT_ORANGE,
+                        },
+                    ),
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font_size: 24.0,
+                            font: asset_server.load("font.ttf"),
+                            color: SPLIT_AHEAD_COLOR,
+                        },
+                    ),
+                ])
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(16.0)),
+                    ..default()
+                }),
+                GameUIRunTimer,
+            ));
+        });
+
+    // Bright half of the needle points at the finish
+    commands
+        .spawn(HudElement::Compass.root_bundle())
         .with_children(|parent| {
             parent
                 .spawn(NodeBundle {
                     style: Style {
-                        flex_direction: FlexDirection::Column,
+                        width: Val::Px(COMPASS_SIZE),
+                        height: Val::Px(COMPASS_SIZE),
+                        margin: UiRect::all(Val::Px(16.0)),
                         align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        border: UiRect::all(Val::Px(2.0)),
                         ..default()
                     },
+                    background_color: COLOR_BLACK.with_a(0.6).into(),
+                    border_color: COLOR_LIGHT_ORANGE.into(),
                     ..default()
                 })
                 .with_children(|parent| {
-                    parent.spawn(
-                        TextBundle::from_section(
-                            "Settings",
-                            TextStyle {
-                                font_size: 70.0,
-                                font: asset_server.load("font.ttf"),
-                                color: COLOR_LIGHT_ORANGE,
-                                ..Default::default()
-                            },
-                        )
-                        .with_style(Style {
-                            margin: UiRect::all(Val::Px(30.0)),
-                            ..default()
-                        }),
-                    );
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: COLOR_ORANGE.into(),
-                                ..default()
-                            },
-                            SettingsMenuButtonAction::Difficulty,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                settings.difficulty_as_str().to_string(),
-                                button_text_style.clone(),
-                            ));
-                        });
                     parent
                         .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: COLOR_ORANGE.into(),
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(8.0),
+                                    height: Val::Px(COMPASS_SIZE * 0.75),
+                                    flex_direction: FlexDirection::Column,
+                                    ..default()
+                                },
                                 ..default()
                             },
-                            SettingsMenuButtonAction::EntityCount,
+                            GameUICompass::default(),
                         ))
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                settings.entity_count_as_str().to_string(),
-                                button_text_style.clone(),
-                            ));
-                        });
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: COLOR_ORANGE.into(),
-                                ..default()
-                            },
-                            SettingsMenuButtonAction::Music,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                settings.music_as_str().to_string(),
-                                button_text_style.clone(),
-                            ));
-                        });
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: COLOR_ORANGE.into(),
-                                ..default()
-                            },
-                            SettingsMenuButtonAction::GodMode,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                settings.god_mode_as_str().to_string(),
-                                button_text_style.clone(),
-                            ));
+                            for color in [COLOR_ORANGE, COLOR_BROWN] {
+                                parent.spawn(NodeBundle {
+                                    style: Style {
+                                        width: Val::Percent(100.0),
+                                        height: Val::Percent(50.0),
+                                        ..default()
+                                    },
+                                    background_color: color.into(),
+                                    ..default()
+                                });
+                            }
                         });
+                });
+        });
+
+    commands
+        .spawn(HudElement::HordePressure.root_bundle())
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(16.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Horde",
+                        TextStyle {
+                            font_size: 20.0,
+                            font: asset_server.load("font.ttf"),
+                            color: COLOR_LIGHT_ORANGE,
+                        },
+                    ));
                     parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: COLOR_ORANGE.into(),
+                        .spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(HORDE_PRESSURE_BAR_WIDTH),
+                                height: Val::Px(10.0),
+                                margin: UiRect::top(Val::Px(4.0)),
                                 ..default()
                             },
-                            SettingsMenuButtonAction::DebugInfo,
-                        ))
+                            background_color: COLOR_BLACK.with_a(0.6).into(),
+                            ..default()
+                        })
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                settings.debug_info_as_str().to_string(),
-                                button_text_style.clone(),
+                            parent.spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        width: Val::Px(0.0),
+                                        height: Val::Percent(100.0),
+                                        ..default()
+                                    },
+                                    background_color: COLOR_ORANGE.into(),
+                                    ..default()
+                                },
+                                GameUIHordePressure,
                             ));
                         });
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style,
-                                background_color: COLOR_ORANGE.into(),
-                                ..default()
-                            },
-                            SettingsMenuButtonAction::ExitToMainMenu,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section("Back", button_text_style));
-                        });
                 });
         });
+
+    commands
+        .spawn(HudElement::Debug.root_bundle())
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 20.0,
+                        font: asset_server.load("font.ttf"),
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(16.0)),
+                    ..default()
+                }),
+                DebugText,
+            ));
+        });
 }
 
-fn setup_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup_minimap(mut commands: Commands) {
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: 1,
+                ..default()
+            },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(Color::rgba_u8(
+                    BG_COLOR.0, BG_COLOR.1, BG_COLOR.2, 255,
+                )),
+            },
+            projection: OrthographicProjection {
+                scale: HUD_MINIMAP_SCALE,
+                ..default()
+            },
+            ..default()
+        },
+        UiCameraConfig { show_ui: false },
+        MinimapCamera,
+        GameEntity,
+    ));
+}
+
+fn update_minimap(
+    settings: Res<GameSettings>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    car_query: Query<&Transform, (With<Car>, Without<MinimapCamera>)>,
+    mut minimap_query: Query<(&mut Camera, &mut Transform), With<MinimapCamera>>,
+) {
+    if minimap_query.is_empty() || q_window.is_empty() {
+        return;
+    }
+
+    let (mut camera, mut transform) = minimap_query.single_mut();
+    camera.is_active = settings.show_minimap;
+    if !settings.show_minimap {
+        return;
+    }
+
+    // Viewport lines up with the minimap frame in the bottom left corner
+    let window = q_window.single();
+    let scale = window.scale_factor() as f32;
+    let inset = 16.0 + 3.0;
+    let size = (HUD_MINIMAP_SIZE - 6.0) * scale;
+    camera.viewport = Some(Viewport {
+        physical_position: UVec2::new(
+            (inset * scale) as u32,
+            (window.physical_height() as f32 - size - inset * scale).max(0.0) as u32,
+        ),
+        physical_size: UVec2::splat(size as u32),
+        ..default()
+    });
+
+    if let Ok(car_transform) = car_query.get_single() {
+        transform.translation.x = car_transform.translation.x;
+        transform.translation.y = car_transform.translation.y;
+    }
+}
+
+fn setup_rear_view(mut commands: Commands) {
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: 2,
+                is_active: false,
+                ..default()
+            },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(Color::rgba_u8(
+                    BG_COLOR.0, BG_COLOR.1, BG_COLOR.2, 255,
+                )),
+            },
+            projection: OrthographicProjection {
+                scale: REAR_VIEW_SCALE,
+                ..default()
+            },
+            ..default()
+        },
+        UiCameraConfig { show_ui: false },
+        RearViewCamera,
+        GameEntity,
+    ));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    align_items: AlignItems::Start,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            RearViewFrame,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(REAR_VIEW_WIDTH),
+                    height: Val::Px(REAR_VIEW_HEIGHT),
+                    margin: UiRect::top(Val::Px(16.0)),
+                    border: UiRect::all(Val::Px(3.0)),
+                    ..default()
+                },
+                border_color: COLOR_LIGHT_ORANGE.into(),
+                ..default()
+            });
+        });
+}
+
+fn update_rear_view(
+    settings: Res<GameSettings>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    car_query: Query<&Transform, (With<Car>, Without<RearViewCamera>)>,
+    mut rear_view_query: Query<(&mut Camera, &mut Transform), With<RearViewCamera>>,
+    mut frame_query: Query<&mut Visibility, With<RearViewFrame>>,
+) {
+    if rear_view_query.is_empty() || q_window.is_empty() {
+        return;
+    }
+
+    let (mut camera, mut transform) = rear_view_query.single_mut();
+    camera.is_active = settings.rear_view;
+    for mut visibility in frame_query.iter_mut() {
+        visibility.set_if_neq(if settings.rear_view {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+    if !settings.rear_view {
+        return;
+    }
+
+    // Viewport lines up with the frame at the top of the screen
+    let window = q_window.single();
+    let scale = window.scale_factor() as f32;
+    let inset = 16.0 + 3.0;
+    let size = vec2(REAR_VIEW_WIDTH - 6.0, REAR_VIEW_HEIGHT - 6.0) * scale;
+    camera.viewport = Some(Viewport {
+        physical_position: UVec2::new(
+            ((window.physical_width() as f32 - size.x) / 2.0).max(0.0) as u32,
+            (inset * scale) as u32,
+        ),
+        physical_size: size.as_uvec2(),
+        ..default()
+    });
+
+    // Looks back down the road, the car sits at the bottom edge of the strip
+    if let Ok(car_transform) = car_query.get_single() {
+        let back = -(car_transform.rotation * Vec3::Y).truncate();
+        let pos =
+            car_transform.translation.truncate() + back * REAR_VIEW_HEIGHT * REAR_VIEW_SCALE / 2.0;
+        transform.translation.x = pos.x;
+        transform.translation.y = pos.y;
+        transform.rotation = car_transform.rotation * Quat::from_rotation_z(std::f32::consts::PI);
+    }
+}
+
+fn update_hud_visibility(
+    settings: Res<GameSettings>,
+    mut hud_query: Query<(&HudElement, &mut Visibility)>,
+) {
+    for (element, mut visibility) in hud_query.iter_mut() {
+        *visibility = if settings.is_hud_element_visible(*element) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_hud_layout(
+    profile: Res<Profile>,
+    mut root_query: Query<(Ref<HudElement>, &mut Style, &Children)>,
+    children_query: Query<&Children>,
+    mut color_query: Query<
+        (
+            Option<&mut BackgroundColor>,
+            Option<&mut BorderColor>,
+            Option<&mut Text>,
+        ),
+        Without<HudElement>,
+    >,
+) {
+    for (element, mut style, children) in root_query.iter_mut() {
+        if !profile.is_changed() && !element.is_added() {
+            continue;
+        }
+
+        let layout = profile.hud_layout(*element);
+        style.left = Val::Px(layout.offset.0);
+        style.top = Val::Px(layout.offset.1);
+
+        // Fully transparent parts are only there for layout and stay that way
+        let mut stack = children.to_vec();
+        while let Some(e) = stack.pop() {
+            if let Ok(children) = children_query.get(e) {
+                stack.extend(children.iter());
+            }
+            let Ok((background, border, text)) = color_query.get_mut(e) else {
+                continue;
+            };
+            if let Some(mut background) = background.filter(|c| c.0.a() > 0.0) {
+                background.0.set_a(layout.opacity);
+            }
+            if let Some(mut border) = border.filter(|c| c.0.a() > 0.0) {
+                border.0.set_a(layout.opacity);
+            }
+            if let Some(mut text) = text {
+                for section in text.sections.iter_mut() {
+                    section.style.color.set_a(layout.opacity);
+                }
+            }
+        }
+    }
+}
+
+fn setup_hud_editor(mut commands: Commands, asset_server: Res<AssetServer>) {
     let button_style = Style {
-        width: Val::Px(250.0),
-        height: Val::Px(65.0),
-        margin: UiRect::all(Val::Px(20.0)),
+        width: Val::Px(200.0),
+        height: Val::Px(55.0),
+        margin: UiRect::all(Val::Px(10.0)),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
         ..default()
     };
     let button_text_style = TextStyle {
-        font_size: 40.0,
+        font_size: 35.0,
         color: COLOR_BLACK,
         font: asset_server.load("font.ttf"),
-        ..Default::default()
     };
 
     commands
@@ -1187,55 +4307,34 @@ fn setup_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                 style: Style {
                     width: Val::Percent(100.0),
                     height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
                     align_items: AlignItems::Center,
                     justify_content: JustifyContent::Center,
                     ..default()
                 },
                 ..default()
             },
-            MainMenuComponent,
+            HudEditorComponent,
         ))
         .with_children(|parent| {
-            parent
-                .spawn(NodeBundle {
-                    style: Style {
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        ..default()
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 30.0,
+                        font: asset_server.load("font.ttf"),
+                        color: COLOR_LIGHT_ORANGE,
                     },
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent.spawn(
-                        TextBundle::from_section(
-                            "That's a LOT of Zombies",
-                            TextStyle {
-                                font_size: 70.0,
-                                color: COLOR_LIGHT_ORANGE,
-                                font: asset_server.load("font.ttf"),
-                                ..Default::default()
-                            },
-                        )
-                        .with_style(Style {
-                            margin: UiRect::axes(Val::Px(30.0), Val::Px(90.0)),
-                            ..default()
-                        }),
-                    );
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: COLOR_ORANGE.into(),
-                                ..default()
-                            },
-                            MainMenuButtonAction::Play,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "New Game",
-                                button_text_style.clone(),
-                            ));
-                        });
+                )
+                .with_text_alignment(TextAlignment::Center),
+                HudEditorHelpText,
+            ));
+            parent.spawn(NodeBundle::default()).with_children(|parent| {
+                for (action, label) in [
+                    (HudEditorButtonAction::Reset, "Reset"),
+                    (HudEditorButtonAction::Done, "Done"),
+                ] {
                     parent
                         .spawn((
                             ButtonBundle {
@@ -1243,1088 +4342,14891 @@ fn setup_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 background_color: COLOR_ORANGE.into(),
                                 ..default()
                             },
-                            MainMenuButtonAction::Settings,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Settings",
-                                button_text_style.clone(),
-                            ));
-                        });
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style,
-                                background_color: COLOR_ORANGE.into(),
-                                ..default()
-                            },
-                            MainMenuButtonAction::Quit,
+                            action,
                         ))
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section("Quit", button_text_style));
+                            parent
+                                .spawn(TextBundle::from_section(label, button_text_style.clone()));
                         });
-                });
+                }
+            });
         });
 }
 
-fn menu_buttons_update(
-    mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
+#[allow(clippy::type_complexity)]
+fn handle_hud_editor_btn_click(
+    interaction_query: Query<
+        (&Interaction, &HudEditorButtonAction),
         (Changed<Interaction>, With<Button>),
     >,
+    mut profile: ResMut<Profile>,
+    mut game_state: ResMut<NextState<GameState>>,
 ) {
-    for (interaction, mut color) in &mut interaction_query {
-        *color = match *interaction {
-            Interaction::Pressed => COLOR_LIGHT_ORANGE.into(),
-            Interaction::Hovered => COLOR_LIGHT_ORANGE.into(),
-            _ => COLOR_ORANGE.into(),
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
         }
-    }
-}
 
-fn handle_main_menu_btn_click(
-    interaction_query: Query<
-        (&Interaction, &MainMenuButtonAction),
-        (Changed<Interaction>, With<Button>),
-    >,
-    mut ev_app_exit: EventWriter<AppExit>,
-    mut game_state: ResMut<NextState<GameState>>,
-) {
-    for (interaction, menu_button_action) in &interaction_query {
-        if *interaction == Interaction::Pressed {
-            match menu_button_action {
-                MainMenuButtonAction::Quit => ev_app_exit.send(AppExit),
-                MainMenuButtonAction::Settings => {
-                    game_state.set(GameState::SettingsMenu);
-                }
-                MainMenuButtonAction::Play => {
-                    game_state.set(GameState::GameInit);
-                }
-            }
+        match menu_button_action {
+            HudEditorButtonAction::Reset => profile.hud_layout.clear(),
+            HudEditorButtonAction::Done => game_state.set(GameState::SettingsMenu),
         }
     }
 }
 
-fn handle_settings_menu_btn_click(
-    interaction_query: Query<
-        (&Interaction, &SettingsMenuButtonAction, &Children),
-        (Changed<Interaction>, With<Button>),
-    >,
-    mut settings: ResMut<GameSettings>,
-    mut text_query: Query<&mut Text>,
-    mut game_state: ResMut<NextState<GameState>>,
+#[allow(clippy::too_many_arguments)]
+fn update_hud_editor(
+    mut profile: ResMut<Profile>,
+    // Element being dragged and the cursor position it was last moved to
+    mut dragging: Local<Option<(HudElement, Vec2)>>,
+    buttons: Res<Input<MouseButton>>,
+    mut reader_mouse_wheel: EventReader<MouseWheel>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    root_query: Query<(&HudElement, &Children)>,
+    node_query: Query<(&Node, &GlobalTransform)>,
+    mut help_text_query: Query<&mut Text, With<HudEditorHelpText>>,
 ) {
-    for (interaction, menu_button_action, children) in &interaction_query {
-        let mut text = text_query.get_mut(children[0]).unwrap();
-        if *interaction == Interaction::Pressed {
-            match menu_button_action {
-                SettingsMenuButtonAction::Difficulty => {
-                    settings.update_difficulty();
-                    text.sections[0].value = settings.difficulty_as_str().to_string();
-                }
-                SettingsMenuButtonAction::EntityCount => {
-                    settings.update_entity_count();
-                    text.sections[0].value = settings.entity_count_as_str().to_string();
-                }
-                SettingsMenuButtonAction::Music => {
-                    settings.music = !settings.music;
-                    text.sections[0].value = settings.music_as_str().to_string();
-                }
-                SettingsMenuButtonAction::DebugInfo => {
-                    settings.debug_info = !settings.debug_info;
-                    text.sections[0].value = settings.debug_info_as_str().to_string();
-                }
-                SettingsMenuButtonAction::GodMode => {
-                    settings.god_mode = !settings.god_mode;
-                    text.sections[0].value = settings.god_mode_as_str().to_string();
-                }
-                SettingsMenuButtonAction::ExitToMainMenu => {
-                    game_state.set(GameState::MainMenu);
-                }
-            }
+    let Some(cursor) = q_window.get_single().ok().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+
+    // The root nodes cover the whole screen, their content is what can be grabbed
+    let hovered = root_query.iter().find_map(|(element, children)| {
+        let (node, transform) = node_query.get(*children.first()?).ok()?;
+        node.logical_rect(transform)
+            .contains(cursor)
+            .then_some(*element)
+    });
+
+    if buttons.just_pressed(MouseButton::Left) {
+        *dragging = hovered.map(|element| (element, cursor));
+    }
+    if !buttons.pressed(MouseButton::Left) {
+        *dragging = None;
+    }
+    if let Some((element, last_cursor)) = dragging.as_mut() {
+        let delta = cursor - *last_cursor;
+        *last_cursor = cursor;
+        if delta != Vec2::ZERO {
+            let layout = profile.hud_layout.entry(*element).or_default();
+            layout.offset = (layout.offset.0 + delta.x, layout.offset.1 + delta.y);
         }
     }
-}
 
-fn handle_pause_menu_btn_click(
-    interaction_query: Query<
-        (&Interaction, &PauseMenuButtonAction),
-        (Changed<Interaction>, With<Button>),
-    >,
-    mut game_state: ResMut<NextState<GameState>>,
-) {
-    for (interaction, menu_button_action) in &interaction_query {
-        if *interaction == Interaction::Pressed {
-            match menu_button_action {
-                PauseMenuButtonAction::Resume => {
-                    game_state.set(GameState::InGame);
-                }
-                PauseMenuButtonAction::ExitToMainMenu => {
-                    game_state.set(GameState::MainMenu);
-                }
-            }
-        }
+    let scroll = reader_mouse_wheel
+        .read()
+        .map(|ev| ev.y.signum())
+        .sum::<f32>();
+    if let Some(element) = hovered.filter(|_| scroll != 0.0) {
+        let layout = profile.hud_layout.entry(element).or_default();
+        layout.opacity = (layout.opacity + scroll * HUD_OPACITY_STEP).clamp(HUD_MIN_OPACITY, 1.0);
+    }
+
+    if let Ok(mut text) = help_text_query.get_single_mut() {
+        let focus = dragging.map(|(element, _)| element).or(hovered);
+        text.sections[0].value = match focus {
+            Some(element) => format!(
+                "{} - Opacity {:.0}%",
+                element.as_str(),
+                profile.hud_layout(element).opacity * 100.0
+            ),
+            None => "Drag the HUD around, scroll over an element to change its opacity".to_string(),
+        };
     }
 }
 
-fn handle_game_over_menu_btn_click(
-    interaction_query: Query<
-        (&Interaction, &GameOverMenuButtonAction),
-        (Changed<Interaction>, With<Button>),
-    >,
-    mut game_state: ResMut<NextState<GameState>>,
+#[allow(clippy::type_complexity)]
+fn cleanup_hud_editor(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    entities: Query<Entity, Or<(With<HudEditorComponent>, With<HudElement>)>>,
 ) {
-    for (interaction, menu_button_action) in &interaction_query {
-        if *interaction == Interaction::Pressed {
-            match menu_button_action {
-                GameOverMenuButtonAction::ExitToMainMenu => {
-                    game_state.set(GameState::MainMenu);
-                }
-                GameOverMenuButtonAction::Restart => {
-                    game_state.set(GameState::GameInit);
-                }
-            }
-        }
+    for e in entities.iter() {
+        commands.entity(e).despawn_recursive();
     }
+    profile.save();
 }
 
-fn cleanup_main_menu(
-    mut commands: Commands,
-    main_menu_query: Query<Entity, With<MainMenuComponent>>,
+fn update_game_ui_health_bar(
+    time: Res<Time>,
+    mut ui_bar_query: Query<(&mut Style, &mut BackgroundColor), With<GameUIHealthBar>>,
+    car_health: Res<CarHealth>,
+    health_regen: Res<HealthRegen>,
 ) {
-    if main_menu_query.is_empty() {
+    if ui_bar_query.is_empty() {
         return;
     }
 
-    let main_menu = main_menu_query.single();
-    commands.entity(main_menu).despawn_recursive();
+    let (mut health_bar, mut color) = ui_bar_query.single_mut();
+    health_bar.width = Val::Px(car_health.0);
+
+    // The alpha is the HUD opacity, only the tint shimmers
+    let shimmer = if health_regen.is_active {
+        ((time.elapsed_seconds() * HEALTH_REGEN_SHIMMER_SPEED).sin() * 0.5 + 0.5)
+            * HEALTH_REGEN_SHIMMER
+    } else {
+        0.0
+    };
+    let tint = Color::rgba(1.0 - shimmer, 1.0, 1.0 - shimmer, color.0.a());
+    if color.0 != tint {
+        color.0 = tint;
+    }
 }
 
-fn cleanup_settings_menu(
-    mut commands: Commands,
-    settings_menu_query: Query<Entity, With<SettingsMenuComponent>>,
+fn update_game_ui_armor_bar(
+    mut ui_bar_query: Query<&mut Style, With<GameUIArmorBar>>,
+    car_armor: Res<CarArmor>,
+    tuning: Res<Tuning>,
 ) {
-    if settings_menu_query.is_empty() {
+    if ui_bar_query.is_empty() {
         return;
     }
 
-    let settings_menu = settings_menu_query.single();
-    commands.entity(settings_menu).despawn_recursive();
+    // Scaled to the same width as a full health bar
+    let mut armor_bar = ui_bar_query.single_mut();
+    armor_bar.width = Val::Px(car_armor.0 / tuning.max_car_armor * tuning.max_car_health);
 }
 
-fn cleanup_pause_menu(
-    mut commands: Commands,
-    pause_menu_query: Query<Entity, With<MainMenuComponent>>,
+fn update_game_ui_heat_bar(
+    mut ui_bar_query: Query<(&mut Style, &mut BackgroundColor), With<GameUIHeatBar>>,
+    gun_cooldown: Res<GunCooldown>,
 ) {
-    if pause_menu_query.is_empty() {
+    let Ok((mut style, mut color)) = ui_bar_query.get_single_mut() else {
         return;
-    }
+    };
 
-    let pause_menu = pause_menu_query.single();
-    commands.entity(pause_menu).despawn_recursive();
+    // A locked gun shows as a full red bar, the alpha is the HUD opacity
+    let (fill, bar_color) = if gun_cooldown.lockout > 0.0 {
+        (1.0, HEAT_BAR_LOCKED_COLOR)
+    } else {
+        (gun_cooldown.heat.min(1.0), COLOR_ORANGE)
+    };
+    let bar_color = bar_color.with_a(color.0.a());
+    let width = Val::Px(fill * HEAT_BAR_WIDTH);
+    if style.width != width {
+        style.width = width;
+    }
+    if color.0 != bar_color {
+        color.0 = bar_color;
+    }
 }
 
-fn cleanup_game_over_menu(
-    mut commands: Commands,
-    game_over_menu_query: Query<Entity, With<GameOverMenuComponent>>,
+fn update_game_ui_horde_pressure(
+    time: Res<Time>,
+    mut pressure: Local<f32>,
+    zombie_grid: Res<ZombieGrid>,
+    car_query: Query<&Transform, With<Car>>,
+    zombie_query: Query<(Has<Spitter>, Has<Bloater>), With<Zombie>>,
+    mut ui_bar_query: Query<(&mut Style, &mut BackgroundColor), With<GameUIHordePressure>>,
 ) {
-    if game_over_menu_query.is_empty() {
+    let Ok((mut style, mut color)) = ui_bar_query.get_single_mut() else {
         return;
-    }
+    };
 
-    let game_over_menu = game_over_menu_query.single();
-    commands.entity(game_over_menu).despawn_recursive();
+    // Straight from the grid, no extra pass over the whole horde
+    let target = car_query.get_single().map_or(0.0, |car_transform| {
+        let weight = zombie_grid
+            .zombies_near(car_transform.translation.truncate(), HORDE_PRESSURE_RADIUS)
+            .into_iter()
+            .filter_map(|e| zombie_query.get(e).ok())
+            .map(|(is_spitter, is_bloater)| {
+                if is_bloater {
+                    HORDE_PRESSURE_BLOATER_WEIGHT
+                } else if is_spitter {
+                    HORDE_PRESSURE_SPITTER_WEIGHT
+                } else {
+                    1.0
+                }
+            })
+            .sum::<f32>();
+        (weight / HORDE_PRESSURE_FULL).min(1.0)
+    });
+    *pressure += (target - *pressure) * (HORDE_PRESSURE_SMOOTHING * time.delta_seconds()).min(1.0);
+
+    let bar_color = if *pressure >= HORDE_PRESSURE_HIGH {
+        HORDE_PRESSURE_HIGH_COLOR
+    } else {
+        COLOR_ORANGE
+    };
+    let bar_color = bar_color.with_a(color.0.a());
+    let width = Val::Px(*pressure * HORDE_PRESSURE_BAR_WIDTH);
+    if style.width != width {
+        style.width = width;
+    }
+    if color.0 != bar_color {
+        color.0 = bar_color;
+    }
 }
 
-fn handle_camera_zoom(
-    mut query: Query<&mut OrthographicProjection, With<Camera>>,
+fn update_game_ui_car_progress(
+    mut car_progress_ui: Query<&mut Style, With<GameUICarProgress>>,
     car_progress: Res<CarProgress>,
-    time: Res<Time>,
 ) {
-    for mut projection in query.iter_mut() {
-        if car_progress.0 > 0.90 {
-            if projection.scale <= 1.8 {
-                return;
-            }
+    if car_progress_ui.is_empty() {
+        return;
+    }
 
-            projection.scale -= 0.2 * time.delta_seconds();
-            return;
-        }
+    // Marker moves up the track, from the bottom at the start to the flag at the end
+    let mut style = car_progress_ui.single_mut();
+    style.top = Val::Percent((1.0 - car_progress.0.clamp(0.0, 1.0)) * 100.0);
+}
 
-        if projection.scale >= 2.0 {
-            return;
+fn setup_damage_indicators(mut commands: Commands) {
+    // One bracket per compass direction, hugging the screen edge on the side it faces
+    let edge = Val::Px(DAMAGE_INDICATOR_THICKNESS);
+    let (long, corner) = (Val::Percent(30.0), Val::Vh(15.0));
+    for (sector, &(dx, dy)) in DAMAGE_INDICATOR_SECTORS.iter().enumerate() {
+        let mut style = Style {
+            position_type: PositionType::Absolute,
+            width: if dx == 0 { long } else { corner },
+            height: if dy == 0 { long } else { corner },
+            ..default()
+        };
+        match dx {
+            1 => (style.right, style.border.right) = (Val::Px(0.0), edge),
+            -1 => (style.left, style.border.left) = (Val::Px(0.0), edge),
+            _ => style.left = Val::Percent(35.0),
         }
-        projection.scale += 0.1 * time.delta_seconds();
+        // UI y points down
+        match dy {
+            1 => (style.top, style.border.top) = (Val::Px(0.0), edge),
+            -1 => (style.bottom, style.border.bottom) = (Val::Px(0.0), edge),
+            _ => style.top = Val::Percent(35.0),
+        }
+        commands.spawn((
+            NodeBundle {
+                style,
+                border_color: DAMAGE_INDICATOR_COLOR.with_a(0.0).into(),
+                ..default()
+            },
+            DamageIndicator {
+                sector,
+                strength: 0.0,
+            },
+            GameEntity,
+        ));
     }
 }
 
-fn handle_turbo_input(
-    mut commands: Commands,
+fn update_damage_indicators(
     time: Res<Time>,
-    asset_server: Res<AssetServer>,
-    mut car_query: Query<&mut Turbo, With<Car>>,
-    keyboard_input: Res<Input<KeyCode>>,
-    settings: Res<GameSettings>,
+    mut reader_zombie_player_hit: EventReader<ZombieHitPlayer>,
+    car_query: Query<&Transform, With<Car>>,
+    mut indicator_query: Query<(&mut DamageIndicator, &mut BorderColor)>,
 ) {
-    if car_query.is_empty() {
-        return;
+    let car_pos = car_query
+        .get_single()
+        .ok()
+        .map(|t| t.translation.truncate());
+    let mut hit_sectors = [false; 8];
+    for hit in reader_zombie_player_hit.read() {
+        let Some(dir) = car_pos.and_then(|pos| (hit.from - pos).try_normalize()) else {
+            continue;
+        };
+        let sector_angle = std::f32::consts::TAU / 8.0;
+        let sector = (dir.y.atan2(dir.x) / sector_angle).round() as i32;
+        hit_sectors[sector.rem_euclid(8) as usize] = true;
     }
 
-    let mut turbo = car_query.single_mut();
-    turbo.0.tick(time.delta());
-
-    if turbo.0.elapsed().as_secs_f32() <= TURBO_INTERVAL_SEC {
-        return;
-    }
-    if !keyboard_input.just_pressed(KeyCode::Space) {
-        return;
+    for (mut indicator, mut border_color) in indicator_query.iter_mut() {
+        if hit_sectors[indicator.sector] {
+            indicator.strength = 1.0;
+        } else if indicator.strength <= 0.0 {
+            continue;
+        }
+        indicator.strength =
+            (indicator.strength - time.delta_seconds() / DAMAGE_INDICATOR_FADE_SEC).max(0.0);
+        border_color.0 =
+            DAMAGE_INDICATOR_COLOR.with_a(DAMAGE_INDICATOR_COLOR.a() * indicator.strength);
     }
+}
 
-    turbo.0.reset();
-    if settings.music {
-        commands.spawn(AudioBundle {
-            source: asset_server.load("turbo.mp3"),
-            settings: PlaybackSettings {
-                volume: Volume::Absolute(VolumeLevel::new(0.5)),
-                ..Default::default()
-            },
+fn setup_spawn_warnings(mut commands: Commands, handle: Res<GlobalTextureHandle>) {
+    let inset = Val::Px(SPAWN_WARNING_INSET);
+    let center = (Val::Percent(50.0), Val::Px(-SPAWN_WARNING_SIZE / 2.0));
+    for (sector, &(dx, dy)) in DAMAGE_INDICATOR_SECTORS.iter().enumerate() {
+        let mut style = Style {
+            position_type: PositionType::Absolute,
+            width: Val::Px(SPAWN_WARNING_SIZE),
+            height: Val::Px(SPAWN_WARNING_SIZE),
             ..default()
-        });
+        };
+        match dx {
+            1 => style.right = inset,
+            -1 => style.left = inset,
+            _ => (style.left, style.margin.left) = center,
+        }
+        // UI y points down
+        match dy {
+            1 => style.top = inset,
+            -1 => style.bottom = inset,
+            _ => (style.top, style.margin.top) = center,
+        }
+        commands.spawn((
+            AtlasImageBundle {
+                style,
+                texture_atlas: handle.atlas(SpriteId::SpawnWarning.atlas()),
+                texture_atlas_image: UiTextureAtlasImage {
+                    index: SpriteId::SpawnWarning.index(),
+                    ..default()
+                },
+                background_color: SPAWN_WARNING_COLOR.with_a(0.0).into(),
+                ..default()
+            },
+            SpawnWarning {
+                sector,
+                strength: 0.0,
+            },
+            GameEntity,
+        ));
     }
 }
 
-fn update_debug_text(
+fn update_spawn_warnings(
     time: Res<Time>,
-    mut text_query: Query<&mut Text, With<DebugText>>,
-    zom_query: Query<(With<Zombie>, Without<DebugText>)>,
-    diagnostics: Res<DiagnosticsStore>,
-    car_progress: Res<CarProgress>,
-    car_health: Res<CarHealth>,
-    player_score: Res<PlayerScore>,
     settings: Res<GameSettings>,
+    new_zombie_query: Query<&Transform, Added<Zombie>>,
+    cam_query: Query<&Transform, (With<MainCamera>, Without<Zombie>)>,
+    mut warning_query: Query<(&mut SpawnWarning, &mut BackgroundColor)>,
 ) {
-    if text_query.is_empty() || !settings.debug_info {
-        return;
+    // Spawns always land off screen, so anything new close by is about to walk in
+    let mut sector_spawns = [0; 8];
+    let cam_pos = cam_query
+        .get_single()
+        .ok()
+        .map(|t| t.translation.truncate());
+    if let Some(cam_pos) = cam_pos.filter(|_| settings.spawn_warnings) {
+        for transform in new_zombie_query.iter() {
+            let offset = transform.translation.truncate() - cam_pos;
+            if offset.length() > SPAWN_WARNING_RANGE {
+                continue;
+            }
+            let sector_angle = std::f32::consts::TAU / 8.0;
+            let sector = (offset.y.atan2(offset.x) / sector_angle).round() as i32;
+            sector_spawns[sector.rem_euclid(8) as usize] += 1;
+        }
     }
 
-    let mut text = text_query.single_mut();
-    let mut fps = 0.0;
-    if let Some(d) = diagnostics.get(FrameTimeDiagnosticsPlugin::FPS) {
-        if let Some(value) = d.smoothed() {
-            fps = value;
+    for (mut warning, mut color) in warning_query.iter_mut() {
+        if sector_spawns[warning.sector] >= SPAWN_WARNING_MIN_GROUP {
+            warning.strength = 1.0;
+        } else if warning.strength <= 0.0 {
+            continue;
         }
+        warning.strength = if settings.spawn_warnings {
+            (warning.strength - time.delta_seconds() / SPAWN_WARNING_FADE_SEC).max(0.0)
+        } else {
+            0.0
+        };
+        color.0 = SPAWN_WARNING_COLOR.with_a(SPAWN_WARNING_COLOR.a() * warning.strength);
     }
-    text.sections[0].value = format!(
-        "Fps: {:.2?}\nTime: {:.2?}\nProgress: {:.2?}\nHealth: {:?}\nZoms: {:?}\nScore: {:?}",
-        fps,
-        time.delta_seconds(),
-        car_progress.0,
-        car_health.0,
-        zom_query.iter().len(),
-        player_score.0
-    );
 }
 
-fn update_car_progress(
-    car_query: Query<&Transform, With<Car>>,
-    mut car_progress: ResMut<CarProgress>,
+fn update_game_ui_compass(
+    time: Res<Time>,
+    car_query: Query<(&Transform, Option<&Speed>), With<Car>>,
+    mut compass_query: Query<(&mut GameUICompass, &mut Transform, &Children), Without<Car>>,
+    mut needle_query: Query<&mut BackgroundColor>,
 ) {
-    if car_query.is_empty() {
+    let Ok((car_transform, speed)) = car_query.get_single() else {
+        return;
+    };
+    let Ok((mut compass, mut transform, children)) = compass_query.get_single_mut() else {
         return;
+    };
+
+    // The road ends due north, UI y points down so the car's own rotation
+    // swings the needle the opposite way to keep it on the finish
+    let heading = (car_transform.rotation * Vec3::Y).truncate();
+    transform.rotation = Quat::from_rotation_z((-heading.x).atan2(heading.y));
+
+    let speed = speed.map_or(0.0, |speed| speed.0);
+    let is_wrong_way = heading.y * speed < 0.0 && speed.abs() >= COMPASS_WRONG_WAY_MIN_SPEED;
+    if is_wrong_way {
+        compass.wrong_way += time.delta_seconds();
+    } else {
+        compass.wrong_way = 0.0;
     }
 
-    let car_transform = car_query.single();
-    let (_, cy) = (car_transform.translation.x, car_transform.translation.y);
+    let pulse = if compass.wrong_way >= COMPASS_WRONG_WAY_SEC {
+        (time.elapsed_seconds() * COMPASS_PULSE_SPEED).sin().abs()
+    } else {
+        0.0
+    };
+    transform.scale = Vec3::splat(1.0 + pulse * 0.3);
+    if let Some(mut color) = children.first().and_then(|e| needle_query.get_mut(*e).ok()) {
+        let target = if pulse > 0.0 {
+            COMPASS_WRONG_WAY_COLOR
+        } else {
+            COLOR_ORANGE
+        };
+        if color.0 != target {
+            color.0 = target;
+        }
+    }
+}
 
-    let total_h = TILE_H as f32 * ROAD_SCALE * ROAD_HEIGHT as f32;
-    car_progress.0 = cy / total_h;
+fn update_run_timer(
+    time: Res<Time<Real>>,
+    car_progress: Res<CarProgress>,
+    mut run_timer: ResMut<RunTimer>,
+) {
+    run_timer.elapsed += time.delta_seconds();
+    while run_timer.splits.len() < SPLIT_COUNT
+        && car_progress.0 >= (run_timer.splits.len() + 1) as f32 / SPLIT_COUNT as f32
+    {
+        let elapsed = run_timer.elapsed;
+        run_timer.splits.push(elapsed);
+    }
 }
 
-fn handle_zombie_player_hit(
-    mut car_health: ResMut<CarHealth>,
-    mut reader_zombie_player_hit: EventReader<ZombieHitPlayer>,
-    mut writer_player_dead: EventWriter<PlayerDeadEvent>,
-    settings: Res<GameSettings>,
+fn update_game_ui_run_timer(
+    mut timer_ui: Query<&mut Text, With<GameUIRunTimer>>,
+    run_timer: Res<RunTimer>,
+    run_config: Res<RunConfig>,
+    profile: Res<Profile>,
 ) {
-    if reader_zombie_player_hit.is_empty() || settings.god_mode {
+    let Ok(mut text) = timer_ui.get_single_mut() else {
         return;
-    }
+    };
 
-    car_health.0 -= ZOMBIE_ATTACK * reader_zombie_player_hit.len() as f32;
-    reader_zombie_player_hit.clear();
+    text.sections[0].value = RunTimer::format(run_timer.elapsed);
 
-    // Player dead
-    if car_health.0 <= 0.0 {
-        writer_player_dead.send(PlayerDeadEvent);
-    }
+    // Latest split against the same split of the best run, green when ahead
+    let best = run_config
+        .splits_key()
+        .and_then(|key| profile.best_splits.get(&key));
+    let idx = run_timer.splits.len().saturating_sub(1);
+    let best_split = best.and_then(|best| best.get(idx));
+    let (Some(split), Some(best_split)) = (run_timer.splits.last(), best_split) else {
+        text.sections[1].value.clear();
+        return;
+    };
+    let delta = split - best_split;
+    text.sections[1].value = format!("\n{delta:+.2}");
+    text.sections[1].style.color = if delta <= 0.0 {
+        SPLIT_AHEAD_COLOR
+    } else {
+        SPLIT_BEHIND_COLOR
+    };
 }
 
-fn check_obstacle_collision(
-    obstacles: Res<VehicleObstacleTiles>,
-    mut car_query: Query<(&Transform, &mut Speed), With<Car>>,
+fn handle_game_complete(
+    mut car_progress: ResMut<CarProgress>,
+    mut game_state: ResMut<NextState<GameState>>,
+    run_config: Res<RunConfig>,
 ) {
-    if car_query.is_empty() {
+    // Player going the opposite way
+    if car_progress.0 < -0.1 {
+        game_state.set(GameState::GameOver);
         return;
     }
 
-    let (car_transform, mut speed) = car_query.single_mut();
-    for obstacle in obstacles.0.iter() {
-        if (obstacle.pos.0 - car_transform.translation.x).abs() <= 25.0
-            && (obstacle.pos.1 - car_transform.translation.y).abs() <= 25.0
-        {
-            speed.0 = -6.0;
-        }
-
-        // speed = 10.0 or some +ve value slows the vehicle. given it was > 10.0 to begin with
+    // Boss rush is only over once the last boss is down
+    if car_progress.0 < 1.0 || run_config.boss_rush {
+        return;
     }
+
+    car_progress.0 = 1.0;
+    game_state.set(GameState::GameOver);
 }
 
-fn spawn_zombies(
-    mut commands: Commands,
-    texture_handle: Res<GlobalTextureHandle>,
-    car_query: Query<&Transform, With<Car>>,
-    zombie_query: Query<With<Zombie>>,
-    car_progress: Res<CarProgress>,
-    settings: Res<GameSettings>,
+fn update_game_ui_turbo(
+    mut turbo_ui: Query<&mut Visibility, With<GameUITurbo>>,
+    car_query: Query<&Turbo, With<Car>>,
+    tuning: Res<Tuning>,
 ) {
+    if turbo_ui.is_empty() {
+        return;
+    }
     if car_query.is_empty() {
         return;
     }
 
-    let max_zombies = settings.get_num_max_zombies();
-    let num_zombies = (max_zombies as f32 * car_progress.0 + 5.0).min(max_zombies as f32);
-    if zombie_query.iter().len() >= num_zombies as usize {
-        return;
+    let turbo = car_query.single();
+    let mut turbo_button = turbo_ui.single_mut();
+    let turbo_percentage =
+        (turbo.0.elapsed().as_secs_f32() / tuning.turbo_interval_sec).min(1.0) * 100.0;
+
+    if turbo_percentage >= 100.0 {
+        *turbo_button = Visibility::Inherited;
+    } else {
+        *turbo_button = Visibility::Hidden;
     }
+}
 
-    let mut rng = rand::thread_rng();
-    let car_transform = car_query.single();
-    let (cx, cy) = (car_transform.translation.x, car_transform.translation.y);
+fn select_difficulty_profile(
+    mut difficulty: ResMut<DifficultyProfile>,
+    tuning: Res<Tuning>,
+    run_config: Res<RunConfig>,
+) {
+    *difficulty = tuning.difficulty_profile(run_config.difficulty);
+}
 
-    let normal_zombie_probability = match settings.difficulty {
-        Difficulty::Easy => 0.99,
-        Difficulty::Moderate => 0.98,
-        Difficulty::Hard => 0.96,
-    };
-    let is_enable_road_zombies = match settings.difficulty {
-        Difficulty::Easy => car_progress.0 >= 0.7,
-        Difficulty::Moderate => car_progress.0 >= 0.6,
-        Difficulty::Hard => true,
-    };
-    let is_enable_half_road_zombies = match settings.difficulty {
-        Difficulty::Easy => car_progress.0 >= 0.4,
-        Difficulty::Moderate => true,
-        Difficulty::Hard => true,
-    };
-
-    for _ in 0..50 {
-        let (mut x, mut y) = (rng.gen_range(0.0..400.0), rng.gen_range(0.0..400.0));
-        if rng.gen_range(0.0..1.0) < normal_zombie_probability {
-            (x, y) = match rng.gen_range(1..=8) {
-                1 => (cx + WW as f32 + x, cy + y),
-                2 => (cx + WW as f32 + x, cy + y + rng.gen_range(1000.0..1200.0)),
-                3 => (cx + WW as f32 + x, cy + y + rng.gen_range(1500.0..1800.0)),
-                7 => (cx + WW as f32 + x, cy + y + rng.gen_range(1800.0..2500.0)),
-                4 => (cx - WW as f32 - x, cy - y),
-                5 => (cx - WW as f32 - x, cy - y + rng.gen_range(1000.0..1200.0)),
-                6 => (cx - WW as f32 - x, cy - y + rng.gen_range(1500.0..1800.0)),
-                _ => (cx - WW as f32 - x, cy - y + rng.gen_range(1800.0..2500.0)),
-            };
-        } else if is_enable_road_zombies {
-            if rng.gen_range(0.0..1.0) <= 0.5 {
-                (x, y) = (
-                    cx + x + (WW as f32) / 2.0 + rng.gen_range(0.0..100.0),
-                    cy + y + rng.gen_range(1000.0..1500.0),
-                );
-            } else {
-                (x, y) = (
-                    cx - x - (WW as f32) / 2.0 - rng.gen_range(0.0..100.0),
-                    cy + y + rng.gen_range(1000.0..1500.0),
-                );
-            }
-        } else if is_enable_half_road_zombies {
-            if rng.gen_range(0.0..1.0) <= 0.5 {
-                (x, y) = (
-                    cx + x + (WW as f32) / 2.0 + rng.gen_range(100.0..200.0),
-                    cy + y + rng.gen_range(1000.0..1500.0),
-                );
-            } else {
-                (x, y) = (
-                    cx - x - (WW as f32) / 2.0 - rng.gen_range(100.0..200.0),
-                    cy + y + rng.gen_range(1000.0..1500.0),
-                );
-            }
-        } else {
-            // Don't spawn the zoms on top of player
-            (x, y) = (10000.0, 10000.0);
-        }
+fn setup_game(
+    mut commands: Commands,
+    mut player_pos: ResMut<PlayerPos>,
+    global_texture_handle: Res<GlobalTextureHandle>,
+    profile: Res<Profile>,
+) {
+    // Spawn Car
+    let (x, y, z) = (150.0, 50.0, 10.0);
+    let mut car = commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: global_texture_handle.atlas(profile.car.sprite().atlas()),
+            sprite: TextureAtlasSprite {
+                index: profile.car.sprite().index(),
+                color: profile.palette.color(),
+                ..default()
+            },
+            transform: Transform::from_scale(Vec3::splat(3.0)).with_translation(vec3(x, y, z)),
+            ..default()
+        },
+        Car,
+        Speed(10.0),
+        TurnSpeed(0.0),
+        Turbo(Stopwatch::new()),
+        GameEntity,
+    ));
+    if let Some((transform, color)) = profile.decal.sprite() {
+        car.with_children(|parent| {
+            parent.spawn(SpriteSheetBundle {
+                texture_atlas: global_texture_handle.atlas(SpriteId::Block.atlas()),
+                sprite: TextureAtlasSprite {
+                    index: SpriteId::Block.index(),
+                    color,
+                    ..default()
+                },
+                transform,
+                ..default()
+            });
+        });
+    }
+    player_pos.0 = vec3(x, y, z);
+}
 
-        let mut tile = rng.gen_range(30..40);
-        let mut scale = 2.5;
-        if rng.gen_range(0.0..1.0) > 0.9 && car_progress.0 >= 0.3 {
-            tile = rng.gen_range(40..44);
-            scale = 3.2;
-        }
+fn setup_crosshair(mut commands: Commands, handle: Res<GlobalTextureHandle>) {
+    commands.spawn((
+        AtlasImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Px(CROSSHAIR_SIZE),
+                height: Val::Px(CROSSHAIR_SIZE),
+                ..default()
+            },
+            texture_atlas: handle.atlas(SpriteId::CrosshairCross.atlas()),
+            visibility: Visibility::Hidden,
+            // Over the HUD and the screen edge markers
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+        CrosshairImage,
+        GameEntity,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: AIM_LINE_COLOR,
+                custom_size: Some(Vec2::ONE),
+                anchor: Anchor::CenterLeft,
+                ..default()
+            },
+            // Over the zombies, under the bullets
+            transform: Transform::from_xyz(0.0, 0.0, 11.0),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        AimLine,
+        GameEntity,
+    ));
+}
+
+fn setup_score_popups(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // Popups are pooled, a horde getting mowed down just recycles the oldest ones
+    for _ in 0..SCORE_POPUP_POOL_SIZE {
         commands.spawn((
-            SpriteSheetBundle {
-                texture_atlas: texture_handle.0.clone().unwrap(),
-                sprite: TextureAtlasSprite::new(tile),
-                transform: Transform::from_scale(Vec3::splat(scale))
-                    .with_translation(vec3(x, y, 1.0)),
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 30.0,
+                        font: asset_server.load("font.ttf"),
+                        color: COLOR_LIGHT_ORANGE,
+                    },
+                ),
+                transform: Transform::from_xyz(0.0, 0.0, 20.0),
+                visibility: Visibility::Hidden,
                 ..default()
             },
-            Zombie,
+            ScorePopup::default(),
             GameEntity,
         ));
     }
 }
 
-fn cleanup_previous_game(
-    mut commands: Commands,
-    mut road_tiles: ResMut<RoadTiles>,
-    mut obstacles: ResMut<VehicleObstacleTiles>,
-    mut car_health: ResMut<CarHealth>,
-    mut car_progress: ResMut<CarProgress>,
-    mut player_score: ResMut<PlayerScore>,
-    mut player_position: ResMut<PlayerPos>,
-    mut cam_query: Query<&mut OrthographicProjection, With<Camera>>,
-    entities: Query<Entity, With<GameEntity>>,
-) {
-    for e in entities.iter() {
-        commands.entity(e).despawn_recursive();
+fn setup_tide_blobs(mut commands: Commands, handle: Res<GlobalTextureHandle>) {
+    for _ in 0..TIDE_POOL_SIZE {
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: handle.atlas(SpriteId::Zombie.atlas()),
+                sprite: TextureAtlasSprite {
+                    color: TIDE_COLOR,
+                    ..default()
+                },
+                // Level with the zombies it stands in for
+                transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TideBlob,
+            GameEntity,
+        ));
     }
+}
 
-    road_tiles.0.clear();
-    obstacles.0.clear();
-    car_health.0 = MAX_CAR_HEALTH;
-    car_progress.0 = 0.0;
-    player_score.0 = 0;
-    player_position.0 = Vec3::ZERO;
-
-    for mut projection in cam_query.iter_mut() {
-        projection.scale = 1.0;
+fn setup_blood_splats(mut commands: Commands) {
+    // Pooled like the popups, the gore setting caps how many are in use
+    for _ in 0..BLOOD_SPLAT_POOL_SIZE {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: BLOOD_SPLAT_COLOR,
+                    custom_size: Some(Vec2::splat(BLOOD_SPLAT_SIZE)),
+                    ..default()
+                },
+                // On the road, under the corpse piles
+                transform: Transform::from_xyz(0.0, 0.0, 0.4),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            BloodSplat::default(),
+            GameEntity,
+        ));
     }
 }
 
-fn despawn_zombies(
-    mut commands: Commands,
-    car_query: Query<&Transform, (With<Car>, Without<Zombie>)>,
-    zombie_query: Query<(Entity, &Transform), With<Zombie>>,
-) {
-    if car_query.is_empty() {
-        return;
+#[cfg(feature = "debug-tools")]
+fn setup_debug_heatmap(mut commands: Commands) {
+    for _ in 0..DEBUG_HEATMAP_POOL_SIZE {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(ZOMBIE_GRID_CELL)),
+                    ..default()
+                },
+                // Over the road and zombies, under the bullets and popups
+                transform: Transform::from_xyz(0.0, 0.0, 12.0),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            HeatmapCell,
+            GameEntity,
+        ));
     }
+}
 
-    let car_transform = car_query.single();
-    for (e, t) in zombie_query.iter() {
-        if car_transform.translation.y - t.translation.y <= 700.0 {
-            continue;
-        }
+fn setup_bullet_vfx(mut commands: Commands) {
+    // Untextured sprites, plain white stretched to size and tinted
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: MUZZLE_FLASH_COLOR,
+                custom_size: Some(Vec2::splat(14.0)),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        MuzzleFlash::default(),
+        GameEntity,
+    ));
 
-        commands.entity(e).despawn();
+    // Trails are pooled like the score popups, continuous fire recycles the oldest
+    for _ in 0..BULLET_TRAIL_POOL_SIZE {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: BULLET_TRAIL_COLOR,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            BulletTrail::default(),
+            GameEntity,
+        ));
+    }
+
+    for _ in 0..PARTICLE_POOL_SIZE {
+        commands.spawn((
+            SpriteBundle {
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Particle::default(),
+            GameEntity,
+        ));
     }
 }
 
-fn bullet_hit_zombie(
-    mut commands: Commands,
-    mut player_score: ResMut<PlayerScore>,
-    bullets_query: Query<&Transform, With<Bullet>>,
-    zombie_query: Query<(Entity, &Transform), (With<Zombie>, Without<Bullet>)>,
+fn emit_casings(
+    new_bullets_query: Query<(&Transform, &BulletDirection), Added<Bullet>>,
+    mut writer_particles: EventWriter<ParticleBurstEvent>,
 ) {
-    for (e, t) in zombie_query.iter() {
-        for b in bullets_query.iter() {
-            if (b.translation.x - t.translation.x).abs() <= BULLET_HIT_BOX
-                && (b.translation.y - t.translation.y).abs() <= BULLET_HIT_BOX
-            {
-                player_score.0 += 1;
-                commands.entity(e).despawn();
-            }
-        }
+    // One casing per shot, thrown out to the right of the aim
+    if let Some((bullet_transform, direction)) = new_bullets_query.iter().next() {
+        let dir = direction.0.truncate().normalize_or_zero();
+        writer_particles.send(ParticleBurstEvent {
+            pos: bullet_transform.translation.truncate(),
+            dir: vec2(dir.y, -dir.x),
+            kind: ParticleKind::Casing,
+        });
     }
 }
 
-fn check_zombie_collision(
-    zombie_query: Query<&Transform, With<Zombie>>,
-    car_query: Query<&Transform, (With<Car>, Without<Zombie>)>,
-    mut writer_player_hit: EventWriter<ZombieHitPlayer>,
+fn emit_dirt(
+    time: Res<Time>,
+    mut cooldown: Local<f32>,
+    road_tiles: Res<RoadTiles>,
+    car_query: Query<(&Transform, &Speed), With<Car>>,
+    mut writer_particles: EventWriter<ParticleBurstEvent>,
 ) {
-    if car_query.is_empty() {
+    *cooldown -= time.delta_seconds();
+    let Ok((transform, speed)) = car_query.get_single() else {
+        return;
+    };
+    if *cooldown > 0.0
+        || speed.0.abs() < DIRT_MIN_SPEED
+        || road_surface_at(&road_tiles, transform.translation).is_some()
+    {
         return;
     }
 
-    let car_transform = car_query.single();
-    let (car_x, car_y) = (car_transform.translation.x, car_transform.translation.y);
-    for t in zombie_query.iter() {
-        let y_dist = (t.translation.y - car_y).abs();
-        let x_dist = (t.translation.x - car_x).abs();
-        if x_dist <= 20.0 && y_dist <= 20.0 {
-            writer_player_hit.send(ZombieHitPlayer);
-        }
-    }
+    // Kicked up behind the rear wheels
+    *cooldown = DIRT_INTERVAL_SEC;
+    let back = -(transform.rotation * Vec3::Y).truncate() * speed.0.signum();
+    writer_particles.send(ParticleBurstEvent {
+        pos: transform.translation.truncate() + back * 20.0,
+        dir: back,
+        kind: ParticleKind::Dirt,
+    });
 }
 
-fn update_zombies(
+fn update_particles(
     time: Res<Time>,
-    mut zombie_query: Query<&mut Transform, With<Zombie>>,
-    car_query: Query<&Transform, (With<Car>, Without<Zombie>)>,
+    mut reader_particles: EventReader<ParticleBurstEvent>,
+    mut particle_query: Query<(&mut Particle, &mut Transform, &mut Sprite, &mut Visibility)>,
 ) {
-    if car_query.is_empty() {
-        return;
+    let mut rng = rand::thread_rng();
+    for burst in reader_particles.read() {
+        for _ in 0..burst.kind.count() {
+            // Over the cap the oldest particles get recycled
+            let particle = particle_query.iter_mut().max_by(|(a, ..), (b, ..)| {
+                a.active
+                    .cmp(&b.active)
+                    .reverse()
+                    .then(a.age.total_cmp(&b.age))
+            });
+            let Some((mut particle, _, mut sprite, _)) = particle else {
+                break;
+            };
+
+            let spread = rng.gen_range(-0.5..0.5);
+            let speed = burst.kind.speed() * rng.gen_range(0.6..1.0);
+            *particle = Particle {
+                active: true,
+                kind: burst.kind,
+                pos: burst.pos,
+                vel: Vec2::from_angle(spread).rotate(burst.dir) * speed,
+                vertical_vel: burst.kind.speed() * rng.gen_range(0.3..0.6),
+                spin: rng.gen_range(-15.0..15.0),
+                ..default()
+            };
+            sprite.color = burst.kind.color();
+            sprite.custom_size = Some(burst.kind.size());
+        }
     }
 
-    let mut rng = rand::thread_rng();
-    let car_transform = car_query.single();
-    let (car_x, car_y) = (car_transform.translation.x, car_transform.translation.y);
-    let target_x = car_x;
+    let dt = time.delta_seconds();
+    for (mut particle, mut transform, mut sprite, mut visibility) in particle_query.iter_mut() {
+        if !particle.active {
+            continue;
+        }
 
-    for mut z in zombie_query.iter_mut() {
-        let mut target_y = car_y;
-        if z.translation.y - target_y > 500.0 && rng.gen_range(0.0..1.0) > 0.5 {
-            target_y += rng.gen_range(500.0..1500.0);
+        let lifetime = particle.kind.lifetime();
+        particle.age += dt;
+        if particle.age >= lifetime {
+            particle.active = false;
+            *visibility = Visibility::Hidden;
+            continue;
         }
 
-        let dir = vec3(target_x - z.translation.x, target_y - z.translation.y, 0.0).normalize();
-        let rand_dir = vec3(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5), 0.0);
+        let vel = particle.vel;
+        particle.pos += vel * dt;
+        particle.vertical_vel -= PARTICLE_GRAVITY * dt;
+        particle.height += particle.vertical_vel * dt;
+        if particle.height <= 0.0 {
+            // Hits the road, bounces a little and skids to a stop
+            particle.height = 0.0;
+            particle.vertical_vel = -particle.vertical_vel * PARTICLE_BOUNCE;
+            particle.vel *= PARTICLE_GROUND_FRICTION;
+            particle.spin *= PARTICLE_GROUND_FRICTION;
+        }
 
-        z.translation += (dir + rand_dir) * ZOMBIE_SPEED * time.delta_seconds();
+        transform.translation = (particle.pos + vec2(0.0, particle.height)).extend(14.0);
+        transform.rotate_z(particle.spin * dt);
+        let fade = ((lifetime - particle.age) / (lifetime * PARTICLE_FADE)).min(1.0);
+        sprite.color.set_a(particle.kind.color().a() * fade);
+        *visibility = Visibility::Visible;
     }
 }
 
-fn spawn_road(
-    mut commands: Commands,
-    mut road_tiles: ResMut<RoadTiles>,
-    mut obstacle_tiles: ResMut<VehicleObstacleTiles>,
-    texture_handle: Res<GlobalTextureHandle>,
+fn update_muzzle_flash(
+    time: Res<Time>,
+    new_bullets_query: Query<(&Transform, &BulletDirection), Added<Bullet>>,
+    mut flash_query: Query<(&mut MuzzleFlash, &mut Transform, &mut Visibility), Without<Bullet>>,
 ) {
-    let mut rng = rand::thread_rng();
-    let top_y = ROAD_HEIGHT as i32;
-    let bottom_y = -10;
-    let left_x = 0;
-    let right_x = ROAD_WIDTH as i32;
-    let mut offset = 0;
-    let mut n_offset = 0;
-    let mut p_offset = 0;
-
-    for j in bottom_y..=top_y {
-        let is_top_y = j == top_y || j == top_y - 1;
+    let Ok((mut flash, mut transform, mut visibility)) = flash_query.get_single_mut() else {
+        return;
+    };
 
-        if is_top_y {
-            for a in 0..=ROAD_WIDTH as i32 {
-                let (x, y) = (
-                    (offset + a) as f32 * TILE_W as f32 * ROAD_SCALE,
-                    j as f32 * TILE_H as f32 * ROAD_SCALE,
-                );
-                commands.spawn((
-                    SpriteSheetBundle {
-                        texture_atlas: texture_handle.0.clone().unwrap(),
-                        sprite: TextureAtlasSprite::new(17),
-                        transform: Transform::from_scale(Vec3::splat(ROAD_SCALE))
-                            .with_translation(vec3(x, y, 1.0)),
-                        ..default()
-                    },
-                    Road,
-                    GameEntity,
-                ));
+    // Pellets of one shot share an origin, any of them will do
+    if let Some((bullet_transform, direction)) = new_bullets_query.iter().next() {
+        let dir = direction.0.truncate().normalize_or_zero();
+        let pos = bullet_transform.translation.truncate() + dir * MUZZLE_FLASH_OFFSET;
+        flash.0 = 0.0;
+        transform.translation = pos.extend(16.0);
+        transform.rotation =
+            Quat::from_rotation_z(dir.y.atan2(dir.x) + std::f32::consts::FRAC_PI_4);
+        *visibility = Visibility::Visible;
+        return;
+    }
+
+    flash.0 += time.delta_seconds();
+    if flash.0 >= MUZZLE_FLASH_SEC {
+        visibility.set_if_neq(Visibility::Hidden);
+    }
+}
+
+fn update_bullet_trails(
+    time: Res<Time>,
+    new_bullets_query: Query<(Entity, &Transform), Added<Bullet>>,
+    bullets_query: Query<(&Transform, Ref<BulletDirection>), With<Bullet>>,
+    mut trail_query: Query<
+        (
+            &mut BulletTrail,
+            &mut Transform,
+            &mut Sprite,
+            &mut Visibility,
+        ),
+        Without<Bullet>,
+    >,
+) {
+    for (bullet, bullet_transform) in new_bullets_query.iter() {
+        let trail = trail_query.iter_mut().max_by(|(a, ..), (b, ..)| {
+            a.active
+                .cmp(&b.active)
+                .reverse()
+                .then(a.age.total_cmp(&b.age))
+        });
+        let Some((mut trail, ..)) = trail else {
+            break;
+        };
+
+        let pos = bullet_transform.translation.truncate();
+        *trail = BulletTrail {
+            active: true,
+            bullet: Some(bullet),
+            origin: pos,
+            head: pos,
+            ..default()
+        };
+    }
+
+    for (mut trail, mut transform, mut sprite, mut visibility) in trail_query.iter_mut() {
+        if !trail.active {
+            continue;
+        }
+
+        trail.age += time.delta_seconds();
+        match trail.bullet.map(|bullet| bullets_query.get(bullet)) {
+            Some(Ok((bullet_transform, direction))) => {
+                // Ricocheted, start the trail over from where it bounced
+                if direction.is_changed() && !direction.is_added() {
+                    trail.origin = trail.head;
+                }
+                trail.head = bullet_transform.translation.truncate();
+            }
+            // Bullet hit something or timed out, the trail stays put and fades
+            _ => {
+                trail.bullet = None;
+                trail.fade += time.delta_seconds();
+                if trail.fade >= BULLET_TRAIL_FADE_SEC {
+                    trail.active = false;
+                    *visibility = Visibility::Hidden;
+                    continue;
+                }
             }
         }
 
-        if j % 5 == 0 && !is_top_y {
-            p_offset = offset;
-            offset = n_offset;
-            n_offset += rng.gen_range(-1..=1);
+        let to_head = trail.head - trail.origin;
+        let length = to_head.length().min(BULLET_TRAIL_MAX_LENGTH);
+        let dir = to_head.normalize_or_zero();
+        let center = trail.head - dir * length / 2.0;
+        transform.translation = center.extend(14.0);
+        transform.rotation =
+            Quat::from_rotation_z(dir.y.atan2(dir.x) - std::f32::consts::FRAC_PI_2);
+        sprite.custom_size = Some(vec2(BULLET_TRAIL_WIDTH, length));
+        sprite
+            .color
+            .set_a(BULLET_TRAIL_COLOR.a() * (1.0 - trail.fade / BULLET_TRAIL_FADE_SEC));
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn update_score_popups(
+    time: Res<Time>,
+    mut reader_score_popup: EventReader<ScorePopupEvent>,
+    mut popup_query: Query<(&mut ScorePopup, &mut Transform, &mut Text, &mut Visibility)>,
+) {
+    for event in reader_score_popup.read() {
+        let popup = popup_query.iter_mut().max_by(|(a, ..), (b, ..)| {
+            a.active
+                .cmp(&b.active)
+                .reverse()
+                .then(a.age.total_cmp(&b.age))
+        });
+        let Some((mut popup, mut transform, mut text, mut visibility)) = popup else {
+            break;
+        };
+
+        popup.active = true;
+        popup.age = 0.0;
+        transform.translation = vec3(event.pos.x, event.pos.y, 20.0);
+        text.sections[0].value = format!("+{}", event.score);
+        text.sections[0].style.color = COLOR_LIGHT_ORANGE;
+        *visibility = Visibility::Visible;
+    }
+
+    for (mut popup, mut transform, mut text, mut visibility) in popup_query.iter_mut() {
+        if !popup.active {
+            continue;
         }
 
-        // if next tile is curve
-        if (j + 1) % 5 == 0 && !is_top_y {
-            if (offset - n_offset) == 1 {
-                let (x, y) = (
-                    (0 + offset - 1) as f32 * TILE_W as f32 * ROAD_SCALE,
-                    j as f32 * TILE_H as f32 * ROAD_SCALE,
-                );
-                let (x, y) = (x + 10.0, y);
-                commands.spawn((
-                    SpriteSheetBundle {
-                        texture_atlas: texture_handle.0.clone().unwrap(),
-                        sprite: TextureAtlasSprite::new(85),
-                        transform: Transform::from_scale(Vec3::splat(ROAD_SCALE))
-                            .with_translation(vec3(x, y, 0.0)),
-                        ..default()
-                    },
-                    Road,
-                    GameEntity,
-                ));
-            } else if (offset - n_offset) == -1 {
-                let (x, y) = (
-                    (0 + n_offset + 1 + ROAD_WIDTH as i32 - 1) as f32 * TILE_W as f32 * ROAD_SCALE,
-                    j as f32 * TILE_H as f32 * ROAD_SCALE,
-                );
-                let (x, y) = (x - 10.0, y);
-                commands.spawn((
-                    SpriteSheetBundle {
-                        texture_atlas: texture_handle.0.clone().unwrap(),
-                        sprite: TextureAtlasSprite::new(86),
-                        transform: Transform::from_scale(Vec3::splat(ROAD_SCALE))
-                            .with_translation(vec3(x, y, 0.0)),
-                        ..default()
-                    },
-                    Road,
-                    GameEntity,
-                ));
-            }
+        popup.age += time.delta_seconds();
+        if popup.age >= SCORE_POPUP_LIFETIME_SEC {
+            popup.active = false;
+            *visibility = Visibility::Hidden;
+            continue;
         }
 
-        // OBSTACLE
-        if rng.gen_range(0.0..1.0) > 0.9 && j > 30 {
-            let (mut x, y) = (
-                (rng.gen_range((offset + 1)..(offset + 5))) as f32 * TILE_W as f32 * ROAD_SCALE,
-                j as f32 * TILE_H as f32 * ROAD_SCALE,
-            );
-            x += rng.gen_range(-1.0..=-1.0) * TILE_W as f32 * ROAD_SCALE;
+        transform.translation.y += SCORE_POPUP_RISE_SPEED * time.delta_seconds();
+        text.sections[0]
+            .style
+            .color
+            .set_a(1.0 - popup.age / SCORE_POPUP_LIFETIME_SEC);
+    }
+}
 
-            let obstacle = VehicleObstacle::new((x, y));
-            commands.spawn((
-                SpriteSheetBundle {
-                    texture_atlas: texture_handle.0.clone().unwrap(),
-                    sprite: TextureAtlasSprite::new(obstacle.vehicle_type.sprite_idx()),
-                    transform: Transform::from_scale(Vec3::splat(3.0))
-                        .with_translation(vec3(x, y, 1.0)),
-                    ..default()
-                },
-                Obstacle,
-                GameEntity,
-            ));
-            obstacle_tiles.0.push(obstacle);
+#[allow(clippy::type_complexity)]
+fn cleanup_main_menu_background(
+    mut commands: Commands,
+    background_query: Query<Entity, Or<(With<MainMenuZombie>, With<MainMenuCar>)>>,
+) {
+    for e in background_query.iter() {
+        commands.entity(e).despawn();
+    }
+}
+
+fn spawn_main_menu_zombies(
+    mut commands: Commands,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    texture_handle: Res<GlobalTextureHandle>,
+) {
+    let mut rng = rand::thread_rng();
+    let center = camera_query.single().translation.truncate();
+    for _ in 0..MENU_ZOMBIE_COUNT {
+        let mut tile = SpriteId::Zombie.random_index(&mut rng);
+        let mut scale = 2.5;
+
+        if rng.gen_range(0.0..1.0) > 0.8 {
+            tile = SpriteId::BigZombie.random_index(&mut rng);
+            scale = 3.2;
         }
 
-        // road decorations
-        if rng.gen_range(0.0..1.0) > 0.6 {
-            let (mut x, y) = (
-                (offset - 2 - rng.gen_range(1..=2)) as f32 * TILE_W as f32 * ROAD_SCALE,
-                j as f32 * TILE_H as f32 * ROAD_SCALE,
+        let pos = center
+            + vec2(
+                rng.gen_range(-MENU_HALF_SIZE.x..MENU_HALF_SIZE.x),
+                rng.gen_range(-MENU_HALF_SIZE.y..MENU_HALF_SIZE.y),
             );
-            if rng.gen_range(0.0..1.0) > 0.5 {
-                x += 2.0 * (ROAD_WIDTH) as f32 * TILE_W as f32 * ROAD_SCALE;
-                x += rng.gen_range(1.0..3.0) * TILE_W as f32 * ROAD_SCALE;
-            }
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(SpriteId::Zombie.atlas()),
+                sprite: TextureAtlasSprite::new(tile),
+                transform: Transform::from_scale(Vec3::splat(scale))
+                    .with_translation(pos.extend(0.0)),
+                ..default()
+            },
+            MainMenuZombie {
+                waypoint: next_menu_waypoint(center, pos, &mut rng),
+                pause: rng.gen_range(0.0..MENU_ZOMBIE_MAX_PAUSE_SEC),
+            },
+        ));
+    }
+}
 
-            commands.spawn((
-                SpriteSheetBundle {
-                    texture_atlas: texture_handle.0.clone().unwrap(),
-                    sprite: TextureAtlasSprite::new(50),
-                    transform: Transform::from_scale(Vec3::splat(ROAD_SCALE))
-                        .with_translation(vec3(x, y, 0.0)),
-                    ..default()
-                },
-                Road,
-                GameEntity,
-            ));
+fn update_main_menu_zombies(
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+    camera_query: Query<&Transform, (With<MainCamera>, Without<MainMenuZombie>)>,
+    car_query: Query<&Transform, (With<MainMenuCar>, Without<MainMenuZombie>)>,
+    mut zombies: Query<(&mut Transform, &mut TextureAtlasSprite, &mut MainMenuZombie)>,
+) {
+    let Ok(camera_transform) = camera_query.get_single() else {
+        return;
+    };
+
+    let center = camera_transform.translation.truncate();
+    let mut rng = rand::thread_rng();
+    let dt = time.delta_seconds();
+    let step = tuning.zombie_speed * MENU_ZOMBIE_SPEED_FACTOR * dt;
+    for (mut transform, mut sprite, mut zombie) in zombies.iter_mut() {
+        let pos = transform.translation.truncate();
+
+        // Shuffle out of the way of a passing car
+        let car = car_query
+            .iter()
+            .map(|t| t.translation.truncate())
+            .find(|car_pos| car_pos.distance(pos) <= MENU_CAR_SCATTER_RADIUS);
+        if let Some(car_pos) = car {
+            let away = (pos - car_pos).normalize_or_zero();
+            zombie.waypoint = (pos + away * MENU_WAYPOINT_RADIUS)
+                .clamp(center - MENU_HALF_SIZE, center + MENU_HALF_SIZE);
+            zombie.pause = 0.0;
         }
 
-        if j % 5 == 0 && !is_top_y {
-            if (offset - p_offset) == 1 {
-                let (x, y) = (
-                    (0 + offset - 1) as f32 * TILE_W as f32 * ROAD_SCALE,
-                    j as f32 * TILE_H as f32 * ROAD_SCALE,
-                );
-                let (x, y) = (x + 10.0, y);
-                commands.spawn((
-                    SpriteSheetBundle {
-                        texture_atlas: texture_handle.0.clone().unwrap(),
-                        sprite: TextureAtlasSprite::new(83),
-                        transform: Transform::from_scale(Vec3::splat(ROAD_SCALE))
-                            .with_translation(vec3(x, y, 0.0)),
-                        ..default()
-                    },
-                    Road,
-                    GameEntity,
-                ));
-            } else if (offset - p_offset) == -1 {
-                let (x, y) = (
-                    (0 + offset + 1 + ROAD_WIDTH as i32) as f32 * TILE_W as f32 * ROAD_SCALE,
-                    j as f32 * TILE_H as f32 * ROAD_SCALE,
-                );
-                let (x, y) = (x - 10.0, y);
-                commands.spawn((
-                    SpriteSheetBundle {
-                        texture_atlas: texture_handle.0.clone().unwrap(),
-                        sprite: TextureAtlasSprite::new(84),
-                        transform: Transform::from_scale(Vec3::splat(ROAD_SCALE))
-                            .with_translation(vec3(x, y, 0.0)),
-                        ..default()
-                    },
-                    Road,
-                    GameEntity,
-                ));
-            }
+        if zombie.pause > 0.0 {
+            zombie.pause -= dt;
+            continue;
         }
 
-        for i in left_x..=right_x {
-            let tile = if i == 0 {
-                80
-            } else if i == right_x {
-                82
-            } else {
-                81
-            };
-            let (x, y) = (
-                (i + offset) as f32 * TILE_W as f32 * ROAD_SCALE,
-                j as f32 * TILE_H as f32 * ROAD_SCALE,
-            );
-            road_tiles.0.insert((i + offset, j));
-            commands.spawn((
-                SpriteSheetBundle {
-                    texture_atlas: texture_handle.0.clone().unwrap(),
-                    sprite: TextureAtlasSprite::new(tile),
-                    transform: Transform::from_scale(Vec3::splat(ROAD_SCALE))
-                        .with_translation(vec3(x, y, 0.0)),
-                    ..default()
-                },
-                Road,
-                GameEntity,
-            ));
+        // Idle for a bit on arrival, then head somewhere new
+        let to_waypoint = zombie.waypoint - pos;
+        if to_waypoint.length() <= step {
+            transform.translation = zombie.waypoint.extend(transform.translation.z);
+            zombie.pause = rng.gen_range(0.0..MENU_ZOMBIE_MAX_PAUSE_SEC);
+            zombie.waypoint = next_menu_waypoint(center, zombie.waypoint, &mut rng);
+            continue;
         }
+
+        let dir = to_waypoint.normalize();
+        transform.translation += (dir * step).extend(0.0);
+        sprite.flip_x = dir.x < 0.0;
     }
 }
 
-fn mark_game_setup_done(mut game_state: ResMut<NextState<GameState>>) {
-    game_state.set(GameState::InGame);
+// A random spot near `from`, kept inside the menu background
+fn next_menu_waypoint(center: Vec2, from: Vec2, rng: &mut impl Rng) -> Vec2 {
+    let offset = vec2(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * MENU_WAYPOINT_RADIUS;
+    (from + offset).clamp(center - MENU_HALF_SIZE, center + MENU_HALF_SIZE)
 }
 
-fn shoot_gun(
+#[allow(clippy::too_many_arguments)]
+fn update_main_menu_cars(
     mut commands: Commands,
+    mut next_car: Local<f32>,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
     asset_server: Res<AssetServer>,
-    buttons: Res<Input<MouseButton>>,
-    q_window: Query<&Window, With<PrimaryWindow>>,
-    q_camera: Query<(&Camera, &GlobalTransform), With<Camera>>,
     texture_handle: Res<GlobalTextureHandle>,
-    car_query: Query<&Transform, With<Car>>,
-    settings: Res<GameSettings>,
+    camera_query: Query<&Transform, (With<MainCamera>, Without<MainMenuCar>)>,
+    mut car_query: Query<(Entity, &mut Transform, &MainMenuCar)>,
 ) {
-    if car_query.is_empty() {
-        return;
-    }
-    if !buttons.pressed(MouseButton::Left) {
+    let Ok(camera_transform) = camera_query.get_single() else {
         return;
-    }
+    };
 
-    let (camera, camera_transform) = q_camera.single();
-    let window = q_window.single();
-    let mut cursor_pos = None;
-    if let Some(world_position) = window
-        .cursor_position()
-        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
-        .map(|ray| ray.origin.truncate())
-    {
-        cursor_pos = Some(vec3(world_position.x, world_position.y, 0.0));
+    let center = camera_transform.translation.truncate();
+    let dt = time.delta_seconds();
+    for (e, mut transform, car) in car_query.iter_mut() {
+        transform.translation += (car.0 * dt).extend(0.0);
+        let offset = transform.translation.truncate() - center;
+        if offset.abs().cmpgt(MENU_HALF_SIZE + 100.0).any() {
+            commands.entity(e).despawn();
+        }
     }
 
-    let car_transform = car_query.single();
-    let (x, y) = (car_transform.translation.x, car_transform.translation.y);
-    let direction = if cursor_pos.is_none() {
-        car_transform.local_y()
-    } else {
-        cursor_pos.unwrap() - car_transform.translation
-    };
-
-    if settings.music {
-        commands.spawn(AudioBundle {
-            source: asset_server.load("bulletfire.mp3"),
-            settings: PlaybackSettings {
-                volume: Volume::Absolute(VolumeLevel::new(0.05)),
-                ..Default::default()
-            },
-            ..default()
-        });
+    *next_car -= dt;
+    if *next_car > 0.0 {
+        return;
     }
+    let mut rng = rand::thread_rng();
+    *next_car = MENU_CAR_INTERVAL_SEC * rng.gen_range(0.5..1.5);
+
+    // Enters past the top or bottom edge and drifts a little sideways on the way across
+    let dir_y = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+    let start = center
+        + vec2(
+            rng.gen_range(-MENU_HALF_SIZE.x..MENU_HALF_SIZE.x) * 0.8,
+            -dir_y * (MENU_HALF_SIZE.y + 50.0),
+        );
+    let velocity = vec2(rng.gen_range(-0.15..0.15), dir_y).normalize() * MENU_CAR_SPEED;
+    let sprite = VehicleObstacleType::random(&mut rng).sprite();
     commands.spawn((
         SpriteSheetBundle {
-            texture_atlas: texture_handle.0.clone().unwrap(),
-            sprite: TextureAtlasSprite::new(1),
-            transform: Transform::from_scale(Vec3::splat(2.0)).with_translation(vec3(x, y, 15.0)),
+            texture_atlas: texture_handle.atlas(sprite.atlas()),
+            sprite: TextureAtlasSprite::new(sprite.index()),
+            transform: Transform::from_scale(Vec3::splat(3.0))
+                .with_translation(start.extend(5.0))
+                .with_rotation(Quat::from_rotation_z((-velocity.x).atan2(velocity.y))),
             ..default()
         },
-        Bullet(Instant::now()),
-        BulletDirection(direction),
-        GameEntity,
+        MainMenuCar(velocity),
     ));
+
+    // Just a low rumble under the music
+    if cfg!(feature = "audio") && settings.music {
+        commands.spawn(AudioBundle {
+            source: asset_server.load("turbo.mp3"),
+            settings: PlaybackSettings::DESPAWN
+                .with_volume(Volume::Absolute(VolumeLevel::new(MENU_CAR_VOLUME)))
+                .with_speed(0.6),
+        });
+    }
+}
+
+fn setup_pause_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tutorial: Res<Tutorial>,
+) {
+    let button_style = Style {
+        width: Val::Px(250.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(12.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 40.0,
+        color: COLOR_BLACK,
+        font: asset_server.load("font.ttf"),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            PauseMenuComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Paused",
+                            TextStyle {
+                                font_size: 70.0,
+                                font: asset_server.load("font.ttf"),
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(30.0)),
+                            ..default()
+                        }),
+                    );
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "- WASD to move\n- SpaceBar for Turbo (when available)\n- Hold left click to shoot\n- H to honk",
+                            TextStyle {
+                                font_size: 30.0,
+                                font: asset_server.load("font.ttf"),
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(30.0)),
+                            ..default()
+                        }),
+                    );
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            PauseMenuButtonAction::Resume,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Resume",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    if tutorial.0.is_some() {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: COLOR_ORANGE.into(),
+                                    ..default()
+                                },
+                                PauseMenuButtonAction::SkipTutorial,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    "Skip Tutorial",
+                                    button_text_style.clone(),
+                                ));
+                            });
+                    }
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            PauseMenuButtonAction::Settings,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Settings",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style,
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            PauseMenuButtonAction::ExitToMainMenu,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Main Menu", button_text_style));
+                        });
+                });
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn setup_game_over_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    player_score: Res<PlayerScore>,
+    car_progress: Res<CarProgress>,
+    on_foot_escape: Res<OnFootEscape>,
+    run_config: Res<RunConfig>,
+    profile: Res<Profile>,
+    coop: Res<Coop>,
+    run_stats: Res<RunStats>,
+) {
+    let button_style = Style {
+        width: Val::Px(250.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 40.0,
+        font: asset_server.load("font.ttf"),
+        color: COLOR_BLACK,
+    };
+    let message = RunOutcome::new(&car_progress, &on_foot_escape).message();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            GameOverMenuComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            message,
+                            TextStyle {
+                                font: asset_server.load("font.ttf"),
+                                font_size: 70.0,
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(16.0)),
+                            ..default()
+                        }),
+                    );
+                    parent.spawn(
+                        TextBundle::from_section(
+                            if run_config.modifiers.is_empty() {
+                                format!("Score: {:?}", player_score.0)
+                            } else {
+                                format!(
+                                    "Score: {:?} (x{:.2})",
+                                    player_score.0,
+                                    run_config.score_multiplier()
+                                )
+                            },
+                            TextStyle {
+                                font: asset_server.load("font.ttf"),
+                                font_size: 60.0,
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(32.0)),
+                            ..default()
+                        }),
+                    );
+                    if let Some(session) = coop.session.as_ref() {
+                        let team_score = player_score.0
+                            + session.peers.values().map(|p| p.state.score).sum::<u32>();
+                        parent.spawn(
+                            TextBundle::from_section(
+                                format!("Team Score: {team_score}"),
+                                TextStyle {
+                                    font: asset_server.load("font.ttf"),
+                                    font_size: 35.0,
+                                    color: COLOR_LIGHT_ORANGE,
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::bottom(Val::Px(24.0)),
+                                ..default()
+                            }),
+                        );
+                    }
+                    if let Some(day) = run_config.daily {
+                        let best = match profile.daily_best {
+                            Some((best_day, best)) if best_day == day => best,
+                            _ => player_score.0,
+                        };
+                        parent.spawn(
+                            TextBundle::from_section(
+                                format!(
+                                    "Daily #{day} ({})\nToday's Best: {best}",
+                                    run_config.describe()
+                                ),
+                                TextStyle {
+                                    font: asset_server.load("font.ttf"),
+                                    font_size: 35.0,
+                                    color: COLOR_LIGHT_ORANGE,
+                                },
+                            )
+                            .with_text_alignment(TextAlignment::Center)
+                            .with_style(Style {
+                                margin: UiRect::bottom(Val::Px(24.0)),
+                                ..default()
+                            }),
+                        );
+                    }
+                    if !run_config.arena {
+                        spawn_damage_strip(parent, &asset_server, &run_stats, car_progress.0);
+                    }
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            GameOverMenuButtonAction::Restart,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Restart",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style,
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            GameOverMenuButtonAction::ExitToMainMenu,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Main Menu", button_text_style));
+                        });
+                });
+        });
+}
+
+// The road from start to finish, redder where more health was lost
+fn spawn_damage_strip(
+    parent: &mut ChildBuilder,
+    asset_server: &AssetServer,
+    run_stats: &RunStats,
+    progress: f32,
+) {
+    let most = run_stats.damage.iter().copied().fold(0.0, f32::max);
+    parent.spawn(TextBundle::from_section(
+        if most > 0.0 {
+            "Damage Taken"
+        } else {
+            "No Damage Taken"
+        },
+        TextStyle {
+            font: asset_server.load("font.ttf"),
+            font_size: 25.0,
+            color: COLOR_LIGHT_ORANGE,
+        },
+    ));
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(DAMAGE_STRIP_WIDTH),
+                height: Val::Px(DAMAGE_STRIP_HEIGHT),
+                margin: UiRect::vertical(Val::Px(8.0)),
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            border_color: COLOR_LIGHT_ORANGE.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            for damage in run_stats.damage {
+                let heat = if most > 0.0 { damage / most } else { 0.0 };
+                let [r, g, b, _] = DAMAGE_STRIP_COLOR.as_rgba_f32();
+                let [hr, hg, hb, _] = DAMAGE_STRIP_HOT_COLOR.as_rgba_f32();
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        flex_grow: 1.0,
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::rgb(
+                        r + (hr - r) * heat,
+                        g + (hg - g) * heat,
+                        b + (hb - b) * heat,
+                    )
+                    .into(),
+                    ..default()
+                });
+            }
+            // Where the run ended
+            parent.spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(progress.clamp(0.0, 1.0) * 100.0),
+                    width: Val::Px(2.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                background_color: COLOR_LIGHT_ORANGE.into(),
+                ..default()
+            });
+        });
+    let label_style = TextStyle {
+        font: asset_server.load("font.ttf"),
+        font_size: 18.0,
+        color: COLOR_LIGHT_ORANGE,
+    };
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(DAMAGE_STRIP_WIDTH),
+                justify_content: JustifyContent::SpaceBetween,
+                margin: UiRect::bottom(Val::Px(16.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Start", label_style.clone()));
+            parent.spawn(TextBundle::from_section("Finish", label_style));
+        });
+}
+
+fn setup_settings_menu(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    settings_page: Res<SettingsPage>,
+    asset_server: Res<AssetServer>,
+    history: Res<MenuHistory>,
+) {
+    spawn_settings_menu(
+        &mut commands,
+        &settings,
+        *settings_page,
+        history.in_run(),
+        &asset_server,
+    );
+}
+
+fn spawn_settings_menu(
+    commands: &mut Commands,
+    settings: &GameSettings,
+    settings_page: SettingsPage,
+    // Opened from the pause menu, nothing that would leave the run
+    in_run: bool,
+    asset_server: &AssetServer,
+) {
+    let mut button_style = Style {
+        width: Val::Px(500.0),
+        height: Val::Px(65.0),
+        margin: UiRect::axes(Val::Px(40.0), Val::Px(1.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 40.0,
+        color: COLOR_BLACK,
+        font: asset_server.load("font.ttf"),
+    };
+
+    let controls_labels = [
+        settings.dead_zone_label(),
+        settings.steer_sensitivity_label(),
+        settings.aim_sensitivity_label(),
+    ];
+    let mut buttons = vec![(SettingsMenuButtonAction::Page, settings_page.as_str())];
+    match settings_page {
+        SettingsPage::General => buttons.extend([
+            (
+                SettingsMenuButtonAction::Difficulty,
+                settings.difficulty_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::RunLength,
+                settings.run_length_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::EntityCount,
+                settings.entity_count_as_str(),
+            ),
+            (SettingsMenuButtonAction::Music, settings.music_as_str()),
+            (
+                SettingsMenuButtonAction::Letterbox,
+                settings.letterbox_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::HitStop,
+                settings.hit_stop_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::CheckpointShop,
+                settings.checkpoint_shop_as_str(),
+            ),
+            (SettingsMenuButtonAction::Gore, settings.gore_as_str()),
+            (
+                SettingsMenuButtonAction::SpawnWarnings,
+                settings.spawn_warnings_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::PauseOnFocusLoss,
+                settings.pause_on_focus_loss_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::GodMode,
+                settings.god_mode_as_str(),
+            ),
+        ]),
+        SettingsPage::Hud => {
+            buttons.extend(HudElement::ALL.map(|element| {
+                (
+                    SettingsMenuButtonAction::Hud(element),
+                    settings.hud_element_as_str(element),
+                )
+            }));
+            if !in_run {
+                buttons.push((SettingsMenuButtonAction::EditHudLayout, "Edit HUD Layout"));
+            }
+        }
+        SettingsPage::Controls => buttons.extend([
+            (
+                SettingsMenuButtonAction::MouseSteering,
+                settings.mouse_steering_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::AutoFire,
+                settings.auto_fire_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::Crosshair,
+                settings.crosshair_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::AimLine,
+                settings.aim_line_as_str(),
+            ),
+            (SettingsMenuButtonAction::Drone, settings.drone_as_str()),
+            (
+                SettingsMenuButtonAction::DeadZone,
+                controls_labels[0].as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::SteerSensitivity,
+                controls_labels[1].as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::AimSensitivity,
+                controls_labels[2].as_str(),
+            ),
+        ]),
+        SettingsPage::Performance => buttons.extend([
+            (
+                SettingsMenuButtonAction::InstancedHorde,
+                settings.instanced_horde_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::AdaptiveQuality,
+                settings.adaptive_quality_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::RearView,
+                settings.rear_view_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::TideRendering,
+                settings.tide_rendering_as_str(),
+            ),
+            (SettingsMenuButtonAction::FpsCap, settings.fps_cap_as_str()),
+            (
+                SettingsMenuButtonAction::BatterySaver,
+                settings.battery_saver_as_str(),
+            ),
+            (
+                SettingsMenuButtonAction::DebugInfo,
+                settings.debug_info_as_str(),
+            ),
+        ]),
+    }
+    // Browsers have no way to send the stats yet
+    #[cfg(all(feature = "telemetry", not(target_arch = "wasm32")))]
+    if matches!(settings_page, SettingsPage::Performance) {
+        buttons.push((
+            SettingsMenuButtonAction::Telemetry,
+            settings.telemetry_as_str(),
+        ));
+    }
+    #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+    if matches!(settings_page, SettingsPage::Performance) {
+        buttons.push((
+            SettingsMenuButtonAction::ClipRecording,
+            settings.clip_recording_as_str(),
+        ));
+    }
+    if matches!(settings_page, SettingsPage::Performance) && !in_run {
+        buttons.push((SettingsMenuButtonAction::Benchmark, "Run Benchmark"));
+    }
+    buttons.push((SettingsMenuButtonAction::Back, "Back"));
+    // Long pages squeeze the buttons to still fit the window
+    if buttons.len() > 10 {
+        button_style.height = Val::Px(55.0);
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            SettingsMenuComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Settings",
+                            TextStyle {
+                                font_size: 70.0,
+                                font: asset_server.load("font.ttf"),
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(30.0)),
+                            ..default()
+                        }),
+                    );
+                    for (action, label) in buttons {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: COLOR_ORANGE.into(),
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    label.to_string(),
+                                    button_text_style.clone(),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+fn setup_unlocks_menu(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    unlocks_page: Res<UnlocksPage>,
+    asset_server: Res<AssetServer>,
+) {
+    spawn_unlocks_menu(&mut commands, &profile, *unlocks_page, &asset_server);
+}
+
+fn spawn_unlocks_menu(
+    commands: &mut Commands,
+    profile: &Profile,
+    unlocks_page: UnlocksPage,
+    asset_server: &AssetServer,
+) {
+    let button_style = Style {
+        width: Val::Px(500.0),
+        height: Val::Px(60.0),
+        margin: UiRect::axes(Val::Px(40.0), Val::Px(1.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 40.0,
+        color: COLOR_BLACK,
+        font: asset_server.load("font.ttf"),
+    };
+
+    let mut buttons = vec![(
+        UnlocksMenuButtonAction::Page,
+        unlocks_page.as_str().to_string(),
+    )];
+    buttons.extend(unlocks_page.items().into_iter().map(|item| {
+        (
+            UnlocksMenuButtonAction::Item(item),
+            profile.item_label(item),
+        )
+    }));
+    if let UnlocksPage::Cosmetics = unlocks_page {
+        // Free to pick, no need to unlock these
+        buttons.extend(
+            [Cosmetic::Decal, Cosmetic::Horn, Cosmetic::TurboSound].map(|cosmetic| {
+                (
+                    UnlocksMenuButtonAction::Cosmetic(cosmetic),
+                    profile.cosmetic_label(cosmetic),
+                )
+            }),
+        );
+    }
+    buttons.push((UnlocksMenuButtonAction::ExitToMainMenu, "Back".to_string()));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            UnlocksMenuComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Unlocks",
+                            TextStyle {
+                                font_size: 70.0,
+                                font: asset_server.load("font.ttf"),
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::top(Val::Px(30.0)),
+                            ..default()
+                        }),
+                    );
+                    parent.spawn(
+                        TextBundle::from_section(
+                            format!("Total Score: {}", profile.total_score),
+                            TextStyle {
+                                font_size: 35.0,
+                                font: asset_server.load("font.ttf"),
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(20.0)),
+                            ..default()
+                        }),
+                    );
+                    for (action, label) in buttons {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: COLOR_ORANGE.into(),
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    label,
+                                    button_text_style.clone(),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+fn setup_mutators_menu(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    preset_slot: Res<MutatorPresetSlot>,
+    asset_server: Res<AssetServer>,
+) {
+    spawn_mutators_menu(&mut commands, &profile, preset_slot.0, &asset_server);
+}
+
+fn spawn_mutators_menu(
+    commands: &mut Commands,
+    profile: &Profile,
+    preset_slot: usize,
+    asset_server: &AssetServer,
+) {
+    // Smaller than the other menus, this one has a lot of buttons
+    let button_style = Style {
+        width: Val::Px(560.0),
+        height: Val::Px(36.0),
+        margin: UiRect::axes(Val::Px(40.0), Val::Px(1.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 24.0,
+        color: COLOR_BLACK,
+        font: asset_server.load("font.ttf"),
+    };
+
+    let mut buttons = Modifier::ALL
+        .into_iter()
+        .map(|modifier| {
+            let state = if profile.mutators.contains(&modifier) {
+                "On"
+            } else {
+                "Off"
+            };
+            (
+                MutatorsMenuButtonAction::Toggle(modifier),
+                format!(
+                    "{} x{:.2} - {state}",
+                    modifier.as_str(),
+                    modifier.score_multiplier()
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+    let preset = &profile.mutator_presets[preset_slot];
+    buttons.extend([
+        (
+            MutatorsMenuButtonAction::PresetSlot,
+            format!("Preset {} - {} Mutators", preset_slot + 1, preset.len()),
+        ),
+        (
+            MutatorsMenuButtonAction::LoadPreset,
+            "Load Preset".to_string(),
+        ),
+        (
+            MutatorsMenuButtonAction::SavePreset,
+            "Save Preset".to_string(),
+        ),
+        (MutatorsMenuButtonAction::StartRun, "Start Run".to_string()),
+        (
+            MutatorsMenuButtonAction::StartBossRush,
+            "Start Boss Rush".to_string(),
+        ),
+        (
+            MutatorsMenuButtonAction::StartArena,
+            "Start Arena".to_string(),
+        ),
+        (MutatorsMenuButtonAction::ExitToMainMenu, "Back".to_string()),
+    ]);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            MutatorsMenuComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Mutators",
+                        TextStyle {
+                            font_size: 70.0,
+                            font: asset_server.load("font.ttf"),
+                            color: COLOR_LIGHT_ORANGE,
+                        },
+                    ));
+                    parent.spawn(
+                        TextBundle::from_section(
+                            format!(
+                                "Score Multiplier: x{:.2}",
+                                mutators_score_multiplier(&profile.mutators)
+                            ),
+                            TextStyle {
+                                font_size: 35.0,
+                                font: asset_server.load("font.ttf"),
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(16.0)),
+                            ..default()
+                        }),
+                    );
+                    for (action, label) in buttons {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: COLOR_ORANGE.into(),
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    label,
+                                    button_text_style.clone(),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+fn setup_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let button_style = Style {
+        width: Val::Px(250.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(8.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 40.0,
+        color: COLOR_BLACK,
+        font: asset_server.load("font.ttf"),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            MainMenuComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "That's a LOT of Zombies",
+                            TextStyle {
+                                font_size: 70.0,
+                                color: COLOR_LIGHT_ORANGE,
+                                font: asset_server.load("font.ttf"),
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::axes(Val::Px(30.0), Val::Px(60.0)),
+                            ..default()
+                        }),
+                    );
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            MainMenuButtonAction::Play,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "New Game",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            MainMenuButtonAction::DailyChallenge,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Daily Challenge",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    // Browsers can't open UDP sockets
+                    #[cfg(not(target_arch = "wasm32"))]
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            MainMenuButtonAction::Coop,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Co-op",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            MainMenuButtonAction::Unlocks,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Unlocks",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            MainMenuButtonAction::Statistics,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Statistics",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            MainMenuButtonAction::Settings,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Settings",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style,
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            MainMenuButtonAction::Quit,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Quit", button_text_style));
+                        });
+                });
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn menu_buttons_update(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        *color = match *interaction {
+            Interaction::Pressed => COLOR_LIGHT_ORANGE.into(),
+            Interaction::Hovered => COLOR_LIGHT_ORANGE.into(),
+            _ => COLOR_ORANGE.into(),
+        }
+    }
+}
+
+impl GameState {
+    // Read out with the menu's buttons
+    fn menu_name(&self) -> &'static str {
+        match self {
+            Self::MainMenu => "Main menu",
+            Self::SettingsMenu => "Settings",
+            Self::UnlocksMenu => "Unlocks",
+            Self::StatsMenu => "Stats",
+            Self::RunHistory => "Run history",
+            Self::MutatorsMenu => "Mutators",
+            Self::RunSetup => "Run setup",
+            Self::Lobby => "Lobby",
+            Self::HudEditor => "HUD editor",
+            Self::PauseMenu => "Paused",
+            Self::CheckpointShop => "Checkpoint shop",
+            Self::GameOver => "Game over",
+            Self::LoadAssets | Self::GameInit | Self::InGame => "Game",
+        }
+    }
+}
+
+impl MenuHistory {
+    // A menu opened on top of the paused run, the run is always the first step back
+    fn in_run(&self) -> bool {
+        self.0.contains(&GameState::InGame)
+    }
+}
+
+fn record_menu_history(
+    game_state: Res<State<GameState>>,
+    mut previous: Local<Option<GameState>>,
+    mut history: ResMut<MenuHistory>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+
+    let current = *game_state.get();
+    let Some(previous) = previous.replace(current) else {
+        return;
+    };
+    match current {
+        // Roots, nothing to go back to from here
+        GameState::LoadAssets | GameState::MainMenu | GameState::GameInit | GameState::InGame => {
+            history.0.clear();
+        }
+        // Got here by going back
+        _ if history.0.last() == Some(&current) => {
+            history.0.pop();
+        }
+        // The run is over, there's no resuming it
+        GameState::GameOver => history.0 = vec![GameState::MainMenu],
+        _ => history.0.push(match previous {
+            GameState::GameOver | GameState::GameInit | GameState::LoadAssets => {
+                GameState::MainMenu
+            }
+            previous => previous,
+        }),
+    }
+}
+
+fn navigate_back(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    game_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    history: Res<MenuHistory>,
+) {
+    let is_back = keyboard_input.just_pressed(KeyCode::Escape)
+        || gamepads.iter().any(|gamepad| {
+            gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::East))
+        });
+    if !is_back {
+        return;
+    }
+
+    match game_state.get() {
+        GameState::InGame => next_state.set(GameState::PauseMenu),
+        _ => {
+            if let Some(state) = history.0.last() {
+                next_state.set(*state);
+            }
+        }
+    }
+}
+
+// Nobody is steering while the game is in the background
+fn pause_on_focus_loss(
+    settings: Res<GameSettings>,
+    mut focus_events: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let lost_focus = focus_events.read().any(|event| !event.focused);
+    if settings.pause_on_focus_loss && (lost_focus || is_page_hidden()) {
+        next_state.set(GameState::PauseMenu);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_page_hidden() -> bool {
+    false
+}
+
+// The canvas doesn't always lose focus when the tab is switched away
+#[cfg(target_arch = "wasm32")]
+fn is_page_hidden() -> bool {
+    let key = |name: &str| js_sys::JsString::from(name);
+    js_sys::Reflect::get(&js_sys::global(), &key("document"))
+        .and_then(|document| js_sys::Reflect::get(&document, &key("hidden")))
+        .ok()
+        .and_then(|hidden| hidden.as_bool())
+        .unwrap_or(false)
+}
+
+fn menu_focus_navigation(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    game_state: Res<State<GameState>>,
+    mut menu_focus: ResMut<MenuFocus>,
+    mut a11y_focus: ResMut<Focus>,
+    mut button_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut Interaction,
+            &mut Style,
+            &mut BorderColor,
+        ),
+        With<Button>,
+    >,
+) {
+    let gamepad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_input.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let is_up = keyboard_input.any_just_pressed([KeyCode::Up, KeyCode::W])
+        || gamepad_pressed(GamepadButtonType::DPadUp);
+    let is_down = keyboard_input.any_just_pressed([KeyCode::Down, KeyCode::S])
+        || gamepad_pressed(GamepadButtonType::DPadDown);
+    let is_activate = keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter])
+        || gamepad_pressed(GamepadButtonType::South);
+
+    // Keyboard users keep a focus on the first button of the next menu
+    if game_state.is_changed() {
+        menu_focus.0 = menu_focus.0.map(|_| 0);
+    }
+
+    // Buttons are ordered top to bottom as laid out on screen
+    let mut buttons = button_query.iter_mut().collect::<Vec<_>>();
+    if buttons.is_empty() {
+        menu_focus.0 = None;
+        return;
+    }
+    buttons.sort_by(|(_, a, ..), (_, b, ..)| {
+        let (a, b) = (a.translation(), b.translation());
+        a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))
+    });
+
+    let num_buttons = buttons.len();
+    let mut focus = menu_focus.0.map(|idx| idx.min(num_buttons - 1));
+    // Screen readers can move the focus on their own
+    if a11y_focus.is_changed() {
+        if let Some(idx) = buttons.iter().position(|(e, ..)| Some(*e) == a11y_focus.0) {
+            focus = Some(idx);
+        }
+    }
+    if is_down {
+        focus = Some(focus.map_or(0, |idx| (idx + 1) % num_buttons));
+    }
+    if is_up {
+        focus = Some(focus.map_or(num_buttons - 1, |idx| (idx + num_buttons - 1) % num_buttons));
+    }
+    menu_focus.0 = focus;
+
+    // The focused button's label gets read out
+    let focused = focus.map(|idx| buttons[idx].0);
+    if (is_up || is_down || game_state.is_changed()) && a11y_focus.0 != focused {
+        a11y_focus.0 = focused;
+    }
+
+    for (idx, (_, _, interaction, style, border_color)) in buttons.iter_mut().enumerate() {
+        let is_focused = focus == Some(idx);
+        let border = if is_focused {
+            UiRect::all(Val::Px(4.0))
+        } else {
+            UiRect::ZERO
+        };
+        // Avoid touching the style every frame, it triggers a relayout
+        if style.border != border {
+            style.border = border;
+            border_color.0 = COLOR_BROWN;
+        }
+        if is_focused && is_activate {
+            **interaction = Interaction::Pressed;
+        }
+    }
+}
+
+// Bevy names the buttons after their text when spawned, this keeps menu groups
+// and the toggle labels that change afterwards up to date for screen readers
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_menu_accessibility(
+    mut commands: Commands,
+    mut action_requests: EventReader<ActionRequest>,
+    mut a11y_focus: ResMut<Focus>,
+    game_state: Res<State<GameState>>,
+    new_button_query: Query<&Parent, Added<Button>>,
+    group_query: Query<(), With<AccessibilityNode>>,
+    text_query: Query<(&Text, &Parent), (Changed<Text>, Without<Button>)>,
+    mut button_query: Query<(&mut Interaction, Option<&mut AccessibilityNode>), With<Button>>,
+) {
+    let mut groups = new_button_query
+        .iter()
+        .map(|parent| parent.get())
+        .filter(|parent| !group_query.contains(*parent))
+        .collect::<Vec<_>>();
+    groups.sort();
+    groups.dedup();
+    for group in groups {
+        let mut node = accesskit::NodeBuilder::new(accesskit::Role::Group);
+        node.set_name(game_state.get().menu_name());
+        commands.entity(group).insert(AccessibilityNode(node));
+    }
+
+    for (text, parent) in text_query.iter() {
+        if let Ok((_, Some(mut node))) = button_query.get_mut(parent.get()) {
+            let label = text
+                .sections
+                .iter()
+                .map(|s| s.value.as_str())
+                .collect::<Vec<_>>();
+            node.set_name(label.join(" "));
+        }
+    }
+
+    // Clicks and focus moves from the screen reader itself
+    for request in action_requests.read() {
+        let target = Entity::from_bits(request.target.0);
+        let Ok((mut interaction, _)) = button_query.get_mut(target) else {
+            continue;
+        };
+        match request.action {
+            accesskit::Action::Default => *interaction = Interaction::Pressed,
+            accesskit::Action::Focus => a11y_focus.0 = Some(target),
+            _ => {}
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_main_menu_btn_click(
+    interaction_query: Query<
+        (&Interaction, &MainMenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut ev_app_exit: EventWriter<AppExit>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut run_config: ResMut<RunConfig>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match menu_button_action {
+                MainMenuButtonAction::Quit => ev_app_exit.send(AppExit),
+                MainMenuButtonAction::Coop => {
+                    game_state.set(GameState::Lobby);
+                }
+                MainMenuButtonAction::DailyChallenge => {
+                    *run_config = RunConfig::daily(current_day());
+                    game_state.set(GameState::GameInit);
+                }
+                MainMenuButtonAction::Unlocks => {
+                    game_state.set(GameState::UnlocksMenu);
+                }
+                MainMenuButtonAction::Statistics => {
+                    game_state.set(GameState::StatsMenu);
+                }
+                MainMenuButtonAction::Settings => {
+                    game_state.set(GameState::SettingsMenu);
+                }
+                MainMenuButtonAction::Play => {
+                    game_state.set(GameState::MutatorsMenu);
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_settings_menu_btn_click(
+    interaction_query: Query<
+        (&Interaction, &SettingsMenuButtonAction, &Children),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut commands: Commands,
+    mut settings: ResMut<GameSettings>,
+    mut settings_page: ResMut<SettingsPage>,
+    mut text_query: Query<&mut Text>,
+    mut game_state: ResMut<NextState<GameState>>,
+    settings_menu_query: Query<Entity, With<SettingsMenuComponent>>,
+    asset_server: Res<AssetServer>,
+    mut benchmark: ResMut<Benchmark>,
+    mut run_config: ResMut<RunConfig>,
+    history: Res<MenuHistory>,
+) {
+    for (interaction, menu_button_action, children) in &interaction_query {
+        let mut text = text_query.get_mut(children[0]).unwrap();
+        if *interaction == Interaction::Pressed {
+            match menu_button_action {
+                SettingsMenuButtonAction::Page => {
+                    // Rebuild the whole menu with the next page's buttons
+                    *settings_page = settings_page.next();
+                    for e in settings_menu_query.iter() {
+                        commands.entity(e).despawn_recursive();
+                    }
+                    spawn_settings_menu(
+                        &mut commands,
+                        &settings,
+                        *settings_page,
+                        history.in_run(),
+                        &asset_server,
+                    );
+                    return;
+                }
+                SettingsMenuButtonAction::Difficulty => {
+                    settings.update_difficulty();
+                    text.sections[0].value = settings.difficulty_as_str().to_string();
+                }
+                SettingsMenuButtonAction::RunLength => {
+                    settings.update_run_length();
+                    text.sections[0].value = settings.run_length_as_str().to_string();
+                }
+                SettingsMenuButtonAction::EntityCount => {
+                    settings.update_entity_count();
+                    text.sections[0].value = settings.entity_count_as_str().to_string();
+                }
+                SettingsMenuButtonAction::Music => {
+                    settings.music = !settings.music;
+                    text.sections[0].value = settings.music_as_str().to_string();
+                }
+                SettingsMenuButtonAction::Hud(element) => {
+                    settings.toggle_hud_element(*element);
+                    text.sections[0].value = settings.hud_element_as_str(*element).to_string();
+                }
+                SettingsMenuButtonAction::EditHudLayout => {
+                    game_state.set(GameState::HudEditor);
+                }
+                SettingsMenuButtonAction::GodMode => {
+                    settings.god_mode = !settings.god_mode;
+                    text.sections[0].value = settings.god_mode_as_str().to_string();
+                }
+                SettingsMenuButtonAction::Letterbox => {
+                    settings.letterbox = !settings.letterbox;
+                    text.sections[0].value = settings.letterbox_as_str().to_string();
+                }
+                SettingsMenuButtonAction::HitStop => {
+                    settings.hit_stop = !settings.hit_stop;
+                    text.sections[0].value = settings.hit_stop_as_str().to_string();
+                }
+                SettingsMenuButtonAction::CheckpointShop => {
+                    settings.checkpoint_shop = !settings.checkpoint_shop;
+                    text.sections[0].value = settings.checkpoint_shop_as_str().to_string();
+                }
+                SettingsMenuButtonAction::AdaptiveQuality => {
+                    settings.adaptive_quality = !settings.adaptive_quality;
+                    text.sections[0].value = settings.adaptive_quality_as_str().to_string();
+                }
+                SettingsMenuButtonAction::InstancedHorde => {
+                    settings.instanced_horde = !settings.instanced_horde;
+                    text.sections[0].value = settings.instanced_horde_as_str().to_string();
+                }
+                SettingsMenuButtonAction::RearView => {
+                    settings.rear_view = !settings.rear_view;
+                    text.sections[0].value = settings.rear_view_as_str().to_string();
+                }
+                SettingsMenuButtonAction::TideRendering => {
+                    settings.tide_rendering = !settings.tide_rendering;
+                    text.sections[0].value = settings.tide_rendering_as_str().to_string();
+                }
+                #[cfg(all(feature = "telemetry", not(target_arch = "wasm32")))]
+                SettingsMenuButtonAction::Telemetry => {
+                    settings.telemetry = !settings.telemetry;
+                    text.sections[0].value = settings.telemetry_as_str().to_string();
+                }
+                #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+                SettingsMenuButtonAction::ClipRecording => {
+                    settings.clip_recording = !settings.clip_recording;
+                    text.sections[0].value = settings.clip_recording_as_str().to_string();
+                }
+                SettingsMenuButtonAction::FpsCap => {
+                    settings.update_fps_cap();
+                    text.sections[0].value = settings.fps_cap_as_str().to_string();
+                }
+                SettingsMenuButtonAction::BatterySaver => {
+                    settings.battery_saver = !settings.battery_saver;
+                    text.sections[0].value = settings.battery_saver_as_str().to_string();
+                }
+                SettingsMenuButtonAction::DebugInfo => {
+                    settings.debug_info = !settings.debug_info;
+                    text.sections[0].value = settings.debug_info_as_str().to_string();
+                }
+                SettingsMenuButtonAction::Gore => {
+                    settings.update_gore();
+                    text.sections[0].value = settings.gore_as_str().to_string();
+                }
+                SettingsMenuButtonAction::SpawnWarnings => {
+                    settings.spawn_warnings = !settings.spawn_warnings;
+                    text.sections[0].value = settings.spawn_warnings_as_str().to_string();
+                }
+                SettingsMenuButtonAction::PauseOnFocusLoss => {
+                    settings.pause_on_focus_loss = !settings.pause_on_focus_loss;
+                    text.sections[0].value = settings.pause_on_focus_loss_as_str().to_string();
+                }
+                SettingsMenuButtonAction::MouseSteering => {
+                    settings.mouse_steering = !settings.mouse_steering;
+                    text.sections[0].value = settings.mouse_steering_as_str().to_string();
+                }
+                SettingsMenuButtonAction::AutoFire => {
+                    settings.auto_fire = !settings.auto_fire;
+                    text.sections[0].value = settings.auto_fire_as_str().to_string();
+                }
+                SettingsMenuButtonAction::Crosshair => {
+                    settings.update_crosshair();
+                    text.sections[0].value = settings.crosshair_as_str().to_string();
+                }
+                SettingsMenuButtonAction::AimLine => {
+                    settings.aim_line = !settings.aim_line;
+                    text.sections[0].value = settings.aim_line_as_str().to_string();
+                }
+                SettingsMenuButtonAction::Drone => {
+                    settings.drone = !settings.drone;
+                    text.sections[0].value = settings.drone_as_str().to_string();
+                }
+                SettingsMenuButtonAction::DeadZone => {
+                    settings.stick_dead_zone =
+                        next_step(&DEAD_ZONE_STEPS, settings.stick_dead_zone);
+                    text.sections[0].value = settings.dead_zone_label();
+                }
+                SettingsMenuButtonAction::SteerSensitivity => {
+                    settings.steer_sensitivity =
+                        next_step(&SENSITIVITY_STEPS, settings.steer_sensitivity);
+                    text.sections[0].value = settings.steer_sensitivity_label();
+                }
+                SettingsMenuButtonAction::AimSensitivity => {
+                    settings.aim_sensitivity =
+                        next_step(&SENSITIVITY_STEPS, settings.aim_sensitivity);
+                    text.sections[0].value = settings.aim_sensitivity_label();
+                }
+                SettingsMenuButtonAction::Benchmark => {
+                    benchmark.start(&mut settings);
+                    *run_config = RunConfig::new(
+                        BENCHMARK_SEED,
+                        Difficulty::default(),
+                        RunLength::Marathon,
+                        Vec::new(),
+                    );
+                    game_state.set(GameState::GameInit);
+                }
+                SettingsMenuButtonAction::Back => {
+                    // The paused run or the main menu, wherever this was opened from
+                    game_state.set(history.0.last().copied().unwrap_or(GameState::MainMenu));
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_unlocks_menu_btn_click(
+    interaction_query: Query<
+        (&Interaction, &UnlocksMenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut commands: Commands,
+    mut profile: ResMut<Profile>,
+    mut unlocks_page: ResMut<UnlocksPage>,
+    mut game_state: ResMut<NextState<GameState>>,
+    unlocks_menu_query: Query<Entity, With<UnlocksMenuComponent>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match menu_button_action {
+            UnlocksMenuButtonAction::Page => *unlocks_page = unlocks_page.next(),
+            UnlocksMenuButtonAction::Item(item) => {
+                // First press claims the item once the score allows it, the next equips it
+                if profile.is_unlocked(*item) {
+                    profile.equip(*item);
+                } else if profile.can_claim(*item) {
+                    profile.unlocked.push(*item);
+                } else {
+                    continue;
+                }
+                profile.save();
+            }
+            UnlocksMenuButtonAction::Cosmetic(cosmetic) => {
+                profile.cycle_cosmetic(*cosmetic);
+                profile.save();
+            }
+            UnlocksMenuButtonAction::ExitToMainMenu => {
+                game_state.set(GameState::MainMenu);
+                return;
+            }
+        }
+
+        // Labels depend on the profile, rebuild the whole menu
+        for e in unlocks_menu_query.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+        spawn_unlocks_menu(&mut commands, &profile, *unlocks_page, &asset_server);
+        return;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_pause_menu_btn_click(
+    interaction_query: Query<
+        (&Interaction, &PauseMenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut tutorial: ResMut<Tutorial>,
+    mut profile: ResMut<Profile>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match menu_button_action {
+                PauseMenuButtonAction::Resume => {
+                    game_state.set(GameState::InGame);
+                }
+                PauseMenuButtonAction::SkipTutorial => {
+                    tutorial.0 = None;
+                    profile.tutorial_done = true;
+                    profile.save();
+                    game_state.set(GameState::InGame);
+                }
+                PauseMenuButtonAction::Settings => {
+                    game_state.set(GameState::SettingsMenu);
+                }
+                PauseMenuButtonAction::ExitToMainMenu => {
+                    game_state.set(GameState::MainMenu);
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_game_over_menu_btn_click(
+    interaction_query: Query<
+        (&Interaction, &GameOverMenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut run_config: ResMut<RunConfig>,
+    coop: Res<Coop>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match menu_button_action {
+                GameOverMenuButtonAction::ExitToMainMenu => {
+                    game_state.set(GameState::MainMenu);
+                }
+                // The host starts the next co-op run for everyone
+                GameOverMenuButtonAction::Restart if coop.session.is_some() => {
+                    game_state.set(GameState::Lobby);
+                }
+                GameOverMenuButtonAction::Restart => {
+                    // Daily runs retry the same road, others get a fresh one
+                    if run_config.daily.is_none() {
+                        run_config.seed = rand::thread_rng().gen();
+                    }
+                    game_state.set(GameState::GameInit);
+                }
+            }
+        }
+    }
+}
+
+fn cleanup_main_menu(
+    mut commands: Commands,
+    main_menu_query: Query<Entity, With<MainMenuComponent>>,
+) {
+    if main_menu_query.is_empty() {
+        return;
+    }
+
+    let main_menu = main_menu_query.single();
+    commands.entity(main_menu).despawn_recursive();
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_mutators_menu_btn_click(
+    interaction_query: Query<
+        (&Interaction, &MutatorsMenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut commands: Commands,
+    mut profile: ResMut<Profile>,
+    mut preset_slot: ResMut<MutatorPresetSlot>,
+    mut run_config: ResMut<RunConfig>,
+    mut game_state: ResMut<NextState<GameState>>,
+    settings: Res<GameSettings>,
+    mutators_menu_query: Query<Entity, With<MutatorsMenuComponent>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match menu_button_action {
+            MutatorsMenuButtonAction::Toggle(modifier) => {
+                if profile.mutators.contains(modifier) {
+                    profile.mutators.retain(|m| m != modifier);
+                } else {
+                    if let Some(conflict) = modifier.conflicts_with() {
+                        profile.mutators.retain(|m| *m != conflict);
+                    }
+                    profile.mutators.push(*modifier);
+                }
+                profile.save();
+            }
+            MutatorsMenuButtonAction::PresetSlot => {
+                preset_slot.0 = (preset_slot.0 + 1) % MUTATOR_PRESET_SLOTS;
+            }
+            MutatorsMenuButtonAction::LoadPreset => {
+                profile.mutators = profile.mutator_presets[preset_slot.0].clone();
+                profile.save();
+            }
+            MutatorsMenuButtonAction::SavePreset => {
+                profile.mutator_presets[preset_slot.0] = profile.mutators.clone();
+                profile.save();
+            }
+            MutatorsMenuButtonAction::StartRun
+            | MutatorsMenuButtonAction::StartBossRush
+            | MutatorsMenuButtonAction::StartArena => {
+                *run_config = RunConfig {
+                    boss_rush: matches!(
+                        menu_button_action,
+                        MutatorsMenuButtonAction::StartBossRush
+                    ),
+                    arena: matches!(menu_button_action, MutatorsMenuButtonAction::StartArena),
+                    ..RunConfig::new(
+                        rand::thread_rng().gen(),
+                        settings.difficulty,
+                        settings.run_length,
+                        profile.mutators.clone(),
+                    )
+                };
+                game_state.set(GameState::RunSetup);
+                return;
+            }
+            MutatorsMenuButtonAction::ExitToMainMenu => {
+                game_state.set(GameState::MainMenu);
+                return;
+            }
+        }
+
+        // Labels depend on the profile, rebuild the whole menu
+        for e in mutators_menu_query.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+        spawn_mutators_menu(&mut commands, &profile, preset_slot.0, &asset_server);
+        return;
+    }
+}
+
+fn cleanup_mutators_menu(
+    mut commands: Commands,
+    mutators_menu_query: Query<Entity, With<MutatorsMenuComponent>>,
+) {
+    for e in mutators_menu_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn track_run_stats(
+    mut run_stats: ResMut<RunStats>,
+    mut last_pos: Local<Option<Vec2>>,
+    mut last_health: Local<f32>,
+    car_health: Res<CarHealth>,
+    car_progress: Res<CarProgress>,
+    mut reader_zombie_killed: EventReader<ZombieKilledEvent>,
+    mut reader_player_out: EventReader<PlayerOutEvent>,
+    new_bullets: Query<(), (Added<Bullet>, Without<DroneShot>)>,
+    car_query: Query<&Transform, With<Car>>,
+) {
+    run_stats.kills += reader_zombie_killed.read().count() as u32;
+    run_stats.shots += new_bullets.iter().count() as u32;
+    if reader_player_out.read().count() > 0 {
+        run_stats.died = true;
+    }
+
+    // Big jumps are a new run or a respawn, not driving
+    let pos = car_query
+        .get_single()
+        .ok()
+        .map(|t| t.translation.truncate());
+    if let (Some(pos), Some(last)) = (pos, *last_pos) {
+        let step = pos.distance(last);
+        if step <= STATS_MAX_STEP {
+            run_stats.distance += step;
+        }
+    }
+    *last_pos = pos;
+
+    // Any drop counts as damage, a new run starts back at full health
+    if car_health.0 < *last_health {
+        let bucket = (car_progress.0.clamp(0.0, 1.0) * DAMAGE_STRIP_BUCKETS as f32) as usize;
+        run_stats.damage[bucket.min(DAMAGE_STRIP_BUCKETS - 1)] += *last_health - car_health.0;
+    }
+    *last_health = car_health.0;
+}
+
+fn record_lifetime_stats(
+    mut profile: ResMut<Profile>,
+    run_stats: Res<RunStats>,
+    player_score: Res<PlayerScore>,
+    run_config: Res<RunConfig>,
+) {
+    let stats = &mut profile.stats;
+    stats.runs += 1;
+    stats.deaths += run_stats.died as u32;
+    stats.kills += run_stats.kills as u64;
+    stats.distance += run_stats.distance / STATS_PX_PER_METER;
+    stats.shots += run_stats.shots as u64;
+    stats.hits += run_stats.hits as u64;
+    stats.difficulty_runs[run_config.difficulty as usize] += 1;
+    stats.recent_scores.push(player_score.0);
+    if stats.recent_scores.len() > STATS_RECENT_RUNS {
+        stats.recent_scores.remove(0);
+    }
+}
+
+fn setup_stats_menu(mut commands: Commands, asset_server: Res<AssetServer>, profile: Res<Profile>) {
+    let stats = &profile.stats;
+    let text_style = TextStyle {
+        font_size: 28.0,
+        font: asset_server.load("font.ttf"),
+        color: COLOR_LIGHT_ORANGE,
+    };
+    let heading_style = TextStyle {
+        font_size: 35.0,
+        ..text_style.clone()
+    };
+    let favorite = [Difficulty::Easy, Difficulty::Moderate, Difficulty::Hard]
+        .into_iter()
+        .max_by_key(|d| stats.difficulty_runs[*d as usize])
+        .filter(|d| stats.difficulty_runs[*d as usize] > 0);
+    let summary = format!(
+        "Runs: {}   Deaths: {}   Kills: {}\nDistance: {:.1} km   Favorite Difficulty: {}",
+        stats.runs,
+        stats.deaths,
+        stats.kills,
+        stats.distance / 1000.0,
+        favorite.as_ref().map_or("-", Difficulty::as_str),
+    );
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            StatsMenuComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Statistics",
+                        TextStyle {
+                            font_size: 70.0,
+                            font: asset_server.load("font.ttf"),
+                            color: COLOR_LIGHT_ORANGE,
+                        },
+                    ));
+                    parent.spawn(
+                        TextBundle::from_section(summary, text_style.clone())
+                            .with_text_alignment(TextAlignment::Center)
+                            .with_style(Style {
+                                margin: UiRect::all(Val::Px(12.0)),
+                                ..default()
+                            }),
+                    );
+
+                    // Runs per difficulty, scaled to the most played one
+                    parent.spawn(TextBundle::from_section(
+                        "Runs per Difficulty",
+                        heading_style.clone(),
+                    ));
+                    let max_runs = stats
+                        .difficulty_runs
+                        .iter()
+                        .copied()
+                        .max()
+                        .unwrap_or(0)
+                        .max(1);
+                    for difficulty in [Difficulty::Easy, Difficulty::Moderate, Difficulty::Hard] {
+                        let runs = stats.difficulty_runs[difficulty as usize];
+                        spawn_stats_bar(
+                            parent,
+                            &format!("{} {runs}", difficulty.as_str()),
+                            runs as f32 / max_runs as f32,
+                            &text_style,
+                        );
+                    }
+
+                    parent.spawn(TextBundle::from_section("Accuracy", heading_style.clone()));
+                    let accuracy = if stats.shots == 0 {
+                        0.0
+                    } else {
+                        (stats.hits as f32 / stats.shots as f32).min(1.0)
+                    };
+                    spawn_stats_bar(
+                        parent,
+                        &format!("{:.0}%", accuracy * 100.0),
+                        accuracy,
+                        &text_style,
+                    );
+
+                    parent.spawn(TextBundle::from_section("Recent Scores", heading_style));
+                    spawn_stats_line_chart(parent, &stats.recent_scores);
+
+                    parent.spawn(NodeBundle::default()).with_children(|parent| {
+                        for (action, label) in [
+                            (StatsMenuButtonAction::RunHistory, "Run History"),
+                            (StatsMenuButtonAction::ExitToMainMenu, "Back"),
+                        ] {
+                            parent
+                                .spawn((
+                                    ButtonBundle {
+                                        style: Style {
+                                            width: Val::Px(250.0),
+                                            height: Val::Px(55.0),
+                                            margin: UiRect::all(Val::Px(12.0)),
+                                            justify_content: JustifyContent::Center,
+                                            align_items: AlignItems::Center,
+                                            ..default()
+                                        },
+                                        background_color: COLOR_ORANGE.into(),
+                                        ..default()
+                                    },
+                                    action,
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(
+                                        label,
+                                        TextStyle {
+                                            font_size: 40.0,
+                                            color: COLOR_BLACK,
+                                            font: asset_server.load("font.ttf"),
+                                        },
+                                    ));
+                                });
+                        }
+                    });
+                });
+        });
+}
+
+fn setup_run_history_menu(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    mut history_menu: ResMut<RunHistoryMenu>,
+    asset_server: Res<AssetServer>,
+) {
+    *history_menu = RunHistoryMenu::default();
+    spawn_run_history_menu(&mut commands, &profile, &history_menu, &asset_server);
+}
+
+fn spawn_run_history_menu(
+    commands: &mut Commands,
+    profile: &Profile,
+    history_menu: &RunHistoryMenu,
+    asset_server: &AssetServer,
+) {
+    let button_style = Style {
+        width: Val::Px(900.0),
+        height: Val::Px(40.0),
+        margin: UiRect::axes(Val::Px(40.0), Val::Px(1.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 26.0,
+        color: COLOR_BLACK,
+        font: asset_server.load("font.ttf"),
+    };
+
+    // Newest first
+    let history = &profile.run_history;
+    let num_pages = history.len().div_ceil(RUN_HISTORY_PAGE_SIZE).max(1);
+    let mut buttons = vec![(
+        RunHistoryMenuButtonAction::Page,
+        format!("Page {}/{num_pages}", history_menu.page + 1),
+    )];
+    buttons.extend(
+        (0..history.len())
+            .rev()
+            .skip(history_menu.page * RUN_HISTORY_PAGE_SIZE)
+            .take(RUN_HISTORY_PAGE_SIZE)
+            .map(|idx| {
+                (
+                    RunHistoryMenuButtonAction::Select(idx),
+                    history[idx].describe(),
+                )
+            }),
+    );
+    if history_menu.selected.is_some() {
+        buttons.push((
+            RunHistoryMenuButtonAction::RetrySeed,
+            "Retry Seed".to_string(),
+        ));
+    }
+    buttons.push((RunHistoryMenuButtonAction::Back, "Back".to_string()));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            RunHistoryMenuComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            if history.is_empty() {
+                                "Run History\nNo runs yet"
+                            } else {
+                                "Run History"
+                            },
+                            TextStyle {
+                                font_size: 60.0,
+                                font: asset_server.load("font.ttf"),
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_text_alignment(TextAlignment::Center)
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(12.0)),
+                            ..default()
+                        }),
+                    );
+                    for (action, label) in buttons {
+                        let is_selected = matches!(
+                            action,
+                            RunHistoryMenuButtonAction::Select(idx)
+                                if history_menu.selected == Some(idx)
+                        );
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: if is_selected {
+                                        COLOR_LIGHT_ORANGE.into()
+                                    } else {
+                                        COLOR_ORANGE.into()
+                                    },
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    label,
+                                    button_text_style.clone(),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+// Label next to a track filled up to `fill`, 0.0 to 1.0
+fn spawn_stats_bar(parent: &mut ChildBuilder, label: &str, fill: f32, text_style: &TextStyle) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(label, text_style.clone()).with_style(Style {
+                    width: Val::Px(STATS_LABEL_WIDTH),
+                    ..default()
+                }),
+            );
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(STATS_CHART_WIDTH),
+                        height: Val::Px(18.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    border_color: COLOR_LIGHT_ORANGE.into(),
+                    background_color: COLOR_BROWN.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Percent(fill * 100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: COLOR_ORANGE.into(),
+                        ..default()
+                    });
+                });
+        });
+}
+
+// Scores as points joined by thin rotated nodes, oldest on the left
+fn spawn_stats_line_chart(parent: &mut ChildBuilder, scores: &[u32]) {
+    let size = vec2(STATS_LABEL_WIDTH + STATS_CHART_WIDTH, STATS_CHART_HEIGHT);
+    let max_score = scores.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let step = size.x / (STATS_RECENT_RUNS - 1) as f32;
+    // UI y points down, the best score touches the top
+    let points = scores
+        .iter()
+        .enumerate()
+        .map(|(idx, score)| {
+            vec2(
+                idx as f32 * step,
+                size.y * (1.0 - *score as f32 / max_score),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(size.x),
+                height: Val::Px(size.y),
+                margin: UiRect::all(Val::Px(8.0)),
+                border: UiRect::new(Val::Px(2.0), Val::ZERO, Val::ZERO, Val::Px(2.0)),
+                ..default()
+            },
+            border_color: COLOR_LIGHT_ORANGE.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            for pair in points.windows(2) {
+                let (from, to) = (pair[0], pair[1]);
+                let (mid, length) = ((from + to) / 2.0, from.distance(to));
+                let angle = (to.y - from.y).atan2(to.x - from.x);
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(mid.x - length / 2.0),
+                        top: Val::Px(mid.y - 1.5),
+                        width: Val::Px(length),
+                        height: Val::Px(3.0),
+                        ..default()
+                    },
+                    background_color: COLOR_ORANGE.into(),
+                    // Layout only moves the node, the rotation sticks
+                    transform: Transform::from_rotation(Quat::from_rotation_z(angle)),
+                    ..default()
+                });
+            }
+            for point in points {
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(point.x - 4.0),
+                        top: Val::Px(point.y - 4.0),
+                        width: Val::Px(8.0),
+                        height: Val::Px(8.0),
+                        ..default()
+                    },
+                    background_color: COLOR_LIGHT_ORANGE.into(),
+                    ..default()
+                });
+            }
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_stats_menu_btn_click(
+    interaction_query: Query<
+        (&Interaction, &StatsMenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match menu_button_action {
+            StatsMenuButtonAction::RunHistory => game_state.set(GameState::RunHistory),
+            StatsMenuButtonAction::ExitToMainMenu => game_state.set(GameState::MainMenu),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_run_history_menu_btn_click(
+    interaction_query: Query<
+        (&Interaction, &RunHistoryMenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut commands: Commands,
+    mut profile: ResMut<Profile>,
+    mut history_menu: ResMut<RunHistoryMenu>,
+    mut run_config: ResMut<RunConfig>,
+    mut game_state: ResMut<NextState<GameState>>,
+    history_menu_query: Query<Entity, With<RunHistoryMenuComponent>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match menu_button_action {
+            RunHistoryMenuButtonAction::Page => {
+                let num_pages = profile.run_history.len().div_ceil(RUN_HISTORY_PAGE_SIZE);
+                history_menu.page = (history_menu.page + 1) % num_pages.max(1);
+            }
+            RunHistoryMenuButtonAction::Select(idx) => history_menu.selected = Some(*idx),
+            RunHistoryMenuButtonAction::RetrySeed => {
+                let record = history_menu
+                    .selected
+                    .and_then(|idx| profile.run_history.get(idx));
+                let Some((config, car)) =
+                    record.and_then(|record| RunConfig::from_run_code(&record.code))
+                else {
+                    continue;
+                };
+                // A retried daily is practice, it doesn't count toward the day's best
+                *run_config = config;
+                if profile.is_unlocked(UnlockItem::Car(car)) {
+                    profile.car = car;
+                    profile.save();
+                }
+                game_state.set(GameState::GameInit);
+                return;
+            }
+            RunHistoryMenuButtonAction::Back => {
+                game_state.set(GameState::StatsMenu);
+                return;
+            }
+        }
+
+        // The selection shows on the buttons, rebuild the whole menu
+        for e in history_menu_query.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+        spawn_run_history_menu(&mut commands, &profile, &history_menu, &asset_server);
+        return;
+    }
+}
+
+fn cleanup_run_history_menu(
+    mut commands: Commands,
+    history_menu_query: Query<Entity, With<RunHistoryMenuComponent>>,
+) {
+    for e in history_menu_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn cleanup_stats_menu(
+    mut commands: Commands,
+    stats_menu_query: Query<Entity, With<StatsMenuComponent>>,
+) {
+    for e in stats_menu_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn cleanup_unlocks_menu(
+    mut commands: Commands,
+    unlocks_menu_query: Query<Entity, With<UnlocksMenuComponent>>,
+) {
+    for e in unlocks_menu_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn apply_score_multiplier(mut player_score: ResMut<PlayerScore>, run_config: Res<RunConfig>) {
+    player_score.0 = (player_score.0 as f32 * run_config.score_multiplier()).round() as u32;
+}
+
+fn record_run_splits(
+    mut profile: ResMut<Profile>,
+    run_timer: Res<RunTimer>,
+    run_config: Res<RunConfig>,
+) {
+    run_timer.export(&run_config);
+
+    // Only finished runs can set a best, on the final time
+    let Some(key) = run_config.splits_key() else {
+        return;
+    };
+    let Some(time) = run_timer.splits.get(SPLIT_COUNT - 1) else {
+        return;
+    };
+    let best = profile.best_splits.get(&key).and_then(|best| best.last());
+    if !best.is_some_and(|best| time >= best) {
+        profile.best_splits.insert(key, run_timer.splits.clone());
+    }
+}
+
+fn record_run_history(
+    mut profile: ResMut<Profile>,
+    player_score: Res<PlayerScore>,
+    run_config: Res<RunConfig>,
+    car_progress: Res<CarProgress>,
+    on_foot_escape: Res<OnFootEscape>,
+) {
+    let record = RunRecord {
+        day: current_day(),
+        code: run_config.run_code(profile.car),
+        daily: run_config.daily.is_some(),
+        score: player_score.0,
+        outcome: RunOutcome::new(&car_progress, &on_foot_escape),
+    };
+    let history = &mut profile.run_history;
+    history.push(record);
+    if history.len() > RUN_HISTORY_LEN {
+        history.remove(0);
+    }
+}
+
+fn record_telemetry_frame(
+    time: Res<Time<Real>>,
+    settings: Res<GameSettings>,
+    mut telemetry: ResMut<RunTelemetry>,
+    zombie_query: Query<With<Zombie>>,
+) {
+    if !settings.telemetry || telemetry.frame_times.len() >= TELEMETRY_MAX_FRAMES {
+        return;
+    }
+    telemetry.frame_times.push(time.delta_seconds());
+    telemetry.max_zombies = telemetry.max_zombies.max(zombie_query.iter().len());
+}
+
+fn send_run_telemetry(
+    settings: Res<GameSettings>,
+    profile: Res<Profile>,
+    run_config: Res<RunConfig>,
+    run_timer: Res<RunTimer>,
+    car_progress: Res<CarProgress>,
+    on_foot_escape: Res<OnFootEscape>,
+    mut telemetry: ResMut<RunTelemetry>,
+) {
+    let telemetry = std::mem::take(&mut *telemetry);
+    if !settings.telemetry || profile.telemetry_endpoint.is_empty() {
+        return;
+    }
+    let body = telemetry.json(
+        &settings,
+        &run_config,
+        RunOutcome::new(&car_progress, &on_foot_escape),
+        car_progress.0,
+        run_timer.elapsed,
+    );
+    post_telemetry(&profile.telemetry_endpoint, body);
+}
+
+fn bank_run_score(
+    mut profile: ResMut<Profile>,
+    player_score: Res<PlayerScore>,
+    run_config: Res<RunConfig>,
+) {
+    profile.total_score += player_score.0 as u64;
+    if let Some(day) = run_config.daily {
+        let best = match profile.daily_best {
+            Some((best_day, best)) if best_day == day => best.max(player_score.0),
+            _ => player_score.0,
+        };
+        profile.daily_best = Some((day, best));
+    }
+    profile.save();
+}
+
+fn cleanup_settings_menu(
+    mut commands: Commands,
+    settings_menu_query: Query<Entity, With<SettingsMenuComponent>>,
+) {
+    if settings_menu_query.is_empty() {
+        return;
+    }
+
+    let settings_menu = settings_menu_query.single();
+    commands.entity(settings_menu).despawn_recursive();
+}
+
+fn cleanup_pause_menu(
+    mut commands: Commands,
+    pause_menu_query: Query<Entity, With<PauseMenuComponent>>,
+) {
+    if pause_menu_query.is_empty() {
+        return;
+    }
+
+    let pause_menu = pause_menu_query.single();
+    commands.entity(pause_menu).despawn_recursive();
+}
+
+fn open_checkpoint_shop(
+    mut reader: EventReader<ProgressMilestoneEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    settings: Res<GameSettings>,
+    benchmark: Res<Benchmark>,
+    tutorial: Res<Tutorial>,
+    coop: Res<Coop>,
+) {
+    // Teammates can't be stopped mid run, and the finale has no time to shop
+    let is_available = settings.checkpoint_shop
+        && !benchmark.active
+        && tutorial.0.is_none()
+        && coop.session.is_none();
+    let is_checkpoint = reader
+        .read()
+        .filter(|milestone| milestone.0 < FINALE_PROGRESS)
+        .count()
+        > 0;
+    if is_checkpoint && is_available {
+        next_state.set(GameState::CheckpointShop);
+    }
+}
+
+fn setup_checkpoint_shop(
+    mut commands: Commands,
+    mut shop: ResMut<CheckpointShop>,
+    asset_server: Res<AssetServer>,
+) {
+    shop.0 = SHOP_DURATION_SEC;
+
+    let button_style = Style {
+        width: Val::Px(450.0),
+        height: Val::Px(60.0),
+        margin: UiRect::all(Val::Px(8.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 35.0,
+        color: COLOR_BLACK,
+        font: asset_server.load("font.ttf"),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: COLOR_BLACK.with_a(0.6).into(),
+                ..default()
+            },
+            CheckpointShopComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Checkpoint",
+                            TextStyle {
+                                font_size: 70.0,
+                                font: asset_server.load("font.ttf"),
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(20.0)),
+                            ..default()
+                        }),
+                    );
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font_size: 30.0,
+                                font: asset_server.load("font.ttf"),
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(20.0)),
+                            ..default()
+                        }),
+                        CheckpointShopText,
+                    ));
+                    for item in ShopItem::ALL {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: COLOR_ORANGE.into(),
+                                    ..default()
+                                },
+                                CheckpointShopButtonAction::Buy(item),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    format!("{} - {}", item.as_str(), item.cost()),
+                                    button_text_style.clone(),
+                                ));
+                            });
+                    }
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style,
+                                background_color: COLOR_ORANGE.into(),
+                                ..default()
+                            },
+                            CheckpointShopButtonAction::Continue,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Continue", button_text_style));
+                        });
+                });
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_checkpoint_shop_btn_click(
+    interaction_query: Query<
+        (&Interaction, &CheckpointShopButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut player_score: ResMut<PlayerScore>,
+    mut car_health: ResMut<CarHealth>,
+    mut status_ammo: ResMut<StatusAmmo>,
+    mut gun_cooldown: ResMut<GunCooldown>,
+    tuning: Res<Tuning>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let item = match menu_button_action {
+            CheckpointShopButtonAction::Buy(item) => *item,
+            CheckpointShopButtonAction::Continue => {
+                game_state.set(GameState::InGame);
+                continue;
+            }
+        };
+        if player_score.0 < item.cost() {
+            continue;
+        }
+
+        player_score.0 -= item.cost();
+        match item {
+            ShopItem::Repair => {
+                car_health.0 = (car_health.0 + tuning.max_car_health * SHOP_REPAIR_AMOUNT)
+                    .min(tuning.max_car_health);
+            }
+            ShopItem::IncendiaryRounds => {
+                status_ammo.kind = Some(StatusKind::Burning);
+                status_ammo.rounds = STATUS_AMMO_ROUNDS;
+            }
+            ShopItem::ColdBarrel => {
+                gun_cooldown.cold_barrel = SHOP_COLD_BARREL_SEC;
+                gun_cooldown.heat = 0.0;
+            }
+        }
+    }
+}
+
+fn update_checkpoint_shop(
+    time: Res<Time<Real>>,
+    mut shop: ResMut<CheckpointShop>,
+    mut game_state: ResMut<NextState<GameState>>,
+    player_score: Res<PlayerScore>,
+    mut text_query: Query<&mut Text, With<CheckpointShopText>>,
+    mut button_query: Query<(&CheckpointShopButtonAction, &mut BackgroundColor)>,
+) {
+    // Real time, the game's clock is stopped while shopping
+    shop.0 -= time.delta_seconds();
+    if shop.0 <= 0.0 {
+        game_state.set(GameState::InGame);
+        return;
+    }
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.sections[0].value = format!(
+            "Score {} - back on the road in {:.0}",
+            player_score.0,
+            shop.0.ceil()
+        );
+    }
+    for (action, mut color) in button_query.iter_mut() {
+        let CheckpointShopButtonAction::Buy(item) = action else {
+            continue;
+        };
+        let button_color = if player_score.0 >= item.cost() {
+            COLOR_ORANGE
+        } else {
+            SHOP_UNAFFORDABLE_COLOR
+        };
+        if color.0 != button_color {
+            color.0 = button_color;
+        }
+    }
+}
+
+fn cleanup_checkpoint_shop(
+    mut commands: Commands,
+    shop_query: Query<Entity, With<CheckpointShopComponent>>,
+) {
+    for e in shop_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn update_benchmark(
+    time: Res<Time<Real>>,
+    mut benchmark: ResMut<Benchmark>,
+    mut settings: ResMut<GameSettings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    entities: Query<Entity>,
+    zombie_query: Query<With<Zombie>>,
+) {
+    if !benchmark.active {
+        return;
+    }
+
+    benchmark.elapsed += time.delta_seconds();
+    benchmark.frame_times.push(time.delta_seconds());
+    benchmark.zombie_counts.push(zombie_query.iter().len());
+    benchmark.max_entities = benchmark.max_entities.max(entities.iter().len());
+    if benchmark.elapsed < BENCHMARK_DURATION_SEC {
+        return;
+    }
+
+    benchmark.export();
+    benchmark.stop(&mut settings);
+    next_state.set(GameState::MainMenu);
+}
+
+// Leaving the benchmark early drops the results
+fn stop_benchmark(mut benchmark: ResMut<Benchmark>, mut settings: ResMut<GameSettings>) {
+    benchmark.stop(&mut settings);
+}
+
+fn freeze_horde(
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut snapshot: ResMut<HordeSnapshot>,
+    scheduler: Res<ZombieScheduler>,
+    zombie_query: Query<(Entity, &Transform), With<Zombie>>,
+) {
+    // Game time stands still too, timers and bullets pick up where they were
+    virtual_time.pause();
+    snapshot.zombies = zombie_query.iter().map(|(e, t)| (e, *t)).collect();
+    snapshot.despawn_cursor = scheduler.despawn_cursor;
+    snapshot.next_spawn_order = scheduler.next_spawn_order;
+}
+
+fn restore_horde(
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut snapshot: ResMut<HordeSnapshot>,
+    mut scheduler: ResMut<ZombieScheduler>,
+    mut zombie_query: Query<&mut Transform, With<Zombie>>,
+) {
+    // Also runs when exiting to the main menu, the next run starts unpaused
+    virtual_time.unpause();
+    for (e, saved) in snapshot.zombies.drain(..) {
+        if let Ok(mut transform) = zombie_query.get_mut(e) {
+            *transform = saved;
+        }
+    }
+    scheduler.despawn_cursor = snapshot.despawn_cursor;
+    scheduler.next_spawn_order = snapshot.next_spawn_order;
+}
+
+fn cleanup_game_over_menu(
+    mut commands: Commands,
+    game_over_menu_query: Query<Entity, With<GameOverMenuComponent>>,
+) {
+    if game_over_menu_query.is_empty() {
+        return;
+    }
+
+    let game_over_menu = game_over_menu_query.single();
+    commands.entity(game_over_menu).despawn_recursive();
+}
+
+fn handle_camera_zoom(
+    mut query: Query<&mut OrthographicProjection, With<MainCamera>>,
+    car_progress: Res<CarProgress>,
+    time: Res<Time>,
+) {
+    for mut projection in query.iter_mut() {
+        if car_progress.0 > FINALE_PROGRESS {
+            if projection.scale <= 1.8 {
+                return;
+            }
+
+            projection.scale -= 0.2 * time.delta_seconds();
+            return;
+        }
+
+        if projection.scale >= 2.0 {
+            return;
+        }
+        projection.scale += 0.1 * time.delta_seconds();
+    }
+}
+
+fn emit_progress_milestones(
+    mut reached: Local<usize>,
+    car_progress: Res<CarProgress>,
+    mut writer: EventWriter<ProgressMilestoneEvent>,
+) {
+    // Counted from the progress every frame, so it starts over with the next run
+    let now = PROGRESS_MILESTONES
+        .iter()
+        .filter(|m| car_progress.0 >= **m)
+        .count();
+    for milestone in PROGRESS_MILESTONES.iter().take(now).skip(*reached) {
+        writer.send(ProgressMilestoneEvent(*milestone));
+    }
+    *reached = now;
+}
+
+fn setup_finale(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    run_config: Res<RunConfig>,
+) {
+    // Thick red border around the screen, the pulse fades it in and out
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                border: UiRect::all(Val::Vw(6.0)),
+                ..default()
+            },
+            border_color: FINALE_VIGNETTE_COLOR.with_a(0.0).into(),
+            z_index: ZIndex::Global(-2),
+            ..default()
+        },
+        FinaleVignette,
+        GameEntity,
+    ));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(30.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            FinaleBanner,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            let briefing = run_config
+                .finish_layout()
+                .map_or("", |layout| layout.briefing());
+            parent.spawn(
+                TextBundle::from_sections([
+                    TextSection::new(
+                        "FINAL STRETCH",
+                        TextStyle {
+                            font_size: 90.0,
+                            font: asset_server.load("font.ttf"),
+                            color: COLOR_ORANGE,
+                        },
+                    ),
+                    TextSection::new(
+                        format!("\n{briefing}"),
+                        TextStyle {
+                            font_size: 36.0,
+                            font: asset_server.load("font.ttf"),
+                            color: COLOR_LIGHT_ORANGE,
+                        },
+                    ),
+                ])
+                .with_text_alignment(TextAlignment::Center),
+            );
+        });
+
+    // Same spot as the toll gate prompt, the two never show at once
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    align_items: AlignItems::End,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 30.0,
+                            font: asset_server.load("font.ttf"),
+                            color: COLOR_LIGHT_ORANGE,
+                        },
+                    ),
+                    style: Style {
+                        margin: UiRect::bottom(Val::Px(120.0)),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                FinishPrompt,
+            ));
+        });
+}
+
+fn start_finale(
+    mut commands: Commands,
+    mut reader: EventReader<ProgressMilestoneEvent>,
+    mut finale: ResMut<Finale>,
+    mut pitch_assets: ResMut<Assets<Pitch>>,
+    settings: Res<GameSettings>,
+) {
+    for milestone in reader.read() {
+        if milestone.0 < FINALE_PROGRESS || finale.0.is_some() {
+            continue;
+        }
+        finale.0 = Some(Stopwatch::new());
+
+        // Low rumble under the key change
+        if settings.music {
+            commands.spawn(PitchBundle {
+                source: pitch_assets.add(Pitch::new(70.0, Duration::from_millis(900))),
+                settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_absolute(0.3)),
+            });
+        }
+    }
+}
+
+fn update_finale(
+    time: Res<Time>,
+    mut finale: ResMut<Finale>,
+    mut vignette_query: Query<&mut BorderColor, With<FinaleVignette>>,
+    mut banner_query: Query<&mut Visibility, With<FinaleBanner>>,
+) {
+    let Some(timer) = finale.0.as_mut() else {
+        return;
+    };
+    timer.tick(time.delta());
+    let elapsed = timer.elapsed_secs();
+
+    let pulse = 0.5 - 0.5 * (elapsed * FINALE_PULSE_HZ * std::f32::consts::TAU).cos();
+    for mut border_color in vignette_query.iter_mut() {
+        border_color.0 = FINALE_VIGNETTE_COLOR.with_a(pulse * FINALE_VIGNETTE_MAX_ALPHA);
+    }
+    for mut visibility in banner_query.iter_mut() {
+        visibility.set_if_neq(if elapsed < FINALE_BANNER_SEC {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_turbo_input(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    asset_server: Res<AssetServer>,
+    mut car_query: Query<&mut Turbo, With<Car>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+    profile: Res<Profile>,
+    tuning: Res<Tuning>,
+) {
+    if car_query.is_empty() || run_config.has(Modifier::NoTurbo) {
+        return;
+    }
+
+    let mut turbo = car_query.single_mut();
+    turbo.0.tick(time.delta());
+
+    if turbo.0.elapsed().as_secs_f32() <= tuning.turbo_interval_sec
+        && !run_config.has(Modifier::InfiniteTurbo)
+    {
+        return;
+    }
+    if !keyboard_input.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    turbo.0.reset();
+    if cfg!(feature = "audio") && settings.music {
+        commands.spawn(AudioBundle {
+            source: asset_server.load("turbo.mp3"),
+            settings: PlaybackSettings {
+                volume: Volume::Absolute(VolumeLevel::new(0.5)),
+                speed: profile.turbo_sound.playback_speed(),
+                ..Default::default()
+            },
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_horn_input(
+    mut commands: Commands,
+    mut horn_sound: Local<Option<(HornSound, Handle<Pitch>)>>,
+    mut pitch_assets: ResMut<Assets<Pitch>>,
+    mut writer_noise: EventWriter<NoiseEvent>,
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<GameSettings>,
+    profile: Res<Profile>,
+    car_query: Query<&Transform, (With<Car>, Without<OnFoot>)>,
+) {
+    if car_query.is_empty() || !keyboard_input.just_pressed(KeyCode::H) {
+        return;
+    }
+
+    writer_noise.send(NoiseEvent {
+        pos: car_query.single().translation.truncate(),
+        radius: HORN_NOISE_RADIUS,
+    });
+    if !settings.music {
+        return;
+    }
+
+    // Rebuilt only when the horn was changed in the garage
+    if !matches!(*horn_sound, Some((horn, _)) if horn == profile.horn) {
+        *horn_sound = Some((profile.horn, pitch_assets.add(profile.horn.pitch())));
+    }
+    let (_, source) = horn_sound.as_ref().unwrap();
+    commands.spawn(PitchBundle {
+        source: source.clone(),
+        settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_absolute(0.3)),
+    });
+}
+
+#[cfg(feature = "debug-tools")]
+#[allow(clippy::too_many_arguments)]
+fn update_debug_text(
+    time: Res<Time>,
+    mut text_query: Query<&mut Text, With<DebugText>>,
+    zom_query: Query<(With<Zombie>, Without<DebugText>)>,
+    diagnostics: Res<DiagnosticsStore>,
+    car_progress: Res<CarProgress>,
+    car_health: Res<CarHealth>,
+    car_armor: Res<CarArmor>,
+    player_score: Res<PlayerScore>,
+    quality: Res<AdaptiveQuality>,
+    settings: Res<GameSettings>,
+    ramp_editor: Res<DebugRampEditor>,
+    tuning: Res<Tuning>,
+) {
+    if text_query.is_empty() || !settings.debug_info {
+        return;
+    }
+
+    let mut text = text_query.single_mut();
+    let mut fps = 0.0;
+    if let Some(d) = diagnostics.get(FrameTimeDiagnosticsPlugin::FPS) {
+        if let Some(value) = d.smoothed() {
+            fps = value;
+        }
+    }
+    text.sections[0].value = format!(
+        "Fps: {:.2?}\nQuality: {:.2?}\nTime: {:.2?}\nProgress: {:.2?}\nHealth: {:?}\nArmor: {:?}\nZoms: {:?}\nScore: {:?}",
+        fps,
+        quality.0,
+        time.delta_seconds(),
+        car_progress.0,
+        car_health.0,
+        car_armor.0,
+        zom_query.iter().len(),
+        player_score.0
+    );
+    if let Some((x, y)) = tuning
+        .spawn_ramp
+        .get(ramp_editor.selected)
+        .filter(|_| ramp_editor.is_open)
+    {
+        text.sections[0].value += &format!(
+            "\nRamp point {}/{}: ({:.2}, {:.2})\nRamp target: {:.2}",
+            ramp_editor.selected + 1,
+            tuning.spawn_ramp.len(),
+            x,
+            y,
+            tuning.spawn_ramp_at(car_progress.0)
+        );
+    }
+}
+
+#[cfg(feature = "debug-tools")]
+fn toggle_debug_heatmap(
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut heatmap: ResMut<DebugHeatmap>,
+) {
+    if settings.debug_info && keyboard_input.just_pressed(KeyCode::F3) {
+        heatmap.0 = !heatmap.0;
+    }
+}
+
+#[cfg(feature = "debug-tools")]
+fn update_debug_heatmap(
+    settings: Res<GameSettings>,
+    heatmap: Res<DebugHeatmap>,
+    zombie_grid: Res<ZombieGrid>,
+    mut cell_query: Query<(&mut Transform, &mut Sprite, &mut Visibility), With<HeatmapCell>>,
+) {
+    let is_enabled = settings.debug_info && heatmap.0;
+    let mut cells = zombie_grid.0.iter().filter(|_| is_enabled);
+    for (mut transform, mut sprite, mut visibility) in cell_query.iter_mut() {
+        let Some(((x, y), zombies)) = cells.next() else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+
+        let center = (vec2(*x as f32, *y as f32) + 0.5) * ZOMBIE_GRID_CELL;
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+        let density = (zombies.len() as f32 / DEBUG_HEATMAP_FULL_COUNT).min(1.0);
+        sprite.color =
+            Color::rgb(1.0, 1.0 - density, 0.0).with_a(density * DEBUG_HEATMAP_MAX_ALPHA);
+        *visibility = Visibility::Visible;
+    }
+}
+
+#[cfg(feature = "debug-tools")]
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn draw_debug_spawn_zones(
+    mut gizmos: Gizmos,
+    settings: Res<GameSettings>,
+    heatmap: Res<DebugHeatmap>,
+    spawn_patterns: Res<SpawnPatterns>,
+    run_config: Res<RunConfig>,
+    car_progress: Res<CarProgress>,
+    finale: Res<Finale>,
+    car_query: Query<&Transform, With<Car>>,
+    cam_query: Query<(&Transform, &OrthographicProjection), (With<MainCamera>, Without<Car>)>,
+) {
+    if !settings.debug_info || !heatmap.0 {
+        return;
+    }
+    let Ok(car_transform) = car_query.get_single() else {
+        return;
+    };
+
+    for (zone, color) in zombie_spawn_zones(
+        car_transform.translation.truncate(),
+        &spawn_patterns,
+        run_config.difficulty,
+        car_progress.0,
+        // Same as spawn_zombies, the finale's patterns only show once it has started
+        run_config.finish_layout().filter(|_| finale.0.is_some()),
+    ) {
+        gizmos.rect_2d(zone.center(), 0.0, zone.size(), color);
+    }
+    if let Some(view) = zombie_spawn_exclusion(&cam_query, &settings) {
+        gizmos.rect_2d(view.center(), 0.0, view.size(), Color::WHITE);
+    }
+}
+
+#[cfg(feature = "debug-tools")]
+fn update_debug_ramp_editor(
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut editor: ResMut<DebugRampEditor>,
+    mut tuning: ResMut<Tuning>,
+) {
+    if !settings.debug_info {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::F4) {
+        editor.is_open = !editor.is_open;
+    }
+    if !editor.is_open {
+        return;
+    }
+
+    // Arrows and WASD still drive, the editor keeps to the keys around them
+    let ramp = &mut tuning.spawn_ramp;
+    if ramp.len() < 2 {
+        *ramp = Tuning::default().spawn_ramp;
+    }
+    editor.selected = editor.selected.min(ramp.len() - 1);
+    let i = editor.selected;
+    if keyboard_input.just_pressed(KeyCode::BracketLeft) {
+        editor.selected = i.saturating_sub(1);
+    }
+    if keyboard_input.just_pressed(KeyCode::BracketRight) {
+        editor.selected = (i + 1).min(ramp.len() - 1);
+    }
+
+    let mut value_step = 0.0;
+    if keyboard_input.just_pressed(KeyCode::Minus) {
+        value_step -= DEBUG_RAMP_VALUE_STEP;
+    }
+    if keyboard_input.just_pressed(KeyCode::Equals) {
+        value_step += DEBUG_RAMP_VALUE_STEP;
+    }
+    ramp[i].1 = (ramp[i].1 + value_step).clamp(0.0, 1.0);
+
+    // Points stay in progress order, a point can't be moved past its neighbours
+    let mut progress_step = 0.0;
+    if keyboard_input.just_pressed(KeyCode::Comma) {
+        progress_step -= DEBUG_RAMP_PROGRESS_STEP;
+    }
+    if keyboard_input.just_pressed(KeyCode::Period) {
+        progress_step += DEBUG_RAMP_PROGRESS_STEP;
+    }
+    let min_x = if i == 0 { 0.0 } else { ramp[i - 1].0 };
+    let max_x = ramp.get(i + 1).map_or(1.0, |p| p.0);
+    ramp[i].0 = (ramp[i].0 + progress_step).clamp(min_x, max_x);
+
+    if keyboard_input.just_pressed(KeyCode::Insert) {
+        // Halfway to the next point, or to the previous one off the end
+        let j = if i + 1 < ramp.len() { i + 1 } else { i - 1 };
+        let mid = ((ramp[i].0 + ramp[j].0) * 0.5, (ramp[i].1 + ramp[j].1) * 0.5);
+        let at = i.max(j);
+        ramp.insert(at, mid);
+        editor.selected = at;
+    }
+    if keyboard_input.just_pressed(KeyCode::Delete) && ramp.len() > 2 {
+        ramp.remove(i);
+        editor.selected = i.min(ramp.len() - 1);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::F6) {
+        let line = tuning.spawn_ramp_ron();
+        info!("Spawn ramp: {line}");
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(e) = export_spawn_ramp(&line) {
+            warn!("Failed to export the spawn ramp: {e}");
+        }
+    }
+}
+
+// Swaps the spawn_ramp line in the tuning file, hot reload then picks it back up
+#[cfg(all(feature = "debug-tools", not(target_arch = "wasm32")))]
+fn export_spawn_ramp(line: &str) -> std::io::Result<()> {
+    let contents = std::fs::read_to_string(DEBUG_RAMP_EXPORT_PATH)?;
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    let Some(existing) = lines
+        .iter_mut()
+        .find(|l| l.trim_start().starts_with("spawn_ramp:"))
+    else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no spawn_ramp entry in the tuning file",
+        ));
+    };
+    let indent = existing.len() - existing.trim_start().len();
+    *existing = format!("{}{line}", &existing[..indent]);
+    std::fs::write(DEBUG_RAMP_EXPORT_PATH, lines.join("\n") + "\n")
+}
+
+#[cfg(feature = "debug-tools")]
+#[allow(clippy::too_many_arguments)]
+fn draw_debug_ramp_editor(
+    mut gizmos: Gizmos,
+    settings: Res<GameSettings>,
+    editor: Res<DebugRampEditor>,
+    tuning: Res<Tuning>,
+    car_progress: Res<CarProgress>,
+    quality: Res<AdaptiveQuality>,
+    zombie_query: Query<With<Zombie>>,
+    cam_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+) {
+    if !settings.debug_info || !editor.is_open {
+        return;
+    }
+    let Ok((cam_transform, projection)) = cam_query.get_single() else {
+        return;
+    };
+
+    // Kept the same size on screen whatever the zoom
+    let size = DEBUG_RAMP_GRAPH_SIZE * projection.scale;
+    let origin = cam_transform.translation.truncate()
+        + projection.area.min
+        + Vec2::splat(DEBUG_RAMP_GRAPH_MARGIN * projection.scale);
+    let to_graph = |x: f32, y: f32| origin + vec2(x, y) * size;
+    gizmos.rect_2d(origin + size * 0.5, 0.0, size, Color::WHITE);
+
+    gizmos.linestrip_2d(
+        (0..=50).map(|k| {
+            let x = k as f32 / 50.0;
+            to_graph(x, tuning.spawn_ramp_at(x))
+        }),
+        COLOR_LIGHT_ORANGE,
+    );
+    for (i, &(x, y)) in tuning.spawn_ramp.iter().enumerate() {
+        let color = if i == editor.selected {
+            Color::RED
+        } else {
+            Color::WHITE
+        };
+        gizmos.circle_2d(to_graph(x, y), 5.0 * projection.scale, color);
+    }
+
+    // Where the run is now, against what is actually out on the road
+    let progress = car_progress.0.clamp(0.0, 1.0);
+    gizmos.line_2d(
+        to_graph(progress, 0.0),
+        to_graph(progress, 1.0),
+        Color::GREEN,
+    );
+    let max_zombies = quality.max_zombies(&settings).max(1);
+    let alive = (zombie_query.iter().len() as f32 / max_zombies as f32).min(1.0);
+    gizmos.circle_2d(
+        to_graph(progress, alive),
+        4.0 * projection.scale,
+        Color::GREEN,
+    );
+}
+
+fn update_adaptive_quality(
+    time: Res<Time<Real>>,
+    diagnostics: Res<DiagnosticsStore>,
+    settings: Res<GameSettings>,
+    mut quality: ResMut<AdaptiveQuality>,
+) {
+    if !settings.adaptive_quality {
+        quality.0 = 1.0;
+        return;
+    }
+
+    let Some(fps) = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|d| d.smoothed())
+    else {
+        return;
+    };
+
+    // Back off quickly, recover slowly
+    if fps < ADAPTIVE_TARGET_FPS {
+        quality.0 -= ADAPTIVE_DROP_RATE * time.delta_seconds();
+    } else if fps > ADAPTIVE_RECOVER_FPS {
+        quality.0 += ADAPTIVE_RECOVER_RATE * time.delta_seconds();
+    }
+    quality.0 = quality.0.clamp(0.0, 1.0);
+}
+
+fn update_car_progress(
+    car_query: Query<&Transform, With<Car>>,
+    mut car_progress: ResMut<CarProgress>,
+    run_config: Res<RunConfig>,
+) {
+    if car_query.is_empty() {
+        return;
+    }
+
+    let car_transform = car_query.single();
+    let (_, cy) = (car_transform.translation.x, car_transform.translation.y);
+
+    let total_h = TILE_H as f32 * ROAD_SCALE * run_config.road_height() as f32;
+    car_progress.0 = cy / total_h;
+}
+
+fn update_health_regen(
+    time: Res<Time>,
+    run_config: Res<RunConfig>,
+    tuning: Res<Tuning>,
+    mut car_health: ResMut<CarHealth>,
+    mut regen: ResMut<HealthRegen>,
+    car_query: Query<(), (With<Car>, Without<OnFoot>)>,
+) {
+    // Any drop counts as damage, whatever dealt it
+    if car_health.0 < regen.last_health {
+        regen.since_damage = 0.0;
+    }
+    regen.since_damage += time.delta_seconds();
+
+    let cap = tuning.max_car_health * HEALTH_REGEN_CAP;
+    regen.is_active = run_config.regenerates_health()
+        && !car_query.is_empty()
+        && regen.since_damage >= HEALTH_REGEN_DELAY_SEC
+        && car_health.0 > 0.0
+        && car_health.0 < cap;
+    if regen.is_active {
+        car_health.0 = (car_health.0 + HEALTH_REGEN_PER_SEC * time.delta_seconds()).min(cap);
+    }
+    regen.last_health = car_health.0;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_zombie_player_hit(
+    mut car_health: ResMut<CarHealth>,
+    mut car_armor: ResMut<CarArmor>,
+    mut reader_zombie_player_hit: EventReader<ZombieHitPlayer>,
+    mut writer_player_dead: EventWriter<PlayerDeadEvent>,
+    settings: Res<GameSettings>,
+    profile: Res<Profile>,
+    run_config: Res<RunConfig>,
+    tuning: Res<Tuning>,
+    airborne_query: Query<With<Airborne>>,
+    sheltered_query: Query<With<Sheltered>>,
+    difficulty: Res<DifficultyProfile>,
+    commandeered_query: Query<&Commandeered, With<Car>>,
+) {
+    if reader_zombie_player_hit.is_empty() || settings.god_mode {
+        return;
+    }
+    // Nothing reaches the car mid-jump or behind the plow
+    if !airborne_query.is_empty() || !sheltered_query.is_empty() {
+        reader_zombie_player_hit.clear();
+        return;
+    }
+
+    // Armor soaks up the chip damage first
+    let damage = tuning.zombie_attack
+        * difficulty.zombie_attack
+        * reader_zombie_player_hit.len() as f32
+        * commandeered_query
+            .get_single()
+            .map_or(profile.car, |c| c.0)
+            .damage_multiplier();
+    let absorbed = damage.min(car_armor.0);
+    car_armor.0 -= absorbed;
+    car_health.0 -= damage - absorbed;
+    reader_zombie_player_hit.clear();
+
+    // Player dead
+    if car_health.0 <= 0.0 || run_config.has(Modifier::OneHitCar) {
+        writer_player_dead.send(PlayerDeadEvent);
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn check_obstacle_collision(
+    obstacles: Res<VehicleObstacleTiles>,
+    mut car_health: ResMut<CarHealth>,
+    mut writer_player_dead: EventWriter<PlayerDeadEvent>,
+    mut writer_hit_stop: EventWriter<HitStopEvent>,
+    mut writer_particles: EventWriter<ParticleBurstEvent>,
+    mut car_query: Query<(&mut Transform, &mut Speed), (With<Car>, Without<Airborne>)>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+    tuning: Res<Tuning>,
+) {
+    if car_query.is_empty() {
+        return;
+    }
+
+    let (mut car_transform, mut speed) = car_query.single_mut();
+    for obstacle in obstacles.0.iter() {
+        let Some((normal, depth)) = obstacle.penetration(car_transform.translation.truncate())
+        else {
+            continue;
+        };
+
+        // Back out of the obstacle so only the frame of impact counts
+        car_transform.translation += (normal * depth).extend(0.0);
+
+        let heading = (car_transform.rotation * Vec3::Y).truncate();
+        let velocity = heading * speed.0;
+        let into = velocity.dot(normal);
+        if into >= 0.0 {
+            continue;
+        }
+
+        // Bounce off the side that was hit and scrape along it, the car keeps its heading
+        let along = velocity - normal * into;
+        let response = along * OBSTACLE_SCRAPE_KEEP - normal * into * OBSTACLE_BOUNCE;
+        speed.0 = response.dot(heading);
+
+        let impact_speed = -into;
+        if impact_speed < OBSTACLE_MIN_IMPACT_SPEED {
+            continue;
+        }
+        writer_hit_stop.send(HitStopEvent(Duration::from_millis(HIT_STOP_OBSTACLE_MS)));
+        writer_particles.send(ParticleBurstEvent {
+            pos: car_transform.translation.truncate(),
+            dir: normal,
+            kind: ParticleKind::Spark,
+        });
+
+        // Obstacles bypass the armor and damage the car directly
+        if !settings.god_mode {
+            car_health.0 -= tuning.obstacle_damage * impact_speed / tuning.max_speed;
+            if car_health.0 <= 0.0 || run_config.has(Modifier::OneHitCar) {
+                writer_player_dead.send(PlayerDeadEvent);
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_ramps(
+    mut commands: Commands,
+    time: Res<Time>,
+    road_tiles: Res<RoadTiles>,
+    mut car_health: ResMut<CarHealth>,
+    mut writer_player_dead: EventWriter<PlayerDeadEvent>,
+    mut writer_particles: EventWriter<ParticleBurstEvent>,
+    mut car_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &Speed,
+            &TextureAtlasSprite,
+            &Handle<TextureAtlas>,
+            Option<&mut Airborne>,
+        ),
+        With<Car>,
+    >,
+    mut shadow_query: Query<&mut Transform, (With<CarShadow>, Without<Car>)>,
+    ramp_query: Query<&Transform, (With<Ramp>, Without<Car>, Without<CarShadow>)>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+) {
+    let Ok((car, mut car_transform, speed, sprite, atlas, airborne)) = car_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let Some(mut airborne) = airborne else {
+        // Launch off any ramp the car drives onto fast enough
+        let car_pos = car_transform.translation.truncate();
+        let is_on_ramp = ramp_query.iter().any(|t| {
+            let offset = (car_pos - t.translation.truncate()).abs();
+            offset.x <= RAMP_SIZE.x / 2.0 && offset.y <= RAMP_SIZE.y / 2.0
+        });
+        if !is_on_ramp || speed.0 < RAMP_MIN_SPEED {
+            return;
+        }
+
+        let shadow = commands
+            .spawn((
+                SpriteSheetBundle {
+                    texture_atlas: atlas.clone(),
+                    sprite: TextureAtlasSprite {
+                        index: sprite.index,
+                        color: RAMP_SHADOW_COLOR,
+                        ..default()
+                    },
+                    transform: car_transform
+                        .with_translation(car_transform.translation - Vec3::Z * 0.5),
+                    ..default()
+                },
+                CarShadow,
+                GameEntity,
+            ))
+            .id();
+        commands.entity(car).insert(Airborne {
+            elapsed: 0.0,
+            base_scale: car_transform.scale,
+            shadow,
+        });
+        return;
+    };
+
+    airborne.elapsed += time.delta_seconds();
+    if airborne.elapsed < RAMP_AIR_SEC {
+        // Rises and falls back down, the shadow drifts away from the car while it's up
+        let height = (airborne.elapsed / RAMP_AIR_SEC * std::f32::consts::PI).sin();
+        car_transform.scale = airborne.base_scale * (1.0 + height * RAMP_AIR_SCALE);
+        if let Ok(mut shadow_transform) = shadow_query.get_mut(airborne.shadow) {
+            *shadow_transform = Transform {
+                translation: car_transform.translation + (RAMP_SHADOW_OFFSET * height).extend(-0.5),
+                scale: airborne.base_scale,
+                ..*car_transform
+            };
+        }
+        return;
+    }
+
+    car_transform.scale = airborne.base_scale;
+    commands.entity(airborne.shadow).despawn();
+    commands.entity(car).remove::<Airborne>();
+
+    // Landing in the dirt is rough on the suspension
+    if road_surface_at(&road_tiles, car_transform.translation).is_some() {
+        return;
+    }
+    writer_particles.send(ParticleBurstEvent {
+        pos: car_transform.translation.truncate(),
+        dir: -(car_transform.rotation * Vec3::Y).truncate(),
+        kind: ParticleKind::Dirt,
+    });
+    if !settings.god_mode {
+        car_health.0 -= RAMP_OFF_ROAD_LANDING_DAMAGE;
+        if car_health.0 <= 0.0 || run_config.has(Modifier::OneHitCar) {
+            writer_player_dead.send(PlayerDeadEvent);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn check_armor_pickup(
+    mut commands: Commands,
+    mut car_armor: ResMut<CarArmor>,
+    car_query: Query<&Transform, With<Car>>,
+    pickup_query: Query<(Entity, &Transform), (With<ArmorPickup>, Without<Car>)>,
+    tuning: Res<Tuning>,
+) {
+    if car_query.is_empty() {
+        return;
+    }
+
+    let car_transform = car_query.single();
+    for (e, t) in pickup_query.iter() {
+        if (t.translation.x - car_transform.translation.x).abs() <= PICKUP_RADIUS
+            && (t.translation.y - car_transform.translation.y).abs() <= PICKUP_RADIUS
+        {
+            car_armor.0 = (car_armor.0 + tuning.armor_pickup_value).min(tuning.max_car_armor);
+            commands.entity(e).despawn();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn check_slow_mo_pickup(
+    mut commands: Commands,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    car_query: Query<&Transform, With<Car>>,
+    pickup_query: Query<(Entity, &Transform), (With<SlowMoPickup>, Without<Car>)>,
+) {
+    if car_query.is_empty() {
+        return;
+    }
+
+    let car_transform = car_query.single();
+    for (e, t) in pickup_query.iter() {
+        if (t.translation.x - car_transform.translation.x).abs() <= PICKUP_RADIUS
+            && (t.translation.y - car_transform.translation.y).abs() <= PICKUP_RADIUS
+        {
+            // Another pickup while active restarts the effect
+            slow_motion.timer = Some(Stopwatch::new());
+            virtual_time.set_relative_speed(SLOW_MO_TIME_SCALE);
+            commands.entity(e).despawn();
+        }
+    }
+}
+
+fn check_status_ammo_pickup(
+    mut commands: Commands,
+    mut status_ammo: ResMut<StatusAmmo>,
+    car_query: Query<&Transform, With<Car>>,
+    pickup_query: Query<(Entity, &Transform, &StatusAmmoPickup), Without<Car>>,
+) {
+    let Ok(car_transform) = car_query.get_single() else {
+        return;
+    };
+
+    for (e, t, pickup) in pickup_query.iter() {
+        if (t.translation.x - car_transform.translation.x).abs() <= PICKUP_RADIUS
+            && (t.translation.y - car_transform.translation.y).abs() <= PICKUP_RADIUS
+        {
+            // Swaps out whatever special rounds were left
+            status_ammo.kind = Some(pickup.0);
+            status_ammo.rounds = STATUS_AMMO_ROUNDS;
+            commands.entity(e).despawn();
+        }
+    }
+}
+
+fn update_slow_motion(
+    mut commands: Commands,
+    real_time: Res<Time<Real>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut pitch_assets: ResMut<Assets<Pitch>>,
+    mut overlay_query: Query<&mut Visibility, With<SlowMoOverlay>>,
+    settings: Res<GameSettings>,
+) {
+    let is_active = slow_motion.timer.is_some();
+    for mut visibility in overlay_query.iter_mut() {
+        visibility.set_if_neq(if is_active {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    let Some(timer) = slow_motion.timer.as_mut() else {
+        return;
+    };
+
+    // The effect runs on real time, virtual time is the thing being slowed down
+    let prev_ticks = (timer.elapsed_secs() / SLOW_MO_TICK_INTERVAL_SEC).ceil();
+    timer.tick(real_time.delta());
+    let elapsed = timer.elapsed_secs();
+    if elapsed >= SLOW_MO_DURATION_SEC {
+        slow_motion.timer = None;
+        virtual_time.set_relative_speed(1.0);
+        return;
+    }
+
+    let is_tick = (elapsed / SLOW_MO_TICK_INTERVAL_SEC).ceil() > prev_ticks;
+    if is_tick && settings.music {
+        let tick_sound = slow_motion
+            .tick_sound
+            .get_or_insert_with(|| pitch_assets.add(Pitch::new(1400.0, Duration::from_millis(30))))
+            .clone();
+        commands.spawn(PitchBundle {
+            source: tick_sound,
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_absolute(0.15)),
+        });
+    }
+}
+
+fn update_hit_stop(
+    mut reader: EventReader<HitStopEvent>,
+    mut hit_stop: ResMut<HitStop>,
+    real_time: Res<Time<Real>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    slow_motion: Res<SlowMotion>,
+    settings: Res<GameSettings>,
+) {
+    for ev in reader.read() {
+        if !settings.hit_stop {
+            continue;
+        }
+        // Overlapping impacts keep the longer freeze instead of stacking
+        let remaining = hit_stop
+            .0
+            .as_ref()
+            .map(|timer| timer.duration() - timer.elapsed())
+            .unwrap_or_default();
+        if ev.0 > remaining {
+            hit_stop.0 = Some(Timer::new(ev.0, TimerMode::Once));
+        }
+    }
+
+    let Some(timer) = hit_stop.0.as_mut() else {
+        return;
+    };
+
+    // Runs after the slow-mo systems so the freeze wins over their speed changes
+    timer.tick(real_time.delta());
+    if timer.finished() {
+        hit_stop.0 = None;
+        virtual_time.set_relative_speed(if slow_motion.timer.is_some() {
+            SLOW_MO_TIME_SCALE
+        } else {
+            1.0
+        });
+    } else {
+        virtual_time.set_relative_speed(0.0);
+    }
+}
+
+// The car and player move on real time, so they need holding separately
+fn hit_stop_inactive(hit_stop: Res<HitStop>) -> bool {
+    hit_stop.0.is_none()
+}
+
+fn road_run(run_config: Res<RunConfig>) -> bool {
+    !run_config.arena
+}
+
+fn arena_run(run_config: Res<RunConfig>) -> bool {
+    run_config.arena
+}
+
+fn start_countdown_done(countdown: Res<StartCountdown>) -> bool {
+    countdown.0.elapsed_secs() >= START_COUNTDOWN_SEC
+}
+
+fn explode_killed_zombies(
+    mut reader_zombie_killed: EventReader<ZombieKilledEvent>,
+    mut writer_player_hit: EventWriter<ZombieHitPlayer>,
+    car_query: Query<&Transform, With<Car>>,
+    run_config: Res<RunConfig>,
+) {
+    if car_query.is_empty() || !run_config.has(Modifier::ExplodingZombies) {
+        reader_zombie_killed.clear();
+        return;
+    }
+
+    let car_pos = car_query.single().translation.truncate();
+    for ev in reader_zombie_killed.read() {
+        if ev.pos.truncate().distance(car_pos) <= EXPLODING_ZOMBIE_RADIUS {
+            writer_player_hit.send_batch((0..EXPLODING_ZOMBIE_HITS).map(|_| ZombieHitPlayer {
+                from: ev.pos.truncate(),
+            }));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn spawn_zombies(
+    mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    mut scheduler: ResMut<ZombieScheduler>,
+    texture_handle: Res<GlobalTextureHandle>,
+    car_query: Query<&Transform, With<Car>>,
+    zombie_query: Query<With<Zombie>>,
+    car_progress: Res<CarProgress>,
+    settings: Res<GameSettings>,
+    quality: Res<AdaptiveQuality>,
+    run_config: Res<RunConfig>,
+    tutorial: Res<Tutorial>,
+    (tuning, difficulty, spawn_patterns, finale): (
+        Res<Tuning>,
+        Res<DifficultyProfile>,
+        Res<SpawnPatterns>,
+        Res<Finale>,
+    ),
+    countdown: Res<StartCountdown>,
+    obstacle_grid: Res<ObstacleGrid>,
+    cam_query: Query<(&Transform, &OrthographicProjection), (With<MainCamera>, Without<Car>)>,
+) {
+    // The tutorial plays on an empty road, and nothing comes for the car at the gates
+    if car_query.is_empty()
+        || tutorial.0.is_some()
+        || countdown.0.elapsed_secs() < START_COUNTDOWN_SEC
+    {
+        return;
+    }
+
+    let max_zombies = quality.max_zombies(&settings);
+    let num_zombies = (max_zombies as f32 * tuning.spawn_ramp_at(car_progress.0) + 5.0)
+        .min(max_zombies as f32) as usize;
+    let current_zombies = zombie_query.iter().len();
+    if current_zombies >= num_zombies {
+        return;
+    }
+
+    // Top up towards the target over several frames instead of all at once
+    let num_to_spawn = (num_zombies - current_zombies).min(tuning.zombie_spawn_budget);
+
+    let rng = run_rng.stream(RngStream::ZombieSpawns);
+    let car_transform = car_query.single();
+    let (cx, cy) = (car_transform.translation.x, car_transform.translation.y);
+
+    let spitter_chance = (tuning.spitter_chance * difficulty.spitter_ratio as f64).min(1.0);
+    let bloater_chance = (tuning.bloater_chance * difficulty.bloater_ratio as f64).min(1.0);
+    let dog_chance = (tuning.dog_pack_chance * difficulty.dog_ratio as f64).min(1.0);
+    let dog_min_progress = match run_config.difficulty {
+        Difficulty::Hard => DOG_HARD_MIN_PROGRESS,
+        _ => DOG_MIN_PROGRESS,
+    };
+    let finish = run_config.finish_layout().filter(|_| finale.0.is_some());
+    let patterns = spawn_patterns.active(run_config.difficulty, car_progress.0, finish);
+    let Ok(pattern_dist) =
+        WeightedIndex::new(patterns.iter().map(|p| p.weight.get(run_config.difficulty)))
+    else {
+        return;
+    };
+
+    let in_dust_storm = dust_storm_intensity(&run_config, cy) > 0.0;
+    let roll_spawn_pos = |rng: &mut ChaCha8Rng| {
+        let Vec2 { x, mut y } = vec2(cx, cy) + patterns[pattern_dist.sample(rng)].roll(rng);
+        // Nobody sees them coming through the dust anyway
+        if in_dust_storm && y > cy {
+            y = cy + (y - cy) * DUST_STORM_SPAWN_PULL;
+        }
+        vec2(x, y)
+    };
+    let view = zombie_spawn_exclusion(&cam_query, &settings);
+
+    for _ in 0..num_to_spawn {
+        // Rerolled a few times, whatever's still on screen or in a wall waits for a later frame
+        let Some(Vec2 { x, y }) = (0..ZOMBIE_SPAWN_ATTEMPTS)
+            .map(|_| roll_spawn_pos(rng))
+            .find(|pos| is_valid_zombie_spawn(*pos, view, &obstacle_grid))
+        else {
+            continue;
+        };
+
+        // Dogs run in small packs, spread around the one spot
+        if car_progress.0 >= dog_min_progress && rng.gen_bool(dog_chance) {
+            for _ in 0..rng.gen_range(DOG_PACK_SIZE.0..DOG_PACK_SIZE.1) {
+                let offset = vec2(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+                let pos = vec2(x, y) + offset * DOG_PACK_SPREAD;
+                commands.spawn((
+                    SpriteSheetBundle {
+                        texture_atlas: texture_handle.atlas(SpriteId::Dog.atlas()),
+                        sprite: TextureAtlasSprite::new(SpriteId::Dog.random_index(rng)),
+                        transform: Transform::from_scale(Vec3::splat(DOG_SCALE))
+                            .with_translation(pos.extend(1.0)),
+                        ..default()
+                    },
+                    Zombie,
+                    ZombieDog::default(),
+                    ZombieAi::default(),
+                    ZombieAttack::new(DOG_SCALE),
+                    scheduler.next_spawn_order(),
+                    GameEntity,
+                ));
+            }
+            continue;
+        }
+
+        let mut tile = SpriteId::Zombie.random_index(rng);
+        let mut scale = 2.5;
+        if rng.gen_range(0.0..1.0) > 0.9 && car_progress.0 >= 0.3 {
+            tile = SpriteId::BigZombie.random_index(rng);
+            scale = 3.2;
+        }
+        let is_spitter = car_progress.0 >= SPITTER_MIN_PROGRESS && rng.gen_bool(spitter_chance);
+        let is_bloater =
+            !is_spitter && car_progress.0 >= BLOATER_MIN_PROGRESS && rng.gen_bool(bloater_chance);
+        if is_bloater {
+            scale = BLOATER_SCALE;
+        }
+        let mut zombie = commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(SpriteId::Zombie.atlas()),
+                sprite: TextureAtlasSprite {
+                    index: tile,
+                    color: if is_spitter {
+                        SPITTER_COLOR
+                    } else if is_bloater {
+                        BLOATER_COLOR
+                    } else {
+                        Color::WHITE
+                    },
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec3::splat(scale))
+                    .with_translation(vec3(x, y, 1.0)),
+                ..default()
+            },
+            Zombie,
+            ZombieAi::default(),
+            ZombieAttack::new(scale),
+            scheduler.next_spawn_order(),
+            GameEntity,
+        ));
+        if is_spitter {
+            zombie.insert(Spitter::default());
+        }
+        if is_bloater {
+            zombie.insert(Bloater::default());
+        }
+    }
+}
+
+// What the camera sees plus a margin, zombies never spawn in there
+#[allow(clippy::type_complexity)]
+fn zombie_spawn_exclusion(
+    cam_query: &Query<(&Transform, &OrthographicProjection), (With<MainCamera>, Without<Car>)>,
+    settings: &GameSettings,
+) -> Option<Rect> {
+    let (cam_transform, projection) = cam_query.get_single().ok()?;
+    let mut half_size = projection.area.half_size();
+    if settings.letterbox {
+        half_size = half_size.min(vec2(WW as f32, WH as f32) * projection.scale / 2.0);
+    }
+    Some(Rect::from_center_half_size(
+        cam_transform.translation.truncate(),
+        half_size + ZOMBIE_SPAWN_VIEW_MARGIN,
+    ))
+}
+
+fn is_valid_zombie_spawn(pos: Vec2, view: Option<Rect>, obstacle_grid: &ObstacleGrid) -> bool {
+    // The barricaded start stays clear for the whole run
+    pos.y >= START_GATE_Y
+        && !view.is_some_and(|view| view.contains(pos))
+        && !obstacle_grid.is_climbing(pos)
+}
+
+// Bounds of the areas spawn_zombies picks from, for the debug overlay
+#[cfg(feature = "debug-tools")]
+fn zombie_spawn_zones(
+    car_pos: Vec2,
+    spawn_patterns: &SpawnPatterns,
+    difficulty: Difficulty,
+    progress: f32,
+    finale: Option<FinishLayout>,
+) -> Vec<(Rect, Color)> {
+    let finale_patterns = finale
+        .and_then(|layout| spawn_patterns.finales.get(&layout))
+        .into_iter()
+        .flatten();
+    let mut zones = Vec::new();
+    for pattern in spawn_patterns.patterns.iter().chain(finale_patterns) {
+        // Patterns that haven't started yet are greyed out
+        let color = if progress >= pattern.min_progress.get(difficulty)
+            && pattern.weight.get(difficulty) > 0.0
+        {
+            COLOR_ORANGE
+        } else {
+            Color::GRAY
+        };
+        let (min, max) = (
+            vec2(pattern.x.0, pattern.y.0),
+            vec2(pattern.x.1, pattern.y.1),
+        );
+        zones.push((Rect::from_corners(car_pos + min, car_pos + max), color));
+        if pattern.mirror {
+            let flip = vec2(-1.0, 1.0);
+            zones.push((
+                Rect::from_corners(car_pos + min * flip, car_pos + max * flip),
+                color,
+            ));
+        }
+    }
+    zones
+}
+
+fn cleanup_previous_game(
+    mut commands: Commands,
+    mut road_tiles: ResMut<RoadTiles>,
+    mut obstacles: ResMut<VehicleObstacleTiles>,
+    mut cam_query: Query<&mut OrthographicProjection, With<MainCamera>>,
+    entities: Query<Entity, With<GameEntity>>,
+) {
+    for e in entities.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    road_tiles.0.clear();
+    obstacles.0.clear();
+
+    for mut projection in cam_query.iter_mut() {
+        projection.scale = 1.0;
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn reset_run_state(
+    mut car_health: ResMut<CarHealth>,
+    mut car_armor: ResMut<CarArmor>,
+    mut car_progress: ResMut<CarProgress>,
+    mut run_timer: ResMut<RunTimer>,
+    mut player_score: ResMut<PlayerScore>,
+    mut player_position: ResMut<PlayerPos>,
+    mut on_foot_escape: ResMut<OnFootEscape>,
+    mut zombie_scheduler: ResMut<ZombieScheduler>,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut hit_stop: ResMut<HitStop>,
+    mut spectator: ResMut<Spectator>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut finale: ResMut<Finale>,
+    mut gun_cooldown: ResMut<GunCooldown>,
+    (
+        mut run_stats,
+        mut status_ammo,
+        mut run_rng,
+        mut health_regen,
+        mut auto_fire,
+        mut car_recoil,
+        mut telemetry,
+    ): (
+        ResMut<RunStats>,
+        ResMut<StatusAmmo>,
+        ResMut<RunRng>,
+        ResMut<HealthRegen>,
+        ResMut<AutoFire>,
+        ResMut<CarRecoil>,
+        ResMut<RunTelemetry>,
+    ),
+    (tuning, run_config): (Res<Tuning>, Res<RunConfig>),
+) {
+    car_health.0 = tuning.max_car_health;
+    car_armor.0 = tuning.max_car_armor;
+    car_progress.0 = 0.0;
+    *run_timer = RunTimer::default();
+    player_score.0 = 0;
+    player_position.0 = Vec3::ZERO;
+    *on_foot_escape = OnFootEscape::default();
+    *zombie_scheduler = ZombieScheduler::default();
+    slow_motion.timer = None;
+    hit_stop.0 = None;
+    *spectator = Spectator::default();
+    virtual_time.set_relative_speed(1.0);
+    finale.0 = None;
+    *gun_cooldown = GunCooldown::default();
+    *run_stats = RunStats::default();
+    *status_ammo = StatusAmmo::default();
+    *run_rng = RunRng::new(run_config.seed);
+    *health_regen = HealthRegen::default();
+    *auto_fire = AutoFire::default();
+    *car_recoil = CarRecoil::default();
+    *telemetry = RunTelemetry::default();
+}
+
+#[allow(clippy::type_complexity)]
+fn despawn_zombies(
+    mut commands: Commands,
+    mut scheduler: ResMut<ZombieScheduler>,
+    cam_query: Query<&Transform, (With<MainCamera>, Without<Zombie>)>,
+    zombie_query: Query<(Entity, &Transform, &SpawnOrder), (With<Zombie>, Without<Latched>)>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+) {
+    if cam_query.is_empty() {
+        return;
+    }
+
+    // Over the hard cap, the oldest zombies go first
+    let num_zombies = zombie_query.iter().len();
+    let max_zombies = settings.get_num_max_zombies();
+    if num_zombies > max_zombies {
+        let num_evict = (num_zombies - max_zombies).min(ZOMBIE_DESPAWN_BUDGET);
+        let mut zombies: Vec<(u64, Entity)> = zombie_query
+            .iter()
+            .map(|(e, _, order)| (order.0, e))
+            .collect();
+        zombies.select_nth_unstable(num_evict - 1);
+        for (_, e) in zombies.iter().take(num_evict) {
+            commands.entity(*e).despawn();
+        }
+        return;
+    }
+    // Nothing gets left behind in the fenced arena
+    if run_config.arena {
+        return;
+    }
+
+    // Only a window of the horde is checked each frame. The list is only rebuilt once a
+    // sweep is through it, zombies spawned since then wait for the next sweep
+    let ZombieScheduler {
+        despawn_queue,
+        despawn_cursor,
+        ..
+    } = &mut *scheduler;
+    if *despawn_cursor >= despawn_queue.len() {
+        *despawn_cursor = 0;
+        despawn_queue.clear();
+        despawn_queue.extend(zombie_query.iter().map(|(e, _, _)| e));
+    }
+
+    let cam_pos = cam_query.single().translation;
+    let end = (*despawn_cursor + ZOMBIE_DESPAWN_SCAN_BUDGET).min(despawn_queue.len());
+    let mut num_despawned = 0;
+    for e in despawn_queue[*despawn_cursor..end].iter() {
+        *despawn_cursor += 1;
+        // Killed or latched on since the sweep started
+        let Ok((e, t, _)) = zombie_query.get(*e) else {
+            continue;
+        };
+        let (dx, dy) = (t.translation.x - cam_pos.x, t.translation.y - cam_pos.y);
+        if dx.abs() <= ZOMBIE_DESPAWN_HALF_W
+            && (-ZOMBIE_DESPAWN_BEHIND..=ZOMBIE_DESPAWN_AHEAD).contains(&dy)
+        {
+            continue;
+        }
+
+        commands.entity(e).despawn();
+        num_despawned += 1;
+        if num_despawned >= ZOMBIE_DESPAWN_BUDGET {
+            break;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn bullet_hit_zombie(
+    mut commands: Commands,
+    mut player_score: ResMut<PlayerScore>,
+    mut writer_score_popup: EventWriter<ScorePopupEvent>,
+    mut writer_zombie_killed: EventWriter<ZombieKilledEvent>,
+    mut run_stats: ResMut<RunStats>,
+    bullets_query: Query<
+        (
+            Entity,
+            &Transform,
+            Has<BulletLanded>,
+            Has<DroneShot>,
+            Option<&StatusRound>,
+        ),
+        With<Bullet>,
+    >,
+    mut zombie_query: Query<
+        (
+            Entity,
+            &Transform,
+            &TextureAtlasSprite,
+            Option<&StatusEffect>,
+            Option<&mut Bloater>,
+        ),
+        (With<Zombie>, Without<Bullet>, Without<Latched>),
+    >,
+) {
+    let mut landed = HashSet::new();
+    for (e, t, sprite, status, bloater) in zombie_query.iter_mut() {
+        for (bullet, b, already_landed, is_drone_shot, status_round) in bullets_query.iter() {
+            if (b.translation.x - t.translation.x).abs() <= BULLET_HIT_BOX
+                && (b.translation.y - t.translation.y).abs() <= BULLET_HIT_BOX
+            {
+                // The drone's hits aren't the player's accuracy
+                if !already_landed && !is_drone_shot && landed.insert(bullet) {
+                    run_stats.hits += 1;
+                    commands.entity(bullet).try_insert(BulletLanded);
+                }
+
+                // Special rounds leave the zombie standing, update_status_effects takes it from there
+                if let Some(StatusRound(kind)) = status_round {
+                    let base_color = status.map_or(sprite.color, |status| status.base_color);
+                    commands
+                        .entity(e)
+                        .try_insert(StatusEffect::new(*kind, base_color));
+                    break;
+                }
+
+                // Bloaters swell up first, update_bloaters scores the blast
+                if let Some(mut bloater) = bloater {
+                    if bloater.fuse.is_none() {
+                        bloater.fuse = Some(BLOATER_SWELL_SEC);
+                    }
+                    break;
+                }
+
+                player_score.0 += 1;
+                writer_score_popup.send(ScorePopupEvent {
+                    pos: t.translation,
+                    score: 1,
+                });
+                writer_zombie_killed.send(ZombieKilledEvent { pos: t.translation });
+                commands.entity(e).despawn();
+                break;
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn bullet_hit_nest(
+    mut commands: Commands,
+    mut player_score: ResMut<PlayerScore>,
+    mut writer_score_popup: EventWriter<ScorePopupEvent>,
+    mut writer_hit_stop: EventWriter<HitStopEvent>,
+    bullets_query: Query<(Entity, &Transform, Option<&Ricochet>, Has<DroneShot>), With<Bullet>>,
+    mut nest_query: Query<(Entity, &Transform, &mut ZombieNest), Without<Bullet>>,
+) {
+    let mut spent = HashSet::new();
+    for (e, t, mut nest) in nest_query.iter_mut() {
+        for (b, bt, ricochet, is_drone_shot) in bullets_query.iter() {
+            if (bt.translation.x - t.translation.x).abs() > NEST_HIT_BOX
+                || (bt.translation.y - t.translation.y).abs() > NEST_HIT_BOX
+            {
+                continue;
+            }
+
+            // Nests soak up bullets, unlike zombies, one bullet for one nest
+            if !spent.insert(b) {
+                continue;
+            }
+            commands.entity(b).despawn();
+            nest.health -= bullet_damage(ricochet, is_drone_shot);
+            if nest.health <= 0.0 {
+                player_score.0 += NEST_SCORE_BONUS;
+                writer_score_popup.send(ScorePopupEvent {
+                    pos: t.translation,
+                    score: NEST_SCORE_BONUS,
+                });
+                writer_hit_stop.send(HitStopEvent(Duration::from_millis(HIT_STOP_NEST_MS)));
+                commands.entity(e).despawn();
+                break;
+            }
+        }
+    }
+}
+
+fn setup_boss_rush_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    run_config: Res<RunConfig>,
+) {
+    if !run_config.boss_rush {
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    top: Val::Px(20.0),
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("font.ttf"),
+                        font_size: 35.0,
+                        color: COLOR_LIGHT_ORANGE,
+                    },
+                ),
+                BossRushText,
+            ));
+        });
+}
+
+fn setup_score_arena(
+    mut commands: Commands,
+    mut score_arena: ResMut<ScoreArena>,
+    mut road_tiles: ResMut<RoadTiles>,
+    texture_handle: Res<GlobalTextureHandle>,
+    asset_server: Res<AssetServer>,
+    run_config: Res<RunConfig>,
+) {
+    if !run_config.arena {
+        return;
+    }
+    *score_arena = ScoreArena::default();
+
+    // Asphalt all the way to the fence, the car handles like it does on the road
+    let tile_size = TILE_W as f32 * ROAD_SCALE;
+    let reach = (ARENA_RADIUS / tile_size).ceil() as i32 + 1;
+    let (cx, cy) = (
+        (ARENA_CENTER.x / tile_size).round() as i32,
+        (ARENA_CENTER.y / tile_size).round() as i32,
+    );
+    commands
+        .spawn((SpatialBundle::default(), GameEntity))
+        .with_children(|parent| {
+            for i in (cx - reach)..=(cx + reach) {
+                for j in (cy - reach)..=(cy + reach) {
+                    let pos = vec2(i as f32, j as f32) * tile_size;
+                    if pos.distance(ARENA_CENTER) > ARENA_RADIUS + tile_size {
+                        continue;
+                    }
+                    road_tiles.0.insert((i, j), RoadSurface::Asphalt);
+                    let sprite = RoadSprite {
+                        color: ARENA_FLOOR_COLOR,
+                        ..RoadSprite::new(SpriteId::Road, pos.extend(0.0), ROAD_SCALE)
+                    };
+                    parent.spawn((sprite.bundle(&texture_handle), Road));
+                }
+            }
+        });
+
+    let num_posts = (std::f32::consts::TAU * ARENA_RADIUS / ARENA_FENCE_SPACING) as usize;
+    for k in 0..num_posts {
+        let pos = ARENA_CENTER
+            + Vec2::from_angle(k as f32 / num_posts as f32 * std::f32::consts::TAU) * ARENA_RADIUS;
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(SpriteId::Block.atlas()),
+                sprite: TextureAtlasSprite {
+                    index: SpriteId::Block.index(),
+                    color: COLOR_BROWN,
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec3::splat(2.5))
+                    .with_translation(pos.extend(5.0)),
+                ..default()
+            },
+            GameEntity,
+        ));
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    top: Val::Px(20.0),
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("font.ttf"),
+                        font_size: 35.0,
+                        color: COLOR_LIGHT_ORANGE,
+                    },
+                ),
+                ScoreArenaText,
+            ));
+        });
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_score_arena(
+    mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    time: Res<Time>,
+    mut score_arena: ResMut<ScoreArena>,
+    mut player_score: ResMut<PlayerScore>,
+    mut scheduler: ResMut<ZombieScheduler>,
+    texture_handle: Res<GlobalTextureHandle>,
+    countdown: Res<StartCountdown>,
+    settings: Res<GameSettings>,
+    quality: Res<AdaptiveQuality>,
+    profile: Res<Profile>,
+    mut car_query: Query<(&mut Transform, Option<&mut Speed>, Option<&HeldWeapon>), With<Car>>,
+    zombie_query: Query<With<Zombie>>,
+    mut text_query: Query<&mut Text, With<ScoreArenaText>>,
+) {
+    let Ok((mut car_transform, speed, held_weapon)) = car_query.get_single_mut() else {
+        return;
+    };
+
+    // The fence is solid, same bounce as the boss arena
+    let offset = car_transform.translation.truncate() - ARENA_CENTER;
+    if offset.length() > ARENA_RADIUS {
+        let pos = ARENA_CENTER + offset.clamp_length_max(ARENA_RADIUS);
+        car_transform.translation = pos.extend(car_transform.translation.z);
+        if let Some(mut speed) = speed {
+            speed.0 = -6.0;
+        }
+    }
+
+    if countdown.0.elapsed_secs() < START_COUNTDOWN_SEC {
+        return;
+    }
+
+    // Points for every full second survived, kills score as usual
+    let prev = score_arena.survived;
+    score_arena.survived += time.delta_seconds();
+    player_score.0 += (score_arena.survived.floor() - prev.floor()) as u32 * ARENA_SCORE_PER_SEC;
+
+    score_arena.wave_timer.tick(time.delta());
+    if score_arena.wave == 0 || score_arena.wave_timer.elapsed_secs() >= ARENA_WAVE_INTERVAL_SEC {
+        score_arena.wave_timer.reset();
+        score_arena.wave += 1;
+
+        // Waves keep growing, only the entity count setting caps them
+        let wave = score_arena.wave;
+        let num_zombies = zombie_query.iter().len();
+        let num_to_spawn = (ARENA_WAVE_BASE + ARENA_WAVE_GROWTH * (wave - 1))
+            .min(quality.max_zombies(&settings).saturating_sub(num_zombies));
+        let rng = run_rng.stream(RngStream::ZombieSpawns);
+        for _ in 0..num_to_spawn {
+            // Climbing in over the fence
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let pos = ARENA_CENTER + Vec2::from_angle(angle) * ARENA_RADIUS * 0.95;
+            let (tile, scale) = if wave >= ARENA_BIG_ZOMBIE_WAVE && rng.gen_bool(0.1) {
+                (SpriteId::BigZombie.random_index(rng), 3.2)
+            } else {
+                (SpriteId::Zombie.random_index(rng), 2.5)
+            };
+            commands.spawn((
+                SpriteSheetBundle {
+                    texture_atlas: texture_handle.atlas(SpriteId::Zombie.atlas()),
+                    sprite: TextureAtlasSprite::new(tile),
+                    transform: Transform::from_scale(Vec3::splat(scale))
+                        .with_translation(pos.extend(1.0)),
+                    ..default()
+                },
+                Zombie,
+                ZombieAi {
+                    state: ZombieState::Chase,
+                    ..default()
+                },
+                ZombieAttack::new(scale),
+                scheduler.next_spawn_order(),
+                GameEntity,
+            ));
+        }
+    }
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        let weapon = held_weapon.map_or(profile.weapon, |held| held.0);
+        text.sections[0].value = format!(
+            "Wave {} - {} - {}",
+            score_arena.wave,
+            RunTimer::format(score_arena.survived),
+            weapon.as_str()
+        );
+    }
+}
+
+fn update_weapon_pickups(
+    mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    time: Res<Time>,
+    mut score_arena: ResMut<ScoreArena>,
+    texture_handle: Res<GlobalTextureHandle>,
+    car_query: Query<(Entity, &Transform), With<Car>>,
+    pickup_query: Query<(Entity, &Transform, &WeaponPickup), Without<Car>>,
+) {
+    let Ok((car, car_transform)) = car_query.get_single() else {
+        return;
+    };
+
+    let car_pos = car_transform.translation.truncate();
+    for (e, t, pickup) in pickup_query.iter() {
+        if t.translation.truncate().distance(car_pos) <= PICKUP_RADIUS {
+            commands.entity(e).despawn();
+            commands.entity(car).insert(HeldWeapon(pickup.0));
+        }
+    }
+
+    // One drop out at a time, the weapons come around in order
+    score_arena.pickup_timer.tick(time.delta());
+    if !pickup_query.is_empty()
+        || score_arena.pickup_timer.elapsed_secs() < ARENA_PICKUP_INTERVAL_SEC
+    {
+        return;
+    }
+    score_arena.pickup_timer.reset();
+
+    let weapon = ARENA_WEAPONS[score_arena.next_weapon % ARENA_WEAPONS.len()];
+    score_arena.next_weapon += 1;
+    let rng = run_rng.stream(RngStream::Loot);
+    let pos = ARENA_CENTER
+        + Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU))
+            * rng.gen_range(0.0..ARENA_RADIUS * 0.6);
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: texture_handle.atlas(SpriteId::Block.atlas()),
+            sprite: TextureAtlasSprite {
+                index: SpriteId::Block.index(),
+                color: COLOR_ORANGE,
+                ..default()
+            },
+            transform: Transform::from_scale(Vec3::splat(2.5)).with_translation(pos.extend(1.0)),
+            ..default()
+        },
+        WeaponPickup(weapon),
+        GameEntity,
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_boss_arena(
+    mut commands: Commands,
+    time: Res<Time>,
+    run_config: Res<RunConfig>,
+    texture_handle: Res<GlobalTextureHandle>,
+    mut boss_arena: ResMut<BossArena>,
+    mut car_query: Query<(&mut Transform, Option<&mut Speed>), With<Car>>,
+    mut fence_query: Query<(&ArenaFence, &mut Transform, &mut Visibility), Without<Car>>,
+    difficulty: Res<DifficultyProfile>,
+) {
+    // The arena only exists once the road has streamed in far enough
+    if !run_config.boss_rush || boss_arena.half_size <= 0.0 {
+        return;
+    }
+    let Ok((mut car_transform, speed)) = car_query.get_single_mut() else {
+        return;
+    };
+
+    let arena = &mut *boss_arena;
+    match &mut arena.state {
+        BossRushState::Entering => {
+            let car_pos = car_transform.translation.truncate();
+            if car_pos.y < arena.center.y - arena.half_size + 150.0 {
+                return;
+            }
+
+            // The gate slams shut behind the car
+            arena.state = BossRushState::Fighting;
+            for (fence, _, mut visibility) in fence_query.iter_mut() {
+                if fence.side == 0 {
+                    *visibility = Visibility::Inherited;
+                }
+            }
+            spawn_boss(&mut commands, &texture_handle, arena, &difficulty);
+        }
+        BossRushState::Intermission(timer) => {
+            timer.tick(time.delta());
+            if timer.elapsed_secs() >= BOSS_INTERMISSION_SEC {
+                arena.state = BossRushState::Fighting;
+                spawn_boss(&mut commands, &texture_handle, arena, &difficulty);
+            }
+        }
+        BossRushState::Fighting | BossRushState::Done => {}
+    }
+
+    arena.half_size = (arena.half_size - BOSS_ARENA_CLOSE_SPEED * time.delta_seconds())
+        .max(arena.target_half_size);
+    for (fence, mut transform, _) in fence_query.iter_mut() {
+        let along = fence.t * arena.half_size;
+        let offset = match fence.side {
+            0 => vec2(along, -arena.half_size),
+            1 => vec2(arena.half_size, along),
+            2 => vec2(along, arena.half_size),
+            _ => vec2(-arena.half_size, along),
+        };
+        transform.translation = (arena.center + offset).extend(5.0);
+    }
+
+    // Fences are solid, same bounce as hitting a parked car
+    let min = arena.center - Vec2::splat(arena.half_size);
+    let max = arena.center + Vec2::splat(arena.half_size);
+    let pos = car_transform.translation.truncate();
+    let clamped = pos.clamp(min, max);
+    if clamped != pos {
+        car_transform.translation = clamped.extend(car_transform.translation.z);
+        if let Some(mut speed) = speed {
+            speed.0 = -6.0;
+        }
+    }
+}
+
+fn spawn_boss(
+    commands: &mut Commands,
+    texture_handle: &GlobalTextureHandle,
+    arena: &BossArena,
+    difficulty: &DifficultyProfile,
+) {
+    let health = BOSS_BASE_HEALTH * difficulty.boss_health * (1.0 + 0.5 * arena.boss as f32);
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: texture_handle.atlas(SpriteId::BigZombie.atlas()),
+            sprite: TextureAtlasSprite {
+                index: SpriteId::BigZombie.index() + arena.boss % SpriteId::BigZombie.variants(),
+                color: BOSS_COLORS[arena.boss % BOSS_COUNT],
+                ..default()
+            },
+            transform: Transform::from_scale(Vec3::splat(8.0))
+                .with_translation((arena.center + vec2(0.0, arena.half_size * 0.6)).extend(9.0)),
+            ..default()
+        },
+        Boss {
+            health,
+            max_health: health,
+            speed: BOSS_BASE_SPEED + 40.0 * arena.boss as f32,
+            attack_timer: Stopwatch::new(),
+        },
+        GameEntity,
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+fn update_bosses(
+    time: Res<Time>,
+    boss_arena: Res<BossArena>,
+    mut boss_query: Query<(&mut Transform, &mut Boss, &mut TextureAtlasSprite)>,
+    mut car_query: Query<(&Transform, Option<&mut Speed>), (With<Car>, Without<Boss>)>,
+    mut writer_player_hit: EventWriter<ZombieHitPlayer>,
+    mut writer_hit_stop: EventWriter<HitStopEvent>,
+) {
+    let Ok((car_transform, mut speed)) = car_query.get_single_mut() else {
+        return;
+    };
+    let car_pos = car_transform.translation.truncate();
+
+    for (mut transform, mut boss, mut sprite) in boss_query.iter_mut() {
+        boss.attack_timer.tick(time.delta());
+        let pos = transform.translation.truncate();
+        let to_car = car_pos - pos;
+        sprite.flip_x = to_car.x < 0.0;
+
+        if to_car.length() > BOSS_HIT_BOX {
+            let step = to_car.normalize() * boss.speed * time.delta_seconds();
+            transform.translation += step.extend(0.0);
+            continue;
+        }
+
+        // Ramming at speed is the big damage, the boss gets shoved back down the arena
+        let car_speed = speed.as_ref().map_or(0.0, |s| s.0);
+        if car_speed > BOSS_RAM_MIN_SPEED {
+            boss.health -= car_speed * BOSS_RAM_DAMAGE_PER_SPEED;
+            let knockback = (car_transform.rotation * Vec3::Y).truncate() * BOSS_KNOCKBACK;
+            let min = boss_arena.center - Vec2::splat(boss_arena.half_size);
+            let max = boss_arena.center + Vec2::splat(boss_arena.half_size);
+            let new_pos = (pos + knockback).clamp(min, max);
+            transform.translation = new_pos.extend(transform.translation.z);
+            if let Some(speed) = speed.as_mut() {
+                speed.0 = -10.0;
+            }
+            writer_hit_stop.send(HitStopEvent(Duration::from_millis(HIT_STOP_OBSTACLE_MS)));
+        } else if boss.attack_timer.elapsed_secs() >= BOSS_ATTACK_INTERVAL_SEC {
+            boss.attack_timer.reset();
+            writer_player_hit
+                .send_batch((0..BOSS_ATTACK_HITS).map(|_| ZombieHitPlayer { from: pos }));
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn bullet_hit_boss(
+    mut commands: Commands,
+    bullets_query: Query<(Entity, &Transform, Option<&Ricochet>, Has<DroneShot>), With<Bullet>>,
+    mut boss_query: Query<(&Transform, &mut Boss), Without<Bullet>>,
+) {
+    let mut spent = HashSet::new();
+    for (t, mut boss) in boss_query.iter_mut() {
+        for (b, bt, ricochet, is_drone_shot) in bullets_query.iter() {
+            if bt.translation.truncate().distance(t.translation.truncate()) > BOSS_HIT_BOX
+                || !spent.insert(b)
+            {
+                continue;
+            }
+
+            commands.entity(b).despawn();
+            boss.health -= bullet_damage(ricochet, is_drone_shot);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_boss_defeated(
+    mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    mut boss_arena: ResMut<BossArena>,
+    mut player_score: ResMut<PlayerScore>,
+    mut car_progress: ResMut<CarProgress>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut writer_score_popup: EventWriter<ScorePopupEvent>,
+    mut writer_hit_stop: EventWriter<HitStopEvent>,
+    texture_handle: Res<GlobalTextureHandle>,
+    boss_query: Query<(Entity, &Transform, &Boss)>,
+) {
+    let rng = run_rng.stream(RngStream::Loot);
+    for (e, t, boss) in boss_query.iter() {
+        if boss.health > 0.0 {
+            continue;
+        }
+
+        commands.entity(e).despawn();
+        boss_arena.boss += 1;
+        let score = BOSS_SCORE_BONUS * boss_arena.boss as u32;
+        player_score.0 += score;
+        writer_score_popup.send(ScorePopupEvent {
+            pos: t.translation,
+            score,
+        });
+        writer_hit_stop.send(HitStopEvent(Duration::from_millis(HIT_STOP_NEST_MS * 2)));
+
+        if boss_arena.boss >= BOSS_COUNT {
+            boss_arena.state = BossRushState::Done;
+            car_progress.0 = 1.0;
+            game_state.set(GameState::GameOver);
+            return;
+        }
+
+        // A breather before the next one, with supplies dropped inside the new fence line
+        boss_arena.state = BossRushState::Intermission(Stopwatch::new());
+        boss_arena.target_half_size *= BOSS_ARENA_SHRINK;
+        let spread = boss_arena.target_half_size * 0.7;
+        for is_armor in [true, false] {
+            let pos = boss_arena.center
+                + vec2(
+                    rng.gen_range(-spread..spread),
+                    rng.gen_range(-spread..spread),
+                );
+            let mut pickup = commands.spawn((
+                SpriteSheetBundle {
+                    texture_atlas: texture_handle.atlas(SpriteId::Block.atlas()),
+                    sprite: TextureAtlasSprite {
+                        index: SpriteId::Block.index(),
+                        color: if is_armor {
+                            Color::WHITE
+                        } else {
+                            Color::rgb(0.55, 0.75, 1.0)
+                        },
+                        ..default()
+                    },
+                    transform: Transform::from_scale(Vec3::splat(2.0))
+                        .with_translation(pos.extend(1.0)),
+                    ..default()
+                },
+                GameEntity,
+            ));
+            if is_armor {
+                pickup.insert(ArmorPickup);
+            } else {
+                pickup.insert(SlowMoPickup);
+            }
+        }
+    }
+}
+
+fn update_boss_rush_ui(
+    boss_arena: Res<BossArena>,
+    boss_query: Query<&Boss>,
+    mut text_query: Query<&mut Text, With<BossRushText>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let round = (boss_arena.boss + 1).min(BOSS_COUNT);
+    text.sections[0].value = match &boss_arena.state {
+        BossRushState::Entering => "Drive into the arena".to_string(),
+        BossRushState::Fighting => match boss_query.get_single() {
+            Ok(boss) => format!(
+                "Boss {round}/{BOSS_COUNT} - {:.0}%",
+                (boss.health / boss.max_health).max(0.0) * 100.0
+            ),
+            Err(_) => String::new(),
+        },
+        BossRushState::Intermission(timer) => format!(
+            "Boss {round}/{BOSS_COUNT} in {:.0}",
+            (BOSS_INTERMISSION_SEC - timer.elapsed_secs()).ceil()
+        ),
+        BossRushState::Done => String::new(),
+    };
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_nest_zombies(
+    mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    mut scheduler: ResMut<ZombieScheduler>,
+    time: Res<Time>,
+    texture_handle: Res<GlobalTextureHandle>,
+    cam_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    mut nest_query: Query<(&Transform, &mut ZombieNest), Without<MainCamera>>,
+    zombie_query: Query<With<Zombie>>,
+    settings: Res<GameSettings>,
+    quality: Res<AdaptiveQuality>,
+    tutorial: Res<Tutorial>,
+    tuning: Res<Tuning>,
+) {
+    if cam_query.is_empty() || tutorial.0.is_some() {
+        return;
+    }
+
+    let (cam_transform, projection) = cam_query.single();
+    let mut half_size = projection.area.half_size();
+    if settings.letterbox {
+        // The rest of the projection is behind the letterbox bars
+        half_size = half_size.min(vec2(WW as f32, WH as f32) * projection.scale / 2.0);
+    }
+    let (half_w, half_h) = (half_size.x, half_size.y);
+    let mut num_zombies = zombie_query.iter().len();
+    let max_zombies = quality.max_zombies(&settings);
+    let rng = run_rng.stream(RngStream::ZombieSpawns);
+
+    for (t, mut nest) in nest_query.iter_mut() {
+        // Nests only wake up while on screen
+        if (t.translation.x - cam_transform.translation.x).abs() > half_w
+            || (t.translation.y - cam_transform.translation.y).abs() > half_h
+        {
+            continue;
+        }
+
+        nest.spawn_timer.tick(time.delta());
+        if nest.spawn_timer.elapsed_secs() < tuning.nest_spawn_interval_sec {
+            continue;
+        }
+        nest.spawn_timer.reset();
+
+        for _ in 0..tuning.nest_spawn_count {
+            if num_zombies >= max_zombies {
+                return;
+            }
+
+            let (x, y) = (
+                t.translation.x + rng.gen_range(-30.0..30.0),
+                t.translation.y + rng.gen_range(-30.0..30.0),
+            );
+            commands.spawn((
+                SpriteSheetBundle {
+                    texture_atlas: texture_handle.atlas(SpriteId::Zombie.atlas()),
+                    sprite: TextureAtlasSprite::new(SpriteId::Zombie.random_index(rng)),
+                    transform: Transform::from_scale(Vec3::splat(2.5))
+                        .with_translation(vec3(x, y, 1.0)),
+                    ..default()
+                },
+                Zombie,
+                // Nests spawn an already riled up horde
+                ZombieAi {
+                    state: ZombieState::Chase,
+                    ..default()
+                },
+                ZombieAttack::new(2.5),
+                scheduler.next_spawn_order(),
+                GameEntity,
+            ));
+            num_zombies += 1;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn check_zombie_collision(
+    mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    time: Res<Time>,
+    mut zombie_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut ZombieAttack,
+            Has<Culled>,
+            Has<TutorialDummy>,
+            Option<&StatusEffect>,
+        ),
+        (With<Zombie>, Without<Latched>),
+    >,
+    car_query: Query<
+        (Entity, &Transform, Option<&Children>, Has<Speed>),
+        (With<Car>, Without<Zombie>, Without<Airborne>),
+    >,
+    latched_query: Query<With<Latched>>,
+    mut writer_player_hit: EventWriter<ZombieHitPlayer>,
+    tuning: Res<Tuning>,
+) {
+    let car = car_query.get_single().ok();
+    let car_pos = car.map(|(_, t, ..)| t.translation);
+    let mut num_latched = car
+        .and_then(|(_, _, children, _)| children)
+        .map_or(0, |children| {
+            children
+                .iter()
+                .filter(|child| latched_query.contains(**child))
+                .count()
+        });
+    let rng = run_rng.stream(RngStream::Ai);
+    let dt = time.delta_seconds();
+    for (e, mut t, mut attack, is_culled, is_dummy, status) in zombie_query.iter_mut() {
+        attack.cooldown = (attack.cooldown - dt).max(0.0);
+        if attack.bite > 0.0 {
+            // Quick swell and back, peaking halfway through the bite
+            attack.bite = (attack.bite - dt).max(0.0);
+            if is_culled {
+                attack.bite = 0.0;
+                t.scale = Vec3::splat(attack.base_scale);
+                continue;
+            }
+            let progress = 1.0 - attack.bite / ZOMBIE_BITE_ANIM_SEC;
+            let swell = (progress * std::f32::consts::PI).sin() * ZOMBIE_BITE_SCALE;
+            t.scale = Vec3::splat(attack.base_scale * (1.0 + swell));
+        }
+
+        let Some(car_pos) = car_pos else {
+            continue;
+        };
+        if status.is_some_and(|status| status.kind.holds_zombie()) {
+            continue;
+        }
+        let y_dist = (t.translation.y - car_pos.y).abs();
+        let x_dist = (t.translation.x - car_pos.x).abs();
+        if x_dist <= 20.0 && y_dist <= 20.0 && attack.cooldown <= 0.0 {
+            attack.cooldown = ZOMBIE_BITE_COOLDOWN_SEC;
+            let from = t.translation.truncate();
+            writer_player_hit
+                .send_batch((0..tuning.zombie_bite_hits).map(|_| ZombieHitPlayer { from }));
+
+            // Only vehicles can be grabbed, not the survivor on foot
+            let Some((car, car_transform, _, true)) = car else {
+                attack.bite = ZOMBIE_BITE_ANIM_SEC;
+                continue;
+            };
+            if is_dummy || num_latched >= GRAB_MAX_LATCHED || !rng.gen_bool(GRAB_CHANCE) {
+                attack.bite = ZOMBIE_BITE_ANIM_SEC;
+                continue;
+            }
+
+            // The car is scaled up, the zombie keeps its size once parented
+            num_latched += 1;
+            let offset = car_transform.rotation.inverse() * (t.translation - car_pos);
+            *t = Transform::from_scale(Vec3::splat(attack.base_scale) / car_transform.scale)
+                .with_translation((offset / car_transform.scale).truncate().extend(0.1));
+            commands.entity(e).insert(Latched).set_parent(car);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_zombie_dogs(
+    time: Res<Time>,
+    car_query: Query<&Transform, (With<Car>, Without<Zombie>)>,
+    mut dog_query: Query<
+        (
+            &mut Transform,
+            &mut TextureAtlasSprite,
+            &mut ZombieDog,
+            &ZombieAi,
+            &ZombieAttack,
+            Option<&StatusEffect>,
+        ),
+        (With<Zombie>, Without<Latched>),
+    >,
+    tuning: Res<Tuning>,
+    difficulty: Res<DifficultyProfile>,
+) {
+    let Ok(car_transform) = car_query.get_single() else {
+        return;
+    };
+
+    let car_pos = car_transform.translation.truncate();
+    let dt = time.delta_seconds();
+    for (mut t, mut sprite, mut dog, ai, attack, status) in dog_query.iter_mut() {
+        dog.cooldown = (dog.cooldown - dt).max(0.0);
+        if status.is_some_and(|status| status.kind.holds_zombie()) {
+            continue;
+        }
+
+        let pos = t.translation.truncate();
+        if dog.windup > 0.0 {
+            dog.windup = (dog.windup - dt).max(0.0);
+            let crouched = dog.windup > 0.0;
+            if status.is_none() {
+                let flash = crouched && (dog.windup * 30.0).sin() > 0.0;
+                sprite.color = if flash {
+                    DOG_WINDUP_COLOR
+                } else {
+                    Color::WHITE
+                };
+            }
+            t.scale.y = if crouched {
+                attack.base_scale * (1.0 - DOG_WINDUP_SQUASH)
+            } else {
+                attack.base_scale
+            };
+            if !crouched {
+                dog.lunge = DOG_LUNGE_SEC;
+            }
+        } else if dog.lunge > 0.0 {
+            dog.lunge = (dog.lunge - dt).max(0.0);
+            t.translation += (dog.lunge_dir * DOG_LUNGE_SPEED * dt).extend(0.0);
+            if dog.lunge <= 0.0 {
+                dog.cooldown = DOG_LUNGE_COOLDOWN_SEC;
+            }
+        } else if ai.state == ZombieState::Chase
+            && dog.cooldown <= 0.0
+            && pos.distance_squared(car_pos) <= DOG_LUNGE_RANGE.powi(2)
+        {
+            dog.windup = tuning.dog_windup_sec * difficulty.dog_windup;
+            dog.lunge_dir = (car_pos - pos).normalize_or_zero();
+            sprite.flip_x = car_pos.x < pos.x;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_latched_zombies(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shake: Local<f32>,
+    obstacles: Res<VehicleObstacleTiles>,
+    car_query: Query<(&Transform, &TurnSpeed, &Speed, &Children), With<Car>>,
+    mut latched_query: Query<
+        (Entity, &mut Transform, &mut ZombieAttack),
+        (With<Latched>, Without<Car>),
+    >,
+    tuning: Res<Tuning>,
+) {
+    let Ok((car_transform, turn_speed, speed, children)) = car_query.get_single() else {
+        return;
+    };
+
+    // Swerving hard builds up the shake, driving straight lets it settle
+    let dt = time.delta_seconds();
+    let is_fast = speed.0.abs() >= GRAB_SHAKE_MIN_SPEED;
+    if is_fast && turn_speed.0.abs() >= tuning.turn_speed * GRAB_SWERVE_TURN_FRACTION {
+        *shake += dt;
+    } else {
+        *shake = (*shake - dt).max(0.0);
+    }
+
+    let car_pos = car_transform.translation;
+    let is_scraping = is_fast
+        && obstacles.0.iter().any(|obstacle| {
+            (obstacle.pos.0 - car_pos.x).abs() <= GRAB_SCRAPE_DISTANCE
+                && (obstacle.pos.1 - car_pos.y).abs() <= GRAB_SCRAPE_DISTANCE
+        });
+    let num_shaken = if is_scraping {
+        usize::MAX
+    } else if *shake >= GRAB_SHAKE_SEC {
+        *shake = 0.0;
+        1
+    } else {
+        0
+    };
+
+    let mut latched = latched_query.iter_many_mut(children);
+    let mut num_flung = 0;
+    while let Some((e, mut t, mut attack)) = latched.fetch_next() {
+        if num_flung >= num_shaken {
+            break;
+        }
+
+        // Back into world space, thrown clear of the car
+        num_flung += 1;
+        let pos = car_transform.mul_transform(*t).translation;
+        let away = (pos - car_pos).truncate().normalize_or_zero();
+        let pos = pos.truncate() + away * GRAB_FLING_DISTANCE;
+        *t =
+            Transform::from_scale(Vec3::splat(attack.base_scale)).with_translation(pos.extend(1.0));
+        attack.cooldown = GRAB_REGRAB_COOLDOWN_SEC;
+        commands.entity(e).remove::<Latched>().remove_parent();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_spitters(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    texture_handle: Res<GlobalTextureHandle>,
+    mut warning_sound: Local<Option<Handle<Pitch>>>,
+    mut pitch_assets: ResMut<Assets<Pitch>>,
+    mut spitter_query: Query<
+        (&Transform, &ZombieAi, &mut Spitter, Option<&StatusEffect>),
+        Without<Latched>,
+    >,
+    car_query: Query<&Transform, (With<Car>, Without<Spitter>)>,
+) {
+    let Ok(car_transform) = car_query.get_single() else {
+        return;
+    };
+    let car_pos = car_transform.translation.truncate();
+
+    let mut num_spat = 0;
+    for (transform, ai, mut spitter, status) in spitter_query.iter_mut() {
+        spitter.0.tick(time.delta());
+        let pos = transform.translation.truncate();
+        if ai.state != ZombieState::Chase
+            || status.is_some_and(|status| status.kind.holds_zombie())
+            || spitter.0.elapsed_secs() < SPITTER_COOLDOWN_SEC
+            || pos.distance_squared(car_pos) > SPITTER_RANGE.powi(2)
+        {
+            continue;
+        }
+
+        spitter.0.reset();
+        num_spat += 1;
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(SpriteId::Bullet.atlas()),
+                sprite: TextureAtlasSprite {
+                    index: SpriteId::Bullet.index(),
+                    color: ACID_COLOR.with_a(1.0),
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec3::splat(3.0))
+                    .with_translation(pos.extend(16.0)),
+                ..default()
+            },
+            AcidGlob {
+                origin: pos,
+                target: car_pos,
+                flight: Stopwatch::new(),
+            },
+            GameEntity,
+        ));
+    }
+
+    // A wet gurgle rising in pitch, the cue to get off the line of fire
+    if num_spat > 0 && settings.music {
+        let source = warning_sound
+            .get_or_insert_with(|| pitch_assets.add(Pitch::new(620.0, Duration::from_millis(120))))
+            .clone();
+        commands.spawn(PitchBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_absolute(0.08)),
+        });
+    }
+}
+
+fn update_acid_globs(
+    mut commands: Commands,
+    time: Res<Time>,
+    texture_handle: Res<GlobalTextureHandle>,
+    mut glob_query: Query<(Entity, &mut AcidGlob, &mut Transform)>,
+    car_query: Query<&Transform, (With<Car>, Without<AcidGlob>)>,
+    mut writer_player_hit: EventWriter<ZombieHitPlayer>,
+) {
+    let car_pos = car_query
+        .get_single()
+        .ok()
+        .map(|t| t.translation.truncate());
+    for (e, mut glob, mut transform) in glob_query.iter_mut() {
+        glob.flight.tick(time.delta());
+        let t = (glob.flight.elapsed_secs() / ACID_GLOB_FLIGHT_SEC).min(1.0);
+
+        // Flat ground track plus a parabola on top, the glob swells as it gets higher
+        let height = 4.0 * ACID_GLOB_ARC_HEIGHT * t * (1.0 - t);
+        let ground = glob.origin.lerp(glob.target, t);
+        transform.translation = (ground + vec2(0.0, height)).extend(16.0);
+        transform.scale = Vec3::splat(3.0 + height / ACID_GLOB_ARC_HEIGHT * 2.0);
+        if t < 1.0 {
+            continue;
+        }
+
+        commands.entity(e).despawn();
+        if car_pos.is_some_and(|p| p.distance(glob.target) <= ACID_GLOB_HIT_BOX) {
+            // Points back at the spitter
+            writer_player_hit
+                .send_batch((0..ACID_GLOB_HITS).map(|_| ZombieHitPlayer { from: glob.origin }));
+        }
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(SpriteId::Block.atlas()),
+                sprite: TextureAtlasSprite {
+                    index: SpriteId::Block.index(),
+                    color: ACID_COLOR,
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec3::splat(ACID_POOL_RADIUS * 2.0 / 16.0))
+                    .with_translation(glob.target.extend(2.0)),
+                ..default()
+            },
+            AcidPool::default(),
+            GameEntity,
+        ));
+    }
+}
+
+fn update_acid_pools(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool_query: Query<(Entity, &mut AcidPool, &Transform, &mut TextureAtlasSprite)>,
+    car_query: Query<&Transform, (With<Car>, Without<AcidPool>)>,
+    mut writer_player_hit: EventWriter<ZombieHitPlayer>,
+) {
+    let car_pos = car_query
+        .get_single()
+        .ok()
+        .map(|t| t.translation.truncate());
+    for (e, mut pool, transform, mut sprite) in pool_query.iter_mut() {
+        pool.lifetime.tick(time.delta());
+        pool.tick.tick(time.delta());
+        let life_left = 1.0 - pool.lifetime.elapsed_secs() / ACID_POOL_LIFETIME_SEC;
+        if life_left <= 0.0 {
+            commands.entity(e).despawn();
+            continue;
+        }
+        sprite.color = ACID_COLOR.with_a(ACID_COLOR.a() * life_left.min(1.0));
+
+        let in_pool = car_pos.is_some_and(|p| {
+            p.distance_squared(transform.translation.truncate()) <= ACID_POOL_RADIUS.powi(2)
+        });
+        if in_pool && pool.tick.elapsed_secs() >= ACID_POOL_TICK_SEC {
+            pool.tick.reset();
+            writer_player_hit.send(ZombieHitPlayer {
+                from: transform.translation.truncate(),
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_zombies(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut frame: Local<u32>,
+    // Noises stick around until every zombie had its turn to hear them
+    mut recent_noises: Local<Vec<(NoiseEvent, u32)>>,
+    mut groan: Local<(Stopwatch, Option<Handle<Pitch>>)>,
+    mut reader_noise: EventReader<NoiseEvent>,
+    mut pitch_assets: ResMut<Assets<Pitch>>,
+    settings: Res<GameSettings>,
+    quality: Res<AdaptiveQuality>,
+    run_config: Res<RunConfig>,
+    // Whatever is in the horde's way
+    (pile_grid, obstacle_grid): (Res<CorpsePileGrid>, Res<ObstacleGrid>),
+    mut zombie_query: Query<
+        (
+            &mut Transform,
+            &mut ZombieAi,
+            &mut TextureAtlasSprite,
+            Option<&StatusEffect>,
+            Option<&ZombieDog>,
+        ),
+        (With<Zombie>, Without<TutorialDummy>, Without<Latched>),
+    >,
+    car_query: Query<(&Transform, Option<&Speed>), (With<Car>, Without<Zombie>)>,
+    remote_car_query: Query<&Transform, (With<RemoteCar>, Without<Wreck>, Without<Zombie>)>,
+    mut run_rng: ResMut<RunRng>,
+    (tuning, difficulty): (Res<Tuning>, Res<DifficultyProfile>),
+) {
+    // Co-op teammates share the horde, each zombie goes for the closest car
+    let car = car_query.get_single().ok();
+    let targets = car
+        .map(|(t, _)| t)
+        .into_iter()
+        .chain(remote_car_query.iter())
+        .map(|t| t.translation.truncate())
+        .collect::<Vec<_>>();
+    if targets.is_empty() {
+        return;
+    }
+
+    let rng = run_rng.stream(RngStream::Ai);
+
+    // Under load each zombie only thinks every few frames, taking a bigger step when it does
+    let stride = quality.ai_stride();
+    *frame = frame.wrapping_add(1);
+    let speed_factor = if run_config.has(Modifier::DoubleSpeedZombies) {
+        2.0
+    } else {
+        1.0
+    };
+    let dt = time.delta_seconds() * stride as f32;
+    let zombie_step = tuning.zombie_speed * difficulty.zombie_speed * speed_factor * dt;
+    let dog_step = tuning.dog_speed * difficulty.dog_speed * speed_factor * dt;
+
+    recent_noises.retain_mut(|(_, frames_left)| {
+        *frames_left = frames_left.saturating_sub(1);
+        *frames_left > 0
+    });
+    recent_noises.extend(reader_noise.read().map(|ev| {
+        (
+            NoiseEvent {
+                pos: ev.pos,
+                radius: ev.radius,
+            },
+            stride,
+        )
+    }));
+
+    // Slow and quiet driving lets the car slip past idle zombies
+    let car_speed = car.and_then(|(_, s)| s).map_or(0.0, |s| s.0.abs());
+    let loudness = (car_speed / ZOMBIE_HEAR_FULL_SPEED).min(1.0).powi(2);
+    let engine_radius = difficulty.engine_hearing
+        * (ZOMBIE_HEAR_ENGINE_BASE
+            + (ZOMBIE_HEAR_ENGINE_FULL - ZOMBIE_HEAR_ENGINE_BASE) * loudness);
+    let (chase_radius, lose_radius) = (
+        ZOMBIE_CHASE_RADIUS * difficulty.sight_range,
+        ZOMBIE_LOSE_RADIUS * difficulty.sight_range,
+    );
+
+    let glide_progress = |ai: &ZombieAi| {
+        if ai.glide_sec > 0.0 {
+            (ai.glide_elapsed / ai.glide_sec).min(1.0)
+        } else {
+            1.0
+        }
+    };
+
+    let mut alerted = Vec::new();
+    for (idx, (mut z, mut ai, mut sprite, status, dog)) in zombie_query.iter_mut().enumerate() {
+        // Mid lunge the dog moves on its own, see update_zombie_dogs
+        let is_held = status.is_some_and(|status| status.kind.holds_zombie())
+            || dog.is_some_and(|dog| dog.windup > 0.0 || dog.lunge > 0.0);
+        if idx as u32 % stride != *frame % stride {
+            // Glide between turns, a whole stride's step at once stutters at high refresh rates.
+            // Only the change in progress is applied so pushes from elsewhere are kept
+            if !is_held {
+                let before = glide_progress(&ai);
+                ai.glide_elapsed += time.delta_seconds();
+                let moved = (ai.glide_to - ai.glide_from) * (glide_progress(&ai) - before);
+                z.translation += moved.extend(0.0);
+            }
+            continue;
+        }
+        if is_held {
+            ai.glide_sec = 0.0;
+            continue;
+        }
+        let step = if dog.is_some() { dog_step } else { zombie_step };
+
+        let pos = z.translation.truncate();
+        let car_pos = targets
+            .iter()
+            .copied()
+            .min_by(|a, b| a.distance_squared(pos).total_cmp(&b.distance_squared(pos)))
+            .unwrap();
+        let (car_x, car_y) = (car_pos.x, car_pos.y);
+        let car_dist_sq = pos.distance_squared(car_pos);
+        let heard = if car_dist_sq <= engine_radius.powi(2) {
+            Some(car_pos)
+        } else {
+            recent_noises
+                .iter()
+                .find(|(noise, _)| pos.distance_squared(noise.pos) <= noise.radius.powi(2))
+                .map(|(noise, _)| noise.pos)
+        };
+
+        ai.timer += dt;
+        if let Some(heard_pos) = heard {
+            ai.last_heard = heard_pos;
+            match ai.state {
+                ZombieState::Idle => {
+                    ai.state = ZombieState::Alert;
+                    ai.timer = 0.0;
+                    sprite.flip_x = heard_pos.x < pos.x;
+                    alerted.push(pos);
+                }
+                // Keeps the turn around intact, only pushes back the timeout
+                ZombieState::Alert => ai.timer = ai.timer.min(ZOMBIE_ALERT_TURN_SEC),
+                ZombieState::Chase => {}
+            }
+        }
+
+        if car_dist_sq <= chase_radius.powi(2) {
+            ai.state = ZombieState::Chase;
+        } else if ai.state == ZombieState::Chase && car_dist_sq > lose_radius.powi(2) {
+            ai.state = ZombieState::Alert;
+            ai.last_heard = car_pos;
+            ai.timer = ZOMBIE_ALERT_TURN_SEC;
+        } else if ai.state == ZombieState::Alert && ai.timer > ZOMBIE_ALERT_TIMEOUT_SEC {
+            ai.state = ZombieState::Idle;
+            ai.timer = 0.0;
+        }
+
+        let rand_dir = vec3(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5), 0.0);
+        match ai.state {
+            ZombieState::Idle => {
+                if ai.wander_dir == Vec2::ZERO || rng.gen_range(0.0..1.0) < dt * 0.5 {
+                    ai.wander_dir = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+                }
+                z.translation += ai.wander_dir.extend(0.0) * step * ZOMBIE_WANDER_SPEED_FACTOR;
+            }
+            ZombieState::Alert => {
+                let to_noise = ai.last_heard - pos;
+                if ai.timer >= ZOMBIE_ALERT_TURN_SEC && to_noise.length_squared() > 400.0 {
+                    let dir = to_noise.normalize().extend(0.0);
+                    z.translation += (dir + rand_dir) * step * ZOMBIE_INVESTIGATE_SPEED_FACTOR;
+                }
+            }
+            ZombieState::Chase => {
+                let mut target_y = car_y;
+                if z.translation.y - target_y > 500.0 && rng.gen_range(0.0..1.0) > 0.5 {
+                    target_y += rng.gen_range(500.0..1500.0);
+                }
+
+                let dir =
+                    vec3(car_x - z.translation.x, target_y - z.translation.y, 0.0).normalize();
+                z.translation += (dir + rand_dir) * step;
+            }
+        }
+
+        // Big corpse piles are in the way, slide around their edge
+        if let Some((center, radius)) = pile_grid.blocking_pile_at(z.translation.truncate()) {
+            let away = (z.translation.truncate() - center).normalize_or_zero();
+            let pos = center + away * radius;
+            z.translation.x = pos.x;
+            z.translation.y = pos.y;
+        }
+
+        // Only part of the step is made while clambering over a car
+        let climbing = obstacle_grid.is_climbing(z.translation.truncate());
+        if climbing {
+            let moved = z.translation.truncate() - pos;
+            let pos = pos + moved * ZOMBIE_CLIMB_SPEED_FACTOR;
+            z.translation.x = pos.x;
+            z.translation.y = pos.y;
+        }
+        if climbing != ai.climbing {
+            ai.climbing = climbing;
+            sprite.anchor = if climbing {
+                Anchor::Custom(vec2(0.0, -ZOMBIE_CLIMB_LIFT))
+            } else {
+                Anchor::Center
+            };
+        }
+
+        // The step covers a whole stride, only this frame's share of it is made now
+        ai.glide_from = pos;
+        ai.glide_to = z.translation.truncate();
+        ai.glide_sec = dt;
+        ai.glide_elapsed = time.delta_seconds();
+        let glided = pos.lerp(ai.glide_to, glide_progress(&ai));
+        z.translation.x = glided.x;
+        z.translation.y = glided.y;
+    }
+
+    // One groan for the whole group that noticed the car, not one each
+    let (groan_cooldown, groan_sound) = &mut *groan;
+    groan_cooldown.tick(time.delta());
+    if !alerted.is_empty()
+        && settings.music
+        && groan_cooldown.elapsed_secs() >= ZOMBIE_ALERT_GROAN_INTERVAL_SEC
+    {
+        groan_cooldown.reset();
+        // Muffled only when every zombie in the group is behind a parked car
+        let muffled = car.is_some_and(|(t, _)| {
+            let listener = t.translation.truncate();
+            let reach = alerted
+                .iter()
+                .map(|pos| pos.distance(listener))
+                .fold(0.0, f32::max);
+            let occluders = obstacle_grid.occluders_near(listener, reach);
+            alerted
+                .iter()
+                .all(|pos| is_sound_occluded(&occluders, *pos, listener))
+        });
+        let volume = if muffled {
+            0.1 * SOUND_OCCLUSION_VOLUME
+        } else {
+            0.1
+        };
+        let source = groan_sound
+            .get_or_insert_with(|| pitch_assets.add(Pitch::new(110.0, Duration::from_millis(180))))
+            .clone();
+        commands.spawn(PitchBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_absolute(volume)),
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_cargo_planes(
+    mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    mut next_drop: Local<f32>,
+    time: Res<Time>,
+    car_progress: Res<CarProgress>,
+    road_tiles: Res<RoadTiles>,
+    run_config: Res<RunConfig>,
+    texture_handle: Res<GlobalTextureHandle>,
+    car_query: Query<&Transform, (With<Car>, Without<CargoPlane>)>,
+    mut plane_query: Query<(Entity, &mut Transform, &mut CargoPlane)>,
+) {
+    let Ok(car_transform) = car_query.get_single() else {
+        return;
+    };
+    let car_pos = car_transform.translation.truncate();
+    let (tile_w, tile_h) = (TILE_W as f32 * ROAD_SCALE, TILE_H as f32 * ROAD_SCALE);
+    let dt = time.delta_seconds();
+    let rng = run_rng.stream(RngStream::Loot);
+
+    // More often and more of it debris the further up the road
+    if car_progress.0 < AIRDROP_MIN_PROGRESS || run_config.boss_rush {
+        *next_drop = AIRDROP_FIRST_DELAY_SEC;
+    } else if plane_query.is_empty() {
+        *next_drop -= dt;
+        if *next_drop <= 0.0 {
+            let progress = car_progress.0.clamp(0.0, 1.0);
+            *next_drop = AIRDROP_INTERVAL_SEC * (1.0 - progress * 0.5) * rng.gen_range(0.75..1.25);
+            let y = car_pos.y + rng.gen_range(AIRDROP_AHEAD_MIN..AIRDROP_AHEAD_MAX);
+            let (col, row) = (
+                (car_pos.x / tile_w).round() as i32,
+                (y / tile_h).round() as i32,
+            );
+            if let Some(x) = road_center_x(&road_tiles, col, row) {
+                let debris_chance = AIRDROP_DEBRIS_CHANCE_START
+                    + (AIRDROP_DEBRIS_CHANCE_END - AIRDROP_DEBRIS_CHANCE_START) * progress;
+                let kind = if rng.gen_bool(debris_chance as f64) {
+                    AirdropKind::Debris
+                } else {
+                    AirdropKind::Crate
+                };
+                let target = vec2(x + rng.gen_range(-AIRDROP_SPREAD..AIRDROP_SPREAD), y);
+                commands.spawn((
+                    SpriteSheetBundle {
+                        texture_atlas: texture_handle.atlas(SpriteId::CargoPlane.atlas()),
+                        sprite: TextureAtlasSprite {
+                            index: SpriteId::CargoPlane.index(),
+                            color: AIRDROP_PLANE_COLOR,
+                            ..default()
+                        },
+                        // Nose first to the right, high over everything
+                        transform: Transform::from_scale(Vec3::splat(AIRDROP_PLANE_SCALE))
+                            .with_rotation(Quat::from_rotation_z(-std::f32::consts::FRAC_PI_2))
+                            .with_translation(vec3(x - AIRDROP_PLANE_LEAD, y, 25.0)),
+                        ..default()
+                    },
+                    CargoPlane {
+                        drop_x: target.x,
+                        cargo: Some((kind, target)),
+                    },
+                    GameEntity,
+                ));
+            }
+        }
+    }
+
+    for (e, mut transform, mut plane) in plane_query.iter_mut() {
+        transform.translation.x += AIRDROP_PLANE_SPEED * dt;
+        if transform.translation.x >= plane.drop_x {
+            if let Some((kind, target)) = plane.cargo.take() {
+                spawn_airdrop(&mut commands, &texture_handle, kind, target);
+            }
+        }
+        if transform.translation.x > plane.drop_x + AIRDROP_PLANE_LEAD {
+            commands.entity(e).despawn();
+        }
+    }
+}
+
+// The shadow telegraphs the landing spot, it darkens as the cargo comes down
+fn spawn_airdrop(
+    commands: &mut Commands,
+    texture_handle: &GlobalTextureHandle,
+    kind: AirdropKind,
+    target: Vec2,
+) {
+    let sprite_id = kind.sprite();
+    commands
+        .spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(sprite_id.atlas()),
+                sprite: TextureAtlasSprite {
+                    index: sprite_id.index(),
+                    color: Color::BLACK.with_a(0.0),
+                    ..default()
+                },
+                // On the road, under the zombies
+                transform: Transform::from_scale(Vec3::splat(AIRDROP_SCALE))
+                    .with_translation(target.extend(0.6)),
+                ..default()
+            },
+            Airdrop {
+                kind,
+                fall: Stopwatch::new(),
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpriteSheetBundle {
+                    texture_atlas: texture_handle.atlas(sprite_id.atlas()),
+                    sprite: TextureAtlasSprite {
+                        index: sprite_id.index(),
+                        color: kind.color(),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, 5.0),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                AirdropCargo,
+            ));
+        });
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn land_airdrops(
+    mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    time: Res<Time>,
+    texture_handle: Res<GlobalTextureHandle>,
+    mut obstacles: ResMut<VehicleObstacleTiles>,
+    mut car_health: ResMut<CarHealth>,
+    mut writer_player_dead: EventWriter<PlayerDeadEvent>,
+    mut writer_hit_stop: EventWriter<HitStopEvent>,
+    mut writer_particles: EventWriter<ParticleBurstEvent>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+    car_query: Query<&Transform, (With<Car>, Without<Airborne>)>,
+    mut drop_query: Query<(
+        Entity,
+        &Transform,
+        &mut Airdrop,
+        &mut TextureAtlasSprite,
+        &Children,
+    )>,
+    mut cargo_query: Query<
+        (&mut Transform, &mut Visibility),
+        (
+            With<AirdropCargo>,
+            Without<Airdrop>,
+            Without<Car>,
+            Without<Zombie>,
+        ),
+    >,
+    zombie_query: Query<(Entity, &Transform), (With<Zombie>, Without<Latched>)>,
+) {
+    let rng = run_rng.stream(RngStream::Loot);
+    for (e, transform, mut airdrop, mut shadow, children) in drop_query.iter_mut() {
+        airdrop.fall.tick(time.delta());
+        let t = (airdrop.fall.elapsed_secs() / AIRDROP_FALL_SEC).min(1.0);
+        shadow.color.set_a(AIRDROP_SHADOW_ALPHA * t);
+
+        // Shrinks toward the road as it falls, relative to the shadow it hangs off
+        for child in children.iter() {
+            if let Ok((mut cargo_transform, mut visibility)) = cargo_query.get_mut(*child) {
+                cargo_transform.scale = Vec3::splat(1.0 + (1.0 - t) * 2.0);
+                cargo_transform.translation.y = (1.0 - t) * 20.0;
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+        }
+        if t < 1.0 {
+            continue;
+        }
+
+        // Touchdown, anything underneath gets flattened
+        let pos = transform.translation.truncate();
+        commands.entity(e).despawn_recursive();
+        writer_particles.send(ParticleBurstEvent {
+            pos,
+            dir: Vec2::Y,
+            kind: ParticleKind::Dirt,
+        });
+        for (zombie, t) in zombie_query.iter() {
+            if t.translation.truncate().distance(pos) <= AIRDROP_CRUSH_RADIUS {
+                commands.entity(zombie).despawn();
+            }
+        }
+        let is_car_hit = car_query
+            .get_single()
+            .is_ok_and(|t| t.translation.truncate().distance(pos) <= AIRDROP_CRUSH_RADIUS);
+        if is_car_hit && !settings.god_mode {
+            car_health.0 -= AIRDROP_CRUSH_DAMAGE;
+            writer_hit_stop.send(HitStopEvent(Duration::from_millis(HIT_STOP_OBSTACLE_MS)));
+            if car_health.0 <= 0.0 || run_config.has(Modifier::OneHitCar) {
+                writer_player_dead.send(PlayerDeadEvent);
+            }
+        }
+
+        match airdrop.kind {
+            AirdropKind::Crate => {
+                let pos = pos.extend(1.0);
+                let loot = match rng.gen_range(0..3) {
+                    0 => RoadProp::ArmorPickup(pos),
+                    1 => RoadProp::SlowMoPickup(pos),
+                    _ => RoadProp::StatusAmmoPickup(pos, StatusKind::random(rng)),
+                };
+                loot.spawn(&mut commands, &texture_handle);
+            }
+            AirdropKind::Debris => {
+                let mut sprite = RoadSprite::new(SpriteId::Debris, pos.extend(0.9), AIRDROP_SCALE);
+                sprite.color = AIRDROP_DEBRIS_COLOR;
+                commands.spawn((sprite.bundle(&texture_handle), GameEntity));
+                obstacles.0.push(VehicleObstacle {
+                    pos: (pos.x, pos.y),
+                    vehicle_type: VehicleObstacleType::Debris,
+                });
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_street_sweeper(
+    mut commands: Commands,
+    mut next_sweeper: Local<f32>,
+    time: Res<Time>,
+    car_progress: Res<CarProgress>,
+    road_tiles: Res<RoadTiles>,
+    run_config: Res<RunConfig>,
+    texture_handle: Res<GlobalTextureHandle>,
+    mut writer_particles: EventWriter<ParticleBurstEvent>,
+    car_query: Query<(Entity, &Transform, Has<Sheltered>), (With<Car>, Without<StreetSweeper>)>,
+    mut sweeper_query: Query<(Entity, &mut Transform), With<StreetSweeper>>,
+    zombie_query: Query<
+        (Entity, &Transform),
+        (With<Zombie>, Without<Latched>, Without<StreetSweeper>),
+    >,
+    corpse_query: Query<(Entity, &Transform), (With<CorpsePile>, Without<StreetSweeper>)>,
+) {
+    let Ok((car, car_transform, is_sheltered)) = car_query.get_single() else {
+        return;
+    };
+    let car_pos = car_transform.translation.truncate();
+    let (tile_w, tile_h) = (TILE_W as f32 * ROAD_SCALE, TILE_H as f32 * ROAD_SCALE);
+    let dt = time.delta_seconds();
+
+    // Held back until the late game, the first one rolls in a little after.
+    // Boss rush roads are too short for it
+    if car_progress.0 < SWEEPER_MIN_PROGRESS || run_config.boss_rush {
+        *next_sweeper = SWEEPER_FIRST_DELAY_SEC;
+    } else if sweeper_query.is_empty() {
+        *next_sweeper -= dt;
+        if *next_sweeper <= 0.0 {
+            *next_sweeper = SWEEPER_INTERVAL_SEC;
+            let y = car_pos.y - SWEEPER_SPAWN_BEHIND;
+            let (col, row) = (
+                (car_pos.x / tile_w).round() as i32,
+                (y / tile_h).round() as i32,
+            );
+            if let Some(x) = road_center_x(&road_tiles, col, row) {
+                commands.spawn((
+                    SpriteSheetBundle {
+                        texture_atlas: texture_handle.atlas(SpriteId::Van.atlas()),
+                        sprite: TextureAtlasSprite {
+                            index: SpriteId::Van.index(),
+                            color: SWEEPER_COLOR,
+                            ..default()
+                        },
+                        transform: Transform::from_scale(Vec3::splat(SWEEPER_SCALE))
+                            .with_translation(vec3(x, y, 5.0)),
+                        ..default()
+                    },
+                    StreetSweeper,
+                    GameEntity,
+                ));
+            }
+        }
+    }
+
+    let mut sheltered = false;
+    for (e, mut transform) in sweeper_query.iter_mut() {
+        // Keeps to the middle of the road a couple of rows ahead, gone once the road ends
+        let pos = transform.translation.truncate();
+        let (col, row) = (
+            (pos.x / tile_w).round() as i32,
+            (pos.y / tile_h).round() as i32 + 2,
+        );
+        let Some(target_x) = road_center_x(&road_tiles, col, row) else {
+            commands.entity(e).despawn();
+            continue;
+        };
+        if (pos.y - car_pos.y).abs() > SWEEPER_DESPAWN_DISTANCE {
+            commands.entity(e).despawn();
+            continue;
+        }
+        let target = vec2(target_x, row as f32 * tile_h);
+        let dir = (target - pos).normalize_or_zero();
+        transform.translation += (dir * SWEEPER_SPEED * dt).extend(0.0);
+        transform.rotation = Quat::from_rotation_z((-dir.x).atan2(dir.y));
+
+        // The blade clears its lane of the horde and whatever is left of it
+        let pos = transform.translation.truncate();
+        let in_lane = |other: Vec2| {
+            let offset = other - pos;
+            offset.x.abs() <= SWEEPER_HALF_WIDTH && (0.0..=SWEEPER_BLADE_REACH).contains(&offset.y)
+        };
+        for (zombie, t) in zombie_query.iter() {
+            if in_lane(t.translation.truncate()) {
+                commands.entity(zombie).despawn();
+                writer_particles.send(ParticleBurstEvent {
+                    pos: t.translation.truncate(),
+                    dir: vec2(t.translation.x - pos.x, 0.0).normalize_or_zero(),
+                    kind: ParticleKind::Dirt,
+                });
+            }
+        }
+        for (corpse, t) in corpse_query.iter() {
+            if in_lane(t.translation.truncate()) {
+                commands.entity(corpse).despawn();
+            }
+        }
+
+        let behind = pos.y - car_pos.y;
+        sheltered |= (car_pos.x - pos.x).abs() <= SWEEPER_HALF_WIDTH
+            && (0.0..=SWEEPER_SHELTER_LENGTH).contains(&behind);
+    }
+
+    if sheltered && !is_sheltered {
+        commands.entity(car).insert(Sheltered);
+    } else if !sheltered && is_sheltered {
+        commands.entity(car).remove::<Sheltered>();
+    }
+}
+
+fn handle_zombie_corpses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut reader_zombie_killed: EventReader<ZombieKilledEvent>,
+    mut pile_query: Query<(Entity, &Transform, &mut CorpsePile)>,
+    texture_handle: Res<GlobalTextureHandle>,
+    settings: Res<GameSettings>,
+) {
+    let mut num_piles = pile_query.iter().len();
+    if let Some(lifetime) = settings.gore.corpse_sec() {
+        for (e, _, mut pile) in pile_query.iter_mut() {
+            pile.age += time.delta_seconds();
+            if pile.age >= lifetime {
+                commands.entity(e).despawn();
+                num_piles -= 1;
+            }
+        }
+    }
+
+    let cap = settings.gore.corpse_pile_cap();
+    for event in reader_zombie_killed.read() {
+        let pos = event.pos.truncate();
+        let nearest = pile_query
+            .iter_mut()
+            .filter(|(_, _, pile)| settings.gore.corpse_sec().is_none_or(|sec| pile.age < sec))
+            .map(|(_, t, pile)| (t.translation.truncate().distance(pos), pile))
+            .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        // Merge into a nearby pile, or any pile at all once the cap is hit
+        if let Some((distance, mut pile)) = nearest {
+            if distance <= CORPSE_MERGE_RADIUS || num_piles >= cap {
+                pile.size += 1;
+                pile.age = 0.0;
+                continue;
+            }
+        }
+        if num_piles >= cap {
+            continue;
+        }
+
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(SpriteId::CorpsePile.atlas()),
+                sprite: TextureAtlasSprite {
+                    index: SpriteId::CorpsePile.index(),
+                    color: CORPSE_PILE_COLOR,
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec3::splat(1.0))
+                    .with_translation(vec3(pos.x, pos.y, 0.5)),
+                ..default()
+            },
+            CorpsePile { size: 1, age: 0.0 },
+            GameEntity,
+        ));
+        num_piles += 1;
+    }
+}
+
+fn update_blood_splats(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    mut reader_zombie_killed: EventReader<ZombieKilledEvent>,
+    mut splat_query: Query<(
+        &mut BloodSplat,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+    )>,
+) {
+    let cap = settings.gore.blood_cap();
+    let mut num_active = splat_query
+        .iter()
+        .filter(|(splat, ..)| splat.active)
+        .count();
+    let mut rng = rand::thread_rng();
+    for event in reader_zombie_killed.read() {
+        if cap == 0 {
+            break;
+        }
+
+        // Recycle the oldest one once the cap is reached
+        let splat = splat_query
+            .iter_mut()
+            .filter(|(splat, ..)| splat.active || num_active < cap)
+            .max_by(|(a, ..), (b, ..)| {
+                a.active
+                    .cmp(&b.active)
+                    .reverse()
+                    .then(a.age.total_cmp(&b.age))
+            });
+        let Some((mut splat, mut transform, mut sprite, mut visibility)) = splat else {
+            break;
+        };
+
+        if !splat.active {
+            num_active += 1;
+        }
+        splat.active = true;
+        splat.age = 0.0;
+        transform.translation = vec3(event.pos.x, event.pos.y, 0.4);
+        transform.rotation = Quat::from_rotation_z(rng.gen_range(0.0..std::f32::consts::TAU));
+        transform.scale = Vec3::splat(rng.gen_range(0.7..1.4));
+        sprite.color = BLOOD_SPLAT_COLOR;
+        *visibility = Visibility::Visible;
+    }
+
+    let lifetime = settings.gore.blood_sec();
+    for (mut splat, _, mut sprite, mut visibility) in splat_query.iter_mut() {
+        if !splat.active {
+            continue;
+        }
+
+        splat.age += time.delta_seconds();
+        if splat.age >= lifetime || cap == 0 {
+            splat.active = false;
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        // Dries out over the last third
+        let fade = ((lifetime - splat.age) / (lifetime / 3.0)).min(1.0);
+        sprite.color.set_a(BLOOD_SPLAT_COLOR.a() * fade);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_zombie_grid(
+    mut zombie_grid: ResMut<ZombieGrid>,
+    zombie_query: Query<(Entity, &Transform), (With<Zombie>, Without<Latched>)>,
+) {
+    zombie_grid.0.clear();
+    for (e, t) in zombie_query.iter() {
+        let pos = t.translation.truncate();
+        zombie_grid
+            .0
+            .entry(ZombieGrid::cell(pos))
+            .or_default()
+            .push((e, pos));
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_status_effects(
+    mut commands: Commands,
+    mut run_rng: ResMut<RunRng>,
+    time: Res<Time>,
+    mut player_score: ResMut<PlayerScore>,
+    mut writer_score_popup: EventWriter<ScorePopupEvent>,
+    mut writer_zombie_killed: EventWriter<ZombieKilledEvent>,
+    zombie_grid: Res<ZombieGrid>,
+    car_query: Query<(&Transform, Option<&Speed>), (With<Car>, Without<Zombie>)>,
+    mut zombie_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut TextureAtlasSprite,
+            Option<&mut StatusEffect>,
+            Option<&mut Bloater>,
+        ),
+        (With<Zombie>, Without<Latched>),
+    >,
+) {
+    let car = car_query
+        .get_single()
+        .ok()
+        .map(|(t, speed)| (t.translation.truncate(), speed.map_or(0.0, |s| s.0.abs())));
+    let dt = time.delta_seconds();
+    let rng = run_rng.stream(RngStream::Ai);
+    let mut ignited = Vec::new();
+    for (e, t, mut sprite, status, bloater) in zombie_query.iter_mut() {
+        let Some(mut status) = status else {
+            continue;
+        };
+        let pos = t.translation.truncate();
+        status.left -= dt;
+
+        // Frozen solid, anything driving into it fast enough breaks it apart
+        let is_shattered = status.kind == StatusKind::Frozen
+            && car.is_some_and(|(car_pos, speed)| {
+                speed >= FROZEN_SHATTER_SPEED && car_pos.distance(pos) <= FROZEN_SHATTER_RADIUS
+            });
+        let is_burnt = status.kind == StatusKind::Burning && status.left <= 0.0;
+        if is_shattered || is_burnt {
+            if let Some(mut bloater) = bloater {
+                // Still goes off, update_bloaters scores the blast
+                if bloater.fuse.is_none() {
+                    bloater.fuse = Some(BLOATER_SWELL_SEC);
+                }
+                sprite.color = status.base_color;
+                commands.entity(e).remove::<StatusEffect>();
+                continue;
+            }
+            player_score.0 += 1;
+            writer_score_popup.send(ScorePopupEvent {
+                pos: t.translation,
+                score: 1,
+            });
+            writer_zombie_killed.send(ZombieKilledEvent { pos: t.translation });
+            commands.entity(e).despawn();
+            continue;
+        }
+        if status.left <= 0.0 {
+            sprite.color = status.base_color;
+            commands.entity(e).remove::<StatusEffect>();
+            continue;
+        }
+
+        if status.kind == StatusKind::Burning {
+            status.spread -= dt;
+            if status.spread <= 0.0 {
+                status.spread = BURNING_SPREAD_INTERVAL_SEC;
+                ignited.extend(
+                    zombie_grid
+                        .zombies_near(pos, BURNING_SPREAD_RADIUS)
+                        .into_iter()
+                        .filter(|other| *other != e && rng.gen_bool(BURNING_SPREAD_CHANCE)),
+                );
+            }
+            // Flickers as it burns
+            let flicker = (status.left * 20.0).sin() * 0.5 + 0.5;
+            sprite.color = BURNING_COLOR * (0.75 + 0.25 * flicker);
+        } else {
+            sprite.color = status.kind.color();
+        }
+    }
+
+    for e in ignited {
+        let Ok((_, _, sprite, status, _)) = zombie_query.get_mut(e) else {
+            continue;
+        };
+        match status {
+            // Ice doesn't catch, and burning ones keep their own clock
+            Some(status) if status.kind != StatusKind::Stunned => {}
+            Some(mut status) => {
+                let base_color = status.base_color;
+                *status = StatusEffect::new(StatusKind::Burning, base_color);
+            }
+            None => {
+                commands
+                    .entity(e)
+                    .try_insert(StatusEffect::new(StatusKind::Burning, sprite.color));
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_bloaters(
+    mut commands: Commands,
+    time: Res<Time>,
+    zombie_grid: Res<ZombieGrid>,
+    mut player_score: ResMut<PlayerScore>,
+    mut writer_score_popup: EventWriter<ScorePopupEvent>,
+    mut writer_zombie_killed: EventWriter<ZombieKilledEvent>,
+    mut writer_player_hit: EventWriter<ZombieHitPlayer>,
+    mut writer_particles: EventWriter<ParticleBurstEvent>,
+    mut bloater_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Bloater,
+            &ZombieAttack,
+            Has<Culled>,
+        ),
+        Without<Latched>,
+    >,
+    zombie_query: Query<
+        &Transform,
+        (
+            With<Zombie>,
+            Without<Bloater>,
+            Without<TutorialDummy>,
+            Without<Latched>,
+        ),
+    >,
+    car_query: Query<&Transform, (With<Car>, Without<Bloater>)>,
+) {
+    let dt = time.delta_seconds();
+    let mut blasts = Vec::new();
+    for (e, mut t, mut bloater, attack, is_culled) in bloater_query.iter_mut() {
+        let Some(fuse) = bloater.fuse.as_mut() else {
+            continue;
+        };
+
+        *fuse -= dt;
+        if *fuse > 0.0 {
+            if !is_culled {
+                let progress = 1.0 - *fuse / BLOATER_SWELL_SEC;
+                t.scale = Vec3::splat(attack.base_scale * (1.0 + progress * BLOATER_SWELL_SCALE));
+            }
+            continue;
+        }
+
+        blasts.push(t.translation);
+        commands.entity(e).despawn();
+    }
+
+    let car_pos = car_query
+        .get_single()
+        .ok()
+        .map(|t| t.translation.truncate());
+    let mut killed = HashSet::new();
+    for blast in blasts {
+        let pos = blast.truncate();
+        player_score.0 += 1;
+        writer_score_popup.send(ScorePopupEvent {
+            pos: blast,
+            score: 1,
+        });
+        writer_zombie_killed.send(ZombieKilledEvent { pos: blast });
+        for dir in [Vec2::X, Vec2::Y, Vec2::NEG_X, Vec2::NEG_Y] {
+            writer_particles.send(ParticleBurstEvent {
+                pos,
+                dir,
+                kind: ParticleKind::Toxic,
+            });
+        }
+
+        if car_pos.is_some_and(|car_pos| car_pos.distance(pos) <= BLOATER_BLAST_RADIUS) {
+            writer_player_hit
+                .send_batch((0..BLOATER_CAR_HITS).map(|_| ZombieHitPlayer { from: pos }));
+        }
+
+        for e in zombie_grid.zombies_near(pos, BLOATER_BLAST_RADIUS) {
+            if let Ok((_, _, mut bloater, ..)) = bloater_query.get_mut(e) {
+                if bloater.fuse.is_none() {
+                    bloater.fuse = Some(BLOATER_CHAIN_SWELL_SEC);
+                }
+                continue;
+            }
+
+            let Ok(t) = zombie_query.get(e) else {
+                continue;
+            };
+            if !killed.insert(e) {
+                continue;
+            }
+            player_score.0 += 1;
+            writer_score_popup.send(ScorePopupEvent {
+                pos: t.translation,
+                score: 1,
+            });
+            writer_zombie_killed.send(ZombieKilledEvent { pos: t.translation });
+            commands.entity(e).despawn();
+        }
+    }
+}
+
+fn update_corpse_pile_grid(
+    mut pile_grid: ResMut<CorpsePileGrid>,
+    mut pile_query: Query<(&mut Transform, &CorpsePile)>,
+) {
+    pile_grid.0.clear();
+    for (mut t, pile) in pile_query.iter_mut() {
+        t.scale = Vec3::splat(pile.radius() / 8.0);
+        if pile.size < CORPSE_PILE_BLOCKING_SIZE {
+            continue;
+        }
+
+        let pos = t.translation.truncate();
+        pile_grid
+            .0
+            .entry(CorpsePileGrid::cell(pos))
+            .or_default()
+            .push((pos, pile.radius()));
+    }
+}
+
+fn update_obstacle_grid(
+    mut obstacle_grid: ResMut<ObstacleGrid>,
+    obstacles: Res<VehicleObstacleTiles>,
+) {
+    if !obstacles.is_changed() {
+        return;
+    }
+
+    obstacle_grid.0.clear();
+    for obstacle in obstacles.0.iter() {
+        let pos = vec2(obstacle.pos.0, obstacle.pos.1);
+        obstacle_grid
+            .0
+            .entry(ObstacleGrid::cell(pos))
+            .or_default()
+            .push(pos);
+    }
+}
+
+fn check_corpse_pile_collision(
+    time: Res<Time>,
+    mut car_query: Query<(&Transform, &mut Speed), With<Car>>,
+    pile_query: Query<(&Transform, &CorpsePile), Without<Car>>,
+) {
+    if car_query.is_empty() {
+        return;
+    }
+
+    let (car_transform, mut speed) = car_query.single_mut();
+    let car_pos = car_transform.translation.truncate();
+    for (t, pile) in pile_query.iter() {
+        if t.translation.truncate().distance(car_pos) <= pile.radius() {
+            speed.0 -= speed.0 * CORPSE_PILE_DRAG * time.delta_seconds();
+            return;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_road(
+    mut commands: Commands,
+    mut road_stream: ResMut<RoadStream>,
+    mut road_tiles: ResMut<RoadTiles>,
+    mut obstacle_tiles: ResMut<VehicleObstacleTiles>,
+    mut boss_arena: ResMut<BossArena>,
+    texture_handle: Res<GlobalTextureHandle>,
+    run_config: Res<RunConfig>,
+    difficulty: Res<DifficultyProfile>,
+) {
+    *boss_arena = BossArena::default();
+    // The score arena lays out its own floor
+    if run_config.arena {
+        *road_stream = RoadStream::default();
+        return;
+    }
+    *road_stream = RoadStream {
+        generator: Some(RoadGenerator::new(&run_config, &difficulty)),
+        ..default()
+    };
+
+    // Enough road around the start for the first frame, stream_road takes over from there
+    stream_road_chunks(
+        &mut commands,
+        &mut road_stream,
+        &mut road_tiles,
+        &mut obstacle_tiles,
+        &mut boss_arena,
+        &texture_handle,
+        (-ROAD_STREAM_BEHIND, ROAD_STREAM_AHEAD),
+    );
+}
+
+fn stream_road(
+    mut commands: Commands,
+    mut road_stream: ResMut<RoadStream>,
+    mut road_tiles: ResMut<RoadTiles>,
+    mut obstacle_tiles: ResMut<VehicleObstacleTiles>,
+    mut boss_arena: ResMut<BossArena>,
+    texture_handle: Res<GlobalTextureHandle>,
+    cam_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+) {
+    let Ok((cam_transform, projection)) = cam_query.get_single() else {
+        return;
+    };
+
+    // Zooming out shows more road, keep the same number of screens loaded
+    let zoom = projection.scale.max(1.0);
+    let y = cam_transform.translation.y;
+    stream_road_chunks(
+        &mut commands,
+        &mut road_stream,
+        &mut road_tiles,
+        &mut obstacle_tiles,
+        &mut boss_arena,
+        &texture_handle,
+        (y - ROAD_STREAM_BEHIND * zoom, y + ROAD_STREAM_AHEAD * zoom),
+    );
+}
+
+// Generates the road up to the top of the range and keeps only the chunks inside it spawned
+fn stream_road_chunks(
+    commands: &mut Commands,
+    road_stream: &mut RoadStream,
+    road_tiles: &mut RoadTiles,
+    obstacle_tiles: &mut VehicleObstacleTiles,
+    boss_arena: &mut BossArena,
+    texture_handle: &GlobalTextureHandle,
+    (min_y, max_y): (f32, f32),
+) {
+    let tile_size = TILE_H as f32 * ROAD_SCALE;
+    let (min_row, max_row) = (
+        (min_y / tile_size).floor() as i32,
+        (max_y / tile_size).ceil() as i32,
+    );
+
+    let RoadStream {
+        generator,
+        chunks,
+        spawned,
+    } = road_stream;
+    if let Some(road_generator) = generator.as_mut() {
+        while road_generator.next_row <= max_row.min(road_generator.top_y) {
+            road_generator.generate_row(chunks, road_tiles, obstacle_tiles);
+        }
+        if road_generator.next_row > road_generator.top_y {
+            if road_generator.boss_rush {
+                spawn_boss_arena(
+                    commands,
+                    chunks,
+                    road_tiles,
+                    boss_arena,
+                    texture_handle,
+                    road_generator.offset + ROAD_WIDTH as i32 / 2,
+                    road_generator.top_y + 1,
+                );
+            }
+            *generator = None;
+        }
+    }
+
+    let (min_chunk, max_chunk) = (road_chunk_of(min_row), road_chunk_of(max_row));
+    spawned.retain(|idx, e| {
+        let keep = (min_chunk..=max_chunk).contains(idx);
+        if !keep {
+            commands.entity(*e).despawn_recursive();
+        }
+        keep
+    });
+
+    for idx in min_chunk..=max_chunk {
+        if spawned.contains_key(&idx) {
+            continue;
+        }
+        let Some(chunk) = chunks.get_mut(&idx) else {
+            continue;
+        };
+
+        let e = commands
+            .spawn((SpatialBundle::default(), RoadChunk, GameEntity))
+            .with_children(|parent| {
+                for sprite in chunk.sprites.iter() {
+                    parent.spawn((sprite.bundle(texture_handle), Road));
+                }
+                for sprite in chunk.obstacles.iter() {
+                    parent.spawn((sprite.bundle(texture_handle), Obstacle));
+                }
+            })
+            .id();
+        spawned.insert(idx, e);
+
+        // Props aren't tied to the chunk, they get picked up or destroyed on their own
+        for prop in std::mem::take(&mut chunk.props) {
+            prop.spawn(commands, texture_handle);
+        }
+    }
+}
+
+fn road_chunk_of(row: i32) -> i32 {
+    (row - ROAD_BOTTOM_Y).div_euclid(ROAD_CHUNK_ROWS)
+}
+
+impl RoadGenerator {
+    fn new(run_config: &RunConfig, difficulty: &DifficultyProfile) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(RngStream::Road.seed(run_config.seed)),
+            next_row: ROAD_BOTTOM_Y,
+            top_y: run_config.road_height() as i32,
+            offset: 0,
+            n_offset: 0,
+            p_offset: 0,
+            patch: (RoadSurface::Asphalt, 0, 0, 0),
+            boss_rush: run_config.boss_rush,
+            pickup_frequency: difficulty.pickup_frequency,
+            finish: run_config.finish_layout(),
+        }
+    }
+
+    fn generate_row(
+        &mut self,
+        chunks: &mut HashMap<i32, RoadChunkData>,
+        road_tiles: &mut RoadTiles,
+        obstacle_tiles: &mut VehicleObstacleTiles,
+    ) {
+        let j = self.next_row;
+        self.next_row += 1;
+        let chunk = chunks.entry(road_chunk_of(j)).or_default();
+        let rng = &mut self.rng;
+        let top_y = self.top_y;
+        let pickup_frequency = self.pickup_frequency;
+        let left_x = 0;
+        let right_x = ROAD_WIDTH as i32;
+        let is_top_y = j == top_y || j == top_y - 1;
+
+        if is_top_y {
+            for a in 0..=ROAD_WIDTH as i32 {
+                let (x, y) = (
+                    (self.offset + a) as f32 * TILE_W as f32 * ROAD_SCALE,
+                    j as f32 * TILE_H as f32 * ROAD_SCALE,
+                );
+                chunk.sprites.push(RoadSprite::new(
+                    SpriteId::RoadEnd,
+                    vec3(x, y, 1.0),
+                    ROAD_SCALE,
+                ));
+            }
+        }
+
+        if j % 5 == 0 && !is_top_y {
+            self.p_offset = self.offset;
+            self.offset = self.n_offset;
+            // Straight through the start zone so its walls line up with the road
+            let drift = rng.gen_range(-1..=1);
+            if j as f32 * TILE_H as f32 * ROAD_SCALE >= START_GATE_Y {
+                self.n_offset += drift;
+            }
+        }
+        let (offset, n_offset, p_offset) = (self.offset, self.n_offset, self.p_offset);
+
+        // if next tile is curve
+        if (j + 1) % 5 == 0 && !is_top_y {
+            if (offset - n_offset) == 1 {
+                let (x, y) = (
+                    (offset - 1) as f32 * TILE_W as f32 * ROAD_SCALE,
+                    j as f32 * TILE_H as f32 * ROAD_SCALE,
+                );
+                let (x, y) = (x + 10.0, y);
+                chunk.sprites.push(RoadSprite::new(
+                    SpriteId::CurveEntryLeft,
+                    vec3(x, y, 0.0),
+                    ROAD_SCALE,
+                ));
+            } else if (offset - n_offset) == -1 {
+                let (x, y) = (
+                    (n_offset + ROAD_WIDTH as i32) as f32 * TILE_W as f32 * ROAD_SCALE,
+                    j as f32 * TILE_H as f32 * ROAD_SCALE,
+                );
+                let (x, y) = (x - 10.0, y);
+                chunk.sprites.push(RoadSprite::new(
+                    SpriteId::CurveEntryRight,
+                    vec3(x, y, 0.0),
+                    ROAD_SCALE,
+                ));
+            }
+        }
+
+        // OBSTACLE
+        if rng.gen_range(0.0..1.0) > 0.9 && j > 30 {
+            let (mut x, y) = (
+                (rng.gen_range((offset + 1)..(offset + 5))) as f32 * TILE_W as f32 * ROAD_SCALE,
+                j as f32 * TILE_H as f32 * ROAD_SCALE,
+            );
+            x += rng.gen_range(-1.0..=-1.0) * TILE_W as f32 * ROAD_SCALE;
+
+            let obstacle = VehicleObstacle::new((x, y), rng);
+            chunk.obstacles.push(RoadSprite::new(
+                obstacle.vehicle_type.sprite(),
+                vec3(x, y, 1.0),
+                3.0,
+            ));
+            obstacle_tiles.0.push(obstacle);
+        } else if rng.gen_range(0.0..1.0) > 1.0 - 0.003 * pickup_frequency && j > 60 {
+            // SLOW MOTION PICKUP, rarer than armor
+            let (x, y) = (
+                (rng.gen_range(offset..(offset + 4))) as f32 * TILE_W as f32 * ROAD_SCALE,
+                j as f32 * TILE_H as f32 * ROAD_SCALE,
+            );
+            chunk.props.push(RoadProp::SlowMoPickup(vec3(x, y, 1.0)));
+        } else if rng.gen_range(0.0..1.0) > 1.0 - 0.004 * pickup_frequency && j > 50 {
+            // STATUS AMMO PICKUP
+            let (x, y) = (
+                (rng.gen_range(offset..(offset + 4))) as f32 * TILE_W as f32 * ROAD_SCALE,
+                j as f32 * TILE_H as f32 * ROAD_SCALE,
+            );
+            let kind = StatusKind::random(rng);
+            chunk
+                .props
+                .push(RoadProp::StatusAmmoPickup(vec3(x, y, 1.0), kind));
+        } else if rng.gen_range(0.0..1.0) > 1.0 - 0.03 * pickup_frequency && j > 30 {
+            // ARMOR PICKUP
+            let (x, y) = (
+                (rng.gen_range(offset..(offset + 4))) as f32 * TILE_W as f32 * ROAD_SCALE,
+                j as f32 * TILE_H as f32 * ROAD_SCALE,
+            );
+            chunk.props.push(RoadProp::ArmorPickup(vec3(x, y, 1.0)));
+        } else if rng.gen_range(0.0..1.0) > 0.99 && j > 40 {
+            // RAMP
+            let (x, y) = (
+                (rng.gen_range(offset..(offset + 4))) as f32 * TILE_W as f32 * ROAD_SCALE,
+                j as f32 * TILE_H as f32 * ROAD_SCALE,
+            );
+            chunk.props.push(RoadProp::Ramp(vec3(x, y, 0.5)));
+        }
+
+        // TOLL GATE, midway along a straight so it sits square across the road
+        let is_gate_row = !self.boss_rush
+            && TOLL_GATE_PROGRESS.iter().any(|p| {
+                let row = (p * top_y as f32) as i32;
+                row - row.rem_euclid(5) + 2 == j
+            });
+        if is_gate_row {
+            let tile = TILE_W as f32 * ROAD_SCALE;
+            let y = j as f32 * TILE_H as f32 * ROAD_SCALE;
+            let center_x = (offset as f32 + ROAD_WIDTH as f32 / 2.0) * tile;
+            // On an edge lane a couple of rows short of the gate, sides alternate
+            let lane = if (j / 5) % 2 == 0 {
+                1
+            } else {
+                ROAD_WIDTH as i32 - 1
+            };
+            let panel = vec2((offset + lane) as f32 * tile, y - 2.0 * tile);
+            chunk.props.push(RoadProp::TollGate(
+                vec3(center_x, y, 2.0),
+                (ROAD_WIDTH + 1) as f32 * tile,
+                panel,
+            ));
+        }
+
+        // FINISH, across the last straight before the road end
+        let finish_row = top_y - FINISH_ROWS_FROM_TOP;
+        if let Some(layout) = self
+            .finish
+            .filter(|_| finish_row - finish_row.rem_euclid(5) + 2 == j)
+        {
+            let tile = TILE_W as f32 * ROAD_SCALE;
+            let y = j as f32 * TILE_H as f32 * ROAD_SCALE;
+            let width = (ROAD_WIDTH + 1) as f32 * tile;
+            let center_x = (offset as f32 + ROAD_WIDTH as f32 / 2.0) * tile;
+            let side = if (j / 5) % 2 == 0 { -1.0 } else { 1.0 };
+            let lever = vec2(
+                center_x + side * (width / 2.0 + DRAWBRIDGE_LEVER_OFFSET_TILES * tile),
+                y - 3.0 * tile,
+            );
+            chunk.props.push(RoadProp::Finish(
+                layout,
+                vec3(center_x, y, 2.0),
+                width,
+                lever,
+            ));
+        }
+
+        // ZOMBIE NEST
+        if rng.gen_range(0.0..1.0) > 0.985 && j > 50 {
+            let mut x = (offset - 3) as f32 * TILE_W as f32 * ROAD_SCALE;
+            if rng.gen_range(0.0..1.0) > 0.5 {
+                x = (offset + ROAD_WIDTH as i32 + 3) as f32 * TILE_W as f32 * ROAD_SCALE;
+            }
+            let y = j as f32 * TILE_H as f32 * ROAD_SCALE;
+            chunk.props.push(RoadProp::Nest(vec3(x, y, 1.0)));
+        }
+
+        // road decorations
+        if rng.gen_range(0.0..1.0) > 0.6 {
+            let (mut x, y) = (
+                (offset - 2 - rng.gen_range(1..=2)) as f32 * TILE_W as f32 * ROAD_SCALE,
+                j as f32 * TILE_H as f32 * ROAD_SCALE,
+            );
+            if rng.gen_range(0.0..1.0) > 0.5 {
+                x += 2.0 * (ROAD_WIDTH) as f32 * TILE_W as f32 * ROAD_SCALE;
+                x += rng.gen_range(1.0..3.0) * TILE_W as f32 * ROAD_SCALE;
+            }
+            chunk.sprites.push(RoadSprite::new(
+                SpriteId::RoadsideDecor,
+                vec3(x, y, 0.0),
+                ROAD_SCALE,
+            ));
+        }
+
+        if j % 5 == 0 && !is_top_y {
+            if (offset - p_offset) == 1 {
+                let (x, y) = (
+                    (offset - 1) as f32 * TILE_W as f32 * ROAD_SCALE,
+                    j as f32 * TILE_H as f32 * ROAD_SCALE,
+                );
+                let (x, y) = (x + 10.0, y);
+                chunk.sprites.push(RoadSprite::new(
+                    SpriteId::CurveExitLeft,
+                    vec3(x, y, 0.0),
+                    ROAD_SCALE,
+                ));
+            } else if (offset - p_offset) == -1 {
+                let (x, y) = (
+                    (offset + 1 + ROAD_WIDTH as i32) as f32 * TILE_W as f32 * ROAD_SCALE,
+                    j as f32 * TILE_H as f32 * ROAD_SCALE,
+                );
+                let (x, y) = (x - 10.0, y);
+                chunk.sprites.push(RoadSprite::new(
+                    SpriteId::CurveExitRight,
+                    vec3(x, y, 0.0),
+                    ROAD_SCALE,
+                ));
+            }
+        }
+
+        // SURFACE PATCHES
+        if self.patch.3 == 0
+            && j > 30
+            && !is_top_y
+            && rng.gen_range(0.0..1.0) < SURFACE_PATCH_CHANCE
+        {
+            let surface = RoadSurface::random(rng);
+            let first = rng.gen_range(left_x..right_x);
+            let last = (first + rng.gen_range(1..=3)).min(right_x);
+            self.patch = (surface, first, last, rng.gen_range(3..=8));
+        }
+        let (patch_surface, patch_first, patch_last, patch_rows) = self.patch;
+        self.patch.3 = patch_rows.saturating_sub(1);
+
+        for i in left_x..=right_x {
+            let tile = if i == 0 {
+                SpriteId::RoadEdgeLeft
+            } else if i == right_x {
+                SpriteId::RoadEdgeRight
+            } else {
+                SpriteId::Road
+            };
+            let (x, y) = (
+                (i + offset) as f32 * TILE_W as f32 * ROAD_SCALE,
+                j as f32 * TILE_H as f32 * ROAD_SCALE,
+            );
+            let surface = if patch_rows > 0 && (patch_first..=patch_last).contains(&i) {
+                patch_surface
+            } else {
+                RoadSurface::Asphalt
+            };
+            road_tiles.0.insert((i + offset, j), surface);
+            chunk.sprites.push(RoadSprite {
+                color: surface.color(),
+                ..RoadSprite::new(tile, vec3(x, y, 0.0), ROAD_SCALE)
+            });
+        }
+    }
+}
+
+impl GlobalTextureHandle {
+    fn atlas(&self, kind: AtlasKind) -> Handle<TextureAtlas> {
+        self.1
+            .get(&kind)
+            .cloned()
+            .unwrap_or_else(|| self.0.clone().unwrap())
+    }
+}
+
+impl SpriteId {
+    fn atlas(&self) -> AtlasKind {
+        match self {
+            Self::Zombie
+            | Self::BigZombie
+            | Self::Dog
+            | Self::Survivor
+            | Self::Bullet
+            | Self::Nest
+            | Self::CorpsePile => AtlasKind::Characters,
+            Self::Hatchback | Self::WreckedCar | Self::Muscle | Self::Van | Self::CargoPlane => {
+                AtlasKind::Vehicles
+            }
+            Self::HealthIcon
+            | Self::HealthBar
+            | Self::ArmorBar
+            | Self::TurboIcon
+            | Self::ProgressFlag
+            | Self::ProgressCar
+            | Self::SpawnWarning
+            | Self::CrosshairCross
+            | Self::CrosshairDot
+            | Self::CrosshairCircle => AtlasKind::Ui,
+            _ => AtlasKind::Environment,
+        }
+    }
+
+    // Tile in its atlas, the bundled manifest points every kind at the one sheet
+    fn index(&self) -> usize {
+        match self {
+            Self::Zombie | Self::Survivor => 30,
+            Self::BigZombie => 40,
+            Self::Dog => 20,
+            Self::Bullet => 1,
+            Self::Nest | Self::CorpsePile => 2,
+            Self::Block | Self::ArmorBar => 16,
+            Self::RoadEnd => 17,
+            Self::RoadsideDecor => 50,
+            Self::Hatchback => 0,
+            Self::WreckedCar => 60,
+            Self::Muscle => 61,
+            Self::Van => 62,
+            Self::RoadEdgeLeft => 80,
+            Self::Road => 81,
+            Self::RoadEdgeRight => 82,
+            Self::CurveExitLeft => 83,
+            Self::CurveExitRight => 84,
+            Self::CurveEntryLeft => 85,
+            Self::CurveEntryRight => 86,
+            Self::CargoPlane => 26,
+            Self::Crate => 27,
+            Self::Debris => 28,
+            Self::HealthIcon => 10,
+            Self::TurboIcon => 12,
+            Self::HealthBar => 13,
+            Self::ProgressFlag => 14,
+            Self::ProgressCar => 15,
+            Self::SpawnWarning => 22,
+            Self::CrosshairCross => 23,
+            Self::CrosshairDot => 24,
+            Self::CrosshairCircle => 25,
+        }
+    }
+
+    // Tiles in a row after index() with other looks for the same thing
+    fn variants(&self) -> usize {
+        match self {
+            Self::Zombie => 10,
+            Self::BigZombie => 4,
+            Self::Dog => 2,
+            _ => 1,
+        }
+    }
+
+    fn random_index(&self, rng: &mut impl Rng) -> usize {
+        self.index() + rng.gen_range(0..self.variants())
+    }
+}
+
+impl RoadSprite {
+    fn new(id: SpriteId, pos: Vec3, scale: f32) -> Self {
+        Self {
+            id,
+            pos,
+            scale,
+            color: Color::WHITE,
+        }
+    }
+
+    fn bundle(&self, texture_handle: &GlobalTextureHandle) -> SpriteSheetBundle {
+        SpriteSheetBundle {
+            texture_atlas: texture_handle.atlas(self.id.atlas()),
+            sprite: TextureAtlasSprite {
+                index: self.id.index(),
+                color: self.color,
+                ..default()
+            },
+            transform: Transform::from_scale(Vec3::splat(self.scale)).with_translation(self.pos),
+            ..default()
+        }
+    }
+}
+
+impl RoadProp {
+    fn spawn(&self, commands: &mut Commands, texture_handle: &GlobalTextureHandle) {
+        match *self {
+            Self::SlowMoPickup(pos) => {
+                commands.spawn((
+                    SpriteSheetBundle {
+                        texture_atlas: texture_handle.atlas(SpriteId::Block.atlas()),
+                        sprite: TextureAtlasSprite {
+                            index: SpriteId::Block.index(),
+                            color: Color::rgb(0.55, 0.75, 1.0),
+                            ..default()
+                        },
+                        transform: Transform::from_scale(Vec3::splat(2.0)).with_translation(pos),
+                        ..default()
+                    },
+                    SlowMoPickup,
+                    GameEntity,
+                ));
+            }
+            Self::StatusAmmoPickup(pos, kind) => {
+                commands.spawn((
+                    SpriteSheetBundle {
+                        texture_atlas: texture_handle.atlas(SpriteId::Block.atlas()),
+                        sprite: TextureAtlasSprite {
+                            index: SpriteId::Block.index(),
+                            color: kind.color(),
+                            ..default()
+                        },
+                        transform: Transform::from_scale(Vec3::splat(2.0)).with_translation(pos),
+                        ..default()
+                    },
+                    StatusAmmoPickup(kind),
+                    GameEntity,
+                ));
+            }
+            Self::ArmorPickup(pos) => {
+                commands.spawn((
+                    SpriteSheetBundle {
+                        texture_atlas: texture_handle.atlas(SpriteId::Block.atlas()),
+                        sprite: TextureAtlasSprite::new(SpriteId::Block.index()),
+                        transform: Transform::from_scale(Vec3::splat(2.0)).with_translation(pos),
+                        ..default()
+                    },
+                    ArmorPickup,
+                    GameEntity,
+                ));
+            }
+            Self::Nest(pos) => {
+                commands.spawn((
+                    SpriteSheetBundle {
+                        texture_atlas: texture_handle.atlas(SpriteId::Nest.atlas()),
+                        sprite: TextureAtlasSprite {
+                            index: SpriteId::Nest.index(),
+                            color: COLOR_BROWN,
+                            ..default()
+                        },
+                        transform: Transform::from_scale(Vec3::splat(ROAD_SCALE))
+                            .with_translation(pos),
+                        ..default()
+                    },
+                    ZombieNest {
+                        health: NEST_HEALTH,
+                        spawn_timer: Stopwatch::new(),
+                    },
+                    GameEntity,
+                ));
+            }
+            Self::Ramp(pos) => {
+                commands.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: RAMP_COLOR,
+                            custom_size: Some(RAMP_SIZE),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(pos),
+                        ..default()
+                    },
+                    Ramp,
+                    GameEntity,
+                ));
+            }
+            Self::TollGate(pos, width, panel) => {
+                let fence = TOLL_GATE_FENCE_TILES * TILE_W as f32 * ROAD_SCALE;
+                let panel_offset = (panel - pos.truncate()).extend(-1.0);
+                commands
+                    .spawn((
+                        SpatialBundle::from_transform(Transform::from_translation(pos)),
+                        TollGate {
+                            width,
+                            panel,
+                            hold: 0.0,
+                            open: 0.0,
+                            alarm: 0.0,
+                        },
+                        GameEntity,
+                    ))
+                    .with_children(|parent| {
+                        for side in [-1.0, 1.0] {
+                            parent.spawn((
+                                SpriteBundle {
+                                    sprite: Sprite {
+                                        color: COLOR_ORANGE,
+                                        custom_size: Some(vec2(width / 2.0, TOLL_GATE_THICKNESS)),
+                                        ..default()
+                                    },
+                                    transform: Transform::from_xyz(side * width / 4.0, 0.0, 0.0),
+                                    ..default()
+                                },
+                                TollGateHalf(side),
+                            ));
+                            parent.spawn(SpriteBundle {
+                                sprite: Sprite {
+                                    color: COLOR_BROWN,
+                                    custom_size: Some(vec2(fence, TOLL_GATE_FENCE_THICKNESS)),
+                                    ..default()
+                                },
+                                transform: Transform::from_xyz(
+                                    side * (width + fence) / 2.0,
+                                    0.0,
+                                    0.0,
+                                ),
+                                ..default()
+                            });
+                        }
+                        parent.spawn(SpriteBundle {
+                            sprite: Sprite {
+                                color: TOLL_PANEL_COLOR,
+                                custom_size: Some(TOLL_PANEL_SIZE),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(panel_offset),
+                            ..default()
+                        });
+                        // Fills up left to right above the panel while it's held
+                        parent.spawn((
+                            SpriteBundle {
+                                sprite: Sprite {
+                                    color: COLOR_LIGHT_ORANGE,
+                                    custom_size: Some(vec2(TOLL_PANEL_SIZE.x, 6.0)),
+                                    anchor: Anchor::CenterLeft,
+                                    ..default()
+                                },
+                                transform: Transform::from_translation(
+                                    panel_offset
+                                        + vec3(-TOLL_PANEL_SIZE.x / 2.0, TOLL_PANEL_SIZE.y, 0.0),
+                                )
+                                .with_scale(vec3(0.0, 1.0, 1.0)),
+                                ..default()
+                            },
+                            TollGateFill,
+                        ));
+                    });
+            }
+            Self::Finish(layout, pos, width, lever) => {
+                let fence = TOLL_GATE_FENCE_TILES * TILE_W as f32 * ROAD_SCALE;
+                let depth = match layout {
+                    FinishLayout::Bunker => BUNKER_GATE_THICKNESS / 2.0,
+                    _ => FINISH_WATER_HEIGHT / 2.0,
+                };
+                let sprite = |color, size| SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    ..default()
+                };
+                commands
+                    .spawn((
+                        SpatialBundle::from_transform(Transform::from_translation(pos)),
+                        FinishBarrier {
+                            layout,
+                            width,
+                            depth,
+                            lever,
+                            hold: 0.0,
+                            health: BUNKER_GATE_HEALTH,
+                            open: 0.0,
+                            alarm: 0.0,
+                        },
+                        GameEntity,
+                    ))
+                    .with_children(|parent| {
+                        // Fill bar over whatever the objective is worked at
+                        let fill_pos = match layout {
+                            FinishLayout::Ferry => vec3(-width / 2.0, -depth - 20.0, 1.0),
+                            FinishLayout::Drawbridge => {
+                                let lever = (lever - pos.truncate()).extend(1.0);
+                                lever + vec3(-TOLL_PANEL_SIZE.x / 2.0, TOLL_PANEL_SIZE.y, 0.0)
+                            }
+                            FinishLayout::Bunker => vec3(-width / 2.0, depth + 20.0, 1.0),
+                        };
+                        let fill_width = match layout {
+                            FinishLayout::Drawbridge => TOLL_PANEL_SIZE.x,
+                            _ => width,
+                        };
+                        parent.spawn((
+                            SpriteBundle {
+                                sprite: Sprite {
+                                    color: COLOR_LIGHT_ORANGE,
+                                    custom_size: Some(vec2(fill_width, 6.0)),
+                                    anchor: Anchor::CenterLeft,
+                                    ..default()
+                                },
+                                transform: Transform::from_translation(fill_pos)
+                                    .with_scale(vec3(0.0, 1.0, 1.0)),
+                                ..default()
+                            },
+                            FinishFill,
+                        ));
+
+                        match layout {
+                            FinishLayout::Ferry | FinishLayout::Drawbridge => {
+                                // A river across the road and the fields either side
+                                parent.spawn(sprite(
+                                    FINISH_WATER_COLOR,
+                                    vec2(width + 2.0 * fence, depth * 2.0),
+                                ));
+                            }
+                            FinishLayout::Bunker => {
+                                for side in [-1.0, 1.0] {
+                                    parent.spawn({
+                                        let mut wall =
+                                            sprite(COLOR_BROWN, vec2(fence, depth * 2.0));
+                                        wall.transform.translation.x = side * (width + fence) / 2.0;
+                                        wall
+                                    });
+                                }
+                            }
+                        }
+                        match layout {
+                            FinishLayout::Ferry => {
+                                parent.spawn({
+                                    let mut dock =
+                                        sprite(FERRY_DOCK_COLOR, vec2(width, FERRY_DOCK_HEIGHT));
+                                    dock.transform.translation =
+                                        vec3(0.0, -depth - FERRY_DOCK_HEIGHT / 2.0, -1.0);
+                                    dock
+                                });
+                                // Sails in from the side once the wait is over
+                                parent.spawn((
+                                    {
+                                        let mut hull =
+                                            sprite(FERRY_COLOR, vec2(width, depth * 1.6));
+                                        hull.transform.translation = vec3(width * 1.5, 0.0, 0.5);
+                                        hull
+                                    },
+                                    FinishMover,
+                                ));
+                            }
+                            FinishLayout::Drawbridge => {
+                                parent.spawn({
+                                    let mut lever_sprite =
+                                        sprite(DRAWBRIDGE_LEVER_COLOR, TOLL_PANEL_SIZE);
+                                    lever_sprite.transform.translation =
+                                        (lever - pos.truncate()).extend(0.0);
+                                    lever_sprite
+                                });
+                                // Swings down from the near bank
+                                parent.spawn((
+                                    SpriteBundle {
+                                        sprite: Sprite {
+                                            color: COLOR_BROWN,
+                                            custom_size: Some(vec2(width, depth * 2.0)),
+                                            anchor: Anchor::BottomCenter,
+                                            ..default()
+                                        },
+                                        transform: Transform::from_xyz(0.0, -depth, 0.5)
+                                            .with_scale(vec3(1.0, 0.0, 1.0)),
+                                        ..default()
+                                    },
+                                    FinishMover,
+                                ));
+                            }
+                            FinishLayout::Bunker => {
+                                parent.spawn((
+                                    sprite(BUNKER_GATE_COLOR, vec2(width, depth * 2.0)),
+                                    FinishMover,
+                                ));
+                            }
+                        }
+                    });
+            }
+        }
+    }
+}
+
+// Square of paved floor right past the finish line, fenced in on every side
+fn spawn_boss_arena(
+    commands: &mut Commands,
+    chunks: &mut HashMap<i32, RoadChunkData>,
+    road_tiles: &mut RoadTiles,
+    boss_arena: &mut BossArena,
+    texture_handle: &GlobalTextureHandle,
+    center_x: i32,
+    bottom_y: i32,
+) {
+    let tile_size = TILE_W as f32 * ROAD_SCALE;
+    let half_tiles = BOSS_ARENA_TILES / 2;
+    for i in (center_x - half_tiles)..=(center_x + half_tiles) {
+        for j in bottom_y..(bottom_y + BOSS_ARENA_TILES) {
+            road_tiles.0.insert((i, j), RoadSurface::Asphalt);
+            let pos = vec3(i as f32 * tile_size, j as f32 * tile_size, 0.0);
+            let chunk = chunks.entry(road_chunk_of(j)).or_default();
+            chunk.sprites.push(RoadSprite {
+                color: BOSS_ARENA_FLOOR_COLOR,
+                ..RoadSprite::new(SpriteId::Road, pos, ROAD_SCALE)
+            });
+        }
+    }
+
+    boss_arena.center = vec2(
+        center_x as f32 * tile_size,
+        (bottom_y + half_tiles) as f32 * tile_size,
+    );
+    boss_arena.half_size = half_tiles as f32 * tile_size;
+    boss_arena.target_half_size = boss_arena.half_size;
+
+    // Posts get placed by update_boss_arena as the fences close in
+    let num_posts = (boss_arena.half_size * 2.0 / BOSS_ARENA_FENCE_SPACING) as usize;
+    for side in 0..4 {
+        for k in 0..=num_posts {
+            commands.spawn((
+                SpriteSheetBundle {
+                    texture_atlas: texture_handle.atlas(SpriteId::Block.atlas()),
+                    sprite: TextureAtlasSprite {
+                        index: SpriteId::Block.index(),
+                        color: COLOR_BROWN,
+                        ..default()
+                    },
+                    transform: Transform::from_scale(Vec3::splat(2.5)),
+                    // The gate stays open until the car is in
+                    visibility: if side == 0 {
+                        Visibility::Hidden
+                    } else {
+                        Visibility::Inherited
+                    },
+                    ..default()
+                },
+                ArenaFence {
+                    side,
+                    t: k as f32 / num_posts as f32 * 2.0 - 1.0,
+                },
+                GameEntity,
+            ));
+        }
+    }
+}
+
+fn mark_game_setup_done(mut game_state: ResMut<NextState<GameState>>) {
+    game_state.set(GameState::InGame);
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn shoot_gun(
+    time: Res<Time>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    buttons: Res<Input<MouseButton>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    texture_handle: Res<GlobalTextureHandle>,
+    car_query: Query<(&Transform, Option<&HeldWeapon>), With<Car>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    gamepad_aim: Res<GamepadAim>,
+    settings: Res<GameSettings>,
+    profile: Res<Profile>,
+    mut gun_cooldown: ResMut<GunCooldown>,
+    mut writer_noise: EventWriter<NoiseEvent>,
+    (tuning, difficulty, mut status_ammo, auto_fire, mut car_recoil): (
+        Res<Tuning>,
+        Res<DifficultyProfile>,
+        ResMut<StatusAmmo>,
+        Res<AutoFire>,
+        ResMut<CarRecoil>,
+    ),
+) {
+    gun_cooldown.fire.tick(time.delta());
+    if car_query.is_empty() || gun_cooldown.lockout > 0.0 {
+        return;
+    }
+    let (is_mouse_fire, is_gamepad_fire) = if settings.auto_fire {
+        (
+            auto_fire.is_firing && !auto_fire.from_gamepad,
+            auto_fire.is_firing && auto_fire.from_gamepad,
+        )
+    } else {
+        (
+            buttons.pressed(MouseButton::Left),
+            gamepads.iter().any(|gamepad| {
+                gamepad_input.pressed(GamepadButton::new(gamepad, GamepadButtonType::RightTrigger))
+            }),
+        )
+    };
+    if !is_mouse_fire && !is_gamepad_fire {
+        return;
+    }
+    let Ok((car_transform, held_weapon)) = car_query.get_single() else {
+        return;
+    };
+    let weapon = held_weapon.map_or(profile.weapon, |held| held.0);
+    if gun_cooldown.fire.elapsed_secs() < weapon.fire_interval(&tuning) {
+        return;
+    }
+    gun_cooldown.fire.reset();
+    gun_cooldown.heat += weapon.heat(&tuning).per_shot;
+
+    let (camera, camera_transform) = q_camera.single();
+    let window = q_window.single();
+    let mut cursor_pos = None;
+    if let Some(world_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+        .map(|ray| ray.origin.truncate())
+    {
+        cursor_pos = Some(vec3(world_position.x, world_position.y, 0.0));
+    }
+
+    let (x, y) = (car_transform.translation.x, car_transform.translation.y);
+    writer_noise.send(NoiseEvent {
+        pos: vec2(x, y),
+        radius: ZOMBIE_GUNSHOT_NOISE_RADIUS * difficulty.gunshot_hearing,
+    });
+    let direction = if !is_mouse_fire && gamepad_aim.0 != Vec2::ZERO {
+        gamepad_aim.0.extend(0.0)
+    } else {
+        match cursor_pos {
+            Some(cursor_pos) => cursor_pos - car_transform.translation,
+            None => car_transform.local_y(),
+        }
+    };
+    car_recoil.impulse -= direction.truncate().normalize_or_zero() * weapon.recoil(&tuning);
+
+    if cfg!(feature = "audio") && settings.music {
+        commands.spawn(AudioBundle {
+            source: asset_server.load("bulletfire.mp3"),
+            settings: PlaybackSettings {
+                volume: Volume::Absolute(VolumeLevel::new(0.05)),
+                ..Default::default()
+            },
+        });
+    }
+    // Pellets fan out evenly around the aim direction
+    let num_pellets = weapon.num_pellets();
+    let has_ricochet = profile.upgrades.contains(&Upgrade::Ricochet);
+    // One special round per trigger pull, every pellet carries it
+    let status_kind = status_ammo.kind.filter(|_| status_ammo.rounds > 0);
+    if status_kind.is_some() {
+        status_ammo.rounds -= 1;
+    }
+    for i in 0..num_pellets {
+        let spread = (i as f32 - (num_pellets - 1) as f32 / 2.0) * 0.12;
+        let mut bullet = commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(SpriteId::Bullet.atlas()),
+                sprite: TextureAtlasSprite {
+                    index: SpriteId::Bullet.index(),
+                    color: status_kind.map_or(Color::WHITE, |kind| kind.color()),
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec3::splat(2.0))
+                    .with_translation(vec3(x, y, 15.0)),
+                ..default()
+            },
+            Bullet(Stopwatch::new()),
+            BulletDirection(Quat::from_rotation_z(spread) * direction),
+            GameEntity,
+        ));
+        if has_ricochet {
+            bullet.insert(Ricochet { bounced: false });
+        }
+        if let Some(kind) = status_kind {
+            bullet.insert(StatusRound(kind));
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_car_recoil(
+    time: Res<Time>,
+    run_config: Res<RunConfig>,
+    mut car_recoil: ResMut<CarRecoil>,
+    mut car_query: Query<(&mut Transform, &mut Speed), (With<Car>, Without<Airborne>)>,
+) {
+    let Ok((mut transform, mut speed)) = car_query.get_single_mut() else {
+        *car_recoil = CarRecoil::default();
+        return;
+    };
+    if matches!(run_config.difficulty, Difficulty::Easy) {
+        *car_recoil = CarRecoil::default();
+        return;
+    }
+
+    // Along the heading the kick speeds the car up or slows it down, sideways it drifts
+    let heading = (transform.rotation * Vec3::Y).truncate();
+    let impulse = std::mem::take(&mut car_recoil.impulse);
+    let along = impulse.dot(heading);
+    speed.0 += along;
+    car_recoil.drift += (impulse - heading * along) * CAR_RECOIL_DRIFT_SCALE;
+
+    let dt = time.delta_seconds();
+    transform.translation += (car_recoil.drift * dt).extend(0.0);
+    car_recoil.drift *= (-CAR_RECOIL_DRIFT_DECAY * dt).exp();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_drone(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    profile: Res<Profile>,
+    zombie_grid: Res<ZombieGrid>,
+    texture_handle: Res<GlobalTextureHandle>,
+    car_query: Query<&Transform, (With<Car>, Without<Drone>)>,
+    mut drone_query: Query<(Entity, &mut Drone, &mut Transform)>,
+) {
+    let enabled = settings.drone && profile.upgrades.contains(&Upgrade::Drone);
+    let Some(car_transform) = car_query.get_single().ok().filter(|_| enabled) else {
+        // Toggled off, or there's no car left to follow
+        for (e, ..) in drone_query.iter() {
+            commands.entity(e).despawn();
+        }
+        return;
+    };
+    let target = car_transform.translation.truncate() + DRONE_OFFSET;
+
+    let Ok((_, mut drone, mut transform)) = drone_query.get_single_mut() else {
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(SpriteId::Block.atlas()),
+                sprite: TextureAtlasSprite {
+                    index: SpriteId::Block.index(),
+                    color: DRONE_COLOR,
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec3::splat(1.8))
+                    .with_translation(target.extend(16.0)),
+                ..default()
+            },
+            Drone {
+                fire: Stopwatch::new(),
+                bob: Stopwatch::new(),
+                hover: target,
+            },
+            GameEntity,
+        ));
+        return;
+    };
+
+    // Trails behind with a little lag, bobbing up and down on its rotors
+    let t = (DRONE_FOLLOW_SPEED * time.delta_seconds()).min(1.0);
+    drone.hover = drone.hover.lerp(target, t);
+    drone.bob.tick(time.delta());
+    let bob =
+        (drone.bob.elapsed_secs() * DRONE_BOB_HZ * std::f32::consts::TAU).sin() * DRONE_BOB_HEIGHT;
+    transform.translation.x = drone.hover.x;
+    transform.translation.y = drone.hover.y + bob;
+
+    drone.fire.tick(time.delta());
+    if drone.fire.elapsed_secs() < DRONE_FIRE_INTERVAL_SEC {
+        return;
+    }
+    let Some(zombie_pos) = zombie_grid.closest_zombie(drone.hover, DRONE_RANGE) else {
+        return;
+    };
+    drone.fire.reset();
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: texture_handle.atlas(SpriteId::Bullet.atlas()),
+            sprite: TextureAtlasSprite {
+                index: SpriteId::Bullet.index(),
+                color: DRONE_COLOR,
+                ..default()
+            },
+            transform: Transform::from_scale(Vec3::splat(1.2))
+                .with_translation(drone.hover.extend(15.0)),
+            ..default()
+        },
+        Bullet(Stopwatch::new()),
+        BulletDirection((zombie_pos - drone.hover).extend(0.0)),
+        DroneShot,
+        GameEntity,
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_gun_heat(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut gun_cooldown: ResMut<GunCooldown>,
+    car_query: Query<Option<&HeldWeapon>, With<Car>>,
+    profile: Res<Profile>,
+    tuning: Res<Tuning>,
+) {
+    let Ok(held_weapon) = car_query.get_single() else {
+        return;
+    };
+    let heat = held_weapon
+        .map_or(profile.weapon, |held| held.0)
+        .heat(&tuning);
+    let dt = time.delta_seconds();
+
+    if gun_cooldown.lockout > 0.0 {
+        gun_cooldown.lockout = (gun_cooldown.lockout - dt).max(0.0);
+        return;
+    }
+    if gun_cooldown.cold_barrel > 0.0 {
+        gun_cooldown.cold_barrel = (gun_cooldown.cold_barrel - dt).max(0.0);
+        gun_cooldown.heat = 0.0;
+        return;
+    }
+
+    // Dumping the heat early locks the gun for less than letting it overheat
+    let is_vent = keyboard_input.just_pressed(KeyCode::R)
+        || gamepads.iter().any(|gamepad| {
+            gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::West))
+        });
+    if is_vent && gun_cooldown.heat > 0.0 {
+        gun_cooldown.lockout = gun_cooldown.heat * heat.overheat_sec * tuning.vent_lockout_factor;
+        gun_cooldown.heat = 0.0;
+        return;
+    }
+
+    if gun_cooldown.heat >= 1.0 {
+        gun_cooldown.lockout = heat.overheat_sec;
+        gun_cooldown.heat = 0.0;
+        return;
+    }
+    gun_cooldown.heat = (gun_cooldown.heat - heat.cool_per_sec * dt).max(0.0);
+}
+
+fn update_bullet(
+    time: Res<Time>,
+    mut bullets_query: Query<(&mut Transform, &BulletDirection), With<Bullet>>,
+    tuning: Res<Tuning>,
+) {
+    // Moved every frame by game time, unlike zombies there are no skipped turns to glide over
+    for (mut transform, bullet_direction) in bullets_query.iter_mut() {
+        transform.translation += Vec3::splat(tuning.bullet_speed * time.delta_seconds())
+            * (bullet_direction.0.normalize());
+        transform.translation.z = 15.0;
+    }
+}
+
+fn bullet_hit_obstacle(
+    mut commands: Commands,
+    obstacles: Res<VehicleObstacleTiles>,
+    mut bullets_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut BulletDirection,
+            Option<&mut Ricochet>,
+        ),
+        With<Bullet>,
+    >,
+) {
+    for (e, mut t, mut direction, ricochet) in bullets_query.iter_mut() {
+        let hit = obstacles
+            .0
+            .iter()
+            .find_map(|obstacle| obstacle.penetration(t.translation.truncate()));
+        let Some((normal, depth)) = hit else {
+            continue;
+        };
+
+        // Bounce off the side that was hit, only once
+        match ricochet {
+            Some(mut ricochet) if !ricochet.bounced => {
+                ricochet.bounced = true;
+                let dir = direction.0.truncate();
+                direction.0 = (dir - 2.0 * dir.dot(normal) * normal).extend(0.0);
+                t.translation += (normal * depth).extend(0.0);
+            }
+            _ => commands.entity(e).despawn(),
+        }
+    }
+}
+
+fn bullet_damage(ricochet: Option<&Ricochet>, is_drone_shot: bool) -> f32 {
+    match ricochet {
+        _ if is_drone_shot => DRONE_SHOT_DAMAGE,
+        Some(Ricochet { bounced: true }) => RICOCHET_DAMAGE,
+        _ => 1.0,
+    }
+}
+
+fn despawn_bullets(
+    mut commands: Commands,
+    time: Res<Time>,
+    run_config: Res<RunConfig>,
+    mut bullets_query: Query<(Entity, &Transform, &mut Bullet)>,
+) {
+    // Ticked on game time so bullets keep their range through pauses and slow-mo
+    for (entity, t, mut bullet) in bullets_query.iter_mut() {
+        bullet.0.tick(time.delta());
+        // The dust eats up a bullet's range
+        let lifetime = if dust_storm_intensity(&run_config, t.translation.y) > 0.0 {
+            BULLET_TIME * DUST_STORM_BULLET_FACTOR
+        } else {
+            BULLET_TIME
+        };
+        if bullet.0.elapsed_secs() > lifetime {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_player_dead_event(
+    mut commands: Commands,
+    mut player_dead_event: EventReader<PlayerDeadEvent>,
+    mut writer_player_out: EventWriter<PlayerOutEvent>,
+    mut on_foot_escape: ResMut<OnFootEscape>,
+    mut car_health: ResMut<CarHealth>,
+    mut car_armor: ResMut<CarArmor>,
+    mut car_query: Query<(Entity, &Transform, &mut TextureAtlasSprite), With<Car>>,
+    texture_handle: Res<GlobalTextureHandle>,
+    asset_server: Res<AssetServer>,
+    tuning: Res<Tuning>,
+    run_config: Res<RunConfig>,
+    road_tiles: Res<RoadTiles>,
+    obstacles: Res<VehicleObstacleTiles>,
+) {
+    if player_dead_event.is_empty() {
+        return;
+    }
+
+    // No spare car waiting in the arena
+    player_dead_event.clear();
+    if on_foot_escape.used || car_query.is_empty() || run_config.arena {
+        writer_player_out.send(PlayerOutEvent);
+        return;
+    }
+
+    // The car broke down, the survivor gets one chance to run for the spare vehicle
+    on_foot_escape.active = true;
+    on_foot_escape.used = true;
+    on_foot_escape.timer.reset();
+    car_health.0 = tuning.on_foot_health;
+    car_armor.0 = 0.0;
+
+    let (car, car_transform, mut car_sprite) = car_query.single_mut();
+    let (x, y) = (car_transform.translation.x, car_transform.translation.y);
+    car_sprite.color = Color::GRAY;
+    commands
+        .entity(car)
+        .remove::<(Car, Speed, TurnSpeed, Turbo)>();
+
+    // The survivor takes over the Car marker so the horde, camera and gun follow them
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: texture_handle.atlas(SpriteId::Survivor.atlas()),
+            sprite: TextureAtlasSprite {
+                index: SpriteId::Survivor.index(),
+                color: COLOR_ORANGE,
+                ..default()
+            },
+            transform: Transform::from_scale(Vec3::splat(2.5)).with_translation(vec3(
+                x + 40.0,
+                y,
+                10.0,
+            )),
+            ..default()
+        },
+        Car,
+        OnFoot,
+        GameEntity,
+    ));
+    let spare_pos =
+        spare_vehicle_spot(&road_tiles, &obstacles, vec2(x, y + SPARE_VEHICLE_DISTANCE));
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: texture_handle.atlas(SpriteId::Hatchback.atlas()),
+            sprite: TextureAtlasSprite::new(SpriteId::Hatchback.index()),
+            transform: Transform::from_scale(Vec3::splat(3.0))
+                .with_translation(spare_pos.extend(10.0)),
+            ..default()
+        },
+        SpareVehicle,
+        GameEntity,
+    ));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 40.0,
+                        font: asset_server.load("font.ttf"),
+                        color: COLOR_LIGHT_ORANGE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center)
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(32.0)),
+                    ..default()
+                }),
+                GameUIOnFootTimer,
+            ));
+        });
+}
+
+fn on_foot_input_system(
+    time: Res<Time<Real>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut player_query: Query<&mut Transform, (With<Car>, With<OnFoot>)>,
+    tuning: Res<Tuning>,
+    run_config: Res<RunConfig>,
+) {
+    if player_query.is_empty() {
+        return;
+    }
+
+    let mut transform = player_query.single_mut();
+    let mut direction = Vec2::ZERO;
+    if keyboard_input.pressed(KeyCode::W) || keyboard_input.pressed(KeyCode::Up) {
+        direction.y += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::S) || keyboard_input.pressed(KeyCode::Down) {
+        direction.y -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::A) || keyboard_input.pressed(KeyCode::Left) {
+        direction.x -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::D) || keyboard_input.pressed(KeyCode::Right) {
+        direction.x += 1.0;
+    }
+
+    // Keys move the way they point on screen, whichever way the camera is turned
+    let direction = run_config.screen_to_world(direction).extend(0.0);
+    transform.translation +=
+        direction.normalize_or_zero() * tuning.on_foot_speed * time.delta_seconds();
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_on_foot_escape(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut on_foot_escape: ResMut<OnFootEscape>,
+    mut car_health: ResMut<CarHealth>,
+    mut writer_player_out: EventWriter<PlayerOutEvent>,
+    player_query: Query<(Entity, &Transform), (With<Car>, With<OnFoot>)>,
+    spare_vehicle_query: Query<(Entity, &Transform), (With<SpareVehicle>, Without<Car>)>,
+    mut timer_text_query: Query<(&mut Text, &Parent), With<GameUIOnFootTimer>>,
+    tuning: Res<Tuning>,
+) {
+    if !on_foot_escape.active || player_query.is_empty() || spare_vehicle_query.is_empty() {
+        return;
+    }
+
+    on_foot_escape.timer.tick(time.delta());
+    let time_left = ON_FOOT_ESCAPE_TIME_SEC - on_foot_escape.timer.elapsed_secs();
+    if time_left <= 0.0 {
+        on_foot_escape.active = false;
+        writer_player_out.send(PlayerOutEvent);
+        return;
+    }
+
+    let (player, player_transform) = player_query.single();
+    let (spare_vehicle, spare_vehicle_transform) = spare_vehicle_query.single();
+    let distance = player_transform
+        .translation
+        .truncate()
+        .distance(spare_vehicle_transform.translation.truncate());
+
+    if distance > PICKUP_RADIUS {
+        if let Ok((mut text, _)) = timer_text_query.get_single_mut() {
+            text.sections[0].value = format!("Reach the spare car!\n{:.1}", time_left);
+        }
+        return;
+    }
+
+    // Made it, back on the road with whatever the spare car has left
+    on_foot_escape.active = false;
+    car_health.0 = tuning.max_car_health * tuning.spare_vehicle_health;
+    commands.entity(player).despawn();
+    commands
+        .entity(spare_vehicle)
+        .remove::<SpareVehicle>()
+        .insert((Car, Speed(0.0), TurnSpeed(0.0), Turbo(Stopwatch::new())));
+    if let Ok((_, parent)) = timer_text_query.get_single() {
+        commands.entity(parent.get()).despawn_recursive();
+    }
+}
+
+fn setup_start_zone(
+    mut commands: Commands,
+    mut countdown: ResMut<StartCountdown>,
+    asset_server: Res<AssetServer>,
+    run_config: Res<RunConfig>,
+) {
+    countdown.0.reset();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 120.0,
+                        font: asset_server.load("font.ttf"),
+                        color: COLOR_LIGHT_ORANGE,
+                    },
+                ),
+                StartBanner,
+            ));
+        });
+
+    // The score arena has its own fences
+    if run_config.arena {
+        return;
+    }
+
+    // Walls along both road edges up to the gates, the road starts ROAD_WIDTH + 1 tiles wide
+    let tile = TILE_W as f32 * ROAD_SCALE;
+    let (left, right) = (-tile / 2.0, ROAD_WIDTH as f32 * tile + tile / 2.0);
+    let wall_height = START_GATE_Y - START_WALL_BOTTOM_Y;
+    for x in [
+        left - START_WALL_THICKNESS / 2.0,
+        right + START_WALL_THICKNESS / 2.0,
+    ] {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: COLOR_BROWN,
+                    custom_size: Some(vec2(START_WALL_THICKNESS, wall_height)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(x, START_WALL_BOTTOM_Y + wall_height / 2.0, 2.0),
+                ..default()
+            },
+            GameEntity,
+        ));
+    }
+
+    let half_width = (right - left) / 2.0;
+    for side in [-1.0, 1.0] {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: COLOR_ORANGE,
+                    custom_size: Some(vec2(half_width, START_GATE_THICKNESS)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(
+                    (left + right) / 2.0 + side * half_width / 2.0,
+                    START_GATE_Y,
+                    2.0,
+                ),
+                ..default()
+            },
+            StartGate(side),
+            GameEntity,
+        ));
+    }
+}
+
+fn update_start_zone(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut countdown: ResMut<StartCountdown>,
+    mut gate_query: Query<(Entity, &mut Transform, &StartGate)>,
+    mut banner_query: Query<(&mut Text, &Parent), With<StartBanner>>,
+) {
+    let prev = countdown.0.elapsed_secs();
+    if prev >= START_COUNTDOWN_SEC + START_GO_SEC {
+        return;
+    }
+    countdown.0.tick(time.delta());
+    let elapsed = countdown.0.elapsed_secs();
+
+    if elapsed >= START_COUNTDOWN_SEC + START_GO_SEC {
+        for (e, _, _) in gate_query.iter() {
+            commands.entity(e).despawn();
+        }
+        for (_, parent) in banner_query.iter() {
+            commands.entity(parent.get()).despawn_recursive();
+        }
+        return;
+    }
+
+    let label = if elapsed < START_COUNTDOWN_SEC {
+        format!("{}", (START_COUNTDOWN_SEC - elapsed).ceil() as u32)
+    } else {
+        "GO!".to_string()
+    };
+    for (mut text, _) in banner_query.iter_mut() {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+
+    // Both halves slide out of the way by their own width over the GO! banner
+    if elapsed > START_COUNTDOWN_SEC {
+        let dt = elapsed - prev.max(START_COUNTDOWN_SEC);
+        let width = (ROAD_WIDTH + 1) as f32 * TILE_W as f32 * ROAD_SCALE / 2.0;
+        for (_, mut transform, gate) in gate_query.iter_mut() {
+            transform.translation.x += gate.0 * width * dt / START_GO_SEC;
+        }
+    }
+}
+
+fn setup_commandeer_prompt(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    align_items: AlignItems::End,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        "E - Take the parked car",
+                        TextStyle {
+                            font_size: 30.0,
+                            font: asset_server.load("font.ttf"),
+                            color: COLOR_LIGHT_ORANGE,
+                        },
+                    ),
+                    style: Style {
+                        margin: UiRect::bottom(Val::Px(80.0)),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                CommandeerPrompt,
+            ));
+        });
+}
+
+fn setup_toll_gate_prompt(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    align_items: AlignItems::End,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 30.0,
+                            font: asset_server.load("font.ttf"),
+                            color: COLOR_LIGHT_ORANGE,
+                        },
+                    ),
+                    style: Style {
+                        margin: UiRect::bottom(Val::Px(120.0)),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                TollGatePrompt,
+            ));
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn update_toll_gates(
+    time: Res<Time>,
+    mut writer_noise: EventWriter<NoiseEvent>,
+    mut car_query: Query<(&mut Transform, Option<&mut Speed>), With<Car>>,
+    mut gate_query: Query<(&Transform, &mut TollGate), Without<Car>>,
+    mut half_query: Query<
+        (&mut Transform, &TollGateHalf, &Parent),
+        (Without<Car>, Without<TollGate>),
+    >,
+    mut fill_query: Query<
+        (&mut Transform, &Parent),
+        (
+            With<TollGateFill>,
+            Without<Car>,
+            Without<TollGate>,
+            Without<TollGateHalf>,
+        ),
+    >,
+    mut prompt_query: Query<(&mut Text, &mut Visibility), With<TollGatePrompt>>,
+) {
+    let Ok((mut car_transform, mut speed)) = car_query.get_single_mut() else {
+        return;
+    };
+
+    let mut prompt = None;
+    for (transform, mut gate) in gate_query.iter_mut() {
+        let center = transform.translation.truncate();
+        let car_pos = car_transform.translation.truncate();
+
+        // Solid until fully open, same bounce as the arena fences
+        let stop_y = center.y - TOLL_GATE_STOP_DIST;
+        if gate.open < 1.0 && car_pos.y > stop_y && car_pos.y < center.y + TOLL_GATE_THICKNESS {
+            car_transform.translation.y = stop_y;
+            if let Some(speed) = speed.as_mut() {
+                speed.0 = -6.0;
+            }
+        }
+
+        let is_opened = gate.hold >= TOLL_GATE_HOLD_SEC;
+        if is_opened {
+            gate.open = (gate.open + time.delta_seconds() / TOLL_GATE_OPEN_SEC).min(1.0);
+            continue;
+        }
+
+        let is_holding = car_pos.distance(gate.panel) <= TOLL_PANEL_RADIUS
+            && speed
+                .as_ref()
+                .is_none_or(|s| s.0.abs() <= TOLL_PANEL_MAX_SPEED);
+        if is_holding {
+            gate.hold += time.delta_seconds();
+            gate.alarm += time.delta_seconds();
+            if gate.alarm >= TOLL_GATE_ALARM_INTERVAL_SEC {
+                gate.alarm = 0.0;
+                writer_noise.send(NoiseEvent {
+                    pos: gate.panel,
+                    radius: TOLL_GATE_ALARM_RADIUS,
+                });
+            }
+        }
+        if car_pos.distance(center) <= TOLL_GATE_PROMPT_RANGE {
+            prompt = Some(if is_holding {
+                format!(
+                    "Opening the gate - {:.0}%",
+                    gate.hold / TOLL_GATE_HOLD_SEC * 100.0
+                )
+            } else {
+                "Stop at the blue panel to open the gate".to_string()
+            });
+        }
+    }
+
+    for (mut transform, half, parent) in half_query.iter_mut() {
+        if let Ok((_, gate)) = gate_query.get(parent.get()) {
+            transform.translation.x = half.0 * gate.width * (0.25 + gate.open / 2.0);
+        }
+    }
+    for (mut transform, parent) in fill_query.iter_mut() {
+        if let Ok((_, gate)) = gate_query.get(parent.get()) {
+            transform.scale.x = (gate.hold / TOLL_GATE_HOLD_SEC).min(1.0);
+        }
+    }
+
+    for (mut text, mut visibility) in prompt_query.iter_mut() {
+        visibility.set_if_neq(if prompt.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+        if let Some(prompt) = prompt.as_ref().filter(|p| **p != text.sections[0].value) {
+            text.sections[0].value = prompt.clone();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_finish_barrier(
+    time: Res<Time>,
+    mut writer_noise: EventWriter<NoiseEvent>,
+    mut car_query: Query<(&mut Transform, Option<&mut Speed>, Has<OnFoot>), With<Car>>,
+    mut barrier_query: Query<(&Transform, &mut FinishBarrier), Without<Car>>,
+    mut mover_query: Query<
+        (&mut Transform, &mut Sprite, &Parent),
+        (With<FinishMover>, Without<Car>, Without<FinishBarrier>),
+    >,
+    mut fill_query: Query<
+        (&mut Transform, &Parent),
+        (
+            With<FinishFill>,
+            Without<Car>,
+            Without<FinishBarrier>,
+            Without<FinishMover>,
+        ),
+    >,
+    mut prompt_query: Query<(&mut Text, &mut Visibility), With<FinishPrompt>>,
+) {
+    let Ok((mut car_transform, mut speed, on_foot)) = car_query.get_single_mut() else {
+        return;
+    };
+
+    let mut prompt = None;
+    for (transform, mut barrier) in barrier_query.iter_mut() {
+        let center = transform.translation.truncate();
+        let car_pos = car_transform.translation.truncate();
+        let dt = time.delta_seconds();
+
+        // Solid until fully open, same bounce as the toll gates
+        let stop_y = center.y - barrier.depth - FINISH_STOP_DIST;
+        if barrier.open < 1.0 && car_pos.y > stop_y && car_pos.y < center.y + barrier.depth {
+            let ram_speed = speed.as_ref().map_or(0.0, |s| s.0);
+            if barrier.layout == FinishLayout::Bunker && ram_speed >= BUNKER_RAM_MIN_SPEED {
+                barrier.health -= ram_speed * BUNKER_RAM_DAMAGE_PER_SPEED;
+                writer_noise.send(NoiseEvent {
+                    pos: center,
+                    radius: FINISH_ALARM_RADIUS,
+                });
+            }
+            car_transform.translation.y = stop_y;
+            if let Some(speed) = speed.as_mut() {
+                speed.0 = -6.0;
+            }
+        }
+
+        let is_done = match barrier.layout {
+            FinishLayout::Ferry => barrier.hold >= FERRY_WAIT_SEC,
+            FinishLayout::Drawbridge => barrier.hold >= DRAWBRIDGE_LEVER_HOLD_SEC,
+            FinishLayout::Bunker => barrier.health <= 0.0,
+        };
+        let is_near = car_pos.distance(center) <= FINISH_PROMPT_RANGE;
+        if is_done {
+            barrier.open = (barrier.open + dt / barrier.layout.open_sec()).min(1.0);
+            if is_near && barrier.open < 1.0 {
+                prompt = Some(
+                    match barrier.layout {
+                        FinishLayout::Ferry => "The ferry is coming in",
+                        FinishLayout::Drawbridge => "The bridge is coming down",
+                        FinishLayout::Bunker => "The gate is giving way",
+                    }
+                    .to_string(),
+                );
+            }
+            continue;
+        }
+
+        // The ferry only needs the car on the dock, the lever needs it stopped
+        let is_holding = match barrier.layout {
+            FinishLayout::Ferry => {
+                (car_pos.x - center.x).abs() <= barrier.width / 2.0
+                    && car_pos.y >= center.y - barrier.depth - FERRY_DOCK_HEIGHT
+            }
+            FinishLayout::Drawbridge => {
+                car_pos.distance(barrier.lever) <= DRAWBRIDGE_LEVER_RADIUS
+                    && speed
+                        .as_ref()
+                        .is_none_or(|s| s.0.abs() <= TOLL_PANEL_MAX_SPEED)
+            }
+            FinishLayout::Bunker => {
+                on_foot
+                    && (car_pos.x - center.x).abs() <= barrier.width / 2.0
+                    && car_pos.y >= stop_y - BUNKER_PRY_RANGE
+            }
+        };
+        if is_holding {
+            if barrier.layout == FinishLayout::Bunker {
+                barrier.health -= dt * BUNKER_GATE_HEALTH / BUNKER_PRY_SEC;
+            }
+            barrier.hold += dt;
+            barrier.alarm += dt;
+            if barrier.alarm >= FINISH_ALARM_INTERVAL_SEC {
+                barrier.alarm = 0.0;
+                writer_noise.send(NoiseEvent {
+                    pos: car_pos,
+                    radius: FINISH_ALARM_RADIUS,
+                });
+            }
+        }
+        if is_near || car_pos.distance(barrier.lever) <= FINISH_PROMPT_RANGE {
+            prompt = Some(match barrier.layout {
+                FinishLayout::Ferry if is_holding => format!(
+                    "Ferry arriving in {:.0}s, hold the dock",
+                    (FERRY_WAIT_SEC - barrier.hold).ceil()
+                ),
+                FinishLayout::Drawbridge if is_holding => format!(
+                    "Lowering the bridge - {:.0}%",
+                    barrier.hold / DRAWBRIDGE_LEVER_HOLD_SEC * 100.0
+                ),
+                FinishLayout::Bunker if on_foot => format!(
+                    "Stand at the gate to pry it open - {:.0}% left",
+                    barrier.health / BUNKER_GATE_HEALTH * 100.0
+                ),
+                FinishLayout::Bunker => format!(
+                    "Ram the gate at full speed - {:.0}% left",
+                    barrier.health / BUNKER_GATE_HEALTH * 100.0
+                ),
+                layout => layout.briefing().to_string(),
+            });
+        }
+    }
+
+    for (mut transform, mut sprite, parent) in mover_query.iter_mut() {
+        let Ok((_, barrier)) = barrier_query.get(parent.get()) else {
+            continue;
+        };
+        match barrier.layout {
+            FinishLayout::Ferry => {
+                transform.translation.x = barrier.width * 1.5 * (1.0 - barrier.open)
+            }
+            FinishLayout::Drawbridge => transform.scale.y = barrier.open,
+            FinishLayout::Bunker => {
+                // Darker with every hit, then it caves in
+                let shade = 0.5 + 0.5 * (barrier.health / BUNKER_GATE_HEALTH).max(0.0);
+                sprite.color = Color::rgb(
+                    BUNKER_GATE_COLOR.r() * shade,
+                    BUNKER_GATE_COLOR.g() * shade,
+                    BUNKER_GATE_COLOR.b() * shade,
+                );
+                transform.scale.y = 1.0 - barrier.open;
+            }
+        }
+    }
+    for (mut transform, parent) in fill_query.iter_mut() {
+        if let Ok((_, barrier)) = barrier_query.get(parent.get()) {
+            transform.scale.x = match barrier.layout {
+                FinishLayout::Ferry => barrier.hold / FERRY_WAIT_SEC,
+                FinishLayout::Drawbridge => barrier.hold / DRAWBRIDGE_LEVER_HOLD_SEC,
+                FinishLayout::Bunker => barrier.health / BUNKER_GATE_HEALTH,
+            }
+            .clamp(0.0, 1.0);
+        }
+    }
+
+    for (mut text, mut visibility) in prompt_query.iter_mut() {
+        visibility.set_if_neq(if prompt.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+        if let Some(prompt) = prompt.as_ref().filter(|p| **p != text.sections[0].value) {
+            text.sections[0].value = prompt.clone();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn commandeer_vehicle(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut car_health: ResMut<CarHealth>,
+    mut car_armor: ResMut<CarArmor>,
+    mut obstacles: ResMut<VehicleObstacleTiles>,
+    mut road_stream: ResMut<RoadStream>,
+    texture_handle: Res<GlobalTextureHandle>,
+    mut car_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut TextureAtlasSprite,
+            Option<&Children>,
+        ),
+        (With<Car>, Without<OnFoot>, Without<Airborne>),
+    >,
+    obstacle_query: Query<(Entity, &Transform), (With<Obstacle>, Without<Car>)>,
+    mut latched_query: Query<
+        (Entity, &mut Transform, &mut ZombieAttack),
+        (With<Latched>, Without<Car>, Without<Obstacle>),
+    >,
+    mut prompt_query: Query<&mut Visibility, With<CommandeerPrompt>>,
+    tuning: Res<Tuning>,
+) {
+    let Ok((car, car_transform, mut car_sprite, children)) = car_query.get_single_mut() else {
+        return;
+    };
+
+    let car_pos = car_transform.translation.truncate();
+    let is_nearly_dead = car_health.0 <= tuning.max_car_health * COMMANDEER_HEALTH_THRESHOLD;
+    let nearest = obstacles
+        .0
+        .iter()
+        .enumerate()
+        .filter_map(|(i, obstacle)| {
+            let model = obstacle.vehicle_type.drivable_model()?;
+            let distance = vec2(obstacle.pos.0, obstacle.pos.1).distance(car_pos);
+            (distance <= COMMANDEER_RADIUS).then_some((i, model, distance))
+        })
+        .min_by_key(|(_, _, distance)| FloatOrd(*distance))
+        .filter(|_| is_nearly_dead);
+    for mut visibility in prompt_query.iter_mut() {
+        visibility.set_if_neq(if nearest.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    let Some((i, model, _)) = nearest else {
+        return;
+    };
+    if !keyboard_input.just_pressed(KeyCode::E) {
+        return;
+    }
+
+    // The parked car leaves the road for good, streamed back in chunks included
+    let obstacle = obstacles.0.swap_remove(i);
+    let pos = vec2(obstacle.pos.0, obstacle.pos.1);
+    let row = (pos.y / (TILE_H as f32 * ROAD_SCALE)).round() as i32;
+    if let Some(chunk) = road_stream.chunks.get_mut(&road_chunk_of(row)) {
+        chunk
+            .obstacles
+            .retain(|sprite| sprite.pos.truncate().distance(pos) > 1.0);
+    }
+    for (e, t) in obstacle_query.iter() {
+        if t.translation.truncate().distance(pos) <= 1.0 {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+
+    // Anyone hanging on gets left behind with the old car
+    if let Some(children) = children {
+        let mut latched = latched_query.iter_many_mut(children);
+        while let Some((e, mut t, mut attack)) = latched.fetch_next() {
+            let world_pos = car_transform.mul_transform(*t).translation.truncate();
+            *t = Transform::from_scale(Vec3::splat(attack.base_scale))
+                .with_translation(world_pos.extend(1.0));
+            attack.cooldown = GRAB_REGRAB_COOLDOWN_SEC;
+            commands.entity(e).remove::<Latched>().remove_parent();
+        }
+    }
+
+    // The abandoned car is a wreck in the road now
+    car_sprite.color = Color::GRAY;
+    commands
+        .entity(car)
+        .remove::<(Car, Speed, TurnSpeed, Turbo, Commandeered)>();
+    obstacles.0.push(VehicleObstacle {
+        pos: (car_pos.x, car_pos.y),
+        vehicle_type: VehicleObstacleType::Car1,
+    });
+
+    car_health.0 = tuning.max_car_health * tuning.commandeer_health;
+    car_armor.0 = 0.0;
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: texture_handle.atlas(obstacle.vehicle_type.sprite().atlas()),
+            sprite: TextureAtlasSprite::new(obstacle.vehicle_type.sprite().index()),
+            transform: Transform::from_scale(Vec3::splat(3.0)).with_translation(pos.extend(10.0)),
+            ..default()
+        },
+        Car,
+        Speed(0.0),
+        TurnSpeed(0.0),
+        Turbo(Stopwatch::new()),
+        Commandeered(model),
+        GameEntity,
+    ));
+}
+
+fn update_tide_blobs(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    zombie_grid: Res<ZombieGrid>,
+    car_query: Query<&Transform, (With<Car>, Without<TideBlob>)>,
+    mut blob_query: Query<
+        (&mut Transform, &mut TextureAtlasSprite, &mut Visibility),
+        With<TideBlob>,
+    >,
+) {
+    let car_pos = car_query
+        .get_single()
+        .ok()
+        .filter(|_| settings.tide_rendering)
+        .map(|t| t.translation.truncate());
+    let mut cells = zombie_grid.0.iter().filter_map(|(cell, zombies)| {
+        let car_pos = car_pos?;
+        // Centered on the zombies so the blob doesn't snap to the grid
+        let center = zombies.iter().map(|(_, pos)| *pos).sum::<Vec2>() / zombies.len() as f32;
+        let dist = center.distance(car_pos);
+        (dist > TIDE_RADIUS && dist <= TIDE_MAX_RANGE).then_some((*cell, center, zombies.len()))
+    });
+
+    let elapsed = time.elapsed_seconds();
+    let (first_tile, num_tiles) = (SpriteId::Zombie.index(), SpriteId::Zombie.variants());
+    for (mut transform, mut sprite, mut visibility) in blob_query.iter_mut() {
+        let Some(((x, y), center, count)) = cells.next() else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+
+        // Each cell gets its own phase so the tide doesn't sway in lockstep
+        let phase =
+            ((x.wrapping_mul(73_856_093) ^ y.wrapping_mul(19_349_663)) & 0xff) as f32 / 40.0;
+        let t = elapsed * TIDE_WOBBLE_SPEED + phase;
+        let scale = (TIDE_BLOB_SCALE * (count as f32).sqrt()).min(TIDE_BLOB_MAX_SCALE)
+            * (1.0 + t.sin() * TIDE_PULSE_SCALE);
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+        transform.rotation = Quat::from_rotation_z((t * 0.7).sin() * TIDE_WOBBLE_ANGLE);
+        transform.scale = Vec3::splat(scale);
+        let frame = (elapsed / TIDE_FRAME_SEC + phase) as usize;
+        sprite.index = first_tile + frame % num_tiles;
+        *visibility = Visibility::Visible;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn cull_zombies(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    cam_query: Query<(&Camera, &Transform, &OrthographicProjection)>,
+    car_query: Query<&Transform, (With<Car>, Without<Zombie>)>,
+    zombie_query: Query<(Entity, &Transform, Has<Culled>), (With<Zombie>, Without<Latched>)>,
+) {
+    // The minimap and rear view count as views too, zombies show up on them
+    let views = cam_query
+        .iter()
+        .filter(|(camera, _, _)| camera.is_active)
+        .map(|(_, t, projection)| {
+            // Bounds of the view after the camera's rotation
+            let half = projection.area.half_size();
+            let extent = (t.rotation * Vec3::X).truncate().abs() * half.x
+                + (t.rotation * Vec3::Y).truncate().abs() * half.y;
+            Rect::from_center_half_size(t.translation.truncate(), extent).inset(ZOMBIE_CULL_MARGIN)
+        })
+        .collect::<Vec<_>>();
+    if views.is_empty() {
+        return;
+    }
+
+    // The tide blobs stand in for everything past the radius
+    let tide_center = car_query
+        .get_single()
+        .ok()
+        .filter(|_| settings.tide_rendering)
+        .map(|t| t.translation.truncate());
+    // Only the marker is set here, sync_horde_visibility hides the sprite
+    for (e, t, is_culled) in zombie_query.iter() {
+        let pos = t.translation.truncate();
+        let is_in_view = views.iter().any(|view| view.contains(pos))
+            && tide_center.is_none_or(|center| pos.distance(center) <= TIDE_RADIUS);
+        if is_in_view && is_culled {
+            commands.entity(e).remove::<Culled>();
+        } else if !is_in_view && !is_culled {
+            commands.entity(e).insert(Culled);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn camera_follow_player(
+    time: Res<Time>,
+    mut reversing_sec: Local<f32>,
+    car_query: Query<(&Transform, Option<&Speed>), With<Car>>,
+    mut cam_query: Query<(&Camera, &mut Transform), (With<MainCamera>, Without<Car>)>,
+) {
+    if car_query.is_empty() {
+        return;
+    }
+    if cam_query.is_empty() {
+        return;
+    }
+
+    let (car_transform, speed) = car_query.single();
+    if speed.is_some_and(|speed| speed.0 < 0.0) {
+        *reversing_sec += time.delta_seconds();
+    } else {
+        *reversing_sec = 0.0;
+    }
+    // Backing out of a pocket, show what's behind instead
+    let lead = if *reversing_sec > CAMERA_REVERSE_DELAY_SEC {
+        CAMERA_REVERSE_LEAD_Y
+    } else {
+        CAMERA_LEAD_Y
+    };
+
+    let (x, y) = (car_transform.translation.x, car_transform.translation.y);
+    let (_, mut transform) = cam_query.single_mut();
+    transform.translation = transform.translation.lerp(vec3(x, y + lead, 0.0), 0.05);
+}
+
+#[allow(clippy::type_complexity)]
+fn orient_main_camera(
+    state: Res<State<GameState>>,
+    history: Res<MenuHistory>,
+    run_config: Res<RunConfig>,
+    mut cam_query: Query<&mut Transform, With<MainCamera>>,
+    mut text_query: Query<&mut Transform, (With<Text>, Without<Node>, Without<MainCamera>)>,
+) {
+    // Menus always look at the world the normal way up
+    let in_run = matches!(
+        state.get(),
+        GameState::GameInit
+            | GameState::InGame
+            | GameState::PauseMenu
+            | GameState::CheckpointShop
+            | GameState::GameOver
+    ) || history.in_run();
+    let (rotation, scale) = if in_run {
+        run_config.camera_view()
+    } else {
+        (Quat::IDENTITY, Vec3::ONE)
+    };
+
+    for mut transform in cam_query.iter_mut() {
+        if transform.rotation != rotation || transform.scale != scale {
+            transform.rotation = rotation;
+            transform.scale = scale;
+        }
+    }
+
+    // World text turns with the camera so it still reads the right way
+    for mut t in text_query.iter_mut() {
+        let text_scale = t.scale.abs() * scale;
+        if t.rotation != rotation || t.scale != text_scale {
+            t.rotation = rotation;
+            t.scale = text_scale;
+        }
+    }
+}
+
+fn update_auto_fire(
+    keyboard_input: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    settings: Res<GameSettings>,
+    mut auto_fire: ResMut<AutoFire>,
+) {
+    if !settings.auto_fire {
+        auto_fire.is_firing = false;
+        return;
+    }
+    let is_gamepad_toggle = gamepads.iter().any(|gamepad| {
+        gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::RightTrigger))
+    });
+    if is_gamepad_toggle
+        || buttons.just_pressed(MouseButton::Left)
+        || keyboard_input.just_pressed(KeyCode::Q)
+    {
+        auto_fire.is_firing = !auto_fire.is_firing;
+        auto_fire.from_gamepad = is_gamepad_toggle;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_crosshair(
+    settings: Res<GameSettings>,
+    mut q_window: Query<&mut Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    car_query: Query<&Transform, (With<Car>, Without<AimLine>)>,
+    mut crosshair_query: Query<
+        (&mut Style, &mut UiTextureAtlasImage, &mut Visibility),
+        With<CrosshairImage>,
+    >,
+    mut aim_line_query: Query<
+        (&mut Transform, &mut Visibility),
+        (With<AimLine>, Without<CrosshairImage>),
+    >,
+) {
+    let Ok(mut window) = q_window.get_single_mut() else {
+        return;
+    };
+    let sprite = settings.crosshair.sprite();
+    let is_cursor_visible = sprite.is_none();
+    if window.cursor.visible != is_cursor_visible {
+        window.cursor.visible = is_cursor_visible;
+    }
+
+    let cursor = window.cursor_position();
+    if let Ok((mut style, mut image, mut visibility)) = crosshair_query.get_single_mut() {
+        match sprite.zip(cursor) {
+            Some((sprite, cursor)) => {
+                style.left = Val::Px(cursor.x - CROSSHAIR_SIZE / 2.0);
+                style.top = Val::Px(cursor.y - CROSSHAIR_SIZE / 2.0);
+                image.index = sprite.index();
+                visibility.set_if_neq(Visibility::Visible);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+
+    let Ok((mut transform, mut visibility)) = aim_line_query.get_single_mut() else {
+        return;
+    };
+    let target = cursor.filter(|_| settings.aim_line).and_then(|cursor| {
+        let (camera, camera_transform) = q_camera.get_single().ok()?;
+        camera.viewport_to_world_2d(camera_transform, cursor)
+    });
+    let (Some(target), Ok(car_transform)) = (target, car_query.get_single()) else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    let from = car_transform.translation.truncate();
+    let delta = target - from;
+    transform.translation.x = from.x;
+    transform.translation.y = from.y;
+    transform.rotation = Quat::from_rotation_z(delta.y.atan2(delta.x));
+    transform.scale = vec3(delta.length(), AIM_LINE_WIDTH, 1.0);
+    visibility.set_if_neq(Visibility::Visible);
+}
+
+// Menus and the game over screen get the OS cursor back
+#[allow(clippy::type_complexity)]
+fn hide_crosshair(
+    mut q_window: Query<&mut Window, With<PrimaryWindow>>,
+    mut crosshair_query: Query<&mut Visibility, Or<(With<CrosshairImage>, With<AimLine>)>>,
+) {
+    if let Ok(mut window) = q_window.get_single_mut() {
+        window.cursor.visible = true;
+    }
+    for mut visibility in crosshair_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn update_gamepad_aim(
+    time: Res<Time<Real>>,
+    gamepads: Res<Gamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+    mut gamepad_aim: ResMut<GamepadAim>,
+) {
+    // The right stick nudges a crosshair around the car
+    for gamepad in gamepads.iter() {
+        let axis = |axis_type| {
+            gamepad_axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or_default()
+        };
+        let stick = apply_dead_zone(
+            vec2(
+                axis(GamepadAxisType::RightStickX),
+                axis(GamepadAxisType::RightStickY),
+            ),
+            settings.stick_dead_zone,
+        );
+        gamepad_aim.0 += run_config.screen_to_world(stick)
+            * GAMEPAD_AIM_SPEED
+            * settings.aim_sensitivity
+            * time.delta_seconds();
+    }
+    gamepad_aim.0 = gamepad_aim.0.clamp_length_max(GAMEPAD_AIM_MAX_DISTANCE);
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn car_manual_input_system(
+    time: Res<Time<Real>>,
+    road_tiles: Res<RoadTiles>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_triggers: Res<Axis<GamepadButton>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    settings: Res<GameSettings>,
+    profile: Res<Profile>,
+    mut car_query: Query<
+        (
+            &mut Speed,
+            &mut TurnSpeed,
+            &mut Transform,
+            &Turbo,
+            Option<&Children>,
+            Option<&Commandeered>,
+        ),
+        With<Car>,
+    >,
+    latched_query: Query<With<Latched>>,
+    tuning: Res<Tuning>,
+    (benchmark, run_config): (Res<Benchmark>, Res<RunConfig>),
+) {
+    if car_query.is_empty() {
+        return;
+    }
+
+    let (mut speed, mut turn_speed, mut transform, turbo, children, commandeered) =
+        car_query.single_mut();
+    let mut controls = if benchmark.active {
+        benchmark_controls(&road_tiles, &transform)
+    } else {
+        let cursor = q_window
+            .get_single()
+            .ok()
+            .and_then(|window| window.cursor_position())
+            .zip(q_camera.get_single().ok())
+            .and_then(|(cursor, (camera, camera_transform))| {
+                camera.viewport_to_world_2d(camera_transform, cursor)
+            });
+        read_car_controls(
+            &keyboard_input,
+            &gamepads,
+            &gamepad_axes,
+            &gamepad_triggers,
+            &mouse_buttons,
+            cursor,
+            &transform,
+            &settings,
+        )
+    };
+
+    let surface = road_surface_at(&road_tiles, transform.translation);
+    let is_on_road = surface.is_some();
+
+    // Every zombie hanging on weighs the car down
+    let num_latched = children.map_or(0, |children| {
+        children
+            .iter()
+            .filter(|child| latched_query.contains(**child))
+            .count()
+    }) as f32;
+    controls.steer *= 1.0 - num_latched * GRAB_STEER_PENALTY;
+    if run_config.has(Modifier::MirroredControls) && !benchmark.active {
+        controls.steer = -controls.steer;
+    }
+    let model = commandeered.map_or(profile.car, |c| c.0);
+    let max_speed = model.max_speed(&tuning) * (1.0 - num_latched * GRAB_SPEED_PENALTY);
+
+    // Off-road handling is covered by the movement factor below
+    update_car_input(
+        controls,
+        &mut turn_speed,
+        &mut speed,
+        max_speed,
+        surface.unwrap_or_default(),
+        &tuning,
+        &time,
+    );
+
+    if turbo.0.elapsed().as_secs_f32() < 0.2 {
+        speed.0 += tuning.turbo_boost;
+    }
+
+    let time_step = 1.0 / 60.0;
+    let rotation_factor = turn_speed.0;
+    let movement_factor = if is_on_road {
+        speed.0 * 0.1
+    } else {
+        speed.0 * 0.05
+    };
+
+    if speed.0.abs() > MIN_SPEED_TO_STEER {
+        transform.rotate_z(rotation_factor * 0.1 * time_step);
+    }
+    let movement_direction = transform.rotation * Vec3::Y;
+    let movement_distance = movement_factor;
+    let translation_delta = movement_direction * movement_distance;
+    transform.translation += translation_delta * time.delta_seconds() * 100.0;
+}
+
+// Surface under a world position, None when off the road
+fn road_surface_at(road_tiles: &RoadTiles, pos: Vec3) -> Option<RoadSurface> {
+    let (x, y) = (
+        pos.x / (TILE_W as f32 * ROAD_SCALE),
+        pos.y / (TILE_H as f32 * ROAD_SCALE),
+    );
+    let (x1, y1) = (x.ceil() as i32, y.ceil() as i32);
+    let (x2, y2) = (x.floor() as i32, y.floor() as i32);
+    road_tiles
+        .0
+        .get(&(x1, y1))
+        .or_else(|| road_tiles.0.get(&(x2, y2)))
+        .copied()
+}
+
+// Full throttle, steering for the middle of the road a few rows ahead
+fn benchmark_controls(road_tiles: &RoadTiles, transform: &Transform) -> CarControls {
+    let (tile_w, tile_h) = (TILE_W as f32 * ROAD_SCALE, TILE_H as f32 * ROAD_SCALE);
+    let pos = transform.translation.truncate();
+    let (col, row) = (
+        (pos.x / tile_w).round() as i32,
+        (pos.y / tile_h).round() as i32 + BENCHMARK_LOOKAHEAD_ROWS,
+    );
+    let Some(center_x) = road_center_x(road_tiles, col, row) else {
+        return CarControls {
+            throttle: 1.0,
+            steer: 0.0,
+        };
+    };
+
+    let target = vec2(center_x, row as f32 * tile_h);
+    let heading = (transform.rotation * Vec3::Y).truncate();
+    // Positive when the target is to the left
+    let steer = heading.perp_dot((target - pos).normalize_or_zero());
+    CarControls {
+        throttle: 1.0,
+        steer: (steer * 2.0).clamp(-1.0, 1.0),
+    }
+}
+
+// Road tile nearest to where the spare car should wait that no parked car is sitting on,
+// falls back to the spot itself when the road there hasn't streamed in
+fn spare_vehicle_spot(
+    road_tiles: &RoadTiles,
+    obstacles: &VehicleObstacleTiles,
+    wanted: Vec2,
+) -> Vec2 {
+    let (tile_w, tile_h) = (TILE_W as f32 * ROAD_SCALE, TILE_H as f32 * ROAD_SCALE);
+    let (col, row) = (
+        (wanted.x / tile_w).round() as i32,
+        (wanted.y / tile_h).round() as i32,
+    );
+    let reach = ROAD_WIDTH as i32 * 2;
+    let mut cols = (col - reach..=col + reach).collect::<Vec<_>>();
+    cols.sort_by_key(|c| (c - col).abs());
+
+    // Rows ahead first, then behind, closest columns first in each
+    (0..=SPARE_VEHICLE_SEARCH_ROWS)
+        .flat_map(|d| [row + d, row - d])
+        .flat_map(|r| cols.iter().map(move |c| (*c, r)))
+        .filter(|tile| road_tiles.0.contains_key(tile))
+        .map(|(c, r)| vec2(c as f32 * tile_w, r as f32 * tile_h))
+        .find(|pos| {
+            obstacles
+                .0
+                .iter()
+                .all(|o| vec2(o.pos.0, o.pos.1).distance(*pos) > SPARE_VEHICLE_CLEARANCE)
+        })
+        .unwrap_or(wanted)
+}
+
+// World x of the middle of the road on a row, looking around the given column
+fn road_center_x(road_tiles: &RoadTiles, col: i32, row: i32) -> Option<f32> {
+    let reach = ROAD_WIDTH as i32 * 2;
+    let cols = (col - reach..=col + reach)
+        .filter(|x| road_tiles.0.contains_key(&(*x, row)))
+        .collect::<Vec<_>>();
+    if cols.is_empty() {
+        return None;
+    }
+    let center = cols.iter().sum::<i32>() as f32 / cols.len() as f32;
+    Some(center * TILE_W as f32 * ROAD_SCALE)
+}
+
+// Input mapping layer, the stick dead zone and steering sensitivity apply here
+#[allow(clippy::too_many_arguments)]
+fn read_car_controls(
+    keyboard_input: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    gamepad_axes: &Axis<GamepadAxis>,
+    gamepad_triggers: &Axis<GamepadButton>,
+    mouse_buttons: &Input<MouseButton>,
+    cursor: Option<Vec2>,
+    transform: &Transform,
+    settings: &GameSettings,
+) -> CarControls {
+    let w_key = keyboard_input.pressed(KeyCode::W) || keyboard_input.pressed(KeyCode::Up);
+    let a_key = keyboard_input.pressed(KeyCode::A) || keyboard_input.pressed(KeyCode::Left);
+    let s_key = keyboard_input.pressed(KeyCode::S) || keyboard_input.pressed(KeyCode::Down);
+    let d_key = keyboard_input.pressed(KeyCode::D) || keyboard_input.pressed(KeyCode::Right);
+    let mut controls = CarControls {
+        throttle: if s_key {
+            -1.0
+        } else if w_key {
+            1.0
+        } else {
+            0.0
+        },
+        steer: if a_key {
+            1.0
+        } else if d_key {
+            -1.0
+        } else {
+            0.0
+        },
+    };
+
+    // The nose follows the cursor, left-click stays free for the gun
+    if settings.mouse_steering {
+        if mouse_buttons.pressed(MouseButton::Right) && !s_key {
+            controls.throttle = 1.0;
+        }
+        let to_cursor = cursor.map_or(Vec2::ZERO, |cursor| {
+            cursor - transform.translation.truncate()
+        });
+        if to_cursor.length() > MOUSE_STEER_DEAD_RADIUS && !a_key && !d_key {
+            let heading = (transform.rotation * Vec3::Y).truncate();
+            // Positive when the cursor is to the left
+            let angle = heading.angle_between(to_cursor);
+            controls.steer =
+                (angle * MOUSE_STEER_GAIN * settings.steer_sensitivity).clamp(-1.0, 1.0);
+        }
+    }
+
+    // Left stick steers, the triggers drive and brake
+    for gamepad in gamepads.iter() {
+        let axis = |axis_type| {
+            gamepad_axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or_default()
+        };
+        let trigger = |button_type| {
+            gamepad_triggers
+                .get(GamepadButton::new(gamepad, button_type))
+                .unwrap_or_default()
+        };
+        let stick = apply_dead_zone(
+            vec2(
+                axis(GamepadAxisType::LeftStickX),
+                axis(GamepadAxisType::LeftStickY),
+            ),
+            settings.stick_dead_zone,
+        );
+        let steer = (-stick.x * settings.steer_sensitivity).clamp(-1.0, 1.0);
+        let throttle =
+            trigger(GamepadButtonType::RightTrigger2) - trigger(GamepadButtonType::LeftTrigger2);
+        if steer.abs() > controls.steer.abs() {
+            controls.steer = steer;
+        }
+        if throttle.abs() > controls.throttle.abs() {
+            controls.throttle = throttle;
+        }
+    }
+
+    controls
+}
+
+// Rescales the stick so it starts from zero just outside the dead zone
+fn apply_dead_zone(stick: Vec2, dead_zone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= dead_zone {
+        return Vec2::ZERO;
+    }
+
+    stick / length * ((length - dead_zone) / (1.0 - dead_zone)).min(1.0)
+}
+
+// Next value up in a settings cycle, wrapping back to the lowest
+fn next_step(steps: &[f32], value: f32) -> f32 {
+    steps
+        .iter()
+        .copied()
+        .find(|step| *step > value + f32::EPSILON)
+        .unwrap_or(steps[0])
+}
+
+fn update_car_input(
+    controls: CarControls,
+    turn_speed: &mut TurnSpeed,
+    speed: &mut Speed,
+    max_speed: f32,
+    surface: RoadSurface,
+    tuning: &Tuning,
+    time: &Time<Real>,
+) {
+    turn_speed.0 = tuning.turn_speed * surface.grip() * controls.steer;
+
+    // Friction code from: https://github.com/Rust-Ninja-Sabi/bevyastro
+    let friction = tuning.friction * surface.braking();
+    speed.0 = if controls.throttle < 0.0 {
+        if speed.0.abs() <= 10.0 {
+            0.0
+        } else {
+            speed.0 - friction * time.delta_seconds() * 1.2 * -controls.throttle
+        }
+    } else if controls.throttle > 0.0 {
+        speed.0
+            + tuning.car_thrust * surface.acceleration() * time.delta_seconds() * controls.throttle
+    } else {
+        if speed.0.abs() <= 5.0 {
+            // Avoid speed from over shooting
+            // and be non zero all the time
+            0.0
+        } else if speed.0 > 0.0 {
+            speed.0 - friction * time.delta_seconds()
+        } else if speed.0 < 0.0 {
+            speed.0 + friction * time.delta_seconds()
+        } else {
+            0.0
+        }
+    };
+
+    speed.0 = speed.0.clamp(-max_speed + max_speed / 2.0, max_speed);
+}
+
+impl ZombieScheduler {
+    fn next_spawn_order(&mut self) -> SpawnOrder {
+        self.next_spawn_order += 1;
+        SpawnOrder(self.next_spawn_order)
+    }
+}
+
+impl AdaptiveQuality {
+    fn max_zombies(&self, settings: &GameSettings) -> usize {
+        let fraction = ADAPTIVE_MIN_ZOMBIE_FRACTION + (1.0 - ADAPTIVE_MIN_ZOMBIE_FRACTION) * self.0;
+        (settings.get_num_max_zombies() as f32 * fraction) as usize
+    }
+
+    fn ai_stride(&self) -> u32 {
+        1 + ((1.0 - self.0) * (ADAPTIVE_MAX_AI_STRIDE - 1) as f32).round() as u32
+    }
+}
+
+impl CorpsePile {
+    fn radius(&self) -> f32 {
+        (8.0 + 2.0 * self.size as f32).min(CORPSE_PILE_MAX_RADIUS)
+    }
+}
+
+impl ZombieGrid {
+    fn cell(pos: Vec2) -> (i32, i32) {
+        (
+            (pos.x / ZOMBIE_GRID_CELL).floor() as i32,
+            (pos.y / ZOMBIE_GRID_CELL).floor() as i32,
+        )
+    }
+
+    fn closest_zombie(&self, pos: Vec2, radius: f32) -> Option<Vec2> {
+        let (cx, cy) = Self::cell(pos);
+        let reach = (radius / ZOMBIE_GRID_CELL).ceil() as i32;
+        ((cx - reach)..=(cx + reach))
+            .flat_map(|x| ((cy - reach)..=(cy + reach)).map(move |y| (x, y)))
+            .filter_map(|cell| self.0.get(&cell))
+            .flatten()
+            .map(|(_, p)| *p)
+            .filter(|p| p.distance_squared(pos) <= radius.powi(2))
+            .min_by(|a, b| a.distance_squared(pos).total_cmp(&b.distance_squared(pos)))
+    }
+
+    fn zombies_near(&self, pos: Vec2, radius: f32) -> Vec<Entity> {
+        let (cx, cy) = Self::cell(pos);
+        let reach = (radius / ZOMBIE_GRID_CELL).ceil() as i32;
+        let mut zombies = Vec::new();
+        for x in (cx - reach)..=(cx + reach) {
+            for y in (cy - reach)..=(cy + reach) {
+                let Some(cell) = self.0.get(&(x, y)) else {
+                    continue;
+                };
+                zombies.extend(
+                    cell.iter()
+                        .filter(|(_, p)| p.distance_squared(pos) <= radius.powi(2))
+                        .map(|(e, _)| *e),
+                );
+            }
+        }
+
+        zombies
+    }
+}
+
+impl RunRng {
+    fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    fn stream(&mut self, stream: RngStream) -> &mut ChaCha8Rng {
+        let seed = stream.seed(self.seed);
+        self.streams
+            .entry(stream)
+            .or_insert_with(|| ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+impl RngStream {
+    fn seed(&self, run_seed: u64) -> u64 {
+        let salt: u64 = match self {
+            // Seed codes shared before the split still lay the same road
+            Self::Road => return run_seed,
+            Self::ZombieSpawns => 1,
+            Self::Loot => 2,
+            Self::Ai => 3,
+            Self::Finish => 4,
+        };
+        // splitmix64, nearby seeds still end up with unrelated streams
+        let mut z = run_seed.wrapping_add(salt.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl ShopItem {
+    const ALL: [ShopItem; 3] = [
+        ShopItem::Repair,
+        ShopItem::IncendiaryRounds,
+        ShopItem::ColdBarrel,
+    ];
+
+    fn cost(&self) -> u32 {
+        match self {
+            Self::Repair => SHOP_REPAIR_COST,
+            Self::IncendiaryRounds => SHOP_ROUNDS_COST,
+            Self::ColdBarrel => SHOP_COLD_BARREL_COST,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Repair => "Repair",
+            Self::IncendiaryRounds => "Incendiary Rounds",
+            Self::ColdBarrel => "Cold Barrel",
+        }
+    }
+}
+
+impl AirdropKind {
+    fn sprite(&self) -> SpriteId {
+        match self {
+            Self::Crate => SpriteId::Crate,
+            Self::Debris => SpriteId::Debris,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Crate => Color::rgb(0.75, 0.55, 0.3),
+            Self::Debris => AIRDROP_DEBRIS_COLOR,
+        }
+    }
+}
+
+impl StatusKind {
+    fn random(rng: &mut impl Rng) -> Self {
+        match rng.gen_range(0..3) {
+            0 => Self::Frozen,
+            1 => Self::Burning,
+            _ => Self::Stunned,
+        }
+    }
+
+    fn duration(&self) -> f32 {
+        match self {
+            Self::Frozen => FROZEN_SEC,
+            Self::Burning => BURNING_SEC,
+            Self::Stunned => STUNNED_SEC,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Frozen => FROZEN_COLOR,
+            Self::Burning => BURNING_COLOR,
+            Self::Stunned => STUNNED_COLOR,
+        }
+    }
+
+    // Frozen and stunned zombies stand still and can't bite
+    fn holds_zombie(&self) -> bool {
+        matches!(self, Self::Frozen | Self::Stunned)
+    }
+}
+
+impl StatusEffect {
+    fn new(kind: StatusKind, base_color: Color) -> Self {
+        Self {
+            kind,
+            left: kind.duration(),
+            base_color,
+            spread: BURNING_SPREAD_INTERVAL_SEC,
+        }
+    }
+}
+
+impl CorpsePileGrid {
+    fn cell(pos: Vec2) -> (i32, i32) {
+        (
+            (pos.x / CORPSE_GRID_CELL).floor() as i32,
+            (pos.y / CORPSE_GRID_CELL).floor() as i32,
+        )
+    }
+
+    fn blocking_pile_at(&self, pos: Vec2) -> Option<(Vec2, f32)> {
+        let (cx, cy) = Self::cell(pos);
+        for x in (cx - 1)..=(cx + 1) {
+            for y in (cy - 1)..=(cy + 1) {
+                let Some(piles) = self.0.get(&(x, y)) else {
+                    continue;
+                };
+                if let Some(pile) = piles
+                    .iter()
+                    .find(|(center, radius)| center.distance(pos) < *radius)
+                {
+                    return Some(*pile);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl ObstacleGrid {
+    fn cell(pos: Vec2) -> (i32, i32) {
+        (
+            (pos.x / OBSTACLE_GRID_CELL).floor() as i32,
+            (pos.y / OBSTACLE_GRID_CELL).floor() as i32,
+        )
+    }
+
+    fn is_climbing(&self, pos: Vec2) -> bool {
+        let (cx, cy) = Self::cell(pos);
+        ((cx - 1)..=(cx + 1))
+            .flat_map(|x| ((cy - 1)..=(cy + 1)).map(move |y| (x, y)))
+            .filter_map(|cell| self.0.get(&cell))
+            .flatten()
+            .any(|center| {
+                let offset = (pos - *center).abs();
+                offset.x <= ZOMBIE_CLIMB_HALF_SIZE && offset.y <= ZOMBIE_CLIMB_HALF_SIZE
+            })
+    }
+
+    // The few parked cars that could stand between the listener and anything within reach
+    fn occluders_near(&self, pos: Vec2, reach: f32) -> Vec<Vec2> {
+        let max_dist = reach + SOUND_OCCLUDER_HALF_SIZE;
+        self.0
+            .values()
+            .flatten()
+            .copied()
+            .filter(|center| center.distance_squared(pos) <= max_dist.powi(2))
+            .collect()
+    }
+}
+
+// Slab test of the straight line between the two against each parked car's box
+fn is_sound_occluded(occluders: &[Vec2], from: Vec2, to: Vec2) -> bool {
+    let dir = to - from;
+    occluders.iter().any(|center| {
+        let (min, max) = (
+            *center - Vec2::splat(SOUND_OCCLUDER_HALF_SIZE),
+            *center + Vec2::splat(SOUND_OCCLUDER_HALF_SIZE),
+        );
+        // The zombie standing on the car is heard clearly
+        if from.cmpge(min).all() && from.cmple(max).all() {
+            return false;
+        }
+        let (mut t_min, mut t_max) = (0.0_f32, 1.0_f32);
+        for axis in 0..2 {
+            if dir[axis].abs() < f32::EPSILON {
+                if from[axis] < min[axis] || from[axis] > max[axis] {
+                    return false;
+                }
+                continue;
+            }
+            let (t0, t1) = (
+                (min[axis] - from[axis]) / dir[axis],
+                (max[axis] - from[axis]) / dir[axis],
+            );
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+        }
+        t_min <= t_max
+    })
+}
+
+impl RunTimer {
+    fn format(secs: f32) -> String {
+        format!("{}:{:05.2}", (secs / 60.0) as u32, secs % 60.0)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export(&self, run_config: &RunConfig) {
+        let export = SplitsExport {
+            seed: run_config.seed,
+            difficulty: run_config.difficulty,
+            run_length: run_config.run_length,
+            modifiers: &run_config.modifiers,
+            daily: run_config.daily,
+            boss_rush: run_config.boss_rush,
+            time: self.elapsed,
+            splits: &self.splits,
+        };
+        let result = ron::ser::to_string_pretty(&export, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|contents| std::fs::write(SPLITS_PATH, contents).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Failed to export splits: {e}");
+        }
+    }
+
+    // No filesystem on the web
+    #[cfg(target_arch = "wasm32")]
+    fn export(&self, _run_config: &RunConfig) {}
+}
+
+impl Benchmark {
+    fn start(&mut self, settings: &mut GameSettings) {
+        *self = Self {
+            active: true,
+            saved: Some((
+                settings.entity_count,
+                settings.god_mode,
+                settings.adaptive_quality,
+                settings.fps_cap,
+            )),
+            ..default()
+        };
+        // Same load every time, nothing thinning the horde or ending the run early
+        settings.entity_count = EntityCount::FiftyThousand;
+        settings.god_mode = true;
+        settings.adaptive_quality = false;
+        settings.fps_cap = FpsCap::Uncapped;
+    }
+
+    fn stop(&mut self, settings: &mut GameSettings) {
+        if let Some((entity_count, god_mode, adaptive_quality, fps_cap)) = self.saved.take() {
+            settings.entity_count = entity_count;
+            settings.god_mode = god_mode;
+            settings.adaptive_quality = adaptive_quality;
+            settings.fps_cap = fps_cap;
+        }
+        *self = Self::default();
+    }
+
+    fn csv_row(&self) -> String {
+        let frame_ms = sorted_frame_ms(&self.frame_times);
+        let frames = self.frame_times.len().max(1) as f32;
+        let avg_zombies = self.zombie_counts.iter().sum::<usize>() as f32 / frames;
+        let max_zombies = self.zombie_counts.iter().max().copied().unwrap_or_default();
+
+        format!(
+            "{},{:.1},{:.2},{:.2},{:.2},{:.2},{},{:.0},{}",
+            self.frame_times.len(),
+            frames / self.elapsed.max(f32::EPSILON),
+            percentile(&frame_ms, 0.5),
+            percentile(&frame_ms, 0.95),
+            percentile(&frame_ms, 0.99),
+            frame_ms.last().copied().unwrap_or_default(),
+            self.max_entities,
+            avg_zombies,
+            max_zombies,
+        )
+    }
+
+    // Appends a row to the csv, the header goes in when the file is new
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export(&self) {
+        use std::io::Write;
+
+        let is_new = !std::path::Path::new(BENCHMARK_PATH).exists();
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(BENCHMARK_PATH)
+            .and_then(|mut file| {
+                if is_new {
+                    writeln!(file, "{BENCHMARK_CSV_HEADER}")?;
+                }
+                writeln!(file, "{}", self.csv_row())
+            });
+        match result {
+            Ok(()) => info!("Benchmark results written to {BENCHMARK_PATH}"),
+            Err(e) => warn!("Failed to export benchmark: {e}"),
+        }
+    }
+
+    // No filesystem on the web, the browser console gets the csv instead
+    #[cfg(target_arch = "wasm32")]
+    fn export(&self) {
+        info!("{BENCHMARK_CSV_HEADER}\n{}", self.csv_row());
+    }
+}
+
+fn sorted_frame_ms(frame_times: &[f32]) -> Vec<f32> {
+    let mut frame_ms = frame_times.iter().map(|t| t * 1000.0).collect::<Vec<_>>();
+    frame_ms.sort_by(|a, b| a.total_cmp(b));
+    frame_ms
+}
+
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    let i = (sorted.len().saturating_sub(1) as f32 * p).round() as usize;
+    sorted.get(i).copied().unwrap_or_default()
+}
+
+impl RunTelemetry {
+    // Plain json, nothing in it points back to the player or the run's seed
+    fn json(
+        &self,
+        settings: &GameSettings,
+        run_config: &RunConfig,
+        outcome: RunOutcome,
+        progress: f32,
+        duration: f32,
+    ) -> String {
+        let frame_ms = sorted_frame_ms(&self.frame_times);
+        let fps = |p: f32| match percentile(&frame_ms, p) {
+            ms if ms > 0.0 => 1000.0 / ms,
+            _ => 0.0,
+        };
+        format!(
+            concat!(
+                "{{\"version\":\"{}\",\"difficulty\":\"{:?}\",\"run_length\":\"{:?}\",",
+                "\"entity_count\":\"{:?}\",\"max_zombies\":{},\"outcome\":\"{}\",",
+                "\"progress_pct\":{:.1},\"duration_sec\":{:.1},",
+                "\"fps_p50\":{:.1},\"fps_p5\":{:.1},\"fps_p1\":{:.1}}}"
+            ),
+            env!("CARGO_PKG_VERSION"),
+            run_config.difficulty,
+            run_config.run_length,
+            settings.entity_count,
+            self.max_zombies,
+            outcome.as_str(),
+            progress.clamp(0.0, 1.0) * 100.0,
+            duration,
+            fps(0.5),
+            fps(0.95),
+            fps(0.99),
+        )
+    }
+}
+
+// Fire and forget on a thread, a slow or missing server never holds up the game over screen
+#[cfg(all(feature = "telemetry", not(target_arch = "wasm32")))]
+fn post_telemetry(endpoint: &str, body: String) {
+    if !endpoint.starts_with("https://") {
+        warn!("Telemetry endpoint must start with https://, got {endpoint}");
+        return;
+    }
+    let endpoint = endpoint.to_string();
+
+    std::thread::spawn(move || {
+        let result = ureq::post(&endpoint)
+            .timeout(Duration::from_secs_f32(TELEMETRY_TIMEOUT_SEC))
+            .set("Content-Type", "application/json")
+            .send_string(&body);
+        if let Err(e) = result {
+            warn!("Failed to send run stats: {e}");
+        }
+    });
+}
+
+// The setting is hidden without the feature and in the browser, there's nothing to send with
+#[cfg(not(all(feature = "telemetry", not(target_arch = "wasm32"))))]
+fn post_telemetry(_endpoint: &str, _body: String) {}
+
+impl RunConfig {
+    // Runs on the same road compare splits, boss rush has its own ending
+    fn splits_key(&self) -> Option<RunLength> {
+        (!self.boss_rush && !self.arena).then_some(self.run_length)
+    }
+
+    fn new(
+        seed: u64,
+        difficulty: Difficulty,
+        run_length: RunLength,
+        modifiers: Vec<Modifier>,
+    ) -> Self {
+        Self {
+            seed,
+            difficulty,
+            run_length,
+            modifiers,
+            daily: None,
+            boss_rush: false,
+            arena: false,
+        }
+    }
+
+    // Which way the main camera looks at the world this run
+    fn camera_view(&self) -> (Quat, Vec3) {
+        if self.has(Modifier::ReversedRoad) {
+            (Quat::IDENTITY, vec3(1.0, -1.0, 1.0))
+        } else if self.has(Modifier::UpsideDownCamera) {
+            (Quat::from_rotation_z(std::f32::consts::PI), Vec3::ONE)
+        } else {
+            (Quat::IDENTITY, Vec3::ONE)
+        }
+    }
+
+    // Screen-relative input to a world direction, following the camera view
+    fn screen_to_world(&self, mut dir: Vec2) -> Vec2 {
+        if self.has(Modifier::MirroredControls) {
+            dir.x = -dir.x;
+        }
+        let (rotation, scale) = self.camera_view();
+        (rotation * (dir.extend(0.0) * scale)).truncate()
+    }
+
+    // Everything is derived from the date, so all players get the same run
+    fn daily(day: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(day);
+        let difficulty = match rng.gen_range(0..3) {
+            0 => Difficulty::Easy,
+            1 => Difficulty::Moderate,
+            _ => Difficulty::Hard,
+        };
+        let modifiers = [
+            Modifier::ExplodingZombies,
+            Modifier::NightOnly,
+            Modifier::DoubleSpeedZombies,
+            Modifier::NoTurbo,
+        ]
+        .into_iter()
+        .filter(|_| rng.gen_bool(0.4))
+        .collect();
+
+        // Everyone runs the same distance so the scores compare
+        Self {
+            seed: rng.gen(),
+            difficulty,
+            run_length: RunLength::Standard,
+            modifiers,
+            daily: Some(day),
+            boss_rush: false,
+            arena: false,
+        }
+    }
+
+    // In tiles, boss rush only has a short warm up before the arena
+    fn road_height(&self) -> usize {
+        if self.boss_rush {
+            BOSS_RUSH_ROAD_HEIGHT
+        } else {
+            self.run_length.road_height()
+        }
+    }
+
+    fn has(&self, modifier: Modifier) -> bool {
+        self.modifiers.contains(&modifier)
+    }
+
+    // Boss rush and the arena end their own way
+    fn finish_layout(&self) -> Option<FinishLayout> {
+        if self.boss_rush || self.arena {
+            return None;
+        }
+        let mut rng = ChaCha8Rng::seed_from_u64(RngStream::Finish.seed(self.seed));
+        Some(FinishLayout::ALL[rng.gen_range(0..FinishLayout::ALL.len())])
+    }
+
+    fn regenerates_health(&self) -> bool {
+        matches!(self.difficulty, Difficulty::Easy) || self.has(Modifier::RegeneratingArmor)
+    }
+
+    // Everything that shapes the run plus the car, so others can race the same setup
+    fn run_code(&self, car: CarModel) -> String {
+        let difficulty = match self.difficulty {
+            Difficulty::Easy => 0,
+            Difficulty::Moderate => 1,
+            Difficulty::Hard => 2,
+        };
+        let run_length = match self.run_length {
+            RunLength::Short => 0,
+            RunLength::Standard => 1,
+            RunLength::Marathon => 2,
+        };
+        let mode = if self.boss_rush {
+            1
+        } else if self.arena {
+            2
+        } else {
+            0
+        };
+        let car = match car {
+            CarModel::Hatchback => 0,
+            CarModel::Muscle => 1,
+            CarModel::Van => 2,
+        };
+        let modifiers = Modifier::ALL
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| self.has(**m))
+            .fold(0u16, |bits, (idx, _)| bits | 1 << idx);
+        format!(
+            "{RUN_CODE_PREFIX}-{:X}-{difficulty}{run_length}{mode}{car}-{modifiers:02X}",
+            self.seed
+        )
+    }
+
+    fn from_run_code(code: &str) -> Option<(Self, CarModel)> {
+        let mut parts = code.trim().split('-');
+        if parts.next()? != RUN_CODE_PREFIX {
+            return None;
+        }
+        let seed = u64::from_str_radix(parts.next()?, 16).ok()?;
+        let setup = parts
+            .next()?
+            .chars()
+            .map(|c| c.to_digit(10))
+            .collect::<Option<Vec<_>>>()?;
+        let modifier_bits = u16::from_str_radix(parts.next()?, 16).ok()?;
+        if setup.len() != 4 || parts.next().is_some() {
+            return None;
+        }
+
+        let difficulty = match setup[0] {
+            0 => Difficulty::Easy,
+            1 => Difficulty::Moderate,
+            2 => Difficulty::Hard,
+            _ => return None,
+        };
+        let run_length = match setup[1] {
+            0 => RunLength::Short,
+            1 => RunLength::Standard,
+            2 => RunLength::Marathon,
+            _ => return None,
+        };
+        let car = match setup[3] {
+            0 => CarModel::Hatchback,
+            1 => CarModel::Muscle,
+            2 => CarModel::Van,
+            _ => return None,
+        };
+        let modifiers = Modifier::ALL
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| modifier_bits & 1 << idx != 0)
+            .map(|(_, m)| m)
+            .collect();
+        let config = Self {
+            boss_rush: setup[2] == 1,
+            arena: setup[2] == 2,
+            ..Self::new(seed, difficulty, run_length, modifiers)
+        };
+        Some((config, car))
+    }
+
+    fn score_multiplier(&self) -> f32 {
+        mutators_score_multiplier(&self.modifiers)
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = vec![self.difficulty.as_str()];
+        parts.extend(self.modifiers.iter().map(|m| m.as_str()));
+        parts.join(", ")
+    }
+}
+
+impl Modifier {
+    const ALL: [Self; 10] = [
+        Self::ExplodingZombies,
+        Self::OneHitCar,
+        Self::InfiniteTurbo,
+        Self::NightOnly,
+        Self::DoubleSpeedZombies,
+        Self::NoTurbo,
+        Self::MirroredControls,
+        Self::ReversedRoad,
+        Self::UpsideDownCamera,
+        Self::RegeneratingArmor,
+    ];
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::ExplodingZombies => "Exploding Zombies",
+            Self::OneHitCar => "One-Hit Car",
+            Self::InfiniteTurbo => "Infinite Turbo",
+            Self::NightOnly => "Night Only",
+            Self::DoubleSpeedZombies => "Double Speed Hordes",
+            Self::NoTurbo => "No Turbo",
+            Self::MirroredControls => "Mirrored Controls",
+            Self::ReversedRoad => "Reversed Road",
+            Self::UpsideDownCamera => "Upside Down",
+            Self::RegeneratingArmor => "Regenerating Armor",
+        }
+    }
+
+    fn score_multiplier(&self) -> f32 {
+        match self {
+            Self::ExplodingZombies => 1.25,
+            Self::OneHitCar => 2.0,
+            Self::InfiniteTurbo => 0.5,
+            Self::NightOnly => 1.25,
+            Self::DoubleSpeedZombies => 1.5,
+            Self::NoTurbo => 1.25,
+            Self::MirroredControls => 1.5,
+            Self::ReversedRoad => 1.1,
+            Self::UpsideDownCamera => 1.25,
+            Self::RegeneratingArmor => 0.75,
+        }
+    }
+
+    // Modifiers that make no sense together
+    fn conflicts_with(&self) -> Option<Self> {
+        match self {
+            Self::InfiniteTurbo => Some(Self::NoTurbo),
+            Self::NoTurbo => Some(Self::InfiniteTurbo),
+            // Both flipped is just the road mirrored sideways
+            Self::ReversedRoad => Some(Self::UpsideDownCamera),
+            Self::UpsideDownCamera => Some(Self::ReversedRoad),
+            _ => None,
+        }
+    }
+}
+
+fn mutators_score_multiplier(modifiers: &[Modifier]) -> f32 {
+    modifiers.iter().map(|m| m.score_multiplier()).product()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn current_day() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or_default()
+}
+
+// std's clock isn't available in the browser
+#[cfg(target_arch = "wasm32")]
+fn current_day() -> u64 {
+    (js_sys::Date::now() / 86_400_000.0) as u64
+}
+
+// Days since the unix epoch as a calendar date, proleptic Gregorian
+fn date_from_day(day: u64) -> String {
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + (m <= 2) as i64;
+    format!("{y}-{m:02}-{d:02}")
+}
+
+// Milliseconds keep quick captures from overwriting each other
+#[cfg(not(target_arch = "wasm32"))]
+fn capture_stamp() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn capture_stamp() -> u128 {
+    js_sys::Date::now() as u128
+}
+
+fn take_screenshot(
+    keyboard_input: Res<Input<KeyCode>>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F12) {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(e) = std::fs::create_dir_all(CAPTURES_DIR) {
+        warn!("Failed to create {CAPTURES_DIR}: {e}");
+        return;
+    }
+    let path = format!("{CAPTURES_DIR}/screenshot-{}.png", capture_stamp());
+    if let Err(e) = screenshot_manager.save_screenshot_to_disk(window, path) {
+        warn!("Failed to take a screenshot: {e}");
+    }
+}
+
+#[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+fn record_gif_frames(
+    time: Res<Time<Real>>,
+    settings: Res<GameSettings>,
+    mut gif_capture: ResMut<GifCapture>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+) {
+    if !settings.clip_recording {
+        // Turned off mid run, a later clip shouldn't jump back to stale frames
+        if let Ok(mut frames) = gif_capture.frames.lock() {
+            frames.clear();
+        }
+        return;
+    }
+    gif_capture.since_frame += time.delta_seconds();
+    if gif_capture.since_frame < 1.0 / GIF_CAPTURE_FPS {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    // Scaled down right away so the ring stays small. The alpha channel holds brightness
+    // with HDR on, it's dropped like in bevy's own screenshots
+    let frames = gif_capture.frames.clone();
+    let result = screenshot_manager.take_screenshot(window, move |img| {
+        let Ok(img) = img.try_into_dynamic() else {
+            return;
+        };
+        let img = img
+            .resize(
+                GIF_CAPTURE_WIDTH,
+                u32::MAX,
+                image::imageops::FilterType::Triangle,
+            )
+            .to_rgb8();
+        let Ok(mut frames) = frames.lock() else {
+            return;
+        };
+        frames.push_back(img);
+        while frames.len() > GIF_CAPTURE_FRAMES {
+            frames.pop_front();
+        }
+    });
+    // A screenshot already took this frame, try again on the next one
+    if result.is_ok() {
+        gif_capture.since_frame = 0.0;
+    }
+}
+
+#[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+fn save_gif_capture(keyboard_input: Res<Input<KeyCode>>, gif_capture: Res<GifCapture>) {
+    if !keyboard_input.just_pressed(KeyCode::F9) {
+        return;
+    }
+    let Ok(frames) = gif_capture.frames.lock() else {
+        return;
+    };
+    if frames.is_empty() {
+        return;
+    }
+    let frames = frames.iter().cloned().collect::<Vec<_>>();
+    let path = std::path::Path::new(CAPTURES_DIR).join(format!("clip-{}.gif", capture_stamp()));
+
+    // Encoding takes a few seconds, the game keeps running meanwhile
+    std::thread::spawn(move || {
+        let delay = image::Delay::from_numer_denom_ms(1000, GIF_CAPTURE_FPS as u32);
+        let result = std::fs::create_dir_all(CAPTURES_DIR)
+            .and_then(|_| std::fs::File::create(&path))
+            .map_err(image::ImageError::IoError)
+            .and_then(|file| {
+                let mut encoder = image::codecs::gif::GifEncoder::new_with_speed(file, 10);
+                encoder.set_repeat(image::codecs::gif::Repeat::Infinite)?;
+                encoder.encode_frames(frames.into_iter().map(|frame| {
+                    let frame = image::DynamicImage::ImageRgb8(frame).to_rgba8();
+                    image::Frame::from_parts(frame, 0, 0, delay)
+                }))
+            });
+        match result {
+            Ok(()) => info!("Clip saved to {}", path.display()),
+            Err(e) => warn!("Failed to save the clip: {e}"),
+        }
+    });
+}
+
+impl VehicleObstacle {
+    fn new(pos: (f32, f32), rng: &mut impl Rng) -> Self {
+        Self {
+            pos,
+            vehicle_type: VehicleObstacleType::random(rng),
+        }
+    }
+
+    // Normal of the nearest side and how deep the point is past it, if inside
+    fn penetration(&self, point: Vec2) -> Option<(Vec2, f32)> {
+        let offset = point - vec2(self.pos.0, self.pos.1);
+        let depth = Vec2::splat(OBSTACLE_HALF_SIZE) - offset.abs();
+        if depth.x < 0.0 || depth.y < 0.0 {
+            return None;
+        }
+
+        if depth.x < depth.y {
+            Some((vec2(offset.x.signum(), 0.0), depth.x))
+        } else {
+            Some((vec2(0.0, offset.y.signum()), depth.y))
+        }
+    }
+}
+
+impl VehicleObstacleType {
+    fn random(rng: &mut impl Rng) -> Self {
+        if rng.gen_range(0.0..1.0) < 0.7 {
+            match rng.gen_range(0..3) {
+                0 => VehicleObstacleType::Car1,
+                1 => VehicleObstacleType::Car2,
+                _ => VehicleObstacleType::Car3,
+            }
+        } else {
+            VehicleObstacleType::Car3
+        }
+    }
+
+    // Car1 is too wrecked to drive
+    fn drivable_model(&self) -> Option<CarModel> {
+        match self {
+            Self::Car1 => None,
+            Self::Car2 => Some(CarModel::Muscle),
+            Self::Car3 => Some(CarModel::Van),
+            Self::Debris => None,
+        }
+    }
+
+    fn sprite(&self) -> SpriteId {
+        match self {
+            Self::Car1 => SpriteId::WreckedCar,
+            Self::Car2 => SpriteId::Muscle,
+            Self::Car3 => SpriteId::Van,
+            Self::Debris => SpriteId::Debris,
+        }
+    }
+}
+
+impl TutorialStep {
+    fn prompt(&self) -> &str {
+        match self {
+            Self::Steer => "W to drive, A and D to steer\nHead north up the road",
+            Self::Shoot => "Aim with the mouse and hold left click\nShoot the zombie ahead",
+            Self::Turbo => "Press SpaceBar for a turbo boost",
+            Self::Progress => "The bar at the top shows how far you've come\nFill it to escape",
+        }
+    }
+}
+
+impl RoadSurface {
+    fn random(rng: &mut impl Rng) -> Self {
+        match rng.gen_range(0..3) {
+            0 => Self::DamagedAsphalt,
+            1 => Self::Gravel,
+            _ => Self::Ice,
+        }
+    }
+
+    // Road tiles are tinted, no extra sprites needed
+    fn color(&self) -> Color {
+        match self {
+            Self::Asphalt => Color::WHITE,
+            Self::DamagedAsphalt => Color::rgb(0.7, 0.65, 0.6),
+            Self::Gravel => Color::rgb(0.9, 0.78, 0.55),
+            Self::Ice => Color::rgb(0.7, 0.9, 1.0),
+        }
+    }
+
+    fn grip(&self) -> f32 {
+        match self {
+            Self::Asphalt => 1.0,
+            Self::DamagedAsphalt => 0.9,
+            Self::Gravel => 0.7,
+            Self::Ice => 0.35,
+        }
+    }
+
+    fn acceleration(&self) -> f32 {
+        match self {
+            Self::Asphalt => 1.0,
+            Self::DamagedAsphalt => 0.7,
+            Self::Gravel => 0.8,
+            Self::Ice => 0.5,
+        }
+    }
+
+    fn braking(&self) -> f32 {
+        match self {
+            Self::Asphalt => 1.0,
+            Self::DamagedAsphalt => 1.0,
+            Self::Gravel => 0.7,
+            Self::Ice => 0.25,
+        }
+    }
+}
+
+impl GameSettings {
+    fn update_difficulty(&mut self) {
+        self.difficulty = match self.difficulty {
+            Difficulty::Easy => Difficulty::Moderate,
+            Difficulty::Moderate => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    fn update_run_length(&mut self) {
+        self.run_length = match self.run_length {
+            RunLength::Short => RunLength::Standard,
+            RunLength::Standard => RunLength::Marathon,
+            RunLength::Marathon => RunLength::Short,
+        }
+    }
+
+    fn update_entity_count(&mut self) {
+        self.entity_count = match self.entity_count {
+            EntityCount::Hundred => EntityCount::FiveHundred,
+            EntityCount::FiveHundred => EntityCount::Thousand,
+            EntityCount::Thousand => EntityCount::FiveThousand,
+            EntityCount::FiveThousand => EntityCount::TenThousand,
+            EntityCount::TenThousand => EntityCount::TwentyThousand,
+            EntityCount::TwentyThousand => EntityCount::FiftyThousand,
+            EntityCount::FiftyThousand => EntityCount::Hundred,
+        }
+    }
+
+    fn update_crosshair(&mut self) {
+        self.crosshair = match self.crosshair {
+            Crosshair::Off => Crosshair::Cross,
+            Crosshair::Cross => Crosshair::Dot,
+            Crosshair::Dot => Crosshair::Circle,
+            Crosshair::Circle => Crosshair::Off,
+        }
+    }
+
+    fn update_gore(&mut self) {
+        self.gore = match self.gore {
+            Gore::Off => Gore::Low,
+            Gore::Low => Gore::Full,
+            Gore::Full => Gore::Off,
+        }
+    }
+
+    fn update_fps_cap(&mut self) {
+        self.fps_cap = match self.fps_cap {
+            FpsCap::Thirty => FpsCap::Sixty,
+            FpsCap::Sixty => FpsCap::OneTwenty,
+            FpsCap::OneTwenty => FpsCap::Uncapped,
+            FpsCap::Uncapped => FpsCap::Thirty,
+        }
+    }
+
+    fn get_num_max_zombies(&self) -> usize {
+        match &self.entity_count {
+            EntityCount::Hundred => 100,
+            EntityCount::FiveHundred => 500,
+            EntityCount::Thousand => 1000,
+            EntityCount::FiveThousand => 5000,
+            EntityCount::TenThousand => 10000,
+            EntityCount::TwentyThousand => 20000,
+            EntityCount::FiftyThousand => 50000,
+        }
+    }
+
+    fn difficulty_as_str(&self) -> &str {
+        match self.difficulty {
+            Difficulty::Easy => "Difficulty - Easy",
+            Difficulty::Moderate => "Difficulty - Moderate",
+            Difficulty::Hard => "Difficulty - Hard",
+        }
+    }
+
+    fn letterbox_as_str(&self) -> &str {
+        if self.letterbox {
+            return "Wide View - Letterbox";
+        }
+
+        "Wide View - Full"
+    }
+
+    fn hit_stop_as_str(&self) -> &str {
+        if self.hit_stop {
+            return "Hit Stop - On";
+        }
+
+        "Hit Stop - Off"
+    }
+
+    fn checkpoint_shop_as_str(&self) -> &str {
+        if self.checkpoint_shop {
+            return "Checkpoint Shop - On";
+        }
+
+        "Checkpoint Shop - Off"
+    }
+
+    fn adaptive_quality_as_str(&self) -> &str {
+        if self.adaptive_quality {
+            return "Adaptive Quality - On";
+        }
+
+        "Adaptive Quality - Off"
+    }
+
+    fn dead_zone_label(&self) -> String {
+        format!("Stick Dead Zone - {:.0}%", self.stick_dead_zone * 100.0)
+    }
+
+    fn steer_sensitivity_label(&self) -> String {
+        format!("Steering - {:.2}x", self.steer_sensitivity)
+    }
+
+    fn aim_sensitivity_label(&self) -> String {
+        format!("Aiming - {:.2}x", self.aim_sensitivity)
+    }
+
+    fn mouse_steering_as_str(&self) -> &str {
+        if self.mouse_steering {
+            return "Driving - Mouse";
+        }
+
+        "Driving - Keys"
+    }
+
+    fn crosshair_as_str(&self) -> &str {
+        match self.crosshair {
+            Crosshair::Off => "Crosshair - Off",
+            Crosshair::Cross => "Crosshair - Cross",
+            Crosshair::Dot => "Crosshair - Dot",
+            Crosshair::Circle => "Crosshair - Circle",
+        }
+    }
+
+    fn aim_line_as_str(&self) -> &str {
+        if self.aim_line {
+            return "Aim Line - On";
+        }
+
+        "Aim Line - Off"
+    }
+
+    fn auto_fire_as_str(&self) -> &str {
+        if self.auto_fire {
+            return "Auto-Fire - On";
+        }
+
+        "Auto-Fire - Off"
+    }
+
+    fn drone_as_str(&self) -> &str {
+        if self.drone {
+            return "Drone - On";
+        }
+
+        "Drone - Off"
+    }
+
+    fn rear_view_as_str(&self) -> &str {
+        if self.rear_view {
+            return "Rear View - On";
+        }
+
+        "Rear View - Off"
+    }
+
+    fn tide_rendering_as_str(&self) -> &str {
+        if self.tide_rendering {
+            return "Zombie Tide - On";
+        }
+
+        "Zombie Tide - Off"
+    }
+
+    #[cfg(all(feature = "telemetry", not(target_arch = "wasm32")))]
+    fn telemetry_as_str(&self) -> &str {
+        if self.telemetry {
+            return "Send Run Stats - On";
+        }
+
+        "Send Run Stats - Off"
+    }
+
+    #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+    fn clip_recording_as_str(&self) -> &str {
+        if self.clip_recording {
+            return "Record Clips - On";
+        }
+
+        "Record Clips - Off"
+    }
+
+    fn fps_cap_as_str(&self) -> &str {
+        match self.fps_cap {
+            FpsCap::Thirty => "FPS Cap - 30",
+            FpsCap::Sixty => "FPS Cap - 60",
+            FpsCap::OneTwenty => "FPS Cap - 120",
+            FpsCap::Uncapped => "FPS Cap - Off",
+        }
+    }
+
+    fn debug_info_as_str(&self) -> &str {
+        if self.debug_info {
+            return "Debug Info - On";
+        }
+
+        "Debug Info - Off"
+    }
+
+    fn battery_saver_as_str(&self) -> &str {
+        if self.battery_saver {
+            return "Battery Saver - On";
+        }
+
+        "Battery Saver - Off"
+    }
+
+    fn instanced_horde_as_str(&self) -> &str {
+        if self.instanced_horde {
+            return "Horde Render - Instanced";
+        }
+
+        "Horde Render - Sprites"
+    }
+
+    fn god_mode_as_str(&self) -> &str {
+        if self.god_mode {
+            return "God Mode - On";
+        }
+
+        "God Mode - Off"
+    }
+
+    fn is_hud_element_visible(&self, element: HudElement) -> bool {
+        match element {
+            HudElement::Health => self.show_health,
+            HudElement::Turbo => self.show_turbo,
+            HudElement::Progress => self.show_progress,
+            HudElement::Minimap => self.show_minimap,
+            HudElement::Debug => self.debug_info,
+            HudElement::Timer => self.show_timer,
+            HudElement::Compass => self.show_compass,
+            HudElement::HordePressure => self.show_horde_pressure,
+        }
+    }
+
+    fn toggle_hud_element(&mut self, element: HudElement) {
+        let visible = match element {
+            HudElement::Health => &mut self.show_health,
+            HudElement::Turbo => &mut self.show_turbo,
+            HudElement::Progress => &mut self.show_progress,
+            HudElement::Minimap => &mut self.show_minimap,
+            HudElement::Debug => &mut self.debug_info,
+            HudElement::Timer => &mut self.show_timer,
+            HudElement::Compass => &mut self.show_compass,
+            HudElement::HordePressure => &mut self.show_horde_pressure,
+        };
+        *visible = !*visible;
+    }
+
+    fn hud_element_as_str(&self, element: HudElement) -> &str {
+        match (element, self.is_hud_element_visible(element)) {
+            (HudElement::Health, true) => "Health Bar - On",
+            (HudElement::Health, false) => "Health Bar - Off",
+            (HudElement::Turbo, true) => "Turbo Icon - On",
+            (HudElement::Turbo, false) => "Turbo Icon - Off",
+            (HudElement::Progress, true) => "Progress Bar - On",
+            (HudElement::Progress, false) => "Progress Bar - Off",
+            (HudElement::Minimap, true) => "Minimap - On",
+            (HudElement::Minimap, false) => "Minimap - Off",
+            (HudElement::Debug, true) => "Debug Info - On",
+            (HudElement::Debug, false) => "Debug Info - Off",
+            (HudElement::Timer, true) => "Run Timer - On",
+            (HudElement::Timer, false) => "Run Timer - Off",
+            (HudElement::Compass, true) => "Compass - On",
+            (HudElement::Compass, false) => "Compass - Off",
+            (HudElement::HordePressure, true) => "Horde Gauge - On",
+            (HudElement::HordePressure, false) => "Horde Gauge - Off",
+        }
+    }
+
+    fn music_as_str(&self) -> &str {
+        if self.music {
+            return "Music - On";
+        }
+
+        "Music - Off"
+    }
+
+    fn spawn_warnings_as_str(&self) -> &str {
+        if self.spawn_warnings {
+            return "Spawn Warnings - On";
+        }
+
+        "Spawn Warnings - Off"
+    }
+
+    fn pause_on_focus_loss_as_str(&self) -> &str {
+        if self.pause_on_focus_loss {
+            return "Pause When Unfocused - On";
+        }
+
+        "Pause When Unfocused - Off"
+    }
+
+    fn gore_as_str(&self) -> &str {
+        match self.gore {
+            Gore::Off => "Gore - Off",
+            Gore::Low => "Gore - Low",
+            Gore::Full => "Gore - Full",
+        }
+    }
+
+    fn run_length_as_str(&self) -> &str {
+        match self.run_length {
+            RunLength::Short => "Run Length - Short",
+            RunLength::Standard => "Run Length - Standard",
+            RunLength::Marathon => "Run Length - Marathon",
+        }
+    }
+
+    fn entity_count_as_str(&self) -> &str {
+        match self.entity_count {
+            EntityCount::Hundred => "Entity Count - 100",
+            EntityCount::FiveHundred => "Entity Count - 500",
+            EntityCount::Thousand => "Entity Count - 1000",
+            EntityCount::FiveThousand => "Entity Count - 5000",
+            EntityCount::TenThousand => "Entity Count - 10000",
+            EntityCount::TwentyThousand => "Entity Count - 20000",
+            EntityCount::FiftyThousand => "Entity Count - 50000",
+        }
+    }
+}
+
+impl RunOutcome {
+    fn new(car_progress: &CarProgress, on_foot_escape: &OnFootEscape) -> Self {
+        if car_progress.0 < 0.0 {
+            Self::WrongWay
+        } else if car_progress.0 >= 0.98 {
+            Self::Survived
+        } else if on_foot_escape.active
+            && on_foot_escape.timer.elapsed_secs() >= ON_FOOT_ESCAPE_TIME_SEC
+        {
+            Self::OutOfTime
+        } else {
+            Self::Mauled
+        }
+    }
+
+    fn message(&self) -> &'static str {
+        match self {
+            Self::WrongWay => "Zombies that way ;)\nGo north!",
+            Self::Survived => "You Survived!",
+            Self::OutOfTime => "Out of Time",
+            Self::Mauled => "You got Mauled",
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::WrongWay => "Wrong Way",
+            Self::Survived => "Survived",
+            Self::OutOfTime => "Out of Time",
+            Self::Mauled => "Mauled",
+        }
+    }
+}
+
+impl RunRecord {
+    // One line for the history list, date, mode, difficulty, seed, score and outcome
+    fn describe(&self) -> String {
+        let Some((config, _)) = RunConfig::from_run_code(&self.code) else {
+            return format!("{}  {}", date_from_day(self.day), self.code);
+        };
+        let mode = if self.daily {
+            "Daily"
+        } else if config.boss_rush {
+            "Boss Rush"
+        } else if config.arena {
+            "Score Arena"
+        } else {
+            config.run_length.as_str()
+        };
+        format!(
+            "{}  {mode}  {}  #{:X}  {}  {}",
+            date_from_day(self.day),
+            config.difficulty.as_str(),
+            config.seed,
+            self.score,
+            self.outcome.as_str()
+        )
+    }
+}
+
+impl Difficulty {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Moderate => "Moderate",
+            Self::Hard => "Hard",
+        }
+    }
+}
+
+impl RunLength {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Short => "Short",
+            Self::Standard => "Standard",
+            Self::Marathon => "Marathon",
+        }
+    }
+
+    fn road_height(&self) -> usize {
+        match self {
+            Self::Short => 300,
+            Self::Standard => 600,
+            Self::Marathon => 1200,
+        }
+    }
+}
+
+impl SettingsPage {
+    fn next(&self) -> Self {
+        match self {
+            Self::General => Self::Hud,
+            Self::Hud => Self::Controls,
+            Self::Controls => Self::Performance,
+            Self::Performance => Self::General,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::General => "Page - General",
+            Self::Hud => "Page - HUD",
+            Self::Controls => "Page - Controls",
+            Self::Performance => "Page - Performance",
+        }
+    }
+}
+
+impl UnlocksPage {
+    fn next(&self) -> Self {
+        match self {
+            Self::Weapons => Self::Cars,
+            Self::Cars => Self::Cosmetics,
+            Self::Cosmetics => Self::Weapons,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Weapons => "Page - Weapons",
+            Self::Cars => "Page - Cars",
+            Self::Cosmetics => "Page - Cosmetics",
+        }
+    }
+
+    fn items(&self) -> Vec<UnlockItem> {
+        match self {
+            Self::Weapons => {
+                let mut items = [Weapon::Pistol, Weapon::Smg, Weapon::Shotgun]
+                    .map(UnlockItem::Weapon)
+                    .to_vec();
+                items.push(UnlockItem::Upgrade(Upgrade::Ricochet));
+                items.push(UnlockItem::Upgrade(Upgrade::Drone));
+                items
+            }
+            Self::Cars => [CarModel::Hatchback, CarModel::Muscle, CarModel::Van]
+                .map(UnlockItem::Car)
+                .to_vec(),
+            Self::Cosmetics => [
+                CarPalette::Factory,
+                CarPalette::Crimson,
+                CarPalette::Toxic,
+                CarPalette::Midnight,
+            ]
+            .map(UnlockItem::Palette)
+            .to_vec(),
+        }
+    }
+}
+
+impl Profile {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load() -> Self {
+        std::fs::read_to_string(PROFILE_PATH)
+            .ok()
+            .and_then(|contents| ron::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    // No filesystem on the web, the profile only lasts for the session
+    #[cfg(target_arch = "wasm32")]
+    fn load() -> Self {
+        Self::default()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|contents| std::fs::write(PROFILE_PATH, contents).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Failed to save profile: {e}");
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn save(&self) {}
+
+    fn hud_layout(&self, element: HudElement) -> HudLayout {
+        self.hud_layout.get(&element).copied().unwrap_or_default()
+    }
+
+    fn is_unlocked(&self, item: UnlockItem) -> bool {
+        item.cost() == 0 || self.unlocked.contains(&item)
+    }
+
+    fn can_claim(&self, item: UnlockItem) -> bool {
+        self.total_score >= item.cost()
+    }
+
+    fn is_equipped(&self, item: UnlockItem) -> bool {
+        match item {
+            UnlockItem::Weapon(weapon) => self.weapon == weapon,
+            UnlockItem::Upgrade(upgrade) => self.upgrades.contains(&upgrade),
+            UnlockItem::Car(car) => self.car == car,
+            UnlockItem::Palette(palette) => self.palette == palette,
+        }
+    }
+
+    fn equip(&mut self, item: UnlockItem) {
+        match item {
+            UnlockItem::Weapon(weapon) => self.weapon = weapon,
+            // Upgrades stack, pressing again takes it off
+            UnlockItem::Upgrade(upgrade) => {
+                if self.upgrades.contains(&upgrade) {
+                    self.upgrades.retain(|u| *u != upgrade);
+                } else {
+                    self.upgrades.push(upgrade);
+                }
+            }
+            UnlockItem::Car(car) => self.car = car,
+            UnlockItem::Palette(palette) => self.palette = palette,
+        }
+    }
+
+    fn cycle_cosmetic(&mut self, cosmetic: Cosmetic) {
+        match cosmetic {
+            Cosmetic::Decal => self.decal = self.decal.next(),
+            Cosmetic::Horn => self.horn = self.horn.next(),
+            Cosmetic::TurboSound => self.turbo_sound = self.turbo_sound.next(),
+        }
+    }
+
+    fn cosmetic_label(&self, cosmetic: Cosmetic) -> String {
+        match cosmetic {
+            Cosmetic::Decal => format!("Decal - {}", self.decal.as_str()),
+            Cosmetic::Horn => format!("Horn - {}", self.horn.as_str()),
+            Cosmetic::TurboSound => format!("Turbo - {}", self.turbo_sound.as_str()),
+        }
+    }
+
+    fn item_label(&self, item: UnlockItem) -> String {
+        if self.is_equipped(item) {
+            format!("{} - Equipped", item.as_str())
+        } else if self.is_unlocked(item) {
+            item.as_str().to_string()
+        } else if self.can_claim(item) {
+            format!("{} - Claim", item.as_str())
+        } else {
+            format!("{} - {} pts", item.as_str(), item.cost())
+        }
+    }
+}
+
+impl UnlockItem {
+    fn cost(&self) -> u64 {
+        match self {
+            Self::Weapon(Weapon::Pistol) => 0,
+            Self::Weapon(Weapon::Smg) => 1500,
+            Self::Weapon(Weapon::Shotgun) => 4000,
+            Self::Upgrade(Upgrade::Ricochet) => 3000,
+            Self::Upgrade(Upgrade::Drone) => 3500,
+            Self::Car(CarModel::Hatchback) => 0,
+            Self::Car(CarModel::Muscle) => 2500,
+            Self::Car(CarModel::Van) => 6000,
+            Self::Palette(CarPalette::Factory) => 0,
+            Self::Palette(CarPalette::Crimson) => 500,
+            Self::Palette(CarPalette::Toxic) => 1000,
+            Self::Palette(CarPalette::Midnight) => 3000,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Weapon(Weapon::Pistol) => "Pistol",
+            Self::Weapon(Weapon::Smg) => "SMG",
+            Self::Weapon(Weapon::Shotgun) => "Shotgun",
+            Self::Upgrade(Upgrade::Ricochet) => "Ricochet Rounds",
+            Self::Upgrade(Upgrade::Drone) => "Companion Drone",
+            Self::Car(CarModel::Hatchback) => "Hatchback",
+            Self::Car(CarModel::Muscle) => "Muscle Car",
+            Self::Car(CarModel::Van) => "Armored Van",
+            Self::Palette(CarPalette::Factory) => "Factory",
+            Self::Palette(CarPalette::Crimson) => "Crimson",
+            Self::Palette(CarPalette::Toxic) => "Toxic",
+            Self::Palette(CarPalette::Midnight) => "Midnight",
+        }
+    }
+}
+
+impl Weapon {
+    fn fire_interval(&self, tuning: &Tuning) -> f32 {
+        match self {
+            Self::Pistol => tuning.bullet_spawn_interval,
+            Self::Smg => tuning.bullet_spawn_interval * 0.4,
+            Self::Shotgun => tuning.bullet_spawn_interval * 2.5,
+        }
+    }
+
+    fn heat(&self, tuning: &Tuning) -> WeaponHeat {
+        match self {
+            Self::Pistol => tuning.pistol_heat,
+            Self::Smg => tuning.smg_heat,
+            Self::Shotgun => tuning.shotgun_heat,
+        }
+    }
+
+    fn recoil(&self, tuning: &Tuning) -> f32 {
+        match self {
+            Self::Pistol => tuning.pistol_recoil,
+            Self::Smg => tuning.smg_recoil,
+            Self::Shotgun => tuning.shotgun_recoil,
+        }
+    }
+
+    fn num_pellets(&self) -> usize {
+        match self {
+            Self::Shotgun => 5,
+            _ => 1,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Pistol => "Pistol",
+            Self::Smg => "SMG",
+            Self::Shotgun => "Shotgun",
+        }
+    }
+}
+
+impl CarModel {
+    fn sprite(&self) -> SpriteId {
+        match self {
+            Self::Hatchback => SpriteId::Hatchback,
+            Self::Muscle => SpriteId::Muscle,
+            Self::Van => SpriteId::Van,
+        }
+    }
+
+    fn max_speed(&self, tuning: &Tuning) -> f32 {
+        match self {
+            Self::Hatchback => tuning.max_speed,
+            Self::Muscle => tuning.max_speed * 1.25,
+            Self::Van => tuning.max_speed * 0.85,
+        }
+    }
+
+    fn damage_multiplier(&self) -> f32 {
+        match self {
+            Self::Van => 0.5,
+            _ => 1.0,
+        }
+    }
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            turn_speed: 20.0,
+            car_thrust: 20.0,
+            max_speed: 40.0,
+            friction: 20.0,
+            max_car_health: 200.0,
+            max_car_armor: 100.0,
+            turbo_boost: 60.0,
+            turbo_interval_sec: 5.0,
+            obstacle_damage: 10.0,
+            on_foot_speed: 150.0,
+            on_foot_health: 20.0,
+            spare_vehicle_health: 0.5,
+            commandeer_health: 0.35,
+            bullet_speed: 20.0 * 100.0,
+            bullet_spawn_interval: 0.3,
+            pistol_heat: WeaponHeat::default(),
+            smg_heat: WeaponHeat {
+                per_shot: 0.05,
+                cool_per_sec: 0.3,
+                overheat_sec: 3.0,
+            },
+            shotgun_heat: WeaponHeat {
+                per_shot: 0.25,
+                cool_per_sec: 0.25,
+                overheat_sec: 2.5,
+            },
+            pistol_recoil: 0.0,
+            smg_recoil: 0.0,
+            shotgun_recoil: 4.0,
+            vent_lockout_factor: 0.4,
+            armor_pickup_value: 50.0,
+            zombie_speed: 2.55 * 100.0,
+            zombie_attack: 2.0,
+            zombie_bite_hits: 4,
+            zombie_spawn_budget: 20,
+            spawn_ramp: vec![(0.0, 0.0), (1.0, 1.0)],
+            spitter_chance: 0.04,
+            bloater_chance: 0.03,
+            dog_pack_chance: 0.01,
+            dog_speed: 4.8 * 100.0,
+            dog_windup_sec: 0.45,
+            nest_spawn_interval_sec: 1.0,
+            nest_spawn_count: 3,
+            easy: DifficultyProfile {
+                zombie_speed: 0.9,
+                zombie_attack: 0.75,
+                spitter_ratio: 0.5,
+                bloater_ratio: 0.5,
+                dog_ratio: 0.5,
+                dog_speed: 0.9,
+                dog_windup: 1.3,
+                pickup_frequency: 1.3,
+                boss_health: 0.8,
+                sight_range: 0.85,
+                gunshot_hearing: 0.8,
+                engine_hearing: 0.85,
+            },
+            moderate: DifficultyProfile::default(),
+            hard: DifficultyProfile {
+                zombie_speed: 1.15,
+                zombie_attack: 1.4,
+                spitter_ratio: 1.75,
+                bloater_ratio: 1.75,
+                dog_ratio: 2.0,
+                dog_speed: 1.1,
+                dog_windup: 0.75,
+                pickup_frequency: 0.7,
+                boss_health: 1.4,
+                sight_range: 1.2,
+                gunshot_hearing: 1.3,
+                engine_hearing: 1.1,
+            },
+        }
+    }
+}
+
+impl Default for DifficultyProfile {
+    fn default() -> Self {
+        Self {
+            zombie_speed: 1.0,
+            zombie_attack: 1.0,
+            spitter_ratio: 1.0,
+            bloater_ratio: 1.0,
+            dog_ratio: 1.0,
+            dog_speed: 1.0,
+            dog_windup: 1.0,
+            pickup_frequency: 1.0,
+            boss_health: 1.0,
+            sight_range: 1.0,
+            gunshot_hearing: 1.0,
+            engine_hearing: 1.0,
+        }
+    }
+}
+
+impl Default for WeaponHeat {
+    fn default() -> Self {
+        Self {
+            per_shot: 0.08,
+            cool_per_sec: 0.35,
+            overheat_sec: 2.0,
+        }
+    }
+}
+
+impl Crosshair {
+    fn sprite(&self) -> Option<SpriteId> {
+        match self {
+            Crosshair::Off => None,
+            Crosshair::Cross => Some(SpriteId::CrosshairCross),
+            Crosshair::Dot => Some(SpriteId::CrosshairDot),
+            Crosshair::Circle => Some(SpriteId::CrosshairCircle),
+        }
+    }
+}
+
+impl Tuning {
+    // Piecewise linear through the ramp points, flat past either end
+    fn spawn_ramp_at(&self, progress: f32) -> f32 {
+        let Some(&(first_x, first_y)) = self.spawn_ramp.first() else {
+            return progress;
+        };
+        if progress <= first_x {
+            return first_y;
+        }
+        for pair in self.spawn_ramp.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            if progress <= x1 {
+                let t = if x1 > x0 {
+                    (progress - x0) / (x1 - x0)
+                } else {
+                    1.0
+                };
+                return y0 + (y1 - y0) * t;
+            }
+        }
+        self.spawn_ramp.last().map_or(progress, |p| p.1)
+    }
+
+    #[cfg(feature = "debug-tools")]
+    fn spawn_ramp_ron(&self) -> String {
+        let points: Vec<String> = self
+            .spawn_ramp
+            .iter()
+            .map(|(x, y)| format!("({x:.2}, {y:.2})"))
+            .collect();
+        format!("spawn_ramp: [{}],", points.join(", "))
+    }
+
+    fn difficulty_profile(&self, difficulty: Difficulty) -> DifficultyProfile {
+        match difficulty {
+            Difficulty::Easy => self.easy,
+            Difficulty::Moderate => self.moderate,
+            Difficulty::Hard => self.hard,
+        }
+    }
+}
+
+impl AssetLoader for TuningLoader {
+    type Asset = Tuning;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Tuning, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+impl AssetLoader for AtlasManifestLoader {
+    type Asset = AtlasManifest;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<AtlasManifest, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["atlases.ron"]
+    }
+}
+
+impl AssetLoader for RadioScriptLoader {
+    type Asset = RadioScript;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<RadioScript, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    // Picked over the plain ron loader by its longer extension
+    fn extensions(&self) -> &[&str] {
+        &["radio.ron"]
+    }
+}
+
+impl AssetLoader for SpawnPatternsLoader {
+    type Asset = SpawnPatterns;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<SpawnPatterns, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["spawns.ron"]
+    }
+}
+
+impl Default for SpawnPatterns {
+    fn default() -> Self {
+        let (ww, half_ww) = (WW as f32, WW as f32 / 2.0);
+        let roadside = |y| SpawnPattern {
+            x: (ww, ww + 400.0),
+            y,
+            mirror: true,
+            weight: PerDifficulty::new(24.75, 24.5, 24.0),
+            min_progress: PerDifficulty::default(),
+        };
+        Self {
+            patterns: vec![
+                roadside((0.0, 400.0)),
+                roadside((1000.0, 1600.0)),
+                roadside((1500.0, 2200.0)),
+                roadside((1800.0, 2900.0)),
+                // On the road ahead of the car
+                SpawnPattern {
+                    x: (half_ww, half_ww + 500.0),
+                    y: (1000.0, 1900.0),
+                    mirror: true,
+                    weight: PerDifficulty::new(1.0, 1.0, 4.0),
+                    min_progress: PerDifficulty::new(0.7, 0.6, 0.0),
+                },
+                // Halfway onto the road
+                SpawnPattern {
+                    x: (half_ww + 100.0, half_ww + 600.0),
+                    y: (1000.0, 1900.0),
+                    mirror: true,
+                    weight: PerDifficulty::new(1.0, 1.0, 0.0),
+                    min_progress: PerDifficulty::new(0.4, 0.0, 0.0),
+                },
+            ],
+            finales: HashMap::from([
+                // The horde catches up from behind while the car waits at the dock
+                (
+                    FinishLayout::Ferry,
+                    vec![SpawnPattern {
+                        x: (0.0, 600.0),
+                        y: (-1400.0, -900.0),
+                        mirror: true,
+                        weight: PerDifficulty::new(30.0, 40.0, 50.0),
+                        min_progress: PerDifficulty::default(),
+                    }],
+                ),
+                // Out of the fields either side, where the lever is
+                (
+                    FinishLayout::Drawbridge,
+                    vec![SpawnPattern {
+                        x: (900.0, 1300.0),
+                        y: (-300.0, 300.0),
+                        mirror: true,
+                        weight: PerDifficulty::new(30.0, 40.0, 50.0),
+                        min_progress: PerDifficulty::default(),
+                    }],
+                ),
+                // Pouring out of the bunker grounds ahead
+                (
+                    FinishLayout::Bunker,
+                    vec![SpawnPattern {
+                        x: (0.0, 500.0),
+                        y: (1100.0, 1600.0),
+                        mirror: true,
+                        weight: PerDifficulty::new(30.0, 40.0, 50.0),
+                        min_progress: PerDifficulty::default(),
+                    }],
+                ),
+            ]),
+        }
+    }
+}
+
+impl FinishLayout {
+    const ALL: [Self; 3] = [Self::Ferry, Self::Drawbridge, Self::Bunker];
+
+    fn briefing(&self) -> &'static str {
+        match self {
+            Self::Ferry => "Hold the ferry dock until the boat comes in",
+            Self::Drawbridge => "Find the lever beside the road to lower the bridge",
+            Self::Bunker => "Ram the bunker gate down",
+        }
+    }
+
+    fn open_sec(&self) -> f32 {
+        match self {
+            Self::Ferry => FERRY_ARRIVE_SEC,
+            Self::Drawbridge => DRAWBRIDGE_LOWER_SEC,
+            Self::Bunker => BUNKER_COLLAPSE_SEC,
+        }
+    }
+}
+
+impl SpawnPatterns {
+    // The finale's own patterns join in once it has started
+    fn active(
+        &self,
+        difficulty: Difficulty,
+        progress: f32,
+        finale: Option<FinishLayout>,
+    ) -> Vec<&SpawnPattern> {
+        let finale_patterns = finale
+            .and_then(|layout| self.finales.get(&layout))
+            .into_iter()
+            .flatten();
+        self.patterns
+            .iter()
+            .chain(finale_patterns)
+            .filter(|p| {
+                progress >= p.min_progress.get(difficulty) && p.weight.get(difficulty) > 0.0
+            })
+            .collect()
+    }
+}
+
+impl SpawnPattern {
+    // Offset from the car
+    fn roll(&self, rng: &mut ChaCha8Rng) -> Vec2 {
+        let x = rng.gen_range(self.x.0.min(self.x.1)..=self.x.0.max(self.x.1));
+        let y = rng.gen_range(self.y.0.min(self.y.1)..=self.y.0.max(self.y.1));
+        if self.mirror && rng.gen_bool(0.5) {
+            vec2(-x, y)
+        } else {
+            vec2(x, y)
+        }
+    }
+}
+
+impl PerDifficulty {
+    fn new(easy: f32, moderate: f32, hard: f32) -> Self {
+        Self {
+            easy,
+            moderate,
+            hard,
+        }
+    }
+
+    fn get(&self, difficulty: Difficulty) -> f32 {
+        match difficulty {
+            Difficulty::Easy => self.easy,
+            Difficulty::Moderate => self.moderate,
+            Difficulty::Hard => self.hard,
+        }
+    }
+}
+
+impl CarPalette {
+    fn color(&self) -> Color {
+        match self {
+            Self::Factory => Color::WHITE,
+            Self::Crimson => Color::rgb(1.0, 0.45, 0.45),
+            Self::Toxic => Color::rgb(0.6, 1.0, 0.4),
+            Self::Midnight => Color::rgb(0.5, 0.55, 1.0),
+        }
+    }
+}
+
+impl Decal {
+    fn next(&self) -> Self {
+        match self {
+            Self::None => Self::RacingStripe,
+            Self::RacingStripe => Self::Flag,
+            Self::Flag => Self::None,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::None => "None",
+            Self::RacingStripe => "Racing Stripe",
+            Self::Flag => "Flag",
+        }
+    }
+
+    // Drawn with the plain square sprite, relative to the car's own scale
+    fn sprite(&self) -> Option<(Transform, Color)> {
+        match self {
+            Self::None => None,
+            Self::RacingStripe => Some((
+                Transform::from_xyz(0.0, 0.0, 0.1).with_scale(vec3(0.12, 0.8, 1.0)),
+                Color::rgb(1.0, 0.94, 0.85),
+            )),
+            Self::Flag => Some((
+                Transform::from_xyz(-3.0, -4.0, 0.1).with_scale(vec3(0.25, 0.2, 1.0)),
+                Color::rgb(0.9, 0.2, 0.2),
+            )),
+        }
+    }
+}
+
+impl HornSound {
+    fn next(&self) -> Self {
+        match self {
+            Self::Classic => Self::Truck,
+            Self::Truck => Self::Clown,
+            Self::Clown => Self::Classic,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Classic => "Classic",
+            Self::Truck => "Truck",
+            Self::Clown => "Clown",
+        }
+    }
+
+    fn pitch(&self) -> Pitch {
+        match self {
+            Self::Classic => Pitch::new(440.0, Duration::from_millis(300)),
+            Self::Truck => Pitch::new(185.0, Duration::from_millis(500)),
+            Self::Clown => Pitch::new(900.0, Duration::from_millis(150)),
+        }
+    }
+}
+
+impl TurboSound {
+    fn next(&self) -> Self {
+        match self {
+            Self::Classic => Self::Jet,
+            Self::Jet => Self::Rumble,
+            Self::Rumble => Self::Classic,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Classic => "Classic",
+            Self::Jet => "Jet",
+            Self::Rumble => "Rumble",
+        }
+    }
+
+    // Variations on the same sample, just played back faster or slower
+    fn playback_speed(&self) -> f32 {
+        match self {
+            Self::Classic => 1.0,
+            Self::Jet => 1.5,
+            Self::Rumble => 0.7,
+        }
+    }
+}
+
+impl HudElement {
+    const ALL: [HudElement; 8] = [
+        HudElement::Health,
+        HudElement::Turbo,
+        HudElement::Progress,
+        HudElement::Minimap,
+        HudElement::Debug,
+        HudElement::Timer,
+        HudElement::Compass,
+        HudElement::HordePressure,
+    ];
+
+    fn anchor(&self) -> HudAnchor {
+        match self {
+            Self::Health => HudAnchor::TopRight,
+            Self::Turbo => HudAnchor::BottomRight,
+            Self::Progress => HudAnchor::Left,
+            Self::Minimap => HudAnchor::BottomLeft,
+            Self::Debug => HudAnchor::TopLeft,
+            Self::Timer => HudAnchor::Right,
+            Self::Compass => HudAnchor::Top,
+            Self::HordePressure => HudAnchor::Bottom,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Health => "Health Bar",
+            Self::Turbo => "Turbo Icon",
+            Self::Progress => "Progress Bar",
+            Self::Minimap => "Minimap",
+            Self::Debug => "Debug Info",
+            Self::Timer => "Run Timer",
+            Self::Compass => "Compass",
+            Self::HordePressure => "Horde Gauge",
+        }
+    }
+
+    // Full screen root node that pins its children to the element's anchor
+    fn root_bundle(&self) -> (NodeBundle, HudElement, GameEntity) {
+        let (align_items, justify_content) = match self.anchor() {
+            HudAnchor::Top => (AlignItems::Start, JustifyContent::Center),
+            HudAnchor::Bottom => (AlignItems::End, JustifyContent::Center),
+            HudAnchor::TopLeft => (AlignItems::Start, JustifyContent::Start),
+            HudAnchor::TopRight => (AlignItems::Start, JustifyContent::End),
+            HudAnchor::Left => (AlignItems::Center, JustifyContent::Start),
+            HudAnchor::Right => (AlignItems::Center, JustifyContent::End),
+            HudAnchor::BottomLeft => (AlignItems::End, JustifyContent::Start),
+            HudAnchor::BottomRight => (AlignItems::End, JustifyContent::End),
+        };
+
+        (
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    align_items,
+                    justify_content,
+                    ..default()
+                },
+                ..default()
+            },
+            *self,
+            GameEntity,
+        )
+    }
+}
+
+impl ZombieAttack {
+    fn new(base_scale: f32) -> Self {
+        Self {
+            cooldown: 0.0,
+            bite: 0.0,
+            base_scale,
+        }
+    }
+}
+
+impl ParticleKind {
+    fn count(&self) -> usize {
+        match self {
+            Self::Casing => 1,
+            Self::Dirt => 3,
+            Self::Spark => 10,
+            Self::Toxic => 6,
+        }
+    }
+
+    fn speed(&self) -> f32 {
+        match self {
+            Self::Casing => 180.0,
+            Self::Dirt => 120.0,
+            Self::Spark => 350.0,
+            Self::Toxic => 260.0,
+        }
+    }
+
+    fn lifetime(&self) -> f32 {
+        match self {
+            Self::Casing => 1.2,
+            Self::Dirt => 0.6,
+            Self::Spark => 0.35,
+            Self::Toxic => 0.5,
+        }
+    }
+
+    fn size(&self) -> Vec2 {
+        match self {
+            Self::Casing => vec2(3.0, 6.0),
+            Self::Dirt => Vec2::splat(5.0),
+            Self::Spark => vec2(2.0, 5.0),
+            Self::Toxic => Vec2::splat(6.0),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Casing => Color::rgb(0.95, 0.75, 0.3),
+            Self::Dirt => Color::rgba(0.45, 0.33, 0.2, 0.9),
+            Self::Spark => Color::rgb(1.0, 0.9, 0.5),
+            Self::Toxic => Color::rgba(0.8, 0.85, 0.25, 0.85),
+        }
+    }
+}
+
+impl Default for HudLayout {
+    fn default() -> Self {
+        Self {
+            offset: (0.0, 0.0),
+            opacity: 1.0,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CrashReport {
+    // Sets up logging and the panic hook, before the app so nothing is missed
+    fn install() -> Self {
+        use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+        let crash_report = Self::default();
+        // Same defaults as bevy's LogPlugin, RUST_LOG still overrides them
+        let filter = EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| EnvFilter::new("info,wgpu=error,naga=warn"));
+        let _ = tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+            .with(CrashLogLayer(crash_report.clone()))
+            .try_init();
+
+        let report = crash_report.clone();
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            match report.write(&info.to_string()) {
+                Ok(path) => eprintln!("Crash report written to {}", path.display()),
+                Err(e) => eprintln!("Failed to write crash report: {e}"),
+            }
+            default_hook(info);
+        }));
+        crash_report
+    }
+
+    fn write(&self, panic: &str) -> std::io::Result<std::path::PathBuf> {
+        // The panic may have come from under the lock, don't wait on it
+        let context = match self.0.try_lock() {
+            Ok(context) => context,
+            Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(std::sync::TryLockError::WouldBlock) => {
+                return Err(std::io::Error::other("crash context is locked"));
+            }
+        };
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let path = std::path::Path::new(CRASH_REPORT_DIR).join(format!("crash-{secs}.txt"));
+        std::fs::create_dir_all(CRASH_REPORT_DIR)?;
+        let log = context.log.iter().cloned().collect::<Vec<_>>().join("\n");
+        std::fs::write(
+            &path,
+            format!(
+                "{panic}\n\nSeed: {:X}\nRun: {}\nRun stats: {}\nSettings: {}\n\nRecent log:\n{log}\n",
+                context.seed, context.run, context.run_stats, context.settings
+            ),
+        )?;
+        Ok(path)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<S: bevy::utils::tracing::Subscriber> tracing_subscriber::Layer<S> for CrashLogLayer {
+    fn on_event(
+        &self,
+        event: &bevy::utils::tracing::Event<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let meta = event.metadata();
+        let mut line = format!("{} {}:", meta.level(), meta.target());
+        event.record(&mut LogLineVisitor(&mut line));
+        let Ok(mut context) = self.0 .0.lock() else {
+            return;
+        };
+        if context.log.len() >= CRASH_LOG_LINES {
+            context.log.pop_front();
+        }
+        context.log.push_back(line);
+    }
+}
+
+// Appends an event's fields to a log line, the message first and bare
+#[cfg(not(target_arch = "wasm32"))]
+struct LogLineVisitor<'a>(&'a mut String);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl bevy::utils::tracing::field::Visit for LogLineVisitor<'_> {
+    fn record_debug(
+        &mut self,
+        field: &bevy::utils::tracing::field::Field,
+        value: &dyn std::fmt::Debug,
+    ) {
+        use std::fmt::Write;
+        let _ = if field.name() == "message" {
+            write!(self.0, " {value:?}")
+        } else {
+            write!(self.0, " {}={value:?}", field.name())
+        };
+    }
+}
+
+impl Gore {
+    fn blood_cap(&self) -> usize {
+        match self {
+            Self::Off => 0,
+            Self::Low => GORE_LOW_BLOOD_CAP,
+            Self::Full => BLOOD_SPLAT_POOL_SIZE,
+        }
+    }
+
+    fn blood_sec(&self) -> f32 {
+        match self {
+            Self::Low => GORE_LOW_BLOOD_SEC,
+            _ => GORE_FULL_BLOOD_SEC,
+        }
+    }
+
+    fn corpse_pile_cap(&self) -> usize {
+        match self {
+            Self::Off => 0,
+            Self::Low => GORE_LOW_CORPSE_PILE_CAP,
+            Self::Full => CORPSE_PILE_CAP,
+        }
+    }
+
+    fn corpse_sec(&self) -> Option<f32> {
+        match self {
+            Self::Low => Some(GORE_LOW_CORPSE_SEC),
+            _ => None,
+        }
+    }
+}
+
+impl FpsCap {
+    fn frame_time(&self) -> Option<Duration> {
+        let fps = match self {
+            Self::Thirty => 30.0,
+            Self::Sixty => 60.0,
+            Self::OneTwenty => 120.0,
+            Self::Uncapped => return None,
+        };
+        Some(Duration::from_secs_f64(1.0 / fps))
+    }
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::default(),
+            entity_count: EntityCount::default(),
+            music: true,
+            god_mode: false,
+            debug_info: false,
+            show_health: true,
+            show_turbo: true,
+            show_progress: true,
+            show_minimap: true,
+            show_timer: true,
+            show_compass: true,
+            show_horde_pressure: true,
+            rear_view: false,
+            mouse_steering: false,
+            auto_fire: false,
+            crosshair: Crosshair::default(),
+            aim_line: false,
+            drone: true,
+            stick_dead_zone: 0.15,
+            steer_sensitivity: 1.0,
+            aim_sensitivity: 1.0,
+            instanced_horde: true,
+            letterbox: true,
+            adaptive_quality: true,
+            hit_stop: true,
+            checkpoint_shop: true,
+            run_length: RunLength::default(),
+            fps_cap: FpsCap::default(),
+            battery_saver: false,
+            gore: Gore::default(),
+            spawn_warnings: true,
+            tide_rendering: false,
+            telemetry: false,
+            pause_on_focus_loss: true,
+            #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+            clip_recording: false,
+        }
+    }
+}
+
+// Co-op networking
+// Players only exchange their own car state over UDP, the host relays it to the other
+// clients. Every game simulates its own horde, which chases whichever car is closest.
+const NET_PORT: u16 = 7878;
+const NET_MAX_PLAYERS: usize = 4;
+const NET_SEND_INTERVAL_SEC: f32 = 0.05;
+const NET_TIMEOUT_SEC: f32 = 5.0;
+const NET_MAX_PACKET_SIZE: usize = 1024;
+const NET_REMOTE_CAR_SMOOTHING: f32 = 12.0;
+const REVIVE_RADIUS: f32 = 80.0;
+const REVIVE_MAX_SPEED: f32 = 2.0;
+const REVIVE_TIME_SEC: f32 = 3.0;
+// Fraction of the max car health a revived teammate gets back
+const REVIVE_HEALTH: f32 = 0.5;
+const SPECTATOR_FREE_CAMERA_SPEED: f32 = 800.0;
+const COOP_CAR_COLORS: [Color; NET_MAX_PLAYERS] = [
+    Color::WHITE,
+    Color::rgb(0.55, 0.75, 1.0),
+    Color::rgb(0.6, 1.0, 0.4),
+    Color::rgb(1.0, 0.6, 0.9),
+];
+
+// Co-op pings and chat
+const PING_WHEEL_KEY: KeyCode = KeyCode::G;
+const PING_WHEEL_SIZE: f32 = 260.0;
+const PING_WHEEL_OPTION_SIZE: (f32, f32) = (120.0, 36.0);
+// How far the cursor has to move from the wheel center to pick an option
+const PING_WHEEL_DEADZONE: f32 = 30.0;
+const PING_COOLDOWN_SEC: f32 = 0.5;
+const PING_LIFETIME_SEC: f32 = 8.0;
+const PING_FADE_SEC: f32 = 1.0;
+const PING_EDGE_MARGIN: f32 = 24.0;
+const PING_EDGE_WIDTH: f32 = 180.0;
+const CHAT_KEY: KeyCode = KeyCode::T;
+const CHAT_MAX_LEN: usize = 60;
+const CHAT_MAX_LINES: usize = 5;
+const CHAT_LINE_SEC: f32 = 10.0;
+
+#[derive(Serialize, Deserialize)]
+enum NetMessage {
+    Join,
+    Welcome {
+        player_id: u8,
+    },
+    Lobby {
+        num_players: u8,
+    },
+    Start {
+        seed: u64,
+        difficulty: Difficulty,
+        run_length: RunLength,
+        modifiers: Vec<Modifier>,
+    },
+    State(PlayerState),
+    Revive {
+        player_id: u8,
+    },
+    Ping(Ping),
+    Chat {
+        player_id: u8,
+        text: String,
+    },
+    Leave {
+        player_id: u8,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+struct PlayerState {
+    player_id: u8,
+    pos: (f32, f32),
+    rotation: f32,
+    score: u32,
+    alive: bool,
+    // Out of the run but waiting for a revive, pos is where the wreck is
+    downed: bool,
+}
+
+enum NetRole {
+    Host {
+        clients: Vec<NetClient>,
+    },
+    // Counting from when the session was opened, so a host that never answers times out too
+    Client {
+        host: SocketAddr,
+        last_heard: Stopwatch,
+    },
+}
+
+// Timed out on its own packets, it may not have sent a state yet while in the lobby
+struct NetClient {
+    player_id: u8,
+    address: SocketAddr,
+    last_seen: Stopwatch,
+}
+
+struct RemotePeer {
+    state: PlayerState,
+    last_seen: Stopwatch,
+}
+
+struct NetSession {
+    socket: UdpSocket,
+    role: NetRole,
+    // The host is always player 0, clients are told theirs on joining
+    player_id: u8,
+    num_players: u8,
+    peers: HashMap<u8, RemotePeer>,
+    send_timer: Stopwatch,
+}
+
+#[derive(Resource, Default)]
+struct Coop {
+    session: Option<NetSession>,
+    status: String,
+}
+
+#[derive(Resource)]
+struct LobbyAddress(String);
+
+#[derive(Component)]
+struct RemoteCar(u8);
+// What is left of a downed player's car
+#[derive(Component)]
+struct Wreck;
+#[derive(Component)]
+struct SpectatorText;
+#[derive(Component)]
+struct ReviveText;
+
+#[derive(Event)]
+struct ReviveEvent;
+
+// Marker a player dropped on the road for the team
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct Ping {
+    player_id: u8,
+    kind: PingKind,
+    pos: (f32, f32),
+}
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+enum PingKind {
+    Horde,
+    Loot,
+    NeedHelp,
+    Regroup,
+}
+#[derive(Event)]
+struct PingEvent(Ping);
+#[derive(Event)]
+struct ChatEvent {
+    player_id: u8,
+    text: String,
+}
+
+#[derive(Resource, Default)]
+struct PingWheel {
+    // Screen and world position the wheel was opened at
+    center: Option<(Vec2, Vec2)>,
+    cooldown: f32,
+}
+#[derive(Resource, Default)]
+struct CoopChat {
+    typing: bool,
+    input: String,
+    // Oldest first, with the time each line came in
+    lines: Vec<(u8, String, f32)>,
+}
+#[derive(Component)]
+struct PingWheelRoot;
+#[derive(Component)]
+struct PingWheelOption(PingKind);
+#[derive(Component)]
+struct PingMarker {
+    player_id: u8,
+    kind: PingKind,
+    age: f32,
+    // Screen edge label pointing at it while it's off screen
+    edge: Entity,
+}
+#[derive(Component)]
+struct ChatText;
+
+#[derive(Resource, Default)]
+struct Spectator {
+    active: bool,
+    target: Option<u8>,
+    free_camera: bool,
+}
+
+// How long the local car has been parked next to each downed teammate
+#[derive(Resource, Default)]
+struct ReviveTimers(HashMap<u8, Stopwatch>);
+
+#[derive(Component)]
+struct LobbyMenuComponent;
+#[derive(Component)]
+struct LobbyStatusText;
+#[derive(Component)]
+struct LobbyAddressText;
+#[derive(Component)]
+enum LobbyMenuButtonAction {
+    Host,
+    Join,
+    Start,
+    ExitToMainMenu,
+}
+
+impl PingKind {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Horde => "Horde here",
+            Self::Loot => "Loot",
+            Self::NeedHelp => "Need help",
+            Self::Regroup => "Regroup",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Horde => Color::rgb(1.0, 0.35, 0.3),
+            Self::Loot => Color::rgb(1.0, 0.85, 0.3),
+            Self::NeedHelp => COLOR_ORANGE,
+            Self::Regroup => Color::rgb(0.55, 0.75, 1.0),
+        }
+    }
+
+    // Up, right, down and left of the wheel center, UI y points down
+    fn from_offset(offset: Vec2) -> Option<Self> {
+        if offset.length() < PING_WHEEL_DEADZONE {
+            None
+        } else if offset.y.abs() > offset.x.abs() {
+            Some(if offset.y < 0.0 {
+                Self::Horde
+            } else {
+                Self::NeedHelp
+            })
+        } else if offset.x > 0.0 {
+            Some(Self::Loot)
+        } else {
+            Some(Self::Regroup)
+        }
+    }
+}
+
+impl NetMessage {
+    // Stamps the sender's slot over whatever id the packet claims
+    fn sent_by(self, player_id: u8) -> Self {
+        match self {
+            Self::State(state) => Self::State(PlayerState { player_id, ..state }),
+            Self::Ping(ping) => Self::Ping(Ping { player_id, ..ping }),
+            Self::Chat { text, .. } => Self::Chat { player_id, text },
+            Self::Leave { .. } => Self::Leave { player_id },
+            message => message,
+        }
+    }
+}
+
+impl NetSession {
+    fn host() -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", NET_PORT))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self::new(socket, NetRole::Host { clients: vec![] }))
+    }
+
+    fn join(address: &str) -> std::io::Result<Self> {
+        let address = if address.contains(':') {
+            address.to_string()
+        } else {
+            format!("{address}:{NET_PORT}")
+        };
+        let host = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or(std::io::ErrorKind::AddrNotAvailable)?;
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.set_nonblocking(true)?;
+
+        // net_send_state keeps asking until the host welcomes us
+        let session = Self::new(
+            socket,
+            NetRole::Client {
+                host,
+                last_heard: Stopwatch::new(),
+            },
+        );
+        session.send_to(&NetMessage::Join, host);
+        Ok(session)
+    }
+
+    fn new(socket: UdpSocket, role: NetRole) -> Self {
+        Self {
+            socket,
+            role,
+            player_id: 0,
+            num_players: 1,
+            peers: HashMap::new(),
+            send_timer: Stopwatch::new(),
+        }
+    }
+
+    fn is_host(&self) -> bool {
+        matches!(self.role, NetRole::Host { .. })
+    }
+
+    fn send_to(&self, message: &NetMessage, address: SocketAddr) {
+        let Ok(contents) = ron::to_string(message) else {
+            return;
+        };
+        // Dropped packets are fine, states are resent constantly
+        let _ = self.socket.send_to(contents.as_bytes(), address);
+    }
+
+    // Host to every client, or client to the host
+    fn broadcast(&self, message: &NetMessage, except: Option<SocketAddr>) {
+        match &self.role {
+            NetRole::Host { clients } => {
+                for client in clients.iter().filter(|c| Some(c.address) != except) {
+                    self.send_to(message, client.address);
+                }
+            }
+            NetRole::Client { host, .. } => self.send_to(message, *host),
+        }
+    }
+
+    fn handle(&mut self, message: NetMessage, from: SocketAddr) -> Option<NetMessage> {
+        // Strangers can only ask to join, clients only listen to the host
+        let message = match &mut self.role {
+            NetRole::Host { clients } => {
+                match (clients.iter_mut().find(|c| c.address == from), message) {
+                    (Some(client), message) => {
+                        client.last_seen.reset();
+                        message.sent_by(client.player_id)
+                    }
+                    (None, NetMessage::Join) => NetMessage::Join,
+                    (None, _) => return None,
+                }
+            }
+            NetRole::Client { host, .. } if *host != from => return None,
+            NetRole::Client { last_heard, .. } => {
+                last_heard.reset();
+                message
+            }
+        };
+        match (&mut self.role, message) {
+            (NetRole::Host { clients }, NetMessage::Join) => {
+                // Joins are resent until welcomed, the first welcome may have been dropped
+                if let Some(client) = clients.iter().find(|c| c.address == from) {
+                    let player_id = client.player_id;
+                    self.send_to(&NetMessage::Welcome { player_id }, from);
+                    return None;
+                }
+                if clients.len() + 1 >= NET_MAX_PLAYERS {
+                    return None;
+                }
+                let player_id = (1..NET_MAX_PLAYERS as u8)
+                    .find(|id| clients.iter().all(|c| c.player_id != *id))
+                    .unwrap();
+                clients.push(NetClient {
+                    player_id,
+                    address: from,
+                    last_seen: Stopwatch::new(),
+                });
+                self.num_players = clients.len() as u8 + 1;
+                self.send_to(&NetMessage::Welcome { player_id }, from);
+                self.broadcast(
+                    &NetMessage::Lobby {
+                        num_players: self.num_players,
+                    },
+                    None,
+                );
+            }
+            (NetRole::Host { .. }, NetMessage::Revive { player_id }) => {
+                self.broadcast(&NetMessage::Revive { player_id }, Some(from));
+                if player_id == self.player_id {
+                    return Some(NetMessage::Revive { player_id });
+                }
+            }
+            (NetRole::Host { .. }, NetMessage::Ping(ping)) => {
+                self.broadcast(&NetMessage::Ping(ping), Some(from));
+                return Some(NetMessage::Ping(ping));
+            }
+            (NetRole::Host { .. }, message @ NetMessage::Chat { .. }) => {
+                self.broadcast(&message, Some(from));
+                return Some(message);
+            }
+            (NetRole::Host { .. }, NetMessage::State(state)) => {
+                self.broadcast(&NetMessage::State(state), Some(from));
+                self.update_peer(state);
+            }
+            (NetRole::Host { .. }, NetMessage::Leave { player_id }) => {
+                self.remove_client(player_id)
+            }
+            (NetRole::Client { .. }, NetMessage::Welcome { player_id }) => {
+                self.player_id = player_id;
+            }
+            (NetRole::Client { .. }, NetMessage::Lobby { num_players }) => {
+                self.num_players = num_players;
+            }
+            (NetRole::Client { .. }, NetMessage::State(state)) => self.update_peer(state),
+            // The host quitting ends the session for everyone
+            (NetRole::Client { .. }, NetMessage::Leave { player_id: 0 }) => {
+                return Some(NetMessage::Leave { player_id: 0 });
+            }
+            (NetRole::Client { .. }, NetMessage::Leave { player_id }) => {
+                self.peers.remove(&player_id);
+            }
+            (NetRole::Client { .. }, NetMessage::Revive { player_id })
+                if player_id == self.player_id =>
+            {
+                return Some(NetMessage::Revive { player_id });
+            }
+            (NetRole::Client { .. }, message @ NetMessage::Start { .. }) => return Some(message),
+            (NetRole::Client { .. }, message @ (NetMessage::Ping(_) | NetMessage::Chat { .. })) => {
+                return Some(message)
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn remove_client(&mut self, player_id: u8) {
+        let NetRole::Host { clients } = &mut self.role else {
+            return;
+        };
+        clients.retain(|c| c.player_id != player_id);
+        self.num_players = clients.len() as u8 + 1;
+        self.peers.remove(&player_id);
+        self.broadcast(&NetMessage::Leave { player_id }, None);
+        self.broadcast(
+            &NetMessage::Lobby {
+                num_players: self.num_players,
+            },
+            None,
+        );
+    }
+
+    // Drops whoever went quiet, false once the host has
+    fn update_timeouts(&mut self, delta: Duration) -> bool {
+        for peer in self.peers.values_mut() {
+            peer.last_seen.tick(delta);
+        }
+        self.peers
+            .retain(|_, peer| peer.last_seen.elapsed_secs() <= NET_TIMEOUT_SEC);
+
+        let timed_out = match &mut self.role {
+            NetRole::Host { clients } => clients
+                .iter_mut()
+                .filter_map(|c| {
+                    c.last_seen.tick(delta);
+                    (c.last_seen.elapsed_secs() > NET_TIMEOUT_SEC).then_some(c.player_id)
+                })
+                .collect::<Vec<_>>(),
+            NetRole::Client { last_heard, .. } => {
+                return last_heard.tick(delta).elapsed_secs() <= NET_TIMEOUT_SEC;
+            }
+        };
+        for player_id in timed_out {
+            self.remove_client(player_id);
+        }
+        true
+    }
+
+    fn is_welcomed(&self) -> bool {
+        self.is_host() || self.player_id != 0
+    }
+
+    fn update_peer(&mut self, state: PlayerState) {
+        if state.player_id == self.player_id {
+            return;
+        }
+        let peer = self.peers.entry(state.player_id).or_insert(RemotePeer {
+            state,
+            last_seen: Stopwatch::new(),
+        });
+        peer.state = state;
+        peer.last_seen.reset();
+    }
+
+    fn status(&self) -> String {
+        match self.role {
+            NetRole::Host { .. } => format!(
+                "Hosting on port {NET_PORT} - {}/{NET_MAX_PLAYERS} players",
+                self.num_players
+            ),
+            NetRole::Client { .. } if !self.is_welcomed() => "Connecting...".to_string(),
+            NetRole::Client { .. } => format!(
+                "Joined as player {} - {}/{NET_MAX_PLAYERS} players\nWaiting for the host",
+                self.player_id + 1,
+                self.num_players
+            ),
+        }
+    }
+}
+
+impl Drop for NetSession {
+    fn drop(&mut self) {
+        self.broadcast(
+            &NetMessage::Leave {
+                player_id: self.player_id,
+            },
+            None,
+        );
+    }
+}
+
+fn leave_coop_session(mut coop: ResMut<Coop>) {
+    coop.session = None;
+}
+
+fn in_coop(coop: Res<Coop>) -> bool {
+    coop.session.is_some()
+}
+
+fn net_receive(
+    time: Res<Time<Real>>,
+    mut coop: ResMut<Coop>,
+    mut run_config: ResMut<RunConfig>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut writer_revive: EventWriter<ReviveEvent>,
+    mut writer_ping: EventWriter<PingEvent>,
+    mut writer_chat: EventWriter<ChatEvent>,
+) {
+    let Some(session) = coop.session.as_mut() else {
+        return;
+    };
+
+    let mut start = None;
+    let mut host_left = false;
+    let mut buf = [0; NET_MAX_PACKET_SIZE];
+    loop {
+        let (len, from) = match session.socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+            // Windows reports an unreachable peer on the next read, the rest waits a frame
+            Err(e) => {
+                warn!("Co-op receive failed: {e}");
+                break;
+            }
+        };
+        let message = std::str::from_utf8(&buf[..len])
+            .ok()
+            .and_then(|contents| ron::from_str::<NetMessage>(contents).ok());
+        match message.and_then(|message| session.handle(message, from)) {
+            Some(NetMessage::Revive { .. }) => writer_revive.send(ReviveEvent),
+            Some(NetMessage::Ping(ping)) => writer_ping.send(PingEvent(ping)),
+            Some(NetMessage::Chat { player_id, text }) => writer_chat.send(ChatEvent {
+                player_id,
+                text: text.chars().take(CHAT_MAX_LEN).collect(),
+            }),
+            Some(NetMessage::Leave { .. }) => host_left = true,
+            Some(message) => start = Some(message),
+            None => {}
+        }
+    }
+
+    let status = if host_left {
+        Some("The host left")
+    } else if !session.update_timeouts(time.delta()) {
+        Some(if session.is_welcomed() {
+            "Lost connection to the host"
+        } else {
+            "Couldn't reach the host"
+        })
+    } else {
+        None
+    };
+    if let Some(status) = status {
+        coop.session = None;
+        coop.status = status.to_string();
+        return;
+    }
+
+    if let Some(NetMessage::Start {
+        seed,
+        difficulty,
+        run_length,
+        modifiers,
+    }) = start
+    {
+        *run_config = RunConfig::new(seed, difficulty, run_length, modifiers);
+        game_state.set(GameState::GameInit);
+    }
+}
+
+fn net_send_state(
+    time: Res<Time<Real>>,
+    mut coop: ResMut<Coop>,
+    game_state: Res<State<GameState>>,
+    player_score: Res<PlayerScore>,
+    spectator: Res<Spectator>,
+    car_query: Query<&Transform, With<Car>>,
+    wreck_query: Query<&Transform, (With<Wreck>, Without<RemoteCar>)>,
+) {
+    let Some(session) = coop.session.as_mut() else {
+        return;
+    };
+
+    session.send_timer.tick(time.delta());
+    if session.send_timer.elapsed_secs() < NET_SEND_INTERVAL_SEC {
+        return;
+    }
+    session.send_timer.reset();
+
+    // Keep asking until the host answers, the first join may have been dropped
+    if !session.is_welcomed() {
+        session.broadcast(&NetMessage::Join, None);
+        return;
+    }
+
+    // Also sent from the menus, it doubles as a keep alive
+    let car_transform = car_query
+        .get_single()
+        .or_else(|_| wreck_query.get_single())
+        .ok();
+    let alive = *game_state.get() == GameState::InGame && !car_query.is_empty();
+    let state = PlayerState {
+        player_id: session.player_id,
+        pos: car_transform.map_or((0.0, 0.0), |t| (t.translation.x, t.translation.y)),
+        rotation: car_transform.map_or(0.0, |t| t.rotation.to_euler(EulerRot::XYZ).2),
+        score: player_score.0,
+        alive,
+        downed: *game_state.get() == GameState::InGame && spectator.active,
+    };
+    session.broadcast(&NetMessage::State(state), None);
+}
+
+fn update_remote_cars(
+    mut commands: Commands,
+    time: Res<Time>,
+    coop: Res<Coop>,
+    texture_handle: Res<GlobalTextureHandle>,
+    mut remote_car_query: Query<(
+        Entity,
+        &RemoteCar,
+        &mut Transform,
+        &mut TextureAtlasSprite,
+        Has<Wreck>,
+    )>,
+) {
+    let peers = coop.session.as_ref().map(|session| &session.peers);
+    let in_run_peer = |player_id| {
+        peers
+            .and_then(|peers| peers.get(&player_id))
+            .filter(|peer| peer.state.alive || peer.state.downed)
+    };
+
+    for (e, remote_car, mut transform, mut sprite, is_wreck) in remote_car_query.iter_mut() {
+        let Some(peer) = in_run_peer(remote_car.0) else {
+            commands.entity(e).despawn_recursive();
+            continue;
+        };
+
+        // Downed teammates stay on the road as a wreck to be revived
+        if peer.state.downed != is_wreck {
+            if peer.state.downed {
+                commands.entity(e).insert(Wreck);
+                sprite.color = Color::GRAY;
+            } else {
+                commands.entity(e).remove::<Wreck>();
+                sprite.color = COOP_CAR_COLORS[remote_car.0 as usize % NET_MAX_PLAYERS];
+            }
+        }
+
+        // States arrive at a lower rate than frames, ease towards the latest one
+        let target = vec3(peer.state.pos.0, peer.state.pos.1, transform.translation.z);
+        let t = (NET_REMOTE_CAR_SMOOTHING * time.delta_seconds()).min(1.0);
+        transform.translation = transform.translation.lerp(target, t);
+        transform.rotation = Quat::from_rotation_z(peer.state.rotation);
+    }
+
+    let Some(peers) = peers else {
+        return;
+    };
+    for (player_id, peer) in peers.iter() {
+        if !peer.state.alive || remote_car_query.iter().any(|(_, r, ..)| r.0 == *player_id) {
+            continue;
+        }
+
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: texture_handle.atlas(SpriteId::Hatchback.atlas()),
+                sprite: TextureAtlasSprite {
+                    index: SpriteId::Hatchback.index(),
+                    color: COOP_CAR_COLORS[*player_id as usize % NET_MAX_PLAYERS],
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec3::splat(3.0))
+                    .with_translation(vec3(peer.state.pos.0, peer.state.pos.1, 9.0))
+                    .with_rotation(Quat::from_rotation_z(peer.state.rotation)),
+                ..default()
+            },
+            RemoteCar(*player_id),
+            GameEntity,
+        ));
+    }
+}
+
+fn alive_teammates(coop: &Coop) -> Vec<u8> {
+    let Some(session) = coop.session.as_ref() else {
+        return vec![];
+    };
+    let mut ids = session
+        .peers
+        .iter()
+        .filter(|(_, peer)| peer.state.alive)
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_player_out(
+    mut commands: Commands,
+    mut reader_player_out: EventReader<PlayerOutEvent>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut spectator: ResMut<Spectator>,
+    coop: Res<Coop>,
+    asset_server: Res<AssetServer>,
+    mut car_query: Query<(Entity, &mut TextureAtlasSprite), With<Car>>,
+    spare_vehicle_query: Query<Entity, With<SpareVehicle>>,
+    timer_text_query: Query<&Parent, With<GameUIOnFootTimer>>,
+) {
+    if reader_player_out.is_empty() {
+        return;
+    }
+    reader_player_out.clear();
+
+    let teammates = alive_teammates(&coop);
+    if teammates.is_empty() || spectator.active {
+        game_state.set(GameState::GameOver);
+        return;
+    }
+
+    // Still a teammate on the road, wait for them to come back for us
+    for (car, mut sprite) in car_query.iter_mut() {
+        sprite.color = Color::GRAY;
+        commands
+            .entity(car)
+            .remove::<(Car, OnFoot, Speed, TurnSpeed, Turbo)>()
+            .insert(Wreck);
+    }
+    for e in spare_vehicle_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+    for parent in timer_text_query.iter() {
+        commands.entity(parent.get()).despawn_recursive();
+    }
+
+    *spectator = Spectator {
+        active: true,
+        target: teammates.first().copied(),
+        free_camera: false,
+    };
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("font.ttf"),
+                font_size: 30.0,
+                color: COLOR_LIGHT_ORANGE,
+            },
+        )
+        .with_text_alignment(TextAlignment::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(40.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        }),
+        SpectatorText,
+        GameEntity,
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_spectator(
+    time: Res<Time<Real>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    coop: Res<Coop>,
+    mut spectator: ResMut<Spectator>,
+    mut game_state: ResMut<NextState<GameState>>,
+    remote_car_query: Query<(&RemoteCar, &Transform), Without<MainCamera>>,
+    mut cam_query: Query<&mut Transform, With<MainCamera>>,
+    mut text_query: Query<&mut Text, With<SpectatorText>>,
+) {
+    if !spectator.active {
+        return;
+    }
+
+    // The whole team is down
+    let teammates = alive_teammates(&coop);
+    if teammates.is_empty() {
+        game_state.set(GameState::GameOver);
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::F) {
+        spectator.free_camera = !spectator.free_camera;
+    }
+    let current = spectator
+        .target
+        .and_then(|target| teammates.iter().position(|id| *id == target));
+    spectator.target = match current {
+        Some(idx) if keyboard_input.just_pressed(KeyCode::Tab) => {
+            Some(teammates[(idx + 1) % teammates.len()])
+        }
+        Some(idx) => Some(teammates[idx]),
+        None => teammates.first().copied(),
+    };
+
+    let Ok(mut cam_transform) = cam_query.get_single_mut() else {
+        return;
+    };
+    if spectator.free_camera {
+        let mut direction = Vec3::ZERO;
+        if keyboard_input.any_pressed([KeyCode::W, KeyCode::Up]) {
+            direction.y += 1.0;
+        }
+        if keyboard_input.any_pressed([KeyCode::S, KeyCode::Down]) {
+            direction.y -= 1.0;
+        }
+        if keyboard_input.any_pressed([KeyCode::A, KeyCode::Left]) {
+            direction.x -= 1.0;
+        }
+        if keyboard_input.any_pressed([KeyCode::D, KeyCode::Right]) {
+            direction.x += 1.0;
+        }
+        cam_transform.translation +=
+            direction.normalize_or_zero() * SPECTATOR_FREE_CAMERA_SPEED * time.delta_seconds();
+    } else if let Some((_, transform)) = remote_car_query
+        .iter()
+        .find(|(remote_car, _)| Some(remote_car.0) == spectator.target)
+    {
+        let (x, y) = (transform.translation.x, transform.translation.y);
+        cam_transform.translation = cam_transform
+            .translation
+            .lerp(vec3(x, y + CAMERA_LEAD_Y, 0.0), 0.05);
+    }
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.sections[0].value = if spectator.free_camera {
+            "Free camera - WASD to move, F to follow a teammate".to_string()
+        } else {
+            format!(
+                "Spectating player {} - Tab to switch, F for free camera",
+                spectator.target.unwrap_or_default() + 1
+            )
+        };
+        text.sections[0].value += "\nA teammate can revive you by stopping at your wreck";
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_revive(
+    mut commands: Commands,
+    mut reader_revive: EventReader<ReviveEvent>,
+    mut spectator: ResMut<Spectator>,
+    mut car_health: ResMut<CarHealth>,
+    mut wreck_query: Query<
+        (Entity, &mut Transform, &mut TextureAtlasSprite),
+        (With<Wreck>, Without<RemoteCar>),
+    >,
+    text_query: Query<Entity, With<SpectatorText>>,
+    tuning: Res<Tuning>,
+) {
+    if reader_revive.is_empty() {
+        return;
+    }
+    reader_revive.clear();
+    if !spectator.active {
+        return;
+    }
+
+    // Patched up enough to drive, the survivor may have gone down on foot so it's always a car
+    for (wreck, mut transform, mut sprite) in wreck_query.iter_mut() {
+        sprite.index = SpriteId::Hatchback.index();
+        sprite.color = Color::WHITE;
+        transform.scale = Vec3::splat(3.0);
+        commands.entity(wreck).remove::<Wreck>().insert((
+            Car,
+            Speed(0.0),
+            TurnSpeed(0.0),
+            Turbo(Stopwatch::new()),
+        ));
+    }
+    for e in text_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+    car_health.0 = tuning.max_car_health * REVIVE_HEALTH;
+    *spectator = Spectator::default();
+}
+
+#[allow(clippy::type_complexity)]
+fn update_revive_teammates(
+    mut commands: Commands,
+    time: Res<Time>,
+    coop: Res<Coop>,
+    asset_server: Res<AssetServer>,
+    mut revive_timers: ResMut<ReviveTimers>,
+    car_query: Query<(&Transform, &Speed), With<Car>>,
+    mut text_query: Query<
+        (&mut Text, &mut Transform, &mut Visibility),
+        (With<ReviveText>, Without<Car>),
+    >,
+) {
+    let Some(session) = coop.session.as_ref() else {
+        return;
+    };
+
+    let downed = session
+        .peers
+        .iter()
+        .filter(|(_, peer)| peer.state.downed)
+        .map(|(id, peer)| (*id, vec2(peer.state.pos.0, peer.state.pos.1)))
+        .collect::<Vec<_>>();
+    revive_timers
+        .0
+        .retain(|id, _| downed.iter().any(|(downed_id, _)| downed_id == id));
+
+    let Ok((car_transform, speed)) = car_query.get_single() else {
+        revive_timers.0.clear();
+        return;
+    };
+    let car_pos = car_transform.translation.truncate();
+    // Only the closest wreck gets a label, that's the one being worked on
+    let closest = downed.iter().copied().min_by(|(_, a), (_, b)| {
+        a.distance_squared(car_pos)
+            .total_cmp(&b.distance_squared(car_pos))
+    });
+
+    for (player_id, pos) in downed.iter().copied() {
+        if pos.distance(car_pos) > REVIVE_RADIUS || speed.0.abs() > REVIVE_MAX_SPEED {
+            revive_timers.0.remove(&player_id);
+            continue;
+        }
+
+        let timer = revive_timers.0.entry(player_id).or_default();
+        timer.tick(time.delta());
+        if timer.elapsed_secs() >= REVIVE_TIME_SEC {
+            session.broadcast(&NetMessage::Revive { player_id }, None);
+            timer.reset();
+        }
+    }
+
+    if text_query.is_empty() {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("font.ttf"),
+                        font_size: 30.0,
+                        color: COLOR_LIGHT_ORANGE,
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            ReviveText,
+            GameEntity,
+        ));
+        return;
+    }
+
+    let (mut text, mut transform, mut visibility) = text_query.single_mut();
+    let Some((player_id, pos)) = closest else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Visible;
+    transform.translation = vec3(pos.x, pos.y + 60.0, 20.0);
+    text.sections[0].value = match revive_timers.0.get(&player_id) {
+        Some(timer) => format!("Reviving {:.1}", REVIVE_TIME_SEC - timer.elapsed_secs()),
+        None => format!("Stop here to revive player {}", player_id + 1),
+    };
+}
+
+fn setup_coop_comms(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut ping_wheel: ResMut<PingWheel>,
+    mut chat: ResMut<CoopChat>,
+) {
+    *ping_wheel = PingWheel::default();
+    chat.typing = false;
+    chat.input.clear();
+
+    let font = asset_server.load("font.ttf");
+    let (option_w, option_h) = PING_WHEEL_OPTION_SIZE;
+    let (center_x, center_y) = (
+        (PING_WHEEL_SIZE - option_w) / 2.0,
+        (PING_WHEEL_SIZE - option_h) / 2.0,
+    );
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(PING_WHEEL_SIZE),
+                    height: Val::Px(PING_WHEEL_SIZE),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(9),
+                ..default()
+            },
+            PingWheelRoot,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            for (kind, left, top) in [
+                (PingKind::Horde, center_x, 0.0),
+                (PingKind::Loot, PING_WHEEL_SIZE - option_w, center_y),
+                (PingKind::NeedHelp, center_x, PING_WHEEL_SIZE - option_h),
+                (PingKind::Regroup, 0.0, center_y),
+            ] {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(left),
+                            top: Val::Px(top),
+                            width: Val::Px(option_w),
+                            height: Val::Px(option_h),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: Color::BLACK.with_a(0.6).into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section(
+                                kind.label(),
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 24.0,
+                                    color: COLOR_LIGHT_ORANGE,
+                                },
+                            ),
+                            PingWheelOption(kind),
+                        ));
+                    });
+            }
+        });
+
+    // Above the minimap
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font,
+                font_size: 24.0,
+                color: COLOR_LIGHT_ORANGE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(16.0),
+            bottom: Val::Px(HUD_MINIMAP_SIZE + 32.0),
+            ..default()
+        }),
+        ChatText,
+        GameEntity,
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_ping_wheel(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    coop: Res<Coop>,
+    mut ping_wheel: ResMut<PingWheel>,
+    mut writer_ping: EventWriter<PingEvent>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    car_query: Query<&Transform, With<Car>>,
+    mut root_query: Query<(&mut Style, &mut Visibility), With<PingWheelRoot>>,
+    mut option_query: Query<(&PingWheelOption, &mut Text)>,
+) {
+    let Some(session) = coop.session.as_ref() else {
+        return;
+    };
+    ping_wheel.cooldown = (ping_wheel.cooldown - time.delta_seconds()).max(0.0);
+    let cursor = q_window.get_single().ok().and_then(|w| w.cursor_position());
+
+    // The d-pad drops one on the car straight away
+    let mut dropped = [
+        (GamepadButtonType::DPadUp, PingKind::Horde),
+        (GamepadButtonType::DPadRight, PingKind::Loot),
+        (GamepadButtonType::DPadDown, PingKind::NeedHelp),
+        (GamepadButtonType::DPadLeft, PingKind::Regroup),
+    ]
+    .into_iter()
+    .find(|(button, _)| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_input.just_pressed(GamepadButton::new(gamepad, *button)))
+    })
+    .zip(car_query.get_single().ok())
+    .map(|((_, kind), transform)| (kind, transform.translation.truncate()));
+
+    if keyboard_input.just_pressed(PING_WHEEL_KEY) {
+        ping_wheel.center = cursor.and_then(|cursor| {
+            let (camera, camera_transform) = q_camera.get_single().ok()?;
+            let world = camera.viewport_to_world_2d(camera_transform, cursor)?;
+            Some((cursor, world))
+        });
+    }
+    let selected = ping_wheel
+        .center
+        .zip(cursor)
+        .and_then(|((center, _), cursor)| PingKind::from_offset(cursor - center));
+    if !keyboard_input.pressed(PING_WHEEL_KEY) {
+        if let Some(((_, world), kind)) = ping_wheel.center.take().zip(selected) {
+            dropped = Some((kind, world));
+        }
+    }
+
+    if let Some((kind, pos)) = dropped.filter(|_| ping_wheel.cooldown <= 0.0) {
+        let ping = Ping {
+            player_id: session.player_id,
+            kind,
+            pos: (pos.x, pos.y),
+        };
+        session.broadcast(&NetMessage::Ping(ping), None);
+        writer_ping.send(PingEvent(ping));
+        ping_wheel.cooldown = PING_COOLDOWN_SEC;
+    }
+
+    if let Ok((mut style, mut visibility)) = root_query.get_single_mut() {
+        match ping_wheel.center {
+            Some((center, _)) => {
+                style.left = Val::Px(center.x - PING_WHEEL_SIZE / 2.0);
+                style.top = Val::Px(center.y - PING_WHEEL_SIZE / 2.0);
+                visibility.set_if_neq(Visibility::Visible);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+    for (option, mut text) in option_query.iter_mut() {
+        let color = if selected == Some(option.0) {
+            option.0.color()
+        } else {
+            COLOR_LIGHT_ORANGE
+        };
+        if text.sections[0].style.color != color {
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+fn update_chat_input(
+    coop: Res<Coop>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut reader_chars: EventReader<ReceivedCharacter>,
+    mut chat: ResMut<CoopChat>,
+    mut writer_chat: EventWriter<ChatEvent>,
+) {
+    let Some(session) = coop.session.as_ref() else {
+        return;
+    };
+    if !chat.typing {
+        if keyboard_input.just_pressed(CHAT_KEY) {
+            chat.typing = true;
+            chat.input.clear();
+            // The key that opened the chat isn't part of the message
+            reader_chars.clear();
+            keyboard_input.reset_all();
+        }
+        return;
+    }
+
+    for ev in reader_chars.read() {
+        if !ev.char.is_control() && chat.input.chars().count() < CHAT_MAX_LEN {
+            chat.input.push(ev.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        chat.input.pop();
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]) {
+        let text = chat.input.trim().to_string();
+        if !text.is_empty() {
+            session.broadcast(
+                &NetMessage::Chat {
+                    player_id: session.player_id,
+                    text: text.clone(),
+                },
+                None,
+            );
+            writer_chat.send(ChatEvent {
+                player_id: session.player_id,
+                text,
+            });
+        }
+        chat.typing = false;
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        chat.typing = false;
+    }
+
+    // Nothing typed should steer, shoot or pause
+    keyboard_input.reset_all();
+}
+
+fn spawn_pings(
+    mut commands: Commands,
+    mut reader_ping: EventReader<PingEvent>,
+    asset_server: Res<AssetServer>,
+    marker_query: Query<(Entity, &PingMarker)>,
+) {
+    for PingEvent(ping) in reader_ping.read() {
+        // One ping per player, a new one replaces the last
+        for (e, marker) in marker_query.iter() {
+            if marker.player_id == ping.player_id {
+                commands.entity(marker.edge).despawn_recursive();
+                commands.entity(e).despawn_recursive();
+            }
+        }
+
+        let style = TextStyle {
+            font: asset_server.load("font.ttf"),
+            font_size: 28.0,
+            color: ping.kind.color(),
+        };
+        let label = format!("P{} {}", ping.player_id + 1, ping.kind.label());
+        let edge = commands
+            .spawn((
+                TextBundle {
+                    visibility: Visibility::Hidden,
+                    ..TextBundle::from_section(label.clone(), style.clone())
+                        .with_text_alignment(TextAlignment::Center)
+                        .with_style(Style {
+                            position_type: PositionType::Absolute,
+                            width: Val::Px(PING_EDGE_WIDTH),
+                            ..default()
+                        })
+                },
+                GameEntity,
+            ))
+            .id();
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(format!("{label}\nv"), style)
+                    .with_alignment(TextAlignment::Center),
+                text_anchor: Anchor::BottomCenter,
+                transform: Transform::from_xyz(ping.pos.0, ping.pos.1, 20.0),
+                ..default()
+            },
+            PingMarker {
+                player_id: ping.player_id,
+                kind: ping.kind,
+                age: 0.0,
+                edge,
+            },
+            GameEntity,
+        ));
+    }
+}
+
+fn update_pings(
+    mut commands: Commands,
+    time: Res<Time>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut marker_query: Query<(Entity, &mut PingMarker, &mut Text, &mut Transform)>,
+    mut edge_query: Query<(&mut Style, &mut Text, &mut Visibility), Without<PingMarker>>,
+) {
+    let camera = q_camera.get_single().ok();
+    for (e, mut marker, mut text, mut transform) in marker_query.iter_mut() {
+        marker.age += time.delta_seconds();
+        if marker.age >= PING_LIFETIME_SEC {
+            commands.entity(marker.edge).despawn_recursive();
+            commands.entity(e).despawn_recursive();
+            continue;
+        }
+
+        let alpha = ((PING_LIFETIME_SEC - marker.age) / PING_FADE_SEC).min(1.0);
+        let color = marker.kind.color().with_a(alpha);
+        text.sections[0].style.color = color;
+        transform.scale = Vec3::splat(1.0 + (marker.age * 6.0).sin().abs() * 0.15);
+
+        let Ok((mut style, mut edge_text, mut visibility)) = edge_query.get_mut(marker.edge) else {
+            continue;
+        };
+        let Some((screen, size)) = camera.and_then(|(camera, camera_transform)| {
+            let screen = camera.world_to_viewport(camera_transform, transform.translation)?;
+            Some((screen, camera.logical_viewport_size()?))
+        }) else {
+            continue;
+        };
+
+        // Off screen it's pinned to the nearest edge with an arrow towards it
+        let min = Vec2::splat(PING_EDGE_MARGIN);
+        let max = size - Vec2::splat(PING_EDGE_MARGIN);
+        let clamped = screen.clamp(min, max.max(min));
+        if clamped == screen {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        let arrow = if screen.y < min.y {
+            "^"
+        } else if screen.y > max.y {
+            "v"
+        } else if screen.x < min.x {
+            "<"
+        } else {
+            ">"
+        };
+        let label = format!(
+            "{arrow} {}",
+            text.sections[0].value.lines().next().unwrap_or("")
+        );
+        if edge_text.sections[0].value != label {
+            edge_text.sections[0].value = label;
+        }
+        edge_text.sections[0].style.color = color;
+        style.left = Val::Px(
+            (clamped.x - PING_EDGE_WIDTH / 2.0).clamp(0.0, (size.x - PING_EDGE_WIDTH).max(0.0)),
+        );
+        style.top = Val::Px(clamped.y - PING_EDGE_MARGIN / 2.0);
+        visibility.set_if_neq(Visibility::Visible);
+    }
+}
+
+fn update_chat_text(
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut reader_chat: EventReader<ChatEvent>,
+    mut chat: ResMut<CoopChat>,
+    mut text_query: Query<&mut Text, With<ChatText>>,
+) {
+    let now = time.elapsed_seconds();
+    for ev in reader_chat.read() {
+        chat.lines.push((ev.player_id, ev.text.clone(), now));
+    }
+    let is_expired = |(_, _, at): &(u8, String, f32)| now - at > CHAT_LINE_SEC;
+    if chat.lines.iter().any(is_expired) || chat.lines.len() > CHAT_MAX_LINES {
+        chat.lines.retain(|line| !is_expired(line));
+        let excess = chat.lines.len().saturating_sub(CHAT_MAX_LINES);
+        chat.lines.drain(..excess);
+    }
+    if !chat.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let style = |color| TextStyle {
+        font: asset_server.load("font.ttf"),
+        font_size: 24.0,
+        color,
+    };
+    let mut sections = chat
+        .lines
+        .iter()
+        .map(|(player_id, line, _)| {
+            let color = COOP_CAR_COLORS[*player_id as usize % NET_MAX_PLAYERS];
+            TextSection::new(format!("P{}: {line}\n", player_id + 1), style(color))
+        })
+        .collect::<Vec<_>>();
+    if chat.typing {
+        sections.push(TextSection::new(
+            format!("> {}_", chat.input),
+            style(COLOR_LIGHT_ORANGE),
+        ));
+    }
+    text.sections = sections;
+}
+
+fn setup_lobby_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    lobby_address: Res<LobbyAddress>,
+) {
+    let button_style = Style {
+        width: Val::Px(300.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 40.0,
+        color: COLOR_BLACK,
+        font: asset_server.load("font.ttf"),
+    };
+    let text_style = TextStyle {
+        font_size: 35.0,
+        font: asset_server.load("font.ttf"),
+        color: COLOR_LIGHT_ORANGE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            LobbyMenuComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Co-op",
+                            TextStyle {
+                                font_size: 70.0,
+                                font: asset_server.load("font.ttf"),
+                                color: COLOR_LIGHT_ORANGE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(20.0)),
+                            ..default()
+                        }),
+                    );
+                    parent.spawn((
+                        TextBundle::from_section("", text_style.clone())
+                            .with_text_alignment(TextAlignment::Center),
+                        LobbyStatusText,
+                    ));
+                    parent.spawn((
+                        TextBundle::from_section(
+                            format!("Host Address: {}", lobby_address.0),
+                            text_style,
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(20.0)),
+                            ..default()
+                        }),
+                        LobbyAddressText,
+                    ));
+                    for (action, label) in [
+                        (LobbyMenuButtonAction::Host, "Host Game"),
+                        (LobbyMenuButtonAction::Join, "Join Game"),
+                        (LobbyMenuButtonAction::Start, "Start Run"),
+                        (LobbyMenuButtonAction::ExitToMainMenu, "Back"),
+                    ] {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: COLOR_ORANGE.into(),
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    label,
+                                    button_text_style.clone(),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_lobby_btn_click(
+    interaction_query: Query<
+        (&Interaction, &LobbyMenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut coop: ResMut<Coop>,
+    mut run_config: ResMut<RunConfig>,
+    mut game_state: ResMut<NextState<GameState>>,
+    lobby_address: Res<LobbyAddress>,
+    settings: Res<GameSettings>,
+    profile: Res<Profile>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match menu_button_action {
+            LobbyMenuButtonAction::Host | LobbyMenuButtonAction::Join => {
+                // Replacing the session says goodbye to the old one
+                coop.session = None;
+                let session = match menu_button_action {
+                    LobbyMenuButtonAction::Host => NetSession::host(),
+                    _ => NetSession::join(&lobby_address.0),
+                };
+                match session {
+                    Ok(session) => {
+                        coop.session = Some(session);
+                        coop.status.clear();
+                    }
+                    Err(e) => coop.status = format!("Network error: {e}"),
+                }
+            }
+            LobbyMenuButtonAction::Start => {
+                let Some(session) = coop.session.as_ref().filter(|s| s.is_host()) else {
+                    continue;
+                };
+                let (seed, modifiers) = (rand::thread_rng().gen(), profile.mutators.clone());
+                session.broadcast(
+                    &NetMessage::Start {
+                        seed,
+                        difficulty: settings.difficulty,
+                        run_length: settings.run_length,
+                        modifiers: modifiers.clone(),
+                    },
+                    None,
+                );
+                *run_config =
+                    RunConfig::new(seed, settings.difficulty, settings.run_length, modifiers);
+                game_state.set(GameState::GameInit);
+            }
+            LobbyMenuButtonAction::ExitToMainMenu => game_state.set(GameState::MainMenu),
+        }
+    }
+}
+
+fn update_lobby_menu(
+    mut lobby_address: ResMut<LobbyAddress>,
+    mut reader_chars: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    coop: Res<Coop>,
+    mut status_query: Query<&mut Text, (With<LobbyStatusText>, Without<LobbyAddressText>)>,
+    mut address_query: Query<&mut Text, (With<LobbyAddressText>, Without<LobbyStatusText>)>,
+) {
+    // Only what an ip and port can be made of, so W and S keep moving the menu focus
+    for ev in reader_chars.read() {
+        if ev.char.is_ascii_digit() || ev.char == '.' || ev.char == ':' {
+            lobby_address.0.push(ev.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        lobby_address.0.pop();
+    }
+
+    if let Ok(mut text) = address_query.get_single_mut() {
+        if lobby_address.is_changed() {
+            text.sections[0].value = format!("Host Address: {}", lobby_address.0);
+        }
+    }
+    if let Ok(mut text) = status_query.get_single_mut() {
+        let status = match coop.session.as_ref() {
+            Some(session) => session.status(),
+            None if coop.status.is_empty() => "Host a game or join one by address".to_string(),
+            None => coop.status.clone(),
+        };
+        if text.sections[0].value != status {
+            text.sections[0].value = status;
+        }
+    }
+}
+
+fn cleanup_lobby_menu(
+    mut commands: Commands,
+    lobby_menu_query: Query<Entity, With<LobbyMenuComponent>>,
+) {
+    for e in lobby_menu_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn setup_run_setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let button_style = Style {
+        width: Val::Px(320.0),
+        height: Val::Px(55.0),
+        margin: UiRect::all(Val::Px(8.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 35.0,
+        color: COLOR_BLACK,
+        font: asset_server.load("font.ttf"),
+    };
+    let text_style = TextStyle {
+        font_size: 32.0,
+        font: asset_server.load("font.ttf"),
+        color: COLOR_LIGHT_ORANGE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            RunSetupMenuComponent,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Run Setup",
+                        TextStyle {
+                            font_size: 70.0,
+                            font: asset_server.load("font.ttf"),
+                            color: COLOR_LIGHT_ORANGE,
+                        },
+                    ));
+                    // Filled in by update_run_setup_menu
+                    parent.spawn((
+                        TextBundle::from_section("", text_style.clone())
+                            .with_text_alignment(TextAlignment::Center)
+                            .with_style(Style {
+                                margin: UiRect::all(Val::Px(16.0)),
+                                ..default()
+                            }),
+                        RunSetupText,
+                    ));
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "Type to edit the seed, Backspace to delete",
+                            text_style,
+                        )
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(16.0)),
+                            ..default()
+                        }),
+                        RunSetupStatusText,
+                    ));
+                    for (action, label) in [
+                        (RunSetupButtonAction::Start, "Start"),
+                        (RunSetupButtonAction::CopyRunCode, "Copy Run Code"),
+                        (RunSetupButtonAction::PasteRunCode, "Paste Run Code"),
+                        (RunSetupButtonAction::Back, "Back"),
+                    ] {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: COLOR_ORANGE.into(),
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    label,
+                                    button_text_style.clone(),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_run_setup_btn_click(
+    interaction_query: Query<
+        (&Interaction, &RunSetupButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut run_config: ResMut<RunConfig>,
+    mut profile: ResMut<Profile>,
+    mut status_query: Query<&mut Text, With<RunSetupStatusText>>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let status = match menu_button_action {
+            RunSetupButtonAction::Start => {
+                game_state.set(GameState::GameInit);
+                return;
+            }
+            RunSetupButtonAction::Back => {
+                game_state.set(GameState::MutatorsMenu);
+                return;
+            }
+            RunSetupButtonAction::CopyRunCode => {
+                let code = run_config.run_code(profile.car);
+                if copy_to_clipboard(&code) {
+                    "Run code copied".to_string()
+                } else {
+                    format!("No clipboard, share this code: {code}")
+                }
+            }
+            RunSetupButtonAction::PasteRunCode => {
+                match paste_from_clipboard().and_then(|code| RunConfig::from_run_code(&code)) {
+                    Some((config, car)) => {
+                        *run_config = config;
+                        // Racing in another car than theirs is still the same road
+                        if profile.is_unlocked(UnlockItem::Car(car)) {
+                            profile.car = car;
+                            profile.save();
+                            "Run code loaded".to_string()
+                        } else {
+                            "Run code loaded, their car is still locked".to_string()
+                        }
+                    }
+                    None => "No run code on the clipboard".to_string(),
+                }
+            }
+        };
+        if let Ok(mut text) = status_query.get_single_mut() {
+            text.sections[0].value = status;
+        }
+    }
+}
+
+fn update_run_setup_menu(
+    mut run_config: ResMut<RunConfig>,
+    mut reader_chars: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    profile: Res<Profile>,
+    settings: Res<GameSettings>,
+    mut text_query: Query<&mut Text, With<RunSetupText>>,
+) {
+    // Digits only, so W and S keep moving the menu focus
+    for ev in reader_chars.read() {
+        if let Some(digit) = ev.char.to_digit(10) {
+            if let Some(seed) = run_config
+                .seed
+                .checked_mul(10)
+                .and_then(|seed| seed.checked_add(digit as u64))
+            {
+                run_config.seed = seed;
+            }
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        run_config.seed /= 10;
+    }
+
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+    if !run_config.is_changed() && !profile.is_changed() && !text.sections[0].value.is_empty() {
+        return;
+    }
+
+    let mode = if run_config.boss_rush {
+        "Boss Rush"
+    } else if run_config.arena {
+        "Score Arena"
+    } else {
+        run_config.run_length.as_str()
+    };
+    let mutators = if run_config.modifiers.is_empty() {
+        "None".to_string()
+    } else {
+        run_config
+            .modifiers
+            .iter()
+            .map(|m| m.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    text.sections[0].value = format!(
+        "Seed: {}\nDifficulty - {}\nMode - {}\n{}\nMutators: {}\nCar: {}\n\nRun Code: {}",
+        run_config.seed,
+        run_config.difficulty.as_str(),
+        mode,
+        settings.entity_count_as_str(),
+        mutators,
+        UnlockItem::Car(profile.car).as_str(),
+        run_config.run_code(profile.car),
+    );
+}
+
+fn cleanup_run_setup_menu(
+    mut commands: Commands,
+    run_setup_menu_query: Query<Entity, With<RunSetupMenuComponent>>,
+) {
+    for e in run_setup_menu_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+// The clipboard drops what it holds when it goes away, unless a clipboard manager is
+// running, which desktops generally have
+#[cfg(not(target_arch = "wasm32"))]
+fn copy_to_clipboard(text: &str) -> bool {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .is_ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn paste_from_clipboard() -> Option<String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .ok()
+}
+
+// The browser only hands out the clipboard asynchronously, the code is shown instead
+#[cfg(target_arch = "wasm32")]
+fn copy_to_clipboard(_text: &str) -> bool {
+    false
 }
 
-fn update_bullet(
-    time: Res<Time>,
-    mut bullets_query: Query<(&mut Transform, &BulletDirection), With<Bullet>>
-) {
-    for (mut transform, bullet_direction) in bullets_query.iter_mut() {
-        transform.translation += Vec3::splat(BULLET_SPEED * time.delta_seconds()) * (bullet_direction.0.normalize());
-        transform.translation.z = 15.0;
-    }
+#[cfg(target_arch = "wasm32")]
+fn paste_from_clipboard() -> Option<String> {
+    None
 }
 
-fn despawn_bullets(mut commands: Commands, bullets_query: Query<(Entity, &Bullet), With<Bullet>>) {
-    for (entity, bullet) in bullets_query.iter() {
-        if bullet.0.elapsed().as_secs_f32() > BULLET_TIME {
-            commands.entity(entity).despawn();
-        }
-    }
+// Horde renderer
+// Zombies skip the sprite pipeline and get drawn as one instanced batch, saving the
+// per-sprite extraction, sorting and batching work at high entity counts.
+// Only the atlas index, color, anchor and uniform scale of zombie sprites are honored.
+struct HordeRenderPlugin;
+
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct HordeInstance {
+    pos_size: [f32; 4],
+    uv_rect: [f32; 4],
+    color: [f32; 4],
 }
 
-fn handle_escape_key(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut game_state: ResMut<NextState<GameState>>,
-) {
-    if !keyboard_input.pressed(KeyCode::Escape) {
-        return;
-    }
+#[derive(Resource, Default)]
+struct ExtractedHorde {
+    batch_entity: Option<Entity>,
+    image: AssetId<Image>,
+}
 
-    // game_state.
-    game_state.set(GameState::PauseMenu);
+#[derive(Resource)]
+struct HordeMeta {
+    instance_buffer: BufferVec<HordeInstance>,
+    view_bind_group: Option<BindGroup>,
+    texture_bind_group: Option<BindGroup>,
 }
 
-fn handle_player_dead_event(
-    mut player_dead_event: EventReader<PlayerDeadEvent>,
-    mut game_state: ResMut<NextState<GameState>>,
-) {
-    if player_dead_event.is_empty() {
-        return;
-    }
+#[derive(Resource)]
+struct HordePipeline {
+    view_layout: BindGroupLayout,
+    texture_layout: BindGroupLayout,
+    shader: Handle<Shader>,
+}
 
-    player_dead_event.clear();
-    game_state.set(GameState::GameOver);
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct HordePipelineKey {
+    hdr: bool,
+    msaa_samples: u32,
 }
 
-fn camera_follow_player(
-    car_query: Query<&Transform, With<Car>>,
-    mut cam_query: Query<(&Camera, &mut Transform), Without<Car>>,
-) {
-    if car_query.is_empty() {
-        return;
+type DrawHorde = (SetItemPipeline, SetHordeBindGroups, DrawHordeInstanced);
+
+struct SetHordeBindGroups;
+struct DrawHordeInstanced;
+
+impl Plugin for HordeRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            sync_horde_visibility.before(VisibilitySystems::CheckVisibility),
+        );
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_command::<Transparent2d, DrawHorde>()
+            .init_resource::<ExtractedHorde>()
+            .init_resource::<HordeMeta>()
+            .init_resource::<SpecializedRenderPipelines<HordePipeline>>()
+            .add_systems(ExtractSchedule, extract_horde)
+            .add_systems(
+                Render,
+                (
+                    queue_horde.in_set(RenderSet::Queue),
+                    prepare_horde_buffers.in_set(RenderSet::PrepareResources),
+                    prepare_horde_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                ),
+            );
     }
-    if cam_query.is_empty() {
-        return;
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<HordePipeline>();
     }
+}
 
-    let car_transform = car_query.single();
-    let (x, y) = (car_transform.translation.x, car_transform.translation.y);
-    let (_, mut transform) = cam_query.single_mut();
-    transform.translation = transform.translation.lerp(vec3(x, y + 200.0, 0.0), 0.05);
+// The only system that touches zombie visibility, gameplay hides a zombie with Culled.
+// Batched zombies keep their sprite hidden so the sprite pipeline skips them. Latched ones
+// stay sprites, they sit at the car's depth as its children and the batch can't follow
+#[allow(clippy::type_complexity)]
+fn sync_horde_visibility(
+    settings: Res<GameSettings>,
+    mut zombie_query: Query<(&mut Visibility, Has<Culled>, Has<Latched>), With<Zombie>>,
+) {
+    for (mut visibility, is_culled, is_latched) in zombie_query.iter_mut() {
+        visibility.set_if_neq(if is_culled || (settings.instanced_horde && !is_latched) {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        });
+    }
 }
 
-fn car_manual_input_system(
-    time: Res<Time>,
-    road_tiles: Res<RoadTiles>,
-    keyboard_input: Res<Input<KeyCode>>,
-    mut car_query: Query<(&mut Speed, &mut TurnSpeed, &mut Transform, &Turbo), With<Car>>,
+#[allow(clippy::type_complexity)]
+fn extract_horde(
+    mut commands: Commands,
+    mut horde: ResMut<ExtractedHorde>,
+    mut meta: ResMut<HordeMeta>,
+    settings: Extract<Res<GameSettings>>,
+    atlases: Extract<Res<Assets<TextureAtlas>>>,
+    // Goes by the same gameplay state as sync_horde_visibility, not by Visibility, which
+    // is hidden for every batched zombie
+    zombie_query: Extract<
+        Query<
+            (&GlobalTransform, &TextureAtlasSprite, &Handle<TextureAtlas>),
+            (With<Zombie>, Without<Culled>, Without<Latched>),
+        >,
+    >,
 ) {
-    if car_query.is_empty() {
+    meta.instance_buffer.clear();
+    horde.batch_entity = None;
+    if !settings.instanced_horde {
         return;
     }
 
-    let (mut speed, mut turn_speed, mut transform, turbo) = car_query.single_mut();
-    let w_key = keyboard_input.pressed(KeyCode::W) || keyboard_input.pressed(KeyCode::Up);
-    let a_key = keyboard_input.pressed(KeyCode::A) || keyboard_input.pressed(KeyCode::Left);
-    let s_key = keyboard_input.pressed(KeyCode::S) || keyboard_input.pressed(KeyCode::Down);
-    let d_key = keyboard_input.pressed(KeyCode::D) || keyboard_input.pressed(KeyCode::Right);
-
-    update_car_input(
-        CarControls(w_key, a_key, s_key, d_key),
-        &mut turn_speed,
-        &mut speed,
-        &time,
-    );
+    for (transform, sprite, atlas_handle) in zombie_query.iter() {
+        let Some(atlas) = atlases.get(atlas_handle) else {
+            continue;
+        };
+        let Some(rect) = atlas.textures.get(sprite.index) else {
+            continue;
+        };
 
-    let (x, y) = (transform.translation.x, transform.translation.y);
-    let (x, y) = (
-        x / (TILE_W as f32 * ROAD_SCALE),
-        y / (TILE_H as f32 * ROAD_SCALE),
-    );
-    let (x1, y1) = (x.ceil() as i32, y.ceil() as i32);
-    let (x2, y2) = (x.floor() as i32, y.floor() as i32);
-    let is_on_road = road_tiles.0.contains(&(x1, y1)) || road_tiles.0.contains(&(x2, y2));
+        let affine = transform.affine();
+        let size = rect.size()
+            * vec2(
+                affine.matrix3.x_axis.length(),
+                affine.matrix3.y_axis.length(),
+            );
+        let center = affine.translation.truncate() - sprite.anchor.as_vec() * size;
+        let (mut uv_min, mut uv_max) = (rect.min / atlas.size, rect.max / atlas.size);
+        if sprite.flip_x {
+            std::mem::swap(&mut uv_min.x, &mut uv_max.x);
+        }
+        meta.instance_buffer.push(HordeInstance {
+            pos_size: [center.x, center.y, size.x, size.y],
+            uv_rect: [uv_min.x, uv_min.y, uv_max.x, uv_max.y],
+            color: sprite.color.as_linear_rgba_f32(),
+        });
+        horde.image = atlas.texture.id();
+    }
 
-    if turbo.0.elapsed().as_secs_f32() < 0.2 {
-        speed.0 += TURBO_BOOST;
+    if !meta.instance_buffer.is_empty() {
+        horde.batch_entity = Some(commands.spawn_empty().id());
     }
+}
 
-    let time_step = 1.0 / 60.0;
-    let rotation_factor = turn_speed.0;
-    let movement_factor = if is_on_road {
-        speed.0 * 0.1
-    } else {
-        speed.0 * 0.05
+fn queue_horde(
+    draw_functions: Res<DrawFunctions<Transparent2d>>,
+    horde_pipeline: Res<HordePipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<HordePipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    msaa: Res<Msaa>,
+    horde: Res<ExtractedHorde>,
+    mut views: Query<(&ExtractedView, &mut RenderPhase<Transparent2d>)>,
+) {
+    let Some(batch_entity) = horde.batch_entity else {
+        return;
     };
 
-    if speed.0.abs() > MIN_SPEED_TO_STEER {
-        transform.rotate_z(rotation_factor * 0.1 * time_step);
+    let draw_horde = draw_functions.read().id::<DrawHorde>();
+    for (view, mut transparent_phase) in views.iter_mut() {
+        let key = HordePipelineKey {
+            hdr: view.hdr,
+            msaa_samples: msaa.samples(),
+        };
+        transparent_phase.add(Transparent2d {
+            // The whole horde is one item at the depth zombies are spawned at. Sprites also at
+            // z 1, like pickups and nests, end up all above or all below it instead of
+            // interleaved, anything that has to show over zombies needs a higher z
+            sort_key: FloatOrd(1.0),
+            entity: batch_entity,
+            pipeline: pipelines.specialize(&pipeline_cache, &horde_pipeline, key),
+            draw_function: draw_horde,
+            batch_range: 0..1,
+            dynamic_offset: None,
+        });
     }
-    let movement_direction = transform.rotation * Vec3::Y;
-    let movement_distance = movement_factor;
-    let translation_delta = movement_direction * movement_distance;
-    transform.translation += translation_delta * time.delta_seconds() * 100.0;
 }
 
-fn update_car_input(
-    controls: CarControls,
-    turn_speed: &mut TurnSpeed,
-    speed: &mut Speed,
-    time: &Time,
+fn prepare_horde_buffers(
+    mut meta: ResMut<HordeMeta>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
 ) {
-    let w_key = controls.0;
-    let a_key = controls.1;
-    let s_key = controls.2;
-    let d_key = controls.3;
+    meta.instance_buffer
+        .write_buffer(&render_device, &render_queue);
+}
 
-    turn_speed.0 = if a_key {
-        TURN_SPEED
-    } else if d_key {
-        -TURN_SPEED
-    } else {
-        0.0
-    };
+fn prepare_horde_bind_groups(
+    mut meta: ResMut<HordeMeta>,
+    render_device: Res<RenderDevice>,
+    horde_pipeline: Res<HordePipeline>,
+    view_uniforms: Res<ViewUniforms>,
+    gpu_images: Res<RenderAssets<Image>>,
+    horde: Res<ExtractedHorde>,
+) {
+    meta.view_bind_group = view_uniforms.uniforms.binding().map(|view_binding| {
+        render_device.create_bind_group(
+            "horde_view_bind_group",
+            &horde_pipeline.view_layout,
+            &BindGroupEntries::single(view_binding),
+        )
+    });
+    meta.texture_bind_group = gpu_images.get(horde.image).map(|gpu_image| {
+        render_device.create_bind_group(
+            "horde_texture_bind_group",
+            &horde_pipeline.texture_layout,
+            &BindGroupEntries::sequential((&gpu_image.texture_view, &gpu_image.sampler)),
+        )
+    });
+}
 
-    // Friction code from: https://github.com/Rust-Ninja-Sabi/bevyastro
-    speed.0 = if s_key {
-        if speed.0.abs() <= 10.0 {
-            0.0
-        } else {
-            speed.0 - FRICTION * time.delta_seconds() * 1.2
-        }
-    } else if w_key {
-        speed.0 + CAR_THRUST * time.delta_seconds()
-    } else {
-        if speed.0.abs() <= 5.0 {
-            // Avoid speed from over shooting
-            // and be non zero all the time
-            0.0
-        } else if speed.0 > 0.0 {
-            speed.0 - FRICTION * time.delta_seconds()
-        } else if speed.0 < 0.0 {
-            speed.0 + FRICTION * time.delta_seconds()
-        } else {
-            0.0
+impl Default for HordeMeta {
+    fn default() -> Self {
+        Self {
+            instance_buffer: BufferVec::new(BufferUsages::VERTEX),
+            view_bind_group: None,
+            texture_bind_group: None,
         }
-    };
-
-    speed.0 = speed.0.clamp(-MAX_SPEED + MAX_SPEED / 2.0, MAX_SPEED);
+    }
 }
 
-impl VehicleObstacle {
-    fn new(pos: (f32, f32)) -> Self {
+impl FromWorld for HordePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let view_layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: Some(ViewUniform::min_size()),
+                },
+                count: None,
+            }],
+            label: Some("horde_view_layout"),
+        });
+        let texture_layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        multisampled: false,
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("horde_texture_layout"),
+        });
+
         Self {
-            pos,
-            vehicle_type: VehicleObstacleType::random(),
+            view_layout,
+            texture_layout,
+            shader: world.resource::<AssetServer>().load("horde.wgsl"),
         }
     }
 }
 
-impl VehicleObstacleType {
-    fn random() -> Self {
-        let mut rng = rand::thread_rng();
-        if rng.gen_range(0.0..1.0) < 0.7 {
-            match rng.gen_range(0..3) {
-                0 => VehicleObstacleType::Car1,
-                1 => VehicleObstacleType::Car2,
-                _ => VehicleObstacleType::Car3,
-            }
+impl SpecializedRenderPipeline for HordePipeline {
+    type Key = HordePipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let format = if key.hdr {
+            ViewTarget::TEXTURE_FORMAT_HDR
         } else {
-            VehicleObstacleType::Car3
+            TextureFormat::bevy_default()
+        };
+        let attributes = (0..3)
+            .map(|location| VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: location as u64 * VertexFormat::Float32x4.size(),
+                shader_location: location,
+            })
+            .collect();
+
+        RenderPipelineDescriptor {
+            vertex: VertexState {
+                shader: self.shader.clone(),
+                entry_point: "vertex".into(),
+                shader_defs: Vec::new(),
+                buffers: vec![VertexBufferLayout {
+                    array_stride: std::mem::size_of::<HordeInstance>() as u64,
+                    step_mode: VertexStepMode::Instance,
+                    attributes,
+                }],
+            },
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs: Vec::new(),
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            layout: vec![self.view_layout.clone(), self.texture_layout.clone()],
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: key.msaa_samples,
+                ..default()
+            },
+            label: Some("horde_pipeline".into()),
+            push_constant_ranges: Vec::new(),
         }
     }
+}
 
-    fn sprite_idx(&self) -> usize {
-        match self {
-            Self::Car1 => 60,
-            Self::Car2 => 61,
-            Self::Car3 => 62,
-            // Self::Truck => 63,
-        }
+impl<P: PhaseItem> RenderCommand<P> for SetHordeBindGroups {
+    type Param = SRes<HordeMeta>;
+    type ViewWorldQuery = Read<ViewUniformOffset>;
+    type ItemWorldQuery = ();
+
+    fn render<'w>(
+        _item: &P,
+        view_uniform: &'_ ViewUniformOffset,
+        _entity: (),
+        meta: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let meta = meta.into_inner();
+        let (Some(view_bind_group), Some(texture_bind_group)) =
+            (&meta.view_bind_group, &meta.texture_bind_group)
+        else {
+            return RenderCommandResult::Failure;
+        };
+
+        pass.set_bind_group(0, view_bind_group, &[view_uniform.offset]);
+        pass.set_bind_group(1, texture_bind_group, &[]);
+        RenderCommandResult::Success
     }
 }
 
-impl GameSettings {
-    fn update_difficulty(&mut self) {
-        self.difficulty = match self.difficulty {
-            Difficulty::Easy => Difficulty::Moderate,
-            Difficulty::Moderate => Difficulty::Hard,
-            Difficulty::Hard => Difficulty::Easy,
-        }
+impl<P: PhaseItem> RenderCommand<P> for DrawHordeInstanced {
+    type Param = SRes<HordeMeta>;
+    type ViewWorldQuery = ();
+    type ItemWorldQuery = ();
+
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        _entity: (),
+        meta: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let meta = meta.into_inner();
+        let Some(instance_buffer) = meta.instance_buffer.buffer() else {
+            return RenderCommandResult::Failure;
+        };
+
+        pass.set_vertex_buffer(0, instance_buffer.slice(..));
+        pass.draw(0..6, 0..meta.instance_buffer.len() as u32);
+        RenderCommandResult::Success
     }
+}
 
-    fn update_entity_count(&mut self) {
-        self.entity_count = match self.entity_count {
-            EntityCount::Hundred => EntityCount::FiveHundred,
-            EntityCount::FiveHundred => EntityCount::Thousand,
-            EntityCount::Thousand => EntityCount::FiveThousand,
-            EntityCount::FiveThousand => EntityCount::TenThousand,
-            EntityCount::TenThousand => EntityCount::TwentyThousand,
-            EntityCount::TwentyThousand => EntityCount::FiftyThousand,
-            EntityCount::FiftyThousand => EntityCount::Hundred,
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_code_roundtrip() {
+        let modifiers = vec![Modifier::OneHitCar, Modifier::NightOnly];
+        let config = RunConfig {
+            arena: true,
+            ..RunConfig::new(
+                0xDEAD_BEEF,
+                Difficulty::Hard,
+                RunLength::Marathon,
+                modifiers.clone(),
+            )
+        };
+        let code = config.run_code(CarModel::Van);
+
+        let (decoded, car) = RunConfig::from_run_code(&code).unwrap();
+        assert_eq!(decoded.seed, 0xDEAD_BEEF);
+        assert!(matches!(decoded.difficulty, Difficulty::Hard));
+        assert_eq!(decoded.run_length, RunLength::Marathon);
+        assert!(decoded.modifiers == modifiers);
+        assert!(decoded.arena && !decoded.boss_rush);
+        assert!(car == CarModel::Van);
+        assert_eq!(decoded.run_code(car), code);
     }
 
-    fn get_num_max_zombies(&self) -> usize {
-        match &self.entity_count {
-            EntityCount::Hundred => 100,
-            EntityCount::FiveHundred => 500,
-            EntityCount::Thousand => 1000,
-            EntityCount::FiveThousand => 5000,
-            EntityCount::TenThousand => 10000,
-            EntityCount::TwentyThousand => 20000,
-            EntityCount::FiftyThousand => 50000,
+    #[test]
+    fn run_code_rejects_malformed() {
+        for code in [
+            "",
+            "ZE1",
+            "ZE0-1F-0100-00",
+            "ZE1-XYZ-0100-00",
+            "ZE1-1F-010-00",
+            "ZE1-1F-3100-00",
+            "ZE1-1F-0103-00",
+            "ZE1-1F-0100-00-00",
+            "ZE1-1F-0100-GG",
+        ] {
+            assert!(RunConfig::from_run_code(code).is_none(), "{code}");
         }
     }
 
-    fn difficulty_as_str(&self) -> &str {
-        match self.difficulty {
-            Difficulty::Easy => "Difficulty - Easy",
-            Difficulty::Moderate => "Difficulty - Moderate",
-            Difficulty::Hard => "Difficulty - Hard",
-        }
+    fn test_session(role: NetRole) -> NetSession {
+        let socket = UdpSocket::bind(("127.0.0.1", 0)).unwrap();
+        socket.set_nonblocking(true).unwrap();
+        NetSession::new(socket, role)
     }
 
-    fn god_mode_as_str(&self) -> &str {
-        if self.god_mode {
-            return "God Mode - On";
-        }
+    fn test_address(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
 
-        "God Mode - Off"
+    fn test_state(player_id: u8) -> NetMessage {
+        NetMessage::State(PlayerState {
+            player_id,
+            ..default()
+        })
     }
 
-    fn debug_info_as_str(&self) -> &str {
-        if self.debug_info {
-            return "Debug Info - On";
+    #[test]
+    fn host_welcomes_joins_into_free_slots() {
+        let mut host = test_session(NetRole::Host { clients: vec![] });
+        for port in 1..=NET_MAX_PLAYERS as u16 {
+            host.handle(NetMessage::Join, test_address(port));
         }
+        // A resent join doesn't take a second slot
+        host.handle(NetMessage::Join, test_address(1));
 
-        "Debug Info - Off"
+        let NetRole::Host { clients } = &host.role else {
+            unreachable!();
+        };
+        let ids = clients.iter().map(|c| c.player_id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(host.num_players, NET_MAX_PLAYERS as u8);
     }
 
-    fn music_as_str(&self) -> &str {
-        if self.music {
-            return "Music - On";
-        }
+    #[test]
+    fn host_trusts_only_registered_senders() {
+        let mut host = test_session(NetRole::Host { clients: vec![] });
+        host.handle(test_state(2), test_address(1));
+        assert!(host.peers.is_empty());
 
-        "Music - Off"
+        // The claimed id is replaced with the sender's slot
+        host.handle(NetMessage::Join, test_address(1));
+        host.handle(test_state(3), test_address(1));
+        assert_eq!(host.peers.keys().copied().collect::<Vec<_>>(), vec![1]);
     }
 
-    fn entity_count_as_str(&self) -> &str {
-        match self.entity_count {
-            EntityCount::Hundred => "Entity Count - 100",
-            EntityCount::FiveHundred => "Entity Count - 500",
-            EntityCount::Thousand => "Entity Count - 1000",
-            EntityCount::FiveThousand => "Entity Count - 5000",
-            EntityCount::TenThousand => "Entity Count - 10000",
-            EntityCount::TwentyThousand => "Entity Count - 20000",
-            EntityCount::FiftyThousand => "Entity Count - 50000",
+    #[test]
+    fn host_frees_the_slot_on_leave() {
+        let mut host = test_session(NetRole::Host { clients: vec![] });
+        host.handle(NetMessage::Join, test_address(1));
+        host.handle(NetMessage::Join, test_address(2));
+        host.handle(NetMessage::Leave { player_id: 2 }, test_address(1));
+
+        let NetRole::Host { clients } = &host.role else {
+            unreachable!();
+        };
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].address, test_address(2));
+        assert_eq!(host.num_players, 2);
+    }
+
+    #[test]
+    fn host_times_out_quiet_lobby_clients() {
+        let mut host = test_session(NetRole::Host { clients: vec![] });
+        host.handle(NetMessage::Join, test_address(1));
+        host.handle(NetMessage::Join, test_address(2));
+
+        let half = Duration::from_secs_f32(NET_TIMEOUT_SEC * 0.6);
+        assert!(host.update_timeouts(half));
+        host.handle(NetMessage::Join, test_address(1));
+        assert!(host.update_timeouts(half));
+
+        let NetRole::Host { clients } = &host.role else {
+            unreachable!();
+        };
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].player_id, 1);
+        assert_eq!(host.num_players, 2);
+    }
+
+    #[test]
+    fn client_listens_only_to_the_host() {
+        let mut client = test_session(NetRole::Client {
+            host: test_address(1),
+            last_heard: Stopwatch::new(),
+        });
+        assert!(!client.is_welcomed());
+        client.handle(NetMessage::Welcome { player_id: 3 }, test_address(2));
+        assert!(!client.is_welcomed());
+
+        client.handle(NetMessage::Welcome { player_id: 2 }, test_address(1));
+        assert_eq!(client.player_id, 2);
+        let left = client.handle(NetMessage::Leave { player_id: 0 }, test_address(1));
+        assert!(matches!(left, Some(NetMessage::Leave { player_id: 0 })));
+    }
+
+    #[test]
+    fn client_times_out_without_the_host() {
+        let mut client = test_session(NetRole::Client {
+            host: test_address(1),
+            last_heard: Stopwatch::new(),
+        });
+        let half = Duration::from_secs_f32(NET_TIMEOUT_SEC * 0.6);
+        assert!(client.update_timeouts(half));
+        client.handle(NetMessage::Lobby { num_players: 2 }, test_address(1));
+        assert!(client.update_timeouts(half));
+        assert!(!client.update_timeouts(half));
+    }
+
+    #[test]
+    fn spare_vehicle_parks_on_a_clear_road_tile() {
+        let (tile_w, tile_h) = (TILE_W as f32 * ROAD_SCALE, TILE_H as f32 * ROAD_SCALE);
+        let road_tiles = RoadTiles((0..3).map(|c| ((c, 10), RoadSurface::Asphalt)).collect());
+        let wanted = vec2(0.0, 10.0 * tile_h);
+
+        let empty = VehicleObstacleTiles(vec![]);
+        assert_eq!(spare_vehicle_spot(&road_tiles, &empty, wanted), wanted);
+
+        // The closest tile is taken, the next one over is free
+        let blocked = VehicleObstacleTiles(vec![VehicleObstacle {
+            pos: (wanted.x, wanted.y),
+            vehicle_type: VehicleObstacleType::Car1,
+        }]);
+        let spot = spare_vehicle_spot(&road_tiles, &blocked, wanted);
+        assert_eq!(spot, vec2(tile_w, wanted.y));
+
+        // Off the streamed road it stays where it was asked for
+        let far = vec2(0.0, 100.0 * tile_h);
+        assert_eq!(spare_vehicle_spot(&road_tiles, &blocked, far), far);
+    }
+
+    #[test]
+    fn corpse_pile_grid_finds_piles_across_cells() {
+        let mut grid = CorpsePileGrid::default();
+        // Right by a cell edge, so a zombie next door has to look at the neighbours
+        let center = vec2(CORPSE_GRID_CELL - 1.0, 0.0);
+        grid.0
+            .entry(CorpsePileGrid::cell(center))
+            .or_default()
+            .push((center, 20.0));
+
+        let inside = center + vec2(10.0, 0.0);
+        assert_ne!(CorpsePileGrid::cell(inside), CorpsePileGrid::cell(center));
+        assert_eq!(grid.blocking_pile_at(inside), Some((center, 20.0)));
+        assert_eq!(grid.blocking_pile_at(center + vec2(25.0, 0.0)), None);
+        assert_eq!(grid.blocking_pile_at(vec2(-500.0, 0.0)), None);
+    }
+
+    #[test]
+    fn date_from_day_handles_leap_years() {
+        assert_eq!(date_from_day(0), "1970-01-01");
+        assert_eq!(date_from_day(10_956), "1999-12-31");
+        assert_eq!(date_from_day(11_017), "2000-03-01");
+        assert_eq!(date_from_day(19_782), "2024-02-29");
+    }
+
+    fn test_pattern(x: f32, min_progress: f32, hard_weight: f32) -> SpawnPattern {
+        SpawnPattern {
+            x: (x, x + 100.0),
+            y: (300.0, 400.0),
+            mirror: false,
+            weight: PerDifficulty::new(1.0, 1.0, hard_weight),
+            min_progress: PerDifficulty::new(min_progress, min_progress, min_progress),
         }
     }
-}
 
-impl Default for GameSettings {
-    fn default() -> Self {
-        Self {
-            difficulty: Difficulty::default(),
-            entity_count: EntityCount::default(),
-            music: true,
-            god_mode: false,
-            debug_info: false,
+    #[test]
+    fn spawn_patterns_filter_by_progress_difficulty_and_finale() {
+        let patterns = SpawnPatterns {
+            patterns: vec![test_pattern(0.0, 0.0, 1.0), test_pattern(1000.0, 0.5, 0.0)],
+            finales: HashMap::from([(FinishLayout::Ferry, vec![test_pattern(2000.0, 0.0, 1.0)])]),
+        };
+        let starts = |difficulty, progress, finale| {
+            patterns
+                .active(difficulty, progress, finale)
+                .iter()
+                .map(|p| p.x.0)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(starts(Difficulty::Easy, 0.2, None), vec![0.0]);
+        assert_eq!(starts(Difficulty::Easy, 0.6, None), vec![0.0, 1000.0]);
+        // Turned off on hard with a zero weight
+        assert_eq!(starts(Difficulty::Hard, 0.6, None), vec![0.0]);
+        let finale = Some(FinishLayout::Ferry);
+        assert_eq!(starts(Difficulty::Easy, 0.2, finale), vec![0.0, 2000.0]);
+        assert_eq!(
+            starts(Difficulty::Easy, 0.2, Some(FinishLayout::Bunker)),
+            vec![0.0]
+        );
+    }
+
+    #[test]
+    fn spawn_pattern_rolls_inside_its_ranges() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let mut pattern = test_pattern(100.0, 0.0, 1.0);
+        for _ in 0..100 {
+            let offset = pattern.roll(&mut rng);
+            assert!((100.0..=200.0).contains(&offset.x));
+            assert!((300.0..=400.0).contains(&offset.y));
         }
+
+        pattern.mirror = true;
+        let offsets = (0..100).map(|_| pattern.roll(&mut rng)).collect::<Vec<_>>();
+        assert!(offsets.iter().all(|o| (100.0..=200.0).contains(&o.x.abs())));
+        assert!(offsets.iter().any(|o| o.x < 0.0) && offsets.iter().any(|o| o.x > 0.0));
+    }
+
+    #[test]
+    fn percentile_picks_the_nearest_rank() {
+        let sorted = sorted_frame_ms(&[0.004, 0.001, 0.003, 0.002, 0.005]);
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 0.5), 3.0);
+        assert_eq!(percentile(&sorted, 1.0), 5.0);
+        assert_eq!(percentile(&[], 0.5), 0.0);
     }
 }